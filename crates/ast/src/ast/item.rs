@@ -376,6 +376,53 @@ impl UserDefinableOperator {
         }
     }
 
+    /// Returns the user-definable operator for the given binary operator, if any.
+    pub const fn from_bin_op(op: BinOpKind) -> Option<Self> {
+        Some(match op {
+            BinOpKind::BitAnd => Self::BitAnd,
+            BinOpKind::BitOr => Self::BitOr,
+            BinOpKind::BitXor => Self::BitXor,
+            BinOpKind::Add => Self::Add,
+            BinOpKind::Div => Self::Div,
+            BinOpKind::Rem => Self::Rem,
+            BinOpKind::Mul => Self::Mul,
+            BinOpKind::Sub => Self::Sub,
+            BinOpKind::Eq => Self::Eq,
+            BinOpKind::Ge => Self::Ge,
+            BinOpKind::Gt => Self::Gt,
+            BinOpKind::Le => Self::Le,
+            BinOpKind::Lt => Self::Lt,
+            BinOpKind::Ne => Self::Ne,
+            _ => return None,
+        })
+    }
+
+    /// Returns the user-definable operator for the given unary operator, if any.
+    ///
+    /// Note that `-` is represented by [`Sub`](Self::Sub) for both the unary and binary operator.
+    pub const fn from_un_op(op: UnOpKind) -> Option<Self> {
+        match op {
+            UnOpKind::Neg => Some(Self::Sub),
+            UnOpKind::BitNot => Some(Self::BitNot),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the operator can be used as a unary operator.
+    pub const fn is_unary(self) -> bool {
+        matches!(self, Self::BitNot | Self::Sub)
+    }
+
+    /// Returns `true` if the operator can be used as a binary operator.
+    pub const fn is_binary(self) -> bool {
+        !matches!(self, Self::BitNot)
+    }
+
+    /// Returns `true` if the operator is a comparison operator.
+    pub const fn is_cmp(self) -> bool {
+        matches!(self, Self::Eq | Self::Ge | Self::Gt | Self::Le | Self::Lt | Self::Ne)
+    }
+
     /// Returns the string representation of the operator.
     pub const fn to_str(self) -> &'static str {
         match self.to_op() {
@@ -692,7 +739,7 @@ pub struct VariableDefinition<'ast> {
     pub ty: Type<'ast>,
     pub visibility: Option<Visibility>,
     pub mutability: Option<VarMut>,
-    pub data_location: Option<DataLocation>,
+    pub data_location: Option<Spanned<DataLocation>>,
     pub override_: Option<Override<'ast>>,
     pub indexed: bool,
     pub name: Option<Ident>,
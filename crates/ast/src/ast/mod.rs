@@ -130,6 +130,9 @@ impl DocComments<'_> {
 pub struct SourceUnit<'ast> {
     /// The source unit's items.
     pub items: Box<'ast, IndexSlice<ItemId, [Item<'ast>]>>,
+    /// The SPDX license identifiers in comments outside of items, e.g. `MIT` in
+    /// `// SPDX-License-Identifier: MIT`.
+    pub licenses: BoxSlice<'ast, Spanned<Symbol>>,
 }
 
 impl fmt::Debug for SourceUnit<'_> {
@@ -142,7 +145,17 @@ impl fmt::Debug for SourceUnit<'_> {
 impl<'ast> SourceUnit<'ast> {
     /// Creates a new source unit from the given items.
     pub fn new(items: BoxSlice<'ast, Item<'ast>>) -> Self {
-        Self { items: IndexSlice::from_slice_mut(items) }
+        Self { items: IndexSlice::from_slice_mut(items), licenses: BoxSlice::default() }
+    }
+
+    /// Returns the SPDX license identifier of the source unit, if there is exactly one and it is
+    /// well-formed.
+    pub fn license(&self) -> Option<Spanned<Symbol>> {
+        let is_valid = |c: char| c.is_ascii_alphanumeric() || " ()+.-".contains(c);
+        match self.licenses[..] {
+            [license] if license.as_str().chars().all(is_valid) => Some(license),
+            _ => None,
+        }
     }
 
     /// Counts the number of contracts in the source unit.
@@ -199,14 +212,14 @@ mod tests {
         assert_size::<Span>(str!["8"]);
         assert_size::<DocComments<'_>>(str!["8"]);
 
-        assert_size::<SourceUnit<'_>>(str!["16"]);
+        assert_size::<SourceUnit<'_>>(str!["24"]);
 
         assert_size::<PragmaDirective<'_>>(str!["32"]);
         assert_size::<ImportDirective<'_>>(str!["32"]);
         assert_size::<UsingDirective<'_>>(str!["48"]);
         assert_size::<ItemContract<'_>>(str!["48"]);
         assert_size::<ItemFunction<'_>>(str!["144"]);
-        assert_size::<VariableDefinition<'_>>(str!["72"]);
+        assert_size::<VariableDefinition<'_>>(str!["88"]);
         assert_size::<ItemStruct<'_>>(str!["24"]);
         assert_size::<ItemEnum<'_>>(str!["24"]);
         assert_size::<ItemUdvt<'_>>(str!["40"]);
@@ -0,0 +1,262 @@
+//! Applying machine-applicable suggestions with `--fix` and `--fix-dry-run`.
+
+use crate::{Opts, run_compiler_with, run_default};
+use solar_interface::{
+    Result, Session, SourceMap,
+    config::ErrorFormat,
+    data_structures::sync::Mutex,
+    diagnostics::{Applicability, Diag, DiagCtxt, DynEmitter, Emitter, Level, Substitution},
+    source_map::{FileName, SourceFile},
+};
+use std::{io, ops::Range, sync::Arc};
+
+type Collected = Arc<Mutex<Vec<Substitution>>>;
+
+/// Runs the compiler, and applies or prints the machine-applicable suggestions of the emitted
+/// diagnostics.
+///
+/// With `--fix`, the diagnostics of the first run are discarded. The fixed sources are analyzed
+/// again to emit the remaining diagnostics, and the changes are reverted if they introduce new
+/// errors.
+pub(crate) fn run(opts: Opts) -> Result {
+    if opts.fix_dry_run {
+        return run_compiler_with(opts, |compiler| {
+            let sess = compiler.gcx().sess;
+            let collected = collect(&sess.dcx, false);
+            let r = run_default(compiler);
+            let fixes = Fixes::resolve(sess.source_map(), std::mem::take(&mut collected.lock()));
+            print!("{}", fixes.diff());
+            fixes.report_skipped(sess);
+            r
+        });
+    }
+
+    // The original emitter is dropped at the end of the first run. Use the human emitter, which
+    // does not write anything if there are no diagnostics, unlike e.g. the SARIF emitter.
+    let first_opts = Opts { error_format: ErrorFormat::Human, ..opts.clone() };
+    let mut fixes = None;
+    let first = run_compiler_with(first_opts, |compiler| {
+        let sess = compiler.gcx().sess;
+        let collected = collect(&sess.dcx, true);
+        let r = run_default(compiler);
+        fixes = Some(Fixes::resolve(sess.source_map(), std::mem::take(&mut collected.lock())));
+        r
+    });
+    let Some(fixes) = fixes else { return first };
+
+    let written = fixes.write();
+    run_compiler_with(opts, |compiler| {
+        let sess = compiler.gcx().sess;
+        if let Err(e) = written {
+            let mut diag = sess.dcx.err(format!("failed to write the fixed sources: {e}"));
+            if let Err(e) = fixes.revert() {
+                diag = diag.note(format!("failed to revert the changes: {e}"));
+            }
+            return Err(diag.emit());
+        }
+
+        let r = run_default(compiler);
+        if first.is_ok() && r.is_err() {
+            let diag = sess.dcx.err("the applied fixes introduced new errors");
+            let diag = match fixes.revert() {
+                Ok(()) => diag.note("the changes have been reverted"),
+                Err(e) => diag.note(format!("failed to revert the changes: {e}")),
+            };
+            return Err(diag.emit());
+        }
+        fixes.report_skipped(sess);
+        r
+    })
+}
+
+/// Wraps the emitter of `dcx` to collect the machine-applicable suggestions of the emitted
+/// diagnostics.
+///
+/// If `silent` is true, only fatal diagnostics are emitted.
+fn collect(dcx: &DiagCtxt, silent: bool) -> Collected {
+    let collected = Collected::default();
+    let fixes = Arc::clone(&collected);
+    dcx.wrap_emitter(|emitter| Box::new(FixCollector { emitter, fixes, silent }));
+    collected
+}
+
+/// Diagnostic emitter that collects machine-applicable suggestions.
+struct FixCollector {
+    emitter: Box<DynEmitter>,
+    fixes: Collected,
+    silent: bool,
+}
+
+impl Emitter for FixCollector {
+    fn emit_diagnostic(&mut self, diagnostic: &mut Diag) {
+        // Suggestions with multiple substitutions are ambiguous, so they are never applied.
+        self.fixes.lock().extend(
+            diagnostic
+                .suggestions
+                .iter()
+                .filter(|sugg| sugg.applicability == Applicability::MachineApplicable)
+                .filter_map(|sugg| match &sugg.substitutions[..] {
+                    [substitution] => Some(substitution.clone()),
+                    _ => None,
+                }),
+        );
+        if !self.silent || diagnostic.level() == Level::Fatal {
+            self.emitter.emit_diagnostic(diagnostic);
+        }
+    }
+
+    fn source_map(&self) -> Option<&Arc<SourceMap>> {
+        self.emitter.source_map()
+    }
+
+    fn supports_color(&self) -> bool {
+        self.emitter.supports_color()
+    }
+}
+
+/// A replacement of a range of text in a source file.
+struct Edit {
+    file: Arc<SourceFile>,
+    range: Range<usize>,
+    snippet: String,
+}
+
+impl PartialEq for Edit {
+    fn eq(&self, other: &Self) -> bool {
+        self.file.start_pos == other.file.start_pos
+            && self.range == other.range
+            && self.snippet == other.snippet
+    }
+}
+
+impl Edit {
+    /// Returns `true` if applying both edits is ambiguous.
+    ///
+    /// Insertions at the same position overlap, since their order is unspecified.
+    fn overlaps(&self, other: &Self) -> bool {
+        self.file.start_pos == other.file.start_pos
+            && (self.range.start == other.range.start
+                || (self.range.start < other.range.end && other.range.start < self.range.end))
+    }
+}
+
+/// The fixed contents of a source file.
+struct FileFix {
+    file: Arc<SourceFile>,
+    /// The file name, for display purposes.
+    name: String,
+    fixed: String,
+}
+
+/// The fixes resolved from the collected suggestions.
+struct Fixes {
+    files: Vec<FileFix>,
+    /// The number of suggestions that were not applied because they overlap with another one.
+    skipped: usize,
+}
+
+impl Fixes {
+    /// Resolves the given substitutions into the fixed contents of their source files.
+    ///
+    /// Substitutions are applied as a whole, in source order. A substitution that overlaps with a
+    /// previously applied one is skipped. Only files on the file system are fixed.
+    fn resolve(source_map: &SourceMap, substitutions: Vec<Substitution>) -> Self {
+        let mut resolved = substitutions
+            .into_iter()
+            .filter_map(|substitution| {
+                substitution
+                    .parts
+                    .into_iter()
+                    .map(|part| {
+                        let source = source_map.span_to_source(part.span).ok()?;
+                        matches!(source.file.name, FileName::Real(_)).then(|| Edit {
+                            file: source.file,
+                            range: source.data,
+                            snippet: part.snippet.as_str().to_string(),
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()
+            })
+            .filter(|edits| !edits.is_empty())
+            .collect::<Vec<_>>();
+        for edits in &mut resolved {
+            edits.sort_by_key(|edit| (edit.file.start_pos, edit.range.start));
+        }
+        resolved.sort_by_key(|edits| (edits[0].file.start_pos, edits[0].range.start));
+
+        let mut applied = Vec::<Edit>::new();
+        let mut skipped = 0;
+        for edits in resolved {
+            // The same suggestion may be emitted by multiple diagnostics.
+            if edits.iter().all(|edit| applied.contains(edit)) {
+                continue;
+            }
+            if edits.iter().any(|edit| applied.iter().any(|other| edit.overlaps(other))) {
+                skipped += 1;
+                continue;
+            }
+            applied.extend(edits);
+        }
+        applied.sort_by_key(|edit| (edit.file.start_pos, edit.range.start));
+
+        let files = applied
+            .chunk_by(|a, b| a.file.start_pos == b.file.start_pos)
+            .map(|edits| {
+                let file = Arc::clone(&edits[0].file);
+                let mut fixed = String::with_capacity(file.src.len());
+                let mut pos = 0;
+                for edit in edits {
+                    fixed.push_str(&file.src[pos..edit.range.start]);
+                    fixed.push_str(&edit.snippet);
+                    pos = edit.range.end;
+                }
+                fixed.push_str(&file.src[pos..]);
+                let name = source_map.filename_for_diagnostics(&file.name).to_string();
+                FileFix { file, name, fixed }
+            })
+            .collect();
+        Self { files, skipped }
+    }
+
+    /// Returns the unified diffs of all fixed files.
+    fn diff(&self) -> String {
+        self.files
+            .iter()
+            .map(|fix| {
+                similar::TextDiff::from_lines(fix.file.src.as_str(), &fix.fixed)
+                    .unified_diff()
+                    .header(&fix.name, &fix.name)
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// Writes the fixed contents to the files.
+    fn write(&self) -> io::Result<()> {
+        for fix in &self.files {
+            let FileName::Real(path) = &fix.file.name else { continue };
+            std::fs::write(path, &fix.fixed)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the original contents back to the files.
+    fn revert(&self) -> io::Result<()> {
+        for fix in &self.files {
+            let FileName::Real(path) = &fix.file.name else { continue };
+            std::fs::write(path, fix.file.src.as_str())?;
+        }
+        Ok(())
+    }
+
+    fn report_skipped(&self, sess: &Session) {
+        if self.skipped == 0 {
+            return;
+        }
+        let msg = match self.skipped {
+            1 => "1 suggestion was not applied because it overlaps with another one".to_string(),
+            n => format!("{n} suggestions were not applied because they overlap with other ones"),
+        };
+        sess.dcx.warn(msg).help("run with `--fix` again to apply them").emit();
+    }
+}
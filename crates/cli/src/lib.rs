@@ -6,12 +6,17 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 use clap::Parser as _;
-use solar_interface::{Result, Session};
+use solar_interface::{
+    Result, Session,
+    diagnostics::{DiagCtxt, error_codes},
+};
 use solar_sema::CompilerRef;
 use std::ops::ControlFlow;
 
 pub use solar_config::{self as config, Opts, UnstableOpts, version};
 
+mod fix;
+
 pub mod utils;
 
 #[cfg(all(unix, any(target_env = "gnu", target_os = "macos")))]
@@ -45,14 +50,27 @@ where
 }
 
 pub fn run_compiler_args(opts: Opts) -> Result {
+    if let Some(code) = &opts.explain {
+        return explain(&opts, code);
+    }
+    if opts.fix || opts.fix_dry_run {
+        return fix::run(opts);
+    }
     run_compiler_with(opts, run_default)
 }
 
+/// Prints the explanation of the given diagnostic code.
+fn explain(opts: &Opts, code: &str) -> Result {
+    let Some(entry) = error_codes::lookup(code) else {
+        let msg = format!("`{code}` is not a valid diagnostic code");
+        return Err(DiagCtxt::from_opts(opts).err(msg).emit());
+    };
+    print!("{}", entry.markdown());
+    Ok(())
+}
+
 fn run_default(compiler: &mut CompilerRef<'_>) -> Result {
     let sess = compiler.gcx().sess;
-    if sess.opts.language.is_yul() && !sess.opts.unstable.parse_yul {
-        return Err(sess.dcx.err("Yul is not supported yet").emit());
-    }
 
     let mut pcx = compiler.parse();
 
@@ -92,6 +110,9 @@ fn run_default(compiler: &mut CompilerRef<'_>) -> Result {
     compiler.drop_asts();
     let ControlFlow::Continue(()) = compiler.analysis()? else { return Ok(()) };
 
+    // Only report unused directives once all of the warnings they may suppress have been emitted.
+    sess.dcx.report_unused_suppressions();
+
     Ok(())
 }
 
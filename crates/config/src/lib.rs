@@ -137,6 +137,15 @@ impl EvmVersion {
     pub fn has_push0(self) -> bool {
         self >= Self::Shanghai
     }
+    pub fn has_blob_hash(self) -> bool {
+        self >= Self::Cancun
+    }
+    pub fn has_mcopy(self) -> bool {
+        self >= Self::Cancun
+    }
+    pub fn supports_transient_storage(self) -> bool {
+        self >= Self::Cancun
+    }
 }
 
 str_enum! {
@@ -146,12 +155,22 @@ str_enum! {
     pub enum CompilerOutput {
         /// JSON ABI.
         Abi,
-        // /// Creation bytecode.
-        // Bin,
-        // /// Runtime bytecode.
-        // BinRuntime,
+        /// Creation bytecode.
+        Bin,
+        /// Runtime bytecode.
+        BinRuntime,
+        /// NatSpec developer documentation.
+        Devdoc,
         /// Function signature hashes.
         Hashes,
+        /// Yul intermediate representation.
+        Ir,
+        /// Yul intermediate representation, as a JSON AST.
+        IrAst,
+        /// Storage layout of state variables.
+        StorageLayout,
+        /// NatSpec user documentation.
+        Userdoc,
     }
 }
 
@@ -202,6 +221,8 @@ str_enum! {
         Json,
         /// Rustc-like JSON output.
         RustcJson,
+        /// SARIF 2.1.0 log, written as a single document at the end of compilation.
+        Sarif,
     }
 }
 
@@ -92,6 +92,9 @@ pub struct Opts {
     /// Stop execution after the given compiler stage.
     #[cfg_attr(feature = "clap", arg(long, value_enum))]
     pub stop_after: Option<CompilerStage>,
+    /// Do not check the `pragma solidity` version requirements of the sources.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub ignore_pragma_version: bool,
 
     /// Directory to write output files.
     #[cfg_attr(feature = "clap", arg(long, value_hint = ValueHint::DirPath))]
@@ -99,6 +102,18 @@ pub struct Opts {
     /// Comma separated list of types of output for the compiler to emit.
     #[cfg_attr(feature = "clap", arg(long, value_delimiter = ','))]
     pub emit: Vec<CompilerOutput>,
+    /// Apply the machine-applicable suggestions of the emitted diagnostics to the source files.
+    ///
+    /// The fixed sources are analyzed again, and the changes are reverted if they introduce new
+    /// errors.
+    #[cfg_attr(feature = "clap", arg(long, conflicts_with = "fix_dry_run"))]
+    pub fix: bool,
+    /// Print the changes that `--fix` would make as unified diffs, without writing them.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub fix_dry_run: bool,
+    /// Print a detailed explanation of the given diagnostic code, such as `9914`.
+    #[cfg_attr(feature = "clap", arg(long, value_name = "CODE"))]
+    pub explain: Option<String>,
 
     /// Coloring.
     #[cfg_attr(
@@ -144,6 +159,49 @@ pub struct Opts {
     /// Whether to disable warnings.
     #[cfg_attr(feature = "clap", arg(help_heading = "Display options", long))]
     pub no_warnings: bool,
+    /// Suppress the warnings with the given diagnostic codes.
+    ///
+    /// `warnings` refers to all warnings. Specific codes take precedence over `warnings`.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            help_heading = "Display options",
+            long,
+            short = 'A',
+            value_name = "CODE",
+            value_delimiter = ','
+        )
+    )]
+    pub allow: Vec<String>,
+    /// Emit the warnings with the given diagnostic codes.
+    ///
+    /// Used to re-enable specific warnings, e.g. `-A warnings -W 2018`.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            help_heading = "Display options",
+            long,
+            short = 'W',
+            value_name = "CODE",
+            value_delimiter = ','
+        )
+    )]
+    pub warn: Vec<String>,
+    /// Turn the warnings with the given diagnostic codes into errors.
+    ///
+    /// `warnings` refers to all warnings, e.g. `-D warnings`. If a code is passed to multiple
+    /// flags, `--deny` takes precedence over `--warn`, which takes precedence over `--allow`.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            help_heading = "Display options",
+            long,
+            short = 'D',
+            value_name = "CODE",
+            value_delimiter = ','
+        )
+    )]
+    pub deny: Vec<String>,
 
     /// Unstable flags. WARNING: these are completely unstable, and may change at any time.
     ///
@@ -270,10 +328,6 @@ pub struct UnstableOpts {
     #[cfg_attr(feature = "clap", arg(long))]
     pub track_diagnostics: bool,
 
-    /// Enables parsing Yul files for testing.
-    #[cfg_attr(feature = "clap", arg(long))]
-    pub parse_yul: bool,
-
     /// Disables import resolution.
     #[cfg_attr(feature = "clap", arg(long))]
     pub no_resolve_imports: bool,
@@ -300,6 +354,10 @@ pub struct UnstableOpts {
     #[cfg_attr(feature = "clap", arg(long))]
     pub typeck: bool,
 
+    /// Run the Yul interpreter on Yul objects, and print the trace and the final state.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub interpret_yul: bool,
+
     // ----------------------------------------
     // Please add new options above this point!
     // ----------------------------------------
@@ -2,17 +2,24 @@ use super::{
     BugAbort, Diag, DiagBuilder, DiagMsg, DynEmitter, EmissionGuarantee, EmittedDiagnostics,
     ErrorGuaranteed, FatalAbort, HumanBufferEmitter, Level, SilentEmitter, emitter::HumanEmitter,
 };
-use crate::{Result, SourceMap};
+use crate::{Result, SourceMap, Span};
 use anstream::ColorChoice;
 use solar_config::{ErrorFormat, Opts};
-use solar_data_structures::{map::FxHashSet, sync::Mutex};
+use solar_data_structures::{
+    map::{FxHashMap, FxHashSet, FxIndexMap},
+    sync::Mutex,
+};
 use std::{borrow::Cow, fmt, hash::BuildHasher, num::NonZeroUsize, sync::Arc};
 
 /// Flags that control the behaviour of a [`DiagCtxt`].
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct DiagCtxtFlags {
     /// If false, warning-level lints are suppressed.
     pub can_emit_warnings: bool,
+    /// The levels of warnings, keyed by their diagnostic code, or by `warnings` for all warnings.
+    ///
+    /// The level of a specific code takes precedence over the level of `warnings`.
+    pub warning_levels: FxHashMap<String, WarningLevel>,
     /// If Some, the Nth error-level diagnostic is upgraded to bug-level.
     pub treat_err_as_bug: Option<NonZeroUsize>,
     /// If true, identical diagnostics are reported only once.
@@ -26,6 +33,7 @@ impl Default for DiagCtxtFlags {
     fn default() -> Self {
         Self {
             can_emit_warnings: true,
+            warning_levels: FxHashMap::default(),
             treat_err_as_bug: None,
             deduplicate_diagnostics: true,
             track_diagnostics: cfg!(debug_assertions),
@@ -40,11 +48,64 @@ impl DiagCtxtFlags {
     /// - `unstable.ui_testing`
     /// - `unstable.track_diagnostics`
     /// - `no_warnings`
+    /// - `allow`, `warn`, and `deny`
     pub fn update_from_opts(&mut self, opts: &Opts) {
         self.deduplicate_diagnostics &= !opts.unstable.ui_testing;
         self.track_diagnostics &= !opts.unstable.ui_testing;
         self.track_diagnostics |= opts.unstable.track_diagnostics;
-        self.can_emit_warnings |= !opts.no_warnings;
+        self.can_emit_warnings &= !opts.no_warnings;
+        // The strictest level wins if a code is passed to multiple flags.
+        for (codes, level) in [
+            (&opts.allow, WarningLevel::Allow),
+            (&opts.warn, WarningLevel::Warn),
+            (&opts.deny, WarningLevel::Deny),
+        ] {
+            self.warning_levels.extend(codes.iter().map(|code| (code.clone(), level)));
+        }
+    }
+}
+
+/// The level of a warning, set with the `-A`, `-W` and `-D` flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WarningLevel {
+    /// Suppress the warning.
+    Allow,
+    /// Emit the warning.
+    Warn,
+    /// Emit the warning as an error.
+    Deny,
+}
+
+impl WarningLevel {
+    /// Returns the command-line flag that sets this level.
+    pub fn flag(self) -> &'static str {
+        match self {
+            Self::Allow => "-A",
+            Self::Warn => "-W",
+            Self::Deny => "-D",
+        }
+    }
+}
+
+/// A comment directive that suppresses warnings in a range of source code, such as
+/// `// solar-disable-next-line 3628`.
+#[derive(Clone, Debug)]
+pub struct Suppression {
+    /// The span of the comment containing the directive.
+    pub directive: Span,
+    /// The suppressed source code.
+    pub range: Span,
+    /// The suppressed diagnostic codes. If empty, all warnings are suppressed.
+    pub codes: Vec<String>,
+}
+
+impl Suppression {
+    /// Returns `true` if this suppresses the given warning.
+    fn matches(&self, diagnostic: &Diag) -> bool {
+        let Some(span) = diagnostic.span.primary_span() else { return false };
+        self.range.lo() <= span.lo()
+            && span.lo() <= self.range.hi()
+            && (self.codes.is_empty() || diagnostic.id().is_some_and(|id| self.codes.contains(&id)))
     }
 }
 
@@ -91,6 +152,10 @@ struct DiagCtxtInner {
     /// This set contains a hash of every diagnostic that has been emitted by this `DiagCtxt`.
     /// These hashes are used to avoid emitting the same error twice.
     emitted_diagnostics: FxHashSet<u64>,
+
+    /// The warning suppression directives, keyed by the span of their comment, and whether they
+    /// have suppressed any warnings.
+    suppressions: FxIndexMap<Span, (Suppression, bool)>,
 }
 
 impl DiagCtxt {
@@ -107,6 +172,7 @@ impl DiagCtxt {
                 note_count: 0,
                 deduplicated_note_count: 0,
                 emitted_diagnostics: FxHashSet::default(),
+                suppressions: FxIndexMap::default(),
             }),
         }
     }
@@ -190,6 +256,13 @@ impl DiagCtxt {
                     .terminal_width(opts.diagnostic_width);
                 Box::new(json)
             }
+            #[cfg(feature = "json")]
+            ErrorFormat::Sarif => {
+                let writer = Box::new(std::io::BufWriter::new(std::io::stderr()));
+                let sarif = crate::diagnostics::SarifEmitter::new(writer, source_map)
+                    .pretty(opts.pretty_json_err);
+                Box::new(sarif)
+            }
             format => unimplemented!("{format:?}"),
         };
         Self::new(emitter).with_flags(|flags| flags.update_from_opts(opts))
@@ -272,6 +345,34 @@ impl DiagCtxt {
         self.inner.lock().emit_diagnostic_without_consuming(diagnostic)
     }
 
+    /// Registers a warning suppression directive.
+    ///
+    /// Warnings whose primary span starts in the suppressed range are not emitted. Registering the
+    /// same directive multiple times has no effect.
+    pub fn add_suppression(&self, suppression: Suppression) {
+        self.inner.lock().suppressions.entry(suppression.directive).or_insert((suppression, false));
+    }
+
+    /// Emits a warning for each suppression directive that has not suppressed any warnings.
+    ///
+    /// This should only be called once all diagnostics have been emitted.
+    pub fn report_unused_suppressions(&self) {
+        let unused = self
+            .inner
+            .lock()
+            .suppressions
+            .values()
+            .filter(|(_, used)| !used)
+            .map(|(suppression, _)| suppression.directive)
+            .collect::<Vec<_>>();
+        for span in unused {
+            self.warn("unused suppression directive")
+                .span(span)
+                .help("no warnings were suppressed by this directive; consider removing it")
+                .emit();
+        }
+    }
+
     /// Returns the number of errors that have been emitted, including duplicates.
     pub fn err_count(&self) -> usize {
         self.inner.lock().err_count
@@ -407,6 +508,13 @@ impl DiagCtxtInner {
         &mut self,
         diagnostic: &mut Diag,
     ) -> Result<(), ErrorGuaranteed> {
+        if diagnostic.level == Level::Warning {
+            if self.suppress(diagnostic) {
+                return Ok(());
+            }
+            self.apply_warning_level(diagnostic);
+        }
+
         if diagnostic.level == Level::Warning && !self.flags.can_emit_warnings {
             return Ok(());
         }
@@ -460,6 +568,41 @@ impl DiagCtxtInner {
         }
     }
 
+    /// Returns `true` if the warning is suppressed by a directive, marking all matching directives
+    /// as used.
+    fn suppress(&mut self, diagnostic: &Diag) -> bool {
+        let mut suppressed = false;
+        for (suppression, used) in self.suppressions.values_mut() {
+            if suppression.matches(diagnostic) {
+                *used = true;
+                suppressed = true;
+            }
+        }
+        suppressed
+    }
+
+    /// Adjusts the level of a warning according to [`DiagCtxtFlags::warning_levels`], recording
+    /// the flag that changed it.
+    fn apply_warning_level(&self, diagnostic: &mut Diag) {
+        let levels = &self.flags.warning_levels;
+        let code = diagnostic.id();
+        let Some((key, level)) = code
+            .and_then(|code| levels.get_key_value(&code))
+            .or_else(|| levels.get_key_value("warnings"))
+        else {
+            return;
+        };
+        match level {
+            WarningLevel::Allow => diagnostic.level = Level::Allow,
+            WarningLevel::Warn => {}
+            WarningLevel::Deny => {
+                diagnostic.level = Level::Error;
+                diagnostic
+                    .note(format!("`{} {key}` turned this warning into an error", level.flag()));
+            }
+        }
+    }
+
     fn print_error_count(&mut self) -> Result {
         // self.emit_stashed_diagnostics();
 
@@ -4,7 +4,7 @@ use crate::{
     diagnostics::{
         ConfusionType, DiagId, DiagMsg, Level, MultiSpan, SpanLabel, Style, SubDiagnostic,
         SuggestionStyle, Suggestions, detect_confusion_type, emitter::normalize_whitespace,
-        is_different,
+        error_codes, is_different,
     },
     source_map::{FileName, SourceFile},
 };
@@ -214,12 +214,15 @@ impl HumanEmitter {
         };
 
         if let Some(c) = code {
-            title = title.id(c.as_string());
-            // Unlike rustc, there is no URL associated with DiagId yet.
-            // TODO: Add URL mapping
-            // if let TerminalUrl::Yes = self.terminal_url {
-            //     title = title.id_url(format!("<TODO_URL>/error_codes/{c}.html"));
-            // }
+            let id = c.as_string();
+            // Link to the explanation only if the output is a terminal, since the hyperlink is
+            // written as an escape sequence.
+            if self.supports_color()
+                && let Some(entry) = error_codes::lookup(&id)
+            {
+                title = title.id_url(entry.url());
+            }
+            title = title.id(id);
         }
 
         let mut report = vec![];
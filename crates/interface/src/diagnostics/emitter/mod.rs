@@ -10,6 +10,11 @@ mod json;
 #[cfg(feature = "json")]
 pub use json::JsonEmitter;
 
+#[cfg(feature = "json")]
+mod sarif;
+#[cfg(feature = "json")]
+pub use sarif::SarifEmitter;
+
 mod mem;
 pub use mem::InMemoryEmitter;
 
@@ -0,0 +1,424 @@
+use super::{Emitter, io_panic};
+use crate::{
+    Span,
+    diagnostics::{CodeSuggestion, Diag, Level, MultiSpan},
+    source_map::{FileName, SourceMap},
+};
+use serde::Serialize;
+use solar_data_structures::map::{FxIndexMap, FxIndexSet};
+use std::{collections::BTreeMap, fmt::Write, io, path::Path, sync::Arc};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+/// The base URI ID that relative artifact URIs are resolved against.
+const SRCROOT: &str = "%SRCROOT%";
+
+/// Diagnostic emitter that emits diagnostics as a [SARIF 2.1.0] log.
+///
+/// Unlike other emitters, diagnostics are collected and the whole log is written as a single
+/// document when the emitter is dropped.
+///
+/// Diagnostics without a location, like the final error count, are reported as tool execution
+/// notifications instead of results.
+///
+/// [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+pub struct SarifEmitter {
+    writer: Box<dyn io::Write + Send>,
+    source_map: Arc<SourceMap>,
+    pretty: bool,
+
+    rules: FxIndexSet<String>,
+    results: Vec<SarifResult>,
+    notifications: Vec<Notification>,
+    execution_successful: bool,
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diagnostic: &mut Diag) {
+        let level = to_sarif_level(diagnostic.level);
+        if matches!(diagnostic.level, Level::Bug | Level::Fatal) {
+            self.execution_successful = false;
+        }
+
+        let mut text = diagnostic.label().into_owned();
+        for child in &diagnostic.children {
+            if child.span.primary_span().is_none() {
+                let _ = write!(text, "\n{}: {}", child.level.to_str(), child.label());
+            }
+        }
+        let message = Message { text };
+
+        if diagnostic.span.primary_span().is_none() {
+            self.notifications.push(Notification {
+                level,
+                message,
+                descriptor: diagnostic.id().map(|id| ReportingDescriptorReference { id }),
+            });
+            return;
+        }
+
+        let rule = diagnostic.id().map(|id| {
+            let (index, _) = self.rules.insert_full(id.clone());
+            (id, index)
+        });
+        let (locations, mut related_locations) = self.locations(&diagnostic.span);
+        for child in &diagnostic.children {
+            let (primary, secondary) = self.locations(&child.span);
+            related_locations.extend(primary.into_iter().map(|mut location| {
+                if location.message.is_none() {
+                    location.message = Some(Message { text: child.label().into_owned() });
+                }
+                location
+            }));
+            related_locations.extend(secondary);
+        }
+
+        self.results.push(SarifResult {
+            rule_index: rule.as_ref().map(|&(_, index)| index),
+            rule_id: rule.map(|(id, _)| id),
+            level,
+            message,
+            locations,
+            related_locations,
+            fixes: diagnostic.suggestions.iter().flat_map(|sugg| self.fixes(sugg)).collect(),
+        });
+    }
+
+    fn source_map(&self) -> Option<&Arc<SourceMap>> {
+        Some(&self.source_map)
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        if let Err(e) = self.emit_log()
+            && !std::thread::panicking()
+        {
+            io_panic(e);
+        }
+    }
+}
+
+impl SarifEmitter {
+    /// Creates a new `SarifEmitter` that writes to given writer.
+    pub fn new(writer: Box<dyn io::Write + Send>, source_map: Arc<SourceMap>) -> Self {
+        Self {
+            writer,
+            source_map,
+            pretty: false,
+            rules: Default::default(),
+            results: Vec::new(),
+            notifications: Vec::new(),
+            execution_successful: true,
+        }
+    }
+
+    /// Sets whether to pretty print the JSON.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Returns the locations of the primary spans, and the related locations of the labeled
+    /// secondary spans.
+    fn locations(&self, msp: &MultiSpan) -> (Vec<Location>, Vec<Location>) {
+        let (mut primary, mut secondary) = (Vec::new(), Vec::new());
+        for label in msp.span_labels() {
+            if label.span.is_dummy() {
+                continue;
+            }
+            let location = Location {
+                physical_location: self.physical_location(label.span),
+                message: label.label.as_ref().map(|msg| Message { text: msg.as_str().into() }),
+            };
+            if label.is_primary {
+                primary.push(location);
+            } else {
+                secondary.push(location);
+            }
+        }
+        (primary, secondary)
+    }
+
+    fn physical_location(&self, span: Span) -> PhysicalLocation {
+        let (artifact_location, region) = self.artifact_region(span);
+        PhysicalLocation { artifact_location, region }
+    }
+
+    /// Returns one fix for every alternative substitution of the suggestion.
+    fn fixes<'a>(&'a self, sugg: &'a CodeSuggestion) -> impl Iterator<Item = Fix> + 'a {
+        sugg.substitutions.iter().map(move |substitution| {
+            let mut changes = FxIndexMap::<String, ArtifactChange>::default();
+            for part in &substitution.parts {
+                let (artifact_location, deleted_region) = self.artifact_region(part.span);
+                changes
+                    .entry(artifact_location.uri.clone())
+                    .or_insert_with(|| ArtifactChange { artifact_location, replacements: vec![] })
+                    .replacements
+                    .push(Replacement {
+                        deleted_region,
+                        inserted_content: ArtifactContent { text: part.snippet.as_str().into() },
+                    });
+            }
+            Fix {
+                description: Message { text: sugg.msg.as_str().into() },
+                artifact_changes: changes.into_values().collect(),
+            }
+        })
+    }
+
+    fn artifact_region(&self, span: Span) -> (ArtifactLocation, Region) {
+        let sm = &*self.source_map;
+        let start = sm.lookup_char_pos(span.lo());
+        let end = sm.lookup_char_pos(span.hi());
+        let byte_offset = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        let region = Region {
+            start_line: start.line,
+            start_column: start.col.0 + 1,
+            end_line: end.line,
+            end_column: end.col.0 + 1,
+            byte_offset,
+            byte_length: byte_end - byte_offset,
+        };
+        (self.artifact_location(&start.file.name), region)
+    }
+
+    /// Returns the location of the file, relative to the base path if possible.
+    fn artifact_location(&self, name: &FileName) -> ArtifactLocation {
+        let path = self.source_map.filename_for_diagnostics(name).to_string();
+        match name {
+            FileName::Real(_) if Path::new(&path).is_absolute() => {
+                ArtifactLocation { uri: file_uri(Path::new(&path)), uri_base_id: None }
+            }
+            FileName::Real(_) => {
+                ArtifactLocation { uri: path_to_uri(&path), uri_base_id: Some(SRCROOT) }
+            }
+            FileName::Stdin | FileName::Custom(_) => {
+                ArtifactLocation { uri: path_to_uri(&path), uri_base_id: None }
+            }
+        }
+    }
+
+    fn emit_log(&mut self) -> io::Result<()> {
+        let base_path = self.source_map.base_path().map(|path| {
+            let mut uri = file_uri(&path);
+            if !uri.ends_with('/') {
+                uri.push('/');
+            }
+            uri
+        });
+        let log = SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: [Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: "solar",
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: "https://github.com/paradigmxyz/solar",
+                        rules: std::mem::take(&mut self.rules)
+                            .into_iter()
+                            .map(|id| ReportingDescriptor { id })
+                            .collect(),
+                    },
+                },
+                invocations: [Invocation {
+                    execution_successful: self.execution_successful,
+                    tool_execution_notifications: std::mem::take(&mut self.notifications),
+                }],
+                original_uri_base_ids: base_path
+                    .map(|uri| {
+                        BTreeMap::from([(SRCROOT, ArtifactLocation { uri, uri_base_id: None })])
+                    })
+                    .unwrap_or_default(),
+                column_kind: "unicodeCodePoints",
+                results: std::mem::take(&mut self.results),
+            }],
+        };
+        if self.pretty {
+            serde_json::to_writer_pretty(&mut *self.writer, &log)
+        } else {
+            serde_json::to_writer(&mut *self.writer, &log)
+        }?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+}
+
+fn to_sarif_level(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::Fatal | Level::Error => "error",
+        Level::Warning => "warning",
+        Level::Note | Level::OnceNote | Level::FailureNote | Level::Help | Level::OnceHelp => {
+            "note"
+        }
+        Level::Allow => "none",
+    }
+}
+
+/// Returns the `file` URI of an absolute path.
+fn file_uri(path: &Path) -> String {
+    let path = path_to_uri(&path.to_string_lossy());
+    if path.starts_with('/') { format!("file://{path}") } else { format!("file:///{path}") }
+}
+
+/// Percent-encodes a path to be used as a URI reference, using `/` as the separator.
+fn path_to_uri(path: &str) -> String {
+    let mut uri = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'\\' => uri.push('/'),
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => {
+                let _ = write!(uri, "%{byte:02X}");
+            }
+        }
+    }
+    uri
+}
+
+#[derive(Serialize)]
+struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    invocations: [Invocation; 1],
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    original_uri_base_ids: BTreeMap<&'static str, ArtifactLocation>,
+    column_kind: &'static str,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: ToolComponent,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<ReportingDescriptor>,
+}
+
+#[derive(Serialize)]
+struct ReportingDescriptor {
+    id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Invocation {
+    execution_successful: bool,
+    tool_execution_notifications: Vec<Notification>,
+}
+
+#[derive(Serialize)]
+struct Notification {
+    level: &'static str,
+    message: Message,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    descriptor: Option<ReportingDescriptorReference>,
+}
+
+#[derive(Serialize)]
+struct ReportingDescriptorReference {
+    id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, in Unicode code points.
+    start_column: usize,
+    end_line: usize,
+    /// Exclusive.
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: ArtifactContent,
+}
+
+#[derive(Serialize)]
+struct ArtifactContent {
+    text: String,
+}
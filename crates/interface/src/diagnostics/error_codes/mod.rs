@@ -0,0 +1,136 @@
+//! Long-form explanations of diagnostic codes.
+//!
+//! Each code emitted with [`error_code!`](crate::error_code) has an entry in this registry, written
+//! in Markdown in `error_codes/<code>.md`. An entry starts with a one-line title, followed by a
+//! long explanation, an erroneous code example, and a corrected code example.
+//!
+//! Explanations are printed with `solar --explain <code>`.
+
+/// The URL of the directory containing the explanations.
+const BASE_URL: &str =
+    "https://github.com/paradigmxyz/solar/blob/main/crates/interface/src/diagnostics/error_codes";
+
+/// The explanation of a diagnostic code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ErrorCode {
+    code: &'static str,
+    markdown: &'static str,
+}
+
+impl ErrorCode {
+    /// Returns the diagnostic code, such as `9914`.
+    #[inline]
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// Returns the title of the explanation.
+    pub fn title(&self) -> &'static str {
+        let line = self.markdown.lines().next().unwrap_or_default();
+        line.trim_start_matches('#').trim()
+    }
+
+    /// Returns the full explanation in Markdown, including the title and the examples.
+    #[inline]
+    pub fn markdown(&self) -> &'static str {
+        self.markdown
+    }
+
+    /// Returns the URL of the explanation.
+    pub fn url(&self) -> String {
+        format!("{BASE_URL}/{}.md", self.code)
+    }
+}
+
+/// Returns the explanation of the given diagnostic code, if any.
+pub fn lookup(code: &str) -> Option<&'static ErrorCode> {
+    ERROR_CODES.binary_search_by(|entry| entry.code.cmp(code)).ok().map(|i| &ERROR_CODES[i])
+}
+
+macro_rules! error_codes {
+    ($($code:literal),* $(,)?) => {
+        /// All diagnostic codes that have an explanation, sorted by code.
+        pub static ERROR_CODES: &[ErrorCode] = &[$(
+            ErrorCode { code: stringify!($code), markdown: include_str!(concat!(stringify!($code), ".md")) },
+        )*];
+    };
+}
+
+error_codes! {
+    1078, 1114, 1395, 1397, 1408, 1430, 1443, 1452, 1456, 1469, 1503, 1536, 1561, 1574, 1581,
+    1699, 1750, 1878, 1884, 1933, 1990, 2018, 2025, 2353, 2527, 2592, 2604, 2658, 2718, 2968,
+    3069, 3083, 3100, 3224, 3242, 3320, 3335, 3441, 3445, 3464, 3517, 3622, 3628, 3656, 3716,
+    3773, 3812, 3859, 3881, 3942, 3950, 3969, 3997, 4006, 4113, 4117, 4130, 4167, 4327, 4334,
+    4487, 4520, 4593, 4599, 4619, 4656, 4682, 4705, 4713, 4718, 4731, 4822, 4990, 5015, 5142,
+    5225, 5256, 5332, 5424, 5430, 5631, 5700, 5740, 5761, 5856, 5887, 5967, 6052, 6162, 6166,
+    6243, 6252, 6321, 6480, 6546, 6564, 6578, 6612, 6617, 6672, 6679, 6792, 6959, 7000, 7110,
+    7484, 7615, 7721, 7723, 7733, 7743, 7755, 7756, 7775, 7792, 7944, 8022, 8149, 8198, 8314,
+    8678, 8936, 8961, 9068, 9098, 9114, 9222, 9231, 9272, 9322, 9397, 9456, 9467, 9592, 9739,
+    9857, 9914, 9942,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn entries_are_well_formed() {
+        assert!(ERROR_CODES.is_sorted_by(|a, b| a.code < b.code), "entries must be sorted");
+        for entry in ERROR_CODES {
+            let code = entry.code;
+            assert!(code.len() == 4 && code.bytes().all(|b| b.is_ascii_digit()), "{code}");
+            assert!(entry.markdown.starts_with("# "), "{code}: missing title");
+            assert!(!entry.title().is_empty(), "{code}: empty title");
+            let erroneous = entry.markdown.find("Erroneous code example:");
+            let corrected = entry.markdown.find("Corrected code example:");
+            assert!(erroneous.is_some(), "{code}: missing erroneous code example");
+            assert!(corrected.is_some(), "{code}: missing corrected code example");
+            assert!(erroneous < corrected, "{code}: examples are in the wrong order");
+            assert!(entry.markdown.matches("```").count() >= 4, "{code}: missing code blocks");
+            assert_eq!(lookup(code), Some(entry));
+        }
+    }
+
+    /// Checks that every code used in the compiler crates has an entry, and vice versa.
+    #[test]
+    fn every_emitted_code_has_an_entry() {
+        let crates = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+        let mut used = Vec::new();
+        collect_codes(crates, &mut used);
+        used.sort_unstable();
+        used.dedup();
+        assert!(!used.is_empty());
+
+        let missing = used.iter().filter(|code| lookup(code).is_none()).collect::<Vec<_>>();
+        assert!(missing.is_empty(), "missing explanations for codes: {missing:?}");
+        let unused = ERROR_CODES
+            .iter()
+            .map(ErrorCode::code)
+            .filter(|code| !used.iter().any(|used| used == code))
+            .collect::<Vec<_>>();
+        assert!(unused.is_empty(), "explanations for codes that are never emitted: {unused:?}");
+    }
+
+    #[allow(clippy::disallowed_methods)] // Not compiler input.
+    fn collect_codes(dir: &Path, codes: &mut Vec<String>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                collect_codes(&path, codes);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                let src = std::fs::read_to_string(&path).unwrap();
+                // Split so that this file does not match.
+                let pat = concat!("error_code", "!(");
+                // Skip examples in comments.
+                for line in src.lines().filter(|line| !line.trim_start().starts_with("//")) {
+                    for (i, _) in line.match_indices(pat) {
+                        let code = line[i + pat.len()..].split(')').next().unwrap();
+                        let code = code.parse::<u32>().unwrap_or_else(|_| panic!("{line}"));
+                        codes.push(format!("{code:04}"));
+                    }
+                }
+            }
+        }
+    }
+}
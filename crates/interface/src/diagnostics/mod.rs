@@ -17,15 +17,17 @@ mod builder;
 pub use builder::{DiagBuilder, EmissionGuarantee};
 
 mod context;
-pub use context::{DiagCtxt, DiagCtxtFlags};
+pub use context::{DiagCtxt, DiagCtxtFlags, Suppression, WarningLevel};
 
 mod emitter;
-#[cfg(feature = "json")]
-pub use emitter::JsonEmitter;
 pub use emitter::{
     DynEmitter, Emitter, HumanBufferEmitter, HumanEmitter, InMemoryEmitter, LocalEmitter,
     SilentEmitter,
 };
+#[cfg(feature = "json")]
+pub use emitter::{JsonEmitter, SarifEmitter};
+
+pub mod error_codes;
 
 mod message;
 pub use message::{DiagMsg, MultiSpan, SpanLabel};
@@ -1410,6 +1412,317 @@ help: consider changing visibility and mutability
         );
     }
 
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_sarif_suggestion() {
+        let (var_span, var_sugg) = (Span::new(BytePos(66), BytePos(72)), "myVar");
+        let fn_span = Span::new(BytePos(30), BytePos(33));
+        let mut diag = Diag::new(Level::Warning, "mutable variables should use mixedCase");
+        diag.code(DiagId::new_str("1234"))
+            .span(var_span)
+            .span_note(fn_span, "declared in this function")
+            .help("see the style guide")
+            .span_suggestion(
+                var_span,
+                "rename the variable",
+                var_sugg,
+                Applicability::MachineApplicable,
+            );
+
+        assert_data_eq!(
+            emit_sarif_diagnostics(vec![diag, Diag::new(Level::Error, "aborting")]),
+            str![[r#"
+{
+  "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+  "runs": [
+    {
+      "columnKind": "unicodeCodePoints",
+      "invocations": [
+        {
+          "executionSuccessful": true,
+          "toolExecutionNotifications": [
+            {
+              "level": "error",
+              "message": {
+                "text": "aborting"
+              }
+            }
+          ]
+        }
+      ],
+      "results": [
+        {
+          "fixes": [
+            {
+              "artifactChanges": [
+                {
+                  "artifactLocation": {
+                    "uri": "%3Ctest.sol%3E"
+                  },
+                  "replacements": [
+                    {
+                      "deletedRegion": {
+                        "byteLength": 6,
+                        "byteOffset": 66,
+                        "endColumn": 23,
+                        "endLine": 4,
+                        "startColumn": 17,
+                        "startLine": 4
+                      },
+                      "insertedContent": {
+                        "text": "myVar"
+                      }
+                    }
+                  ]
+                }
+              ],
+              "description": {
+                "text": "rename the variable"
+              }
+            }
+          ],
+          "level": "warning",
+          "locations": [
+            {
+              "physicalLocation": {
+                "artifactLocation": {
+                  "uri": "%3Ctest.sol%3E"
+                },
+                "region": {
+                  "byteLength": 6,
+                  "byteOffset": 66,
+                  "endColumn": 23,
+                  "endLine": 4,
+                  "startColumn": 17,
+                  "startLine": 4
+                }
+              }
+            }
+          ],
+          "message": {
+            "text": "mutable variables should use mixedCase\nhelp: see the style guide"
+          },
+          "relatedLocations": [
+            {
+              "message": {
+                "text": "declared in this function"
+              },
+              "physicalLocation": {
+                "artifactLocation": {
+                  "uri": "%3Ctest.sol%3E"
+                },
+                "region": {
+                  "byteLength": 3,
+                  "byteOffset": 30,
+                  "endColumn": 17,
+                  "endLine": 3,
+                  "startColumn": 14,
+                  "startLine": 3
+                }
+              }
+            }
+          ],
+          "ruleId": "1234",
+          "ruleIndex": 0
+        }
+      ],
+      "tool": {
+        "driver": {
+          "informationUri": "https://github.com/paradigmxyz/solar",
+          "name": "solar",
+          "rules": [
+            {
+              "id": "1234"
+            }
+          ],
+          "version": "[VERSION]"
+        }
+      }
+    }
+  ],
+  "version": "2.1.0"
+}
+"#]]
+            .is(snapbox::data::DataFormat::Json)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_sarif_error_and_warning() {
+        let (var_span, fn_span) =
+            (Span::new(BytePos(66), BytePos(72)), Span::new(BytePos(30), BytePos(33)));
+        let mut error = Diag::new(Level::Error, "undeclared identifier");
+        error.code(DiagId::new_str("7576")).span(var_span);
+        let mut warning =
+            Diag::new(Level::Warning, "function state mutability can be restricted to pure");
+        warning.span(fn_span).span_suggestion(
+            Span::new(BytePos(43), BytePos(47)),
+            "change the state mutability",
+            "pure",
+            Applicability::MachineApplicable,
+        );
+
+        assert_data_eq!(
+            emit_sarif_diagnostics(vec![
+                error,
+                warning,
+                Diag::new(Level::Error, "aborting due to 1 previous error; 1 warning emitted"),
+            ]),
+            str![[r#"
+{
+  "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+  "runs": [
+    {
+      "columnKind": "unicodeCodePoints",
+      "invocations": [
+        {
+          "executionSuccessful": true,
+          "toolExecutionNotifications": [
+            {
+              "level": "error",
+              "message": {
+                "text": "aborting due to 1 previous error; 1 warning emitted"
+              }
+            }
+          ]
+        }
+      ],
+      "results": [
+        {
+          "level": "error",
+          "locations": [
+            {
+              "physicalLocation": {
+                "artifactLocation": {
+                  "uri": "%3Ctest.sol%3E"
+                },
+                "region": {
+                  "byteLength": 6,
+                  "byteOffset": 66,
+                  "endColumn": 23,
+                  "endLine": 4,
+                  "startColumn": 17,
+                  "startLine": 4
+                }
+              }
+            }
+          ],
+          "message": {
+            "text": "undeclared identifier"
+          },
+          "ruleId": "7576",
+          "ruleIndex": 0
+        },
+        {
+          "fixes": [
+            {
+              "artifactChanges": [
+                {
+                  "artifactLocation": {
+                    "uri": "%3Ctest.sol%3E"
+                  },
+                  "replacements": [
+                    {
+                      "deletedRegion": {
+                        "byteLength": 4,
+                        "byteOffset": 43,
+                        "endColumn": 31,
+                        "endLine": 3,
+                        "startColumn": 27,
+                        "startLine": 3
+                      },
+                      "insertedContent": {
+                        "text": "pure"
+                      }
+                    }
+                  ]
+                }
+              ],
+              "description": {
+                "text": "change the state mutability"
+              }
+            }
+          ],
+          "level": "warning",
+          "locations": [
+            {
+              "physicalLocation": {
+                "artifactLocation": {
+                  "uri": "%3Ctest.sol%3E"
+                },
+                "region": {
+                  "byteLength": 3,
+                  "byteOffset": 30,
+                  "endColumn": 17,
+                  "endLine": 3,
+                  "startColumn": 14,
+                  "startLine": 3
+                }
+              }
+            }
+          ],
+          "message": {
+            "text": "function state mutability can be restricted to pure"
+          }
+        }
+      ],
+      "tool": {
+        "driver": {
+          "informationUri": "https://github.com/paradigmxyz/solar",
+          "name": "solar",
+          "rules": [
+            {
+              "id": "7576"
+            }
+          ],
+          "version": "[VERSION]"
+        }
+      }
+    }
+  ],
+  "version": "2.1.0"
+}
+"#]]
+            .is(snapbox::data::DataFormat::Json)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_sarif_no_diagnostics() {
+        assert_data_eq!(
+            emit_sarif_diagnostics(vec![]),
+            str![[r#"
+{
+  "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+  "runs": [
+    {
+      "columnKind": "unicodeCodePoints",
+      "invocations": [
+        {
+          "executionSuccessful": true,
+          "toolExecutionNotifications": []
+        }
+      ],
+      "results": [],
+      "tool": {
+        "driver": {
+          "informationUri": "https://github.com/paradigmxyz/solar",
+          "name": "solar",
+          "rules": [],
+          "version": "[VERSION]"
+        }
+      }
+    }
+  ],
+  "version": "2.1.0"
+}
+"#]]
+            .is(snapbox::data::DataFormat::Json)
+        );
+    }
+
     // --- HELPERS -------------------------------------------------------------
 
     const CONTRACT: &str = r#"
@@ -1461,4 +1774,24 @@ contract Test {
         let buffer = writer.lock().unwrap();
         String::from_utf8(buffer.clone()).expect("JSON output was not valid UTF-8")
     }
+
+    // Helper to setup the run the SARIF emitter. The log is written when the emitter is dropped.
+    #[cfg(feature = "json")]
+    fn emit_sarif_diagnostics(diags: Vec<Diag>) -> String {
+        let sm = Arc::new(source_map::SourceMap::empty());
+        sm.new_source_file(source_map::FileName::custom("test.sol"), CONTRACT.to_string()).unwrap();
+
+        let writer = Arc::new(Mutex::new(Vec::new()));
+        let emitter = SarifEmitter::new(Box::new(SharedWriter(writer.clone())), Arc::clone(&sm));
+        let dcx = DiagCtxt::new(Box::new(emitter));
+        for diag in diags {
+            let _ = dcx.emit_diagnostic(diag);
+        }
+        drop(dcx);
+
+        let buffer = writer.lock().unwrap();
+        String::from_utf8(buffer.clone())
+            .expect("SARIF output was not valid UTF-8")
+            .replace(env!("CARGO_PKG_VERSION"), "[VERSION]")
+    }
 }
@@ -45,6 +45,11 @@ pub type Result<T = (), E = ErrorGuaranteed> = std::result::Result<T, E>;
 /// Solar aims to support Solidity 0.8.* and later versions.
 pub const MIN_SOLIDITY_VERSION: semver::Version = semver::Version::new(0, 8, 0);
 
+/// The version of the Solidity language that is implemented.
+///
+/// `pragma solidity` version requirements are checked against this version.
+pub const SOLIDITY_VERSION: semver::Version = semver::Version::new(0, 8, 30);
+
 /// Creates new session globals on the current thread if they doesn't exist already and then
 /// executes the given closure.
 ///
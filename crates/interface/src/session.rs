@@ -205,6 +205,7 @@ impl Session {
     pub fn validate(&self) -> crate::Result<()> {
         let mut result = Ok(());
         result = result.and(self.check_unique("emit", &self.opts.emit));
+        result = result.and(self.check_codegen_outputs());
         result
     }
 
@@ -228,6 +229,29 @@ impl Session {
         }
     }
 
+    /// Code generation from Solidity relies on the results of the expression type checker, which
+    /// is still unstable.
+    fn check_codegen_outputs(&self) -> crate::Result<()> {
+        if self.opts.language != solar_config::Language::Solidity || self.opts.unstable.typeck {
+            return Ok(());
+        }
+        let mut result = Ok(());
+        for &emit in &self.opts.emit {
+            if matches!(
+                emit,
+                CompilerOutput::Bin
+                    | CompilerOutput::BinRuntime
+                    | CompilerOutput::Ir
+                    | CompilerOutput::IrAst
+            ) {
+                let msg = format!("`--emit {emit}` requires `-Ztypeck`");
+                let note = "code generation from Solidity is unstable and relies on type checking";
+                result = Err(self.dcx.err(msg).note(note).emit());
+            }
+        }
+        result
+    }
+
     fn check_unique<T: Eq + std::hash::Hash + std::fmt::Display>(
         &self,
         name: &str,
@@ -820,7 +820,7 @@ symbols! {
         // Some builtins have already been previously declared, so they can't be redeclared here.
         // See `is_yul_builtin`.
         // https://docs.soliditylang.org/en/latest/yul.html#evm-dialect
-        // TODO: The remaining internal dialect builtins.
+        // https://docs.soliditylang.org/en/latest/yul.html#specification-of-yul-object
         Add:            "add",
         Addmod:         "addmod",
         And:            "and",
@@ -837,9 +837,14 @@ symbols! {
         Caller:         "caller",
         Callvalue:      "callvalue",
         Chainid:        "chainid",
+        Codecopy:       "codecopy",
+        Codesize:       "codesize",
         Coinbase:       "coinbase",
         Create:         "create",
         Create2:        "create2",
+        Datacopy:       "datacopy",
+        Dataoffset:     "dataoffset",
+        Datasize:       "datasize",
         Delegatecall:   "delegatecall",
         Difficulty:     "difficulty",
         Div:            "div",
@@ -855,6 +860,8 @@ symbols! {
         Invalid:        "invalid",
         Iszero:         "iszero",
         Keccak256:      "keccak256",
+        Linkersymbol:   "linkersymbol",
+        Loadimmutable:  "loadimmutable",
         Log0:           "log0",
         Log1:           "log1",
         Log2:           "log2",
@@ -862,6 +869,7 @@ symbols! {
         Log4:           "log4",
         Lt:             "lt",
         Mcopy:          "mcopy",
+        Memoryguard:    "memoryguard",
         Mload:          "mload",
         Mod:            "mod",
         Msize:          "msize",
@@ -881,6 +889,7 @@ symbols! {
         Sdiv:           "sdiv",
         Selfbalance:    "selfbalance",
         Selfdestruct:   "selfdestruct",
+        Setimmutable:   "setimmutable",
         Sgt:            "sgt",
         Shl:            "shl",
         Shr:            "shr",
@@ -959,6 +968,7 @@ symbols! {
         msg,
         name,
         object,
+        offset,
         push,
         require,
         ripemd160,
@@ -968,6 +978,7 @@ symbols! {
         sender,
         sha256,
         sig,
+        slot,
         solidity,
         super_: "super",
         this,
@@ -1,12 +1,13 @@
 //! Solidity and Yul lexer.
 
+use crate::parser::LICENSE_PREFIX;
 use solar_ast::{
     Base, StrKind,
     token::{CommentKind, Token, TokenKind, TokenLitKind},
 };
 use solar_data_structures::hint::cold_path;
 use solar_interface::{
-    BytePos, Session, Span, Symbol, diagnostics::DiagCtxt, source_map::SourceFile,
+    BytePos, Session, Span, Symbol, diagnostics::DiagCtxt, error_code, source_map::SourceFile,
 };
 
 mod cursor;
@@ -15,6 +16,9 @@ pub use cursor::*;
 
 pub mod unescape;
 
+mod suppression;
+use suppression::OpenSuppression;
+
 mod unicode_chars;
 
 mod utf8;
@@ -40,6 +44,9 @@ pub struct Lexer<'sess, 'src> {
     /// in this file, it's safe to treat further occurrences of the non-breaking
     /// space character as whitespace.
     nbsp_is_whitespace: bool,
+
+    /// The `solar-disable` directives that have not been closed yet.
+    open_suppressions: Vec<OpenSuppression>,
 }
 
 impl<'sess, 'src> Lexer<'sess, 'src> {
@@ -65,6 +72,7 @@ impl<'sess, 'src> Lexer<'sess, 'src> {
             src,
             cursor: Cursor::new(src),
             nbsp_is_whitespace: false,
+            open_suppressions: Vec::new(),
         }
     }
 
@@ -76,7 +84,8 @@ impl<'sess, 'src> Lexer<'sess, 'src> {
 
     /// Consumes the lexer and collects the remaining tokens into a vector.
     ///
-    /// Note that this skips comments, as [required by the parser](crate::Parser::new).
+    /// Note that this skips comments, as [required by the parser](crate::Parser::new), except for
+    /// the ones containing an SPDX license identifier.
     ///
     /// Prefer using this method instead of manually collecting tokens using [`Iterator`].
     #[instrument(name = "lex", level = "debug", skip_all)]
@@ -88,7 +97,9 @@ impl<'sess, 'src> Lexer<'sess, 'src> {
             if token.is_eof() {
                 break;
             }
-            if token.is_comment() {
+            if token.is_comment()
+                && !token.comment().is_some_and(|(_, _, s)| s.as_str().contains(LICENSE_PREFIX))
+            {
                 continue;
             }
             tokens.push(token);
@@ -121,6 +132,9 @@ impl<'sess, 'src> Lexer<'sess, 'src> {
                     // Opening delimiter is not included into the symbol.
                     let content_start = start + BytePos(if is_doc { 3 } else { 2 });
                     let content = self.str_from(content_start);
+                    if !is_doc {
+                        self.suppression_directive(start, content);
+                    }
                     self.cook_doc_comment(content_start, content, is_doc, CommentKind::Line)
                 }
                 RawTokenKind::BlockComment { is_doc, terminated } => {
@@ -138,6 +152,9 @@ impl<'sess, 'src> Lexer<'sess, 'src> {
                     let content_start = start + BytePos(if is_doc { 3 } else { 2 });
                     let content_end = self.pos - (terminated as u32) * 2;
                     let content = self.str_from_to(content_start, content_end);
+                    if !is_doc {
+                        self.suppression_directive(start, content);
+                    }
                     self.cook_doc_comment(content_start, content, is_doc, CommentKind::Block)
                 }
                 RawTokenKind::Whitespace => {
@@ -192,7 +209,10 @@ impl<'sess, 'src> Lexer<'sess, 'src> {
                     }
                 }
 
-                RawTokenKind::Eof => TokenKind::Eof,
+                RawTokenKind::Eof => {
+                    self.close_suppressions();
+                    TokenKind::Eof
+                }
             };
             let span = self.new_span(start, self.pos);
             return Token::new(kind, span);
@@ -276,11 +296,12 @@ impl<'sess, 'src> Lexer<'sess, 'src> {
 
     fn cook_doc_comment(
         &self,
-        _content_start: BytePos,
+        content_start: BytePos,
         content: &str,
         is_doc: bool,
         comment_kind: CommentKind,
     ) -> TokenKind {
+        self.check_text_flow_control_chars(content_start, content, "comment");
         TokenKind::Comment(is_doc, comment_kind, self.intern(content))
     }
 
@@ -357,9 +378,65 @@ impl<'sess, 'src> Lexer<'sess, 'src> {
         let content_start = start + 1 + BytePos(kind.prefix().len() as u32);
         let content_end = end - 1;
         let lit_content = self.str_from_to(content_start, content_end);
+        if kind != StrKind::Hex {
+            self.check_text_flow_control_chars(content_start, lit_content, "string literal");
+        }
         self.intern(lit_content)
     }
 
+    /// Checks that the Unicode direction overrides, embeddings, and isolates in a comment or
+    /// string literal are balanced.
+    fn check_text_flow_control_chars(&self, content_start: BytePos, content: &str, kind: &str) {
+        if !unicode_chars::contains_text_flow_control_chars(content) {
+            return;
+        }
+        cold_path();
+
+        let mut spans = Vec::new();
+        let mut depth = 0usize;
+        let mut underflow = None;
+        for (i, c) in content.char_indices() {
+            let Some(&(_, push)) =
+                unicode_chars::TEXT_FLOW_CONTROL_CHARS.iter().find(|&&(ch, _)| ch == c)
+            else {
+                continue;
+            };
+            let lo = content_start + BytePos::from_usize(i);
+            let span = self.new_span(lo, lo + BytePos::from_usize(c.len_utf8()));
+            spans.push((span, c));
+            if push {
+                depth += 1;
+            } else if depth == 0 {
+                underflow.get_or_insert(span);
+            } else {
+                depth -= 1;
+            }
+        }
+
+        let content_span =
+            self.new_span(content_start, content_start + BytePos::from_usize(content.len()));
+        let mut err = if let Some(span) = underflow {
+            let msg = format!("Unicode direction override underflow in {kind}");
+            self.dcx().err(msg).span(span)
+        } else if depth > 0 {
+            let msg = format!("mismatching Unicode direction override markers in {kind}");
+            self.dcx().err(msg).span(content_span)
+        } else {
+            return;
+        };
+        for (span, c) in spans {
+            err = err.span_label(span, escaped_char(c));
+        }
+        err.code(error_code!(8936))
+            .note(
+                "these kinds of Unicode codepoints change the way text flows on applications that \
+                 support them, but can cause confusion because they change the order of \
+                 characters on the screen",
+            )
+            .help("if their presence wasn't intentional, you can remove them")
+            .emit();
+    }
+
     #[inline]
     fn new_span(&self, lo: BytePos, hi: BytePos) -> Span {
         Span::new_unchecked(lo, hi)
@@ -0,0 +1,105 @@
+//! Warning suppression comment directives.
+//!
+//! The following directives are supported in non-doc comments, optionally followed by a list of
+//! diagnostic codes separated by commas or whitespace. If no codes are given, all warnings are
+//! suppressed.
+//! - `solar-disable-next-line`: suppresses warnings on the line after the comment;
+//! - `solar-disable-line`: suppresses warnings on the lines of the comment;
+//! - `solar-disable`: suppresses warnings until the matching `solar-enable`, or the end of the
+//!   file;
+//! - `solar-enable`: ends the `solar-disable` ranges with the same codes, or all of them if no
+//!   codes are given.
+
+use super::Lexer;
+use solar_interface::{BytePos, Span, diagnostics::Suppression};
+
+const PREFIX: &str = "solar-";
+
+/// A `solar-disable` directive that has not been closed yet.
+pub(super) struct OpenSuppression {
+    directive: Span,
+    start: BytePos,
+    codes: Vec<String>,
+}
+
+impl Lexer<'_, '_> {
+    /// Registers the suppression directive in the comment starting at `start` and ending at the
+    /// current position, if any.
+    pub(super) fn suppression_directive(&mut self, start: BytePos, content: &str) {
+        let Some(rest) = content.trim().strip_prefix(PREFIX) else { return };
+        let (name, codes) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let codes = codes
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|code| !code.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let directive = self.new_span(start, self.pos);
+        match name {
+            "disable-next-line" => {
+                let next_line = (self.line_end(self.pos) + 1).min(self.src.len());
+                let range = self
+                    .new_span(self.pos_at(next_line), self.pos_at(self.line_end_idx(next_line)));
+                self.add_suppression(directive, range, codes);
+            }
+            "disable-line" => {
+                let lo = self.pos_at(self.line_start(start));
+                let hi = self.pos_at(self.line_end(self.pos));
+                self.add_suppression(directive, self.new_span(lo, hi), codes);
+            }
+            "disable" => self.open_suppressions.push(OpenSuppression { directive, start, codes }),
+            "enable" => {
+                let closed = self
+                    .open_suppressions
+                    .extract_if(.., |s| codes.is_empty() || s.codes == codes)
+                    .collect::<Vec<_>>();
+                if closed.is_empty() {
+                    self.dcx()
+                        .warn("`solar-enable` directive without a matching `solar-disable`")
+                        .span(directive)
+                        .emit();
+                }
+                for s in closed {
+                    self.add_suppression(s.directive, self.new_span(s.start, start), s.codes);
+                }
+            }
+            _ if name.starts_with("disable") || name.starts_with("enable") => {
+                self.dcx()
+                    .warn(format!("unknown suppression directive `{PREFIX}{name}`"))
+                    .span(directive)
+                    .help("valid directives are `solar-disable-next-line`, `solar-disable-line`, `solar-disable`, and `solar-enable`")
+                    .emit();
+            }
+            _ => {}
+        }
+    }
+
+    /// Closes all `solar-disable` ranges that are still open at the end of the file.
+    pub(super) fn close_suppressions(&mut self) {
+        for s in std::mem::take(&mut self.open_suppressions) {
+            self.add_suppression(s.directive, self.new_span(s.start, self.pos), s.codes);
+        }
+    }
+
+    fn add_suppression(&self, directive: Span, range: Span, codes: Vec<String>) {
+        self.dcx().add_suppression(Suppression { directive, range, codes });
+    }
+
+    /// Returns the index of the start of the line containing `pos`.
+    fn line_start(&self, pos: BytePos) -> usize {
+        let idx = self.src_index(pos);
+        self.src[..idx].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// Returns the index of the end of the line containing `pos`, excluding the newline.
+    fn line_end(&self, pos: BytePos) -> usize {
+        self.line_end_idx(self.src_index(pos))
+    }
+
+    fn line_end_idx(&self, idx: usize) -> usize {
+        self.src[idx..].find('\n').map_or(self.src.len(), |i| idx + i)
+    }
+
+    fn pos_at(&self, idx: usize) -> BytePos {
+        self.start_pos + BytePos::from_usize(idx)
+    }
+}
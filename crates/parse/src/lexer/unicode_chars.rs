@@ -398,3 +398,34 @@ fn peek_delimited(text: &str, from_ch: char, to_ch: char) -> Option<&str> {
     let last_char_idx = chars.as_str().find(to_ch)?;
     Some(&chars.as_str()[..last_char_idx])
 }
+
+/// Unicode characters that change the direction of the text that follows them, and whether they
+/// start (`true`) or terminate (`false`) a directional embedding, override, or isolate.
+pub(crate) const TEXT_FLOW_CONTROL_CHARS: &[(char, bool)] = &[
+    ('\u{202A}', true),  // LEFT-TO-RIGHT EMBEDDING
+    ('\u{202B}', true),  // RIGHT-TO-LEFT EMBEDDING
+    ('\u{202C}', false), // POP DIRECTIONAL FORMATTING
+    ('\u{202D}', true),  // LEFT-TO-RIGHT OVERRIDE
+    ('\u{202E}', true),  // RIGHT-TO-LEFT OVERRIDE
+    ('\u{2066}', true),  // LEFT-TO-RIGHT ISOLATE
+    ('\u{2067}', true),  // RIGHT-TO-LEFT ISOLATE
+    ('\u{2068}', true),  // FIRST STRONG ISOLATE
+    ('\u{2069}', false), // POP DIRECTIONAL ISOLATE
+];
+
+/// Returns `true` if the given string contains any of the [`TEXT_FLOW_CONTROL_CHARS`].
+#[inline]
+pub(crate) fn contains_text_flow_control_chars(s: &str) -> bool {
+    // All of the characters start with `0xE2 0x80` or `0xE2 0x81` in UTF-8:
+    // U+202A..=U+202E: E2 80 AA..=AE
+    // U+2066..=U+2069: E2 81 A6..=A9
+    let mut bytes = s.as_bytes();
+    while let Some(idx) = memchr::memchr(0xE2, bytes) {
+        bytes = &bytes[idx + 1..];
+        match *bytes {
+            [0x80, 0xAA..=0xAE, ..] | [0x81, 0xA6..=0xA9, ..] => return true,
+            _ => {}
+        }
+    }
+    false
+}
@@ -48,11 +48,11 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         let mut precedence = token_precedence(self.token);
         while precedence >= min_precedence {
             while token_precedence(self.token) == precedence {
-                // Parse a**b**c as a**(b**c)
-                let next_precedence = if self.token.kind == TokenKind::BinOp(BinOpToken::Star) {
-                    precedence + 1
-                } else {
+                // Parse a**b**c as a**(b**c), and all the other operators as left-associative.
+                let next_precedence = if self.token.kind == TokenKind::StarStar {
                     precedence
+                } else {
+                    precedence + 1
                 };
 
                 let token = self.token;
@@ -341,3 +341,42 @@ unsafe fn option_boxes_unwrap_unchecked<'a, 'b, T>(
     // SAFETY: Caller must ensure that all elements are `Some`.
     unsafe { std::mem::transmute(list) }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solar_interface::{Result, Session, source_map::FileName};
+
+    #[test]
+    fn binary_associativity() {
+        fn fmt(expr: &Expr<'_>) -> String {
+            match &expr.kind {
+                ExprKind::Binary(lhs, op, rhs) => {
+                    format!("({} {} {})", fmt(lhs), op.kind.to_str(), fmt(rhs))
+                }
+                ExprKind::Ident(ident) => ident.to_string(),
+                kind => panic!("unexpected expression: {kind:?}"),
+            }
+        }
+
+        let sess = Session::builder().with_test_emitter().single_threaded().build();
+        sess.enter_sequential(|| -> Result {
+            for (src, expected) in [
+                ("a - b - c", "((a - b) - c)"),
+                ("a / b * c", "((a / b) * c)"),
+                ("a ** b ** c", "(a ** (b ** c))"),
+                ("a - b * c - d", "((a - (b * c)) - d)"),
+                ("a << b << c", "((a << b) << c)"),
+                ("a == b != c", "((a == b) != c)"),
+            ] {
+                let arena = Arena::new();
+                let mut parser =
+                    Parser::from_source_code(&sess, &arena, FileName::Custom(src.into()), src)?;
+                let expr = parser.parse_expr().map_err(|e| e.emit())?;
+                assert_eq!(fmt(expr), expected, "{src:?}");
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+}
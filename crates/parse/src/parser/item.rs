@@ -9,7 +9,14 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
     /// Parses a source unit.
     #[instrument(level = "debug", skip_all)]
     pub fn parse_file(&mut self) -> PResult<'sess, SourceUnit<'ast>> {
-        self.parse_items(TokenKind::Eof).map(SourceUnit::new)
+        let mut source_unit = self.parse_items(TokenKind::Eof).map(SourceUnit::new)?;
+        // Only comments outside of top-level items are considered.
+        let mut licenses = std::mem::take(&mut self.licenses);
+        licenses.retain(|license| {
+            !source_unit.items.iter().any(|item| item.span.contains(license.span))
+        });
+        source_unit.licenses = self.alloc_vec(licenses);
+        Ok(source_unit)
     }
 
     /// Parses a list of items until the given token is encountered.
@@ -746,7 +753,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
                     let msg = "data location already specified";
                     self.dcx().err(msg).span(self.prev_token.span).emit();
                 } else {
-                    data_location = Some(s);
+                    data_location = Some(Spanned { span: self.prev_token.span, data: s });
                 }
             } else if let Some(v) = self.parse_visibility() {
                 if !flags.contains(VarFlags::from_visibility(v)) {
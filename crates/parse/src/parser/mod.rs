@@ -6,7 +6,7 @@ use solar_ast::{
 };
 use solar_data_structures::{BumpExt, fmt::or_list};
 use solar_interface::{
-    BytePos, Ident, Result, Session, Span, Symbol,
+    BytePos, Ident, Result, Session, Span, Spanned, Symbol,
     diagnostics::DiagCtxt,
     source_map::{FileName, SourceFile},
 };
@@ -19,6 +19,9 @@ mod stmt;
 mod ty;
 mod yul;
 
+/// The prefix of SPDX license identifier comments.
+pub(crate) const LICENSE_PREFIX: &str = "SPDX-License-Identifier:";
+
 /// Maximum allowed recursive descent depth for selected parser entry points.
 const PARSER_RECURSION_LIMIT: usize = 128;
 
@@ -47,6 +50,8 @@ pub struct Parser<'sess, 'ast> {
     last_unexpected_token_span: Option<Span>,
     /// The current doc-comments.
     docs: Vec<DocComment<'ast>>,
+    /// The SPDX license identifiers found in comments.
+    licenses: Vec<Spanned<Symbol>>,
 
     /// The token stream.
     tokens: std::vec::IntoIter<Token>,
@@ -149,6 +154,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
             expected_tokens: Vec::with_capacity(8),
             last_unexpected_token_span: None,
             docs: Vec::with_capacity(4),
+            licenses: Vec::new(),
             tokens: tokens.into_iter(),
             in_yul: false,
             in_contract: false,
@@ -757,6 +763,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
                 };
                 self.docs.push(DocComment { kind, span: self.token.span, symbol, natspec });
             }
+            self.record_licenses(is_doc, symbol);
             // Don't set `prev_token` on purpose.
             self.token = self.next_token();
         }
@@ -764,6 +771,25 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         self.expected_tokens.clear();
     }
 
+    /// Records the `SPDX-License-Identifier:` values in the current comment token.
+    fn record_licenses(&mut self, is_doc: bool, symbol: Symbol) {
+        let content = symbol.as_str();
+        // The opening delimiter is not included in the symbol.
+        let content_lo = self.token.span.lo() + BytePos(2 + is_doc as u32);
+        for (start, _) in content.match_indices(LICENSE_PREFIX) {
+            let rest = &content[start + LICENSE_PREFIX.len()..];
+            let value = rest.trim_start();
+            let offset = start + LICENSE_PREFIX.len() + (rest.len() - value.len());
+            let value = value.split(['\n', '\r']).next().unwrap_or_default().trim_end();
+            if value.is_empty() {
+                continue;
+            }
+            let lo = content_lo + BytePos(offset as u32);
+            let span = Span::new(lo, lo + BytePos(value.len() as u32));
+            self.licenses.push(Spanned { span, data: Symbol::intern(value) });
+        }
+    }
+
     /// Advances the internal `tokens` iterator, without updating the parser state.
     ///
     /// Use [`bump`](Self::bump) and [`token`](Self::token) instead.
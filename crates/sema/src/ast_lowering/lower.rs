@@ -10,6 +10,12 @@ impl<'gcx> super::LoweringContext<'gcx> {
                 file: source.file.clone(),
                 imports: self.arena.alloc_slice_copy(&source.imports),
                 items: &[],
+                usings: &[],
+                license: source
+                    .ast
+                    .as_ref()
+                    .and_then(|ast| ast.license())
+                    .map(|license| license.data),
             };
             if let Some(ast) = &source.ast {
                 let mut items = SmallVec::<[_; 16]>::new();
@@ -44,12 +50,14 @@ impl<'gcx> super::LoweringContext<'gcx> {
         let id = self.hir.contracts.push(hir::Contract {
             source: self.current_source_id,
             span: item.span,
+            docs: self.lower_docs(&item.docs),
             name: contract.name,
             kind: contract.kind,
 
             // Set later.
             bases: &mut [],
             bases_args: &[],
+            layout: None,
             linearized_bases: &[],
             linearized_bases_args: &[],
 
@@ -57,6 +65,7 @@ impl<'gcx> super::LoweringContext<'gcx> {
             fallback: None,
             receive: None,
             items: &[],
+            usings: &[],
         });
         let prev_contract_id = Option::replace(&mut self.current_contract_id, id);
         debug_assert_eq!(prev_contract_id, None);
@@ -105,7 +114,9 @@ impl<'gcx> super::LoweringContext<'gcx> {
                 } else {
                     hir::VarKind::Global
                 };
-                hir::ItemId::Variable(self.lower_variable(i, kind))
+                let id = self.lower_variable(i, kind);
+                self.hir.variables[id].docs = self.lower_docs(&item.docs);
+                hir::ItemId::Variable(id)
             }
             ast::ItemKind::Struct(i) => hir::ItemId::Struct(self.lower_struct(item, i)),
             ast::ItemKind::Enum(i) => hir::ItemId::Enum(self.lower_enum(item, i)),
@@ -117,6 +128,15 @@ impl<'gcx> super::LoweringContext<'gcx> {
         item_id
     }
 
+    fn lower_docs(&self, docs: &ast::DocComments<'_>) -> &'gcx [hir::DocComment<'gcx>] {
+        self.arena.alloc_slice_fill_iter(docs.iter().map(|doc| hir::DocComment {
+            kind: doc.kind,
+            span: doc.span,
+            symbol: doc.symbol,
+            natspec: self.arena.alloc_slice_copy(doc.natspec),
+        }))
+    }
+
     fn lower_function(
         &mut self,
         item: &ast::Item<'_>,
@@ -127,7 +147,7 @@ impl<'gcx> super::LoweringContext<'gcx> {
         let ast::FunctionHeader {
             span: _,
             name,
-            parameters: _,
+            ref parameters,
             visibility,
             state_mutability,
             modifiers: _,
@@ -139,6 +159,7 @@ impl<'gcx> super::LoweringContext<'gcx> {
             source: self.current_source_id,
             contract: self.current_contract_id,
             span: item.span,
+            docs: self.lower_docs(&item.docs),
             name,
             kind,
             gettee: None,
@@ -161,6 +182,14 @@ impl<'gcx> super::LoweringContext<'gcx> {
             state_mutability: state_mutability
                 .map(|s| s.data)
                 .unwrap_or(ast::StateMutability::NonPayable),
+            state_mutability_span: state_mutability.map(|s| s.span).unwrap_or_else(|| {
+                // Insert after the parameters and the visibility.
+                let prev = visibility
+                    .map(|vis| vis.span)
+                    .filter(|vis| vis.hi() > parameters.span.hi())
+                    .unwrap_or(parameters.span);
+                prev.shrink_to_hi()
+            }),
             parameters: &[],
             returns: &[],
             body: None,
@@ -190,6 +219,7 @@ impl<'gcx> super::LoweringContext<'gcx> {
             source: self.current_source_id,
             contract: self.current_contract_id,
             span: item.span,
+            docs: self.lower_docs(&item.docs),
             name,
             fields: &[],
         })
@@ -201,6 +231,7 @@ impl<'gcx> super::LoweringContext<'gcx> {
             source: self.current_source_id,
             contract: self.current_contract_id,
             span: item.span,
+            docs: self.lower_docs(&item.docs),
             name,
             variants: self.arena.alloc_slice_copy(variants),
         })
@@ -213,6 +244,7 @@ impl<'gcx> super::LoweringContext<'gcx> {
             source: self.current_source_id,
             contract: self.current_contract_id,
             span: item.span,
+            docs: self.lower_docs(&item.docs),
             name,
             ty: hir::Type::DUMMY,
         })
@@ -225,6 +257,7 @@ impl<'gcx> super::LoweringContext<'gcx> {
             source: self.current_source_id,
             contract: self.current_contract_id,
             span: item.span,
+            docs: self.lower_docs(&item.docs),
             name,
             parameters: &[],
         })
@@ -237,6 +270,7 @@ impl<'gcx> super::LoweringContext<'gcx> {
             source: self.current_source_id,
             contract: self.current_contract_id,
             span: item.span,
+            docs: self.lower_docs(&item.docs),
             name,
             anonymous,
             parameters: &[],
@@ -256,7 +290,7 @@ pub(super) fn lower_variable_partial(
     // handled later: ty, override_, initializer
     let ast::VariableDefinition {
         span,
-        ty: _,
+        ref ty,
         visibility,
         mutability,
         data_location,
@@ -270,12 +304,14 @@ pub(super) fn lower_variable_partial(
         contract,
         function,
         span,
+        docs: &[],
         kind,
         ty: hir::Type::DUMMY,
         name,
         visibility,
         mutability,
-        data_location,
+        data_location: data_location.map(|loc| loc.data),
+        data_location_span: data_location.map(|loc| loc.span).unwrap_or(ty.span.shrink_to_hi()),
         override_: override_.is_some(),
         overrides: &[],
         indexed,
@@ -295,12 +331,14 @@ fn generate_partial_getter(hir: &mut hir::Hir<'_>, id: hir::VariableId) -> hir::
         contract,
         function: _,
         span,
+        docs: _,
         kind,
         ty: _,
         name,
         visibility,
         mutability: _,
         data_location: _,
+        data_location_span: _,
         override_,
         overrides,
         indexed,
@@ -315,10 +353,12 @@ fn generate_partial_getter(hir: &mut hir::Hir<'_>, id: hir::VariableId) -> hir::
         source,
         contract,
         span,
+        docs: &[],
         name,
         kind: ast::FunctionKind::Function,
         visibility: ast::Visibility::External,
         state_mutability: ast::StateMutability::View,
+        state_mutability_span: span,
         modifiers: &[],
         marked_virtual: false,
         virtual_: false,
@@ -15,7 +15,7 @@ mod lower;
 mod linearize;
 
 pub(crate) mod resolve;
-pub(crate) use resolve::{Res, SymbolResolver};
+pub(crate) use resolve::{Res, SymbolResolver, lower_yul_object};
 
 #[instrument(name = "ast_lowering", level = "debug", skip_all)]
 pub(crate) fn lower(mut gcx: GcxMut<'_>) {
@@ -113,3 +113,32 @@ fn get_two_mut_idx<I: Idx, T>(sl: &mut IndexVec<I, T>, idx_1: I, idx_2: I) -> (&
 fn get_two_mut<T>(sl: &mut [T], idx_1: usize, idx_2: usize) -> (&mut T, &mut T) {
     sl.get_disjoint_mut([idx_1, idx_2]).unwrap().into()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Compiler;
+    use solar_interface::{Session, sym};
+    use std::{ops::ControlFlow, path::PathBuf};
+
+    #[test]
+    fn struct_getter_initializes_temporary() {
+        let src = "struct S { uint256 a; uint256 b; } contract C { S public s; }";
+        let mut compiler = Compiler::new(Session::builder().with_test_emitter().build());
+        compiler.enter_mut(|c| {
+            let mut pcx = c.parse();
+            let file = c.sess().source_map().new_source_file(PathBuf::from("test.sol"), src);
+            pcx.add_file(file.unwrap());
+            pcx.parse();
+            assert_eq!(c.lower_asts(), Ok(ControlFlow::Continue(())));
+
+            // `S storage __tmp_struct = s;`
+            let hir = &c.gcx().hir;
+            let mut tmps = hir
+                .variables()
+                .filter(|var| var.name.is_some_and(|name| name.name == sym::__tmp_struct));
+            let tmp = tmps.next().expect("missing getter temporary");
+            assert!(tmp.initializer.is_some());
+            assert!(tmps.next().is_none());
+        });
+    }
+}
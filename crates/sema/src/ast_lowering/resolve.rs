@@ -15,6 +15,9 @@ use std::fmt;
 
 pub(crate) use crate::hir::Res;
 
+mod yul;
+pub(crate) use yul::lower_yul_object;
+
 impl super::LoweringContext<'_> {
     #[instrument(level = "debug", skip_all)]
     pub(super) fn collect_exports(&mut self) {
@@ -308,6 +311,8 @@ impl<'gcx> ResolveContext<'gcx> {
                 self.lower_variables(*ast_event.parameters, hir::VarKind::Event);
         }
 
+        self.resolve_using_directives();
+
         // Resolve constants and state variables.
         let normal_vars = self.hir.variables.len();
         for id in self.hir.variable_ids() {
@@ -384,6 +389,79 @@ impl<'gcx> ResolveContext<'gcx> {
         }
     }
 
+    /// Lowers the file-level and contract-level `using for` directives.
+    fn resolve_using_directives(&mut self) {
+        let sources = self.sources;
+        for (source_id, source) in sources.iter_enumerated() {
+            let Some(ast) = &source.ast else { continue };
+            self.init(source_id, None, None);
+            let usings = self.lower_using_directives(ast.items.iter());
+            self.hir.sources[source_id].usings = usings;
+        }
+
+        for id in self.hir.contract_ids() {
+            let ast_item = self.hir_to_ast[&hir::ItemId::Contract(id)];
+            let ast::ItemKind::Contract(ast_contract) = &ast_item.kind else { unreachable!() };
+            let contract = self.hir.contract(id);
+            self.init(contract.source, Some(id), None);
+            let usings = self.lower_using_directives(ast_contract.body.iter());
+            self.hir.contracts[id].usings = usings;
+        }
+    }
+
+    fn lower_using_directives<'a>(
+        &mut self,
+        items: impl Iterator<Item = &'a ast::Item<'a>>,
+    ) -> &'gcx [hir::UsingDirective<'gcx>] {
+        let mut usings = SmallVec::<[_; 4]>::new();
+        for item in items {
+            let ast::ItemKind::Using(using) = &item.kind else { continue };
+            let list = match &using.list {
+                ast::UsingList::Single(path) => {
+                    match self.resolve_path_as::<hir::ItemId>(path, "library") {
+                        Ok(hir::ItemId::Contract(id))
+                            if self.hir.contract(id).kind.is_library() =>
+                        {
+                            hir::UsingList::Library(id)
+                        }
+                        Ok(id) => {
+                            let msg = format!(
+                                "expected library, found {}",
+                                self.hir.item(id).description()
+                            );
+                            self.dcx()
+                                .err(msg)
+                                .span(path.span())
+                                .help("to attach a single function, use `using { f } for T;`")
+                                .emit();
+                            continue;
+                        }
+                        Err(_) => continue,
+                    }
+                }
+                ast::UsingList::Multiple(paths) => {
+                    let functions = paths.iter().filter_map(|&(ref path, op)| {
+                        let id = self.resolve_path_as::<hir::ItemId>(path, "function").ok()?;
+                        let Some(f_id) = id.as_function().filter(|&f| {
+                            let f = self.hir.function(f);
+                            f.kind.is_function()
+                                && f.contract.is_none_or(|c| self.hir.contract(c).kind.is_library())
+                        }) else {
+                            let msg = "only file-level functions and library functions can be attached to a type in a `using` statement";
+                            self.dcx().err(msg).code(error_code!(4167)).span(path.span()).emit();
+                            return None;
+                        };
+                        Some((f_id, op))
+                    });
+                    hir::UsingList::Functions(self.arena.alloc_from_iter(functions))
+                }
+            };
+            let ty = using.ty.as_ref().map(|ty| self.lower_type(ty));
+            usings.push(hir::UsingDirective { span: item.span, list, ty, global: using.global });
+        }
+        self.arena.alloc_smallvec(usings)
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub(super) fn resolve_base_args(&mut self) {
         for c_id in self.hir.contract_ids() {
@@ -404,7 +482,7 @@ impl<'gcx> ResolveContext<'gcx> {
                 Declaration { res: Res::Builtin(Builtin::Super), span: Span::DUMMY },
             );
 
-            // Lower the base modifiers.
+            // Lower the base modifiers and the storage layout specifier.
             self.resolve_base_args_inner(c_id);
 
             // Exit the manually created scope that only contains `this` and `super`.
@@ -425,6 +503,8 @@ impl<'gcx> ResolveContext<'gcx> {
                 },
             ),
         );
+        self.hir.contracts[c_id].layout =
+            ast_contract.layout.as_ref().map(|layout| self.lower_expr(layout.slot));
         let contract = self.hir.contract(c_id);
 
         if contract.linearization_failed() {
@@ -677,6 +757,7 @@ impl<'gcx> ResolveContext<'gcx> {
                         let decl_name = Ident::new(sym::__tmp_struct, ast_var.span);
                         let mut decl_var = self.mk_var_stmt(id, span, ret_ty.clone(), decl_name);
                         decl_var.data_location = Some(hir::DataLocation::Storage);
+                        decl_var.initializer = Some(expr);
                         let decl_id = self.hir.variables.push(decl_var);
                         let builder = self.hir_builder();
                         let decl_stmt = builder.stmt(hir::StmtKind::DeclSingle(decl_id), span);
@@ -802,10 +883,9 @@ impl<'gcx> ResolveContext<'gcx> {
                 })),
                 self.lower_expr(expr),
             ),
-            ast::StmtKind::Assembly(_) => hir::StmtKind::Err(
-                // self.dcx().err("assembly is not yet implemented").span(stmt.span).emit(),
-                ErrorGuaranteed::new_unchecked(),
-            ),
+            ast::StmtKind::Assembly(assembly) => {
+                hir::StmtKind::Assembly(self.lower_assembly(assembly))
+            }
             ast::StmtKind::Block(stmts) => hir::StmtKind::Block(self.lower_block(stmts)),
             ast::StmtKind::UncheckedBlock(stmts) => {
                 hir::StmtKind::UncheckedBlock(self.lower_block(stmts))
@@ -1315,6 +1395,18 @@ impl<'gcx> SymbolResolver<'gcx> {
         }
     }
 
+    /// Resolves a path in the scope of the given source and contract, without emitting errors.
+    pub(crate) fn resolve_path_in(
+        &self,
+        path: &ast::PathSlice,
+        source: hir::SourceId,
+        contract: Option<hir::ContractId>,
+    ) -> Option<Res> {
+        let mut scopes = SymbolResolverScopes::new();
+        scopes.init(source, contract);
+        self.resolve_path(path, &scopes).ok().map(|decl| decl.res)
+    }
+
     fn resolve_path_as<T: TryFrom<Res>>(
         &self,
         path: &ast::PathSlice,
@@ -0,0 +1,450 @@
+//! Yul lowering and name resolution.
+
+use super::{Declaration, ResolveContext};
+use crate::{
+    hir::{self, Res, yul},
+    yul::{Dialect, builtin},
+};
+use solar_ast as ast;
+use solar_data_structures::map::{FxHashMap, FxHashSet};
+use solar_interface::{
+    Ident, Session, Span, Symbol,
+    diagnostics::{DiagCtxt, ErrorGuaranteed},
+    error_code, sym,
+};
+
+impl<'gcx> ResolveContext<'gcx> {
+    /// Lowers an assembly block, resolving references to Yul and Solidity declarations.
+    pub(super) fn lower_assembly(
+        &self,
+        assembly: &ast::StmtAssembly<'_>,
+    ) -> &'gcx hir::StmtAssembly<'gcx> {
+        let dialect = Dialect::inline_assembly(self.sess.opts.evm_version);
+        let lcx = YulLoweringContext::new(self.sess, self.arena, dialect, Some(self));
+        self.arena.alloc(hir::StmtAssembly {
+            dialect: assembly.dialect.clone(),
+            flags: self.arena.alloc_slice_clone(assembly.flags),
+            code: lcx.lower_code(&assembly.block),
+        })
+    }
+}
+
+/// Lowers a Yul object and its sub-objects, resolving the references inside of their code.
+pub(crate) fn lower_yul_object<'gcx>(
+    sess: &Session,
+    arena: &'gcx hir::Arena,
+    object: &ast::yul::Object<'_>,
+) -> yul::Object<'gcx> {
+    let dialect = Dialect::objects(sess.opts.evm_version);
+    let lcx = YulLoweringContext::new(sess, arena, dialect, None);
+    yul::Object {
+        span: object.span,
+        name: object.name.clone(),
+        code: lcx.lower_code(&object.code.code),
+        children: arena.alloc_slice_fill_iter(
+            object.children.iter().map(|child| lower_yul_object(sess, arena, child)),
+        ),
+        data: arena.alloc_slice_fill_iter(object.data.iter().map(|data| yul::Data {
+            span: data.span,
+            name: data.name.clone(),
+            data: arena.alloc(data.data.copy_without_data()),
+        })),
+    }
+}
+
+/// Yul lowering context.
+struct YulLoweringContext<'a, 'gcx> {
+    dcx: &'a DiagCtxt,
+    arena: &'gcx hir::Arena,
+    dialect: Dialect,
+    /// The Solidity context of an inline assembly block. `None` for Yul objects.
+    outer: Option<&'a ResolveContext<'gcx>>,
+    /// The Yul scopes, innermost last.
+    scopes: Vec<YulScope>,
+    variables: Vec<Ident>,
+    function_names: Vec<Ident>,
+    /// The lowered functions. `None` while the function is being lowered.
+    functions: Vec<Option<yul::Function<'gcx>>>,
+    /// Whether we are inside of a Yul function definition.
+    in_function: bool,
+}
+
+#[derive(Default)]
+struct YulScope {
+    variables: FxHashMap<Symbol, yul::VarId>,
+    functions: FxHashMap<Symbol, yul::FunctionId>,
+    /// The variables that are declared in this scope, including the ones that are not yet
+    /// visible. Used to report uses before declaration.
+    declared: FxHashSet<Symbol>,
+    /// Whether this is the outermost scope of a function, which hides the variables of the
+    /// enclosing scopes.
+    is_function: bool,
+}
+
+impl<'a, 'gcx> YulLoweringContext<'a, 'gcx> {
+    fn new(
+        sess: &'a Session,
+        arena: &'gcx hir::Arena,
+        dialect: Dialect,
+        outer: Option<&'a ResolveContext<'gcx>>,
+    ) -> Self {
+        Self {
+            dcx: &sess.dcx,
+            arena,
+            dialect,
+            outer,
+            scopes: Vec::new(),
+            variables: Vec::new(),
+            function_names: Vec::new(),
+            functions: Vec::new(),
+            in_function: false,
+        }
+    }
+
+    fn lower_code(mut self, block: &ast::yul::Block<'_>) -> yul::Code<'gcx> {
+        let block = self.lower_block(block);
+        let functions = self.functions.into_iter().map(|f| f.expect("function not lowered"));
+        yul::Code {
+            block,
+            variables: self.arena.alloc_slice_copy(&self.variables),
+            functions: self.arena.alloc_slice_fill_iter(functions),
+        }
+    }
+
+    fn in_scope<T>(&mut self, is_function: bool, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.scopes.push(YulScope { is_function, ..Default::default() });
+        let t = f(self);
+        self.scopes.pop();
+        t
+    }
+
+    fn lower_block(&mut self, block: &ast::yul::Block<'_>) -> yul::Block<'gcx> {
+        self.in_scope(false, |this| this.lower_stmts(block))
+    }
+
+    /// Lowers the statements of the given block in the current scope.
+    fn lower_stmts(&mut self, block: &ast::yul::Block<'_>) -> yul::Block<'gcx> {
+        // Functions are visible in the whole block they're defined in, including before their
+        // definition.
+        let mut function_ids = Vec::new();
+        for stmt in block.iter() {
+            match &stmt.kind {
+                ast::yul::StmtKind::FunctionDef(func) => {
+                    function_ids.push(self.declare_function(func.name));
+                }
+                ast::yul::StmtKind::VarDecl(names, _) => {
+                    let scope = self.scopes.last_mut().unwrap();
+                    scope.declared.extend(names.iter().map(|name| name.name));
+                }
+                _ => {}
+            }
+        }
+        let mut function_ids = function_ids.into_iter();
+
+        let stmts = self.arena.alloc_slice_fill_iter(block.iter().map(|stmt| {
+            let kind = match &stmt.kind {
+                ast::yul::StmtKind::FunctionDef(func) => {
+                    let id = function_ids.next().unwrap();
+                    self.lower_function(id, func, stmt.span);
+                    yul::StmtKind::FunctionDef(id)
+                }
+                _ => self.lower_stmt_kind(&stmt.kind),
+            };
+            yul::Stmt { span: stmt.span, kind }
+        }));
+        yul::Block { span: block.span, stmts }
+    }
+
+    fn lower_stmt_kind(&mut self, kind: &ast::yul::StmtKind<'_>) -> yul::StmtKind<'gcx> {
+        let arena = self.arena;
+        match kind {
+            ast::yul::StmtKind::Block(block) => yul::StmtKind::Block(self.lower_block(block)),
+            ast::yul::StmtKind::AssignSingle(path, expr) => yul::StmtKind::Assign(
+                arena.alloc_slice_fill_iter([self.lower_path_expr(path)]),
+                self.lower_expr(expr),
+            ),
+            ast::yul::StmtKind::AssignMulti(paths, expr) => yul::StmtKind::Assign(
+                arena.alloc_slice_fill_iter(paths.iter().map(|path| self.lower_path_expr(path))),
+                self.lower_expr(expr),
+            ),
+            ast::yul::StmtKind::Expr(expr) => yul::StmtKind::Expr(self.lower_expr(expr)),
+            ast::yul::StmtKind::If(cond, block) => {
+                yul::StmtKind::If(self.lower_expr(cond), self.lower_block(block))
+            }
+            ast::yul::StmtKind::For(for_) => {
+                // The variables declared in `init` are visible in the rest of the statement.
+                let for_ = self.in_scope(false, |this| yul::StmtFor {
+                    init: this.lower_stmts(&for_.init),
+                    cond: this.lower_expr_full(&for_.cond),
+                    step: this.lower_block(&for_.step),
+                    body: this.lower_block(&for_.body),
+                });
+                yul::StmtKind::For(arena.alloc(for_))
+            }
+            ast::yul::StmtKind::Switch(switch) => {
+                let selector = self.lower_expr_full(&switch.selector);
+                let cases = arena.alloc_slice_fill_iter(switch.cases.iter().map(|case| {
+                    yul::StmtSwitchCase {
+                        span: case.span,
+                        constant: case.constant.as_ref().map(|lit| self.lower_lit(lit)),
+                        body: self.lower_block(&case.body),
+                    }
+                }));
+                yul::StmtKind::Switch(arena.alloc(yul::StmtSwitch { selector, cases }))
+            }
+            ast::yul::StmtKind::Leave => yul::StmtKind::Leave,
+            ast::yul::StmtKind::Break => yul::StmtKind::Break,
+            ast::yul::StmtKind::Continue => yul::StmtKind::Continue,
+            ast::yul::StmtKind::FunctionDef(_) => unreachable!("handled in `lower_stmts`"),
+            ast::yul::StmtKind::VarDecl(names, value) => {
+                // The variables are not visible in their own initializer.
+                let value = value.as_ref().map(|value| self.lower_expr(value));
+                let ids = arena.alloc_slice_fill_iter(names.iter().map(|&name| {
+                    self.check_outer_shadowing(name);
+                    self.declare_var(name)
+                }));
+                yul::StmtKind::VarDecl(ids, value)
+            }
+        }
+    }
+
+    fn lower_function(&mut self, id: yul::FunctionId, func: &ast::yul::Function<'_>, span: Span) {
+        let prev_in_function = std::mem::replace(&mut self.in_function, true);
+        let function = self.in_scope(true, |this| {
+            let arena = this.arena;
+            let parameters =
+                arena.alloc_slice_fill_iter(func.parameters.iter().map(|&p| this.declare_var(p)));
+            let returns =
+                arena.alloc_slice_fill_iter(func.returns.iter().map(|&r| this.declare_var(r)));
+            yul::Function {
+                span,
+                name: func.name,
+                parameters,
+                returns,
+                body: this.lower_block(&func.body),
+            }
+        });
+        self.in_function = prev_in_function;
+        self.functions[id.index()] = Some(function);
+    }
+
+    fn declare_var(&mut self, name: Ident) -> yul::VarId {
+        self.validate_name(name);
+        self.check_shadowing(name, false);
+        let id = yul::VarId::from_usize(self.variables.len());
+        self.variables.push(name);
+        let scope = self.scopes.last_mut().unwrap();
+        scope.variables.insert(name.name, id);
+        scope.declared.insert(name.name);
+        id
+    }
+
+    fn declare_function(&mut self, name: Ident) -> yul::FunctionId {
+        self.validate_name(name);
+        self.check_shadowing(name, true);
+        let id = yul::FunctionId::from_usize(self.functions.len());
+        self.functions.push(None);
+        self.function_names.push(name);
+        self.scopes.last_mut().unwrap().functions.entry(name.name).or_insert(id);
+        id
+    }
+
+    fn lower_expr(&mut self, expr: &ast::yul::Expr<'_>) -> &'gcx yul::Expr<'gcx> {
+        let expr = self.lower_expr_full(expr);
+        self.arena.alloc(expr)
+    }
+
+    fn lower_expr_full(&mut self, expr: &ast::yul::Expr<'_>) -> yul::Expr<'gcx> {
+        let kind = match &expr.kind {
+            ast::yul::ExprKind::Path(path) => self.lower_path(path),
+            ast::yul::ExprKind::Call(call) => {
+                let arguments = self.arena.alloc_slice_fill_iter(
+                    call.arguments.iter().map(|arg| self.lower_expr_full(arg)),
+                );
+                match self.resolve_callee(call.name) {
+                    Ok(callee) => {
+                        yul::ExprKind::Call(yul::ExprCall { name: call.name, callee, arguments })
+                    }
+                    Err(guar) => yul::ExprKind::Err(guar),
+                }
+            }
+            ast::yul::ExprKind::Lit(lit) => yul::ExprKind::Lit(self.lower_lit(lit)),
+        };
+        yul::Expr { span: expr.span, kind }
+    }
+
+    fn lower_path_expr(&mut self, path: &ast::PathSlice) -> yul::Expr<'gcx> {
+        yul::Expr { span: path.span(), kind: self.lower_path(path) }
+    }
+
+    fn lower_lit(&self, lit: &ast::Lit<'_>) -> &'gcx ast::Lit<'gcx> {
+        self.arena.alloc(lit.copy_without_data())
+    }
+
+    fn resolve_callee(&self, name: Ident) -> Result<yul::Callee, ErrorGuaranteed> {
+        if builtin(name.name, self.dialect).is_some() {
+            return Ok(yul::Callee::Builtin);
+        }
+        if let Some(id) = self.resolve_function(name.name) {
+            return Ok(yul::Callee::Function(id));
+        }
+        Err(self
+            .dcx
+            .err(format!("function `{name}` not found"))
+            .code(error_code!(4619))
+            .span(name.span)
+            .emit())
+    }
+
+    /// Returns the scopes in which variables are visible, innermost first.
+    fn variable_scopes(&self) -> impl Iterator<Item = &YulScope> {
+        let start = self.scopes.iter().rposition(|scope| scope.is_function).unwrap_or(0);
+        self.scopes[start..].iter().rev()
+    }
+
+    fn resolve_var(&self, name: Symbol) -> Option<yul::VarId> {
+        self.variable_scopes().find_map(|scope| scope.variables.get(&name).copied())
+    }
+
+    /// Functions are visible across function boundaries.
+    fn resolve_function(&self, name: Symbol) -> Option<yul::FunctionId> {
+        self.scopes.iter().rev().find_map(|scope| scope.functions.get(&name).copied())
+    }
+
+    /// Resolves a path to either a Yul variable or a Solidity declaration, with an optional
+    /// suffix.
+    fn lower_path(&self, path: &ast::PathSlice) -> yul::ExprKind<'gcx> {
+        if let Some(ident) = path.get_ident()
+            && let Some(id) = self.resolve_var(ident.name)
+        {
+            return yul::ExprKind::Var(id);
+        }
+
+        let Some(rcx) = self.outer else {
+            return yul::ExprKind::Err(self.report_not_found(path));
+        };
+        let (name, suffix) = match *path.segments() {
+            [name] => (name, None),
+            [name, suffix] => match yul::Suffix::from_symbol(suffix.name) {
+                Some(suffix) => (name, Some(suffix)),
+                None => return yul::ExprKind::Err(self.report_not_found(path)),
+            },
+            _ => return yul::ExprKind::Err(self.report_not_found(path)),
+        };
+
+        let decls = match rcx.resolver.resolve_name_raw(name, &rcx.scopes) {
+            Some(decls) => decls,
+            None => return yul::ExprKind::Err(self.report_not_found(path)),
+        };
+        let [decl] = decls else {
+            let guar = self
+                .dcx
+                .err("multiple matching identifiers")
+                .code(error_code!(4718))
+                .span(name.span)
+                .note("resolving overloaded identifiers is not supported")
+                .emit();
+            return yul::ExprKind::Err(guar);
+        };
+        if let Res::Err(guar) = decl.res {
+            return yul::ExprKind::Err(guar);
+        }
+        if self.in_function
+            && let Res::Item(hir::ItemId::Variable(id)) = decl.res
+            && rcx.hir.variable(id).is_local_variable()
+        {
+            let guar = self
+                .dcx
+                .err("cannot access local Solidity variables from inside an inline assembly function")
+                .code(error_code!(6578))
+                .span(path.span())
+                .emit();
+            return yul::ExprKind::Err(guar);
+        }
+        yul::ExprKind::External(yul::ExternalRef { name, res: decl.res, suffix })
+    }
+
+    fn report_not_found(&self, path: &ast::PathSlice) -> ErrorGuaranteed {
+        if let Some(ident) = path.get_ident()
+            && self.variable_scopes().any(|scope| scope.declared.contains(&ident.name))
+        {
+            return self
+                .dcx
+                .err(format!("variable `{ident}` used before it was declared"))
+                .code(error_code!(4990))
+                .span(path.span())
+                .emit();
+        }
+
+        let name = path.last().as_str();
+        if self.outer.is_some() && (name.ends_with("_slot") || name.ends_with("_offset")) {
+            self.dcx
+                .err(format!("identifier `{path}` not found"))
+                .code(error_code!(9467))
+                .span(path.span())
+                .help("use `.slot` and `.offset` to access storage or transient storage variables")
+                .emit()
+        } else {
+            self.dcx
+                .err(format!("identifier `{path}` not found"))
+                .code(error_code!(8198))
+                .span(path.span())
+                .emit()
+        }
+    }
+
+    /// Checks that a user-defined Yul identifier is not a reserved name.
+    fn validate_name(&self, name: Ident) {
+        if self.outer.is_some() && matches!(name.name, sym::this | sym::super_ | sym::underscore) {
+            self.dcx
+                .err(format!("the identifier name `{name}` is reserved"))
+                .code(error_code!(4113))
+                .span(name.span)
+                .emit();
+        }
+    }
+
+    /// Checks that a Yul declaration does not shadow another Yul declaration.
+    ///
+    /// Unlike variable accesses, this also applies across function boundaries.
+    fn check_shadowing(&self, name: Ident, is_function: bool) {
+        let previous = self.scopes.iter().rev().find_map(|scope| {
+            if let Some(&id) = scope.functions.get(&name.name) {
+                return Some(self.function_names[id.index()]);
+            }
+            scope.variables.get(&name.name).map(|&id| self.variables[id.index()])
+        });
+        let Some(previous) = previous else { return };
+        let (kind, code) = if is_function {
+            ("function", error_code!(6052))
+        } else {
+            ("variable", error_code!(1395))
+        };
+        self.dcx
+            .err(format!("{kind} name `{name}` already taken in this scope"))
+            .code(code)
+            .span(name.span)
+            .span_note(previous.span, "previous declaration is here")
+            .emit();
+    }
+
+    /// Checks that a Yul variable declaration does not shadow a Solidity declaration.
+    fn check_outer_shadowing(&self, name: Ident) {
+        let Some(rcx) = self.outer else { return };
+        let Some(decls) = rcx.resolver.resolve_name_raw(name, &rcx.scopes) else { return };
+        let shadowed = decls.iter().filter(|decl| !decl.span.is_dummy());
+        let mut shadowed = shadowed.peekable();
+        if shadowed.peek().is_none() {
+            return;
+        }
+        let mut err = self
+            .dcx
+            .err("this declaration shadows a declaration outside the inline assembly block")
+            .code(error_code!(3859))
+            .span(name.span);
+        for &Declaration { span, .. } in shadowed {
+            err = err.span_note(span, "the shadowed declaration is here");
+        }
+        err.emit();
+    }
+}
@@ -3,12 +3,13 @@
 use alloy_primitives::Address;
 use solar_ast::{self as ast, visit::Visit};
 use solar_data_structures::Never;
-use solar_interface::{Session, Span, diagnostics::DiagCtxt, sym};
+use solar_interface::{SOLIDITY_VERSION, Session, Span, diagnostics::DiagCtxt, error_code, sym};
 use std::ops::ControlFlow;
 
 #[instrument(name = "ast_passes", level = "debug", skip_all)]
 pub(crate) fn run<'ast>(sess: &Session, ast: &'ast ast::SourceUnit<'ast>) {
     validate(sess, ast);
+    check_licenses(sess, ast);
 }
 
 /// Performs AST validation.
@@ -18,10 +19,55 @@ fn validate<'ast>(sess: &Session, ast: &'ast ast::SourceUnit<'ast>) {
     let _ = validator.visit_source_unit(ast);
 }
 
+/// Checks the SPDX license identifiers recorded by the parser.
+fn check_licenses(sess: &Session, ast: &ast::SourceUnit<'_>) {
+    let dcx = &sess.dcx;
+    match ast.licenses[..] {
+        [] => {
+            let span = ast.items.first().map_or(Span::DUMMY, |item| item.span.shrink_to_lo());
+            dcx.warn("SPDX license identifier not provided in source file")
+                .code(error_code!(1878))
+                .span(span)
+                .help("consider adding `// SPDX-License-Identifier: <SPDX-License>` to the source file, or `// SPDX-License-Identifier: UNLICENSED` for non-open-source code")
+                .note("see <https://spdx.org> for more information")
+                .emit();
+        }
+        [license] => {
+            if ast.license().is_none() {
+                dcx.err("invalid SPDX license identifier")
+                    .code(error_code!(1114))
+                    .span(license.span)
+                    .emit();
+            }
+        }
+        [first, second, ..] => {
+            dcx.err("multiple SPDX license identifiers found in source file")
+                .code(error_code!(3716))
+                .span(second.span)
+                .span_note(first.span, "first license identifier found here")
+                .help("use `AND` or `OR` to combine multiple licenses")
+                .note("see <https://spdx.org> for more information")
+                .emit();
+        }
+    }
+}
+
+/// Returns the version of the Solidity language that is implemented.
+fn solidity_version() -> ast::SemverVersion {
+    ast::SemverVersion {
+        span: Span::DUMMY,
+        major: SOLIDITY_VERSION.major.into(),
+        minor: Some(SOLIDITY_VERSION.minor.into()),
+        patch: Some(SOLIDITY_VERSION.patch.into()),
+    }
+}
+
 /// AST validator.
 struct AstValidator<'sess, 'ast> {
     item_span: Span,
     dcx: &'sess DiagCtxt,
+    /// The version to check `pragma solidity` requirements against, if any.
+    solidity_version: Option<ast::SemverVersion>,
     contract: Option<&'ast ast::ItemContract<'ast>>,
     function_kind: Option<ast::FunctionKind>,
     in_unchecked_block: bool,
@@ -34,6 +80,7 @@ impl<'sess> AstValidator<'sess, '_> {
         Self {
             item_span: Span::DUMMY,
             dcx: &sess.dcx,
+            solidity_version: (!sess.opts.ignore_pragma_version).then(solidity_version),
             contract: None,
             function_kind: None,
             in_unchecked_block: false,
@@ -183,10 +230,21 @@ impl<'ast> Visit<'ast> for AstValidator<'_, 'ast> {
         pragma: &'ast ast::PragmaDirective<'ast>,
     ) -> ControlFlow<Self::BreakValue> {
         match &pragma.tokens {
-            ast::PragmaTokens::Version(name, _version) => {
+            ast::PragmaTokens::Version(name, req) => {
                 if name.name != sym::solidity {
                     let msg = "only `solidity` is supported as a version pragma";
                     self.dcx().err(msg).span(name.span).emit();
+                } else if let Some(version) = &self.solidity_version
+                    && !req.matches(version)
+                {
+                    let req_span = req.dis.first().unwrap().span.to(req.dis.last().unwrap().span);
+                    self.dcx()
+                        .err("source file requires different compiler version")
+                        .code(error_code!(3997))
+                        .span(self.item_span)
+                        .span_note(req_span, format!("current compiler is {SOLIDITY_VERSION}"))
+                        .help("use `--ignore-pragma-version` to ignore the requirement")
+                        .emit();
                 }
             }
             ast::PragmaTokens::Custom(name, value) => {
@@ -229,16 +287,11 @@ impl<'ast> Visit<'ast> for AstValidator<'_, 'ast> {
                     self.check_single_statement_variable_declaration(else_);
                 }
             }
-            ast::StmtKind::Break | ast::StmtKind::Continue => {
-                if !self.in_loop() {
-                    let kind = if matches!(stmt.kind, ast::StmtKind::Break) {
-                        "break"
-                    } else {
-                        "continue"
-                    };
-                    let msg = format!("`{kind}` outside of a loop");
-                    self.dcx().err(msg).span(stmt.span).emit();
-                }
+            ast::StmtKind::Break | ast::StmtKind::Continue if !self.in_loop() => {
+                let kind =
+                    if matches!(stmt.kind, ast::StmtKind::Break) { "break" } else { "continue" };
+                let msg = format!("`{kind}` outside of a loop");
+                self.dcx().err(msg).span(stmt.span).emit();
             }
             ast::StmtKind::UncheckedBlock(_block) => {
                 if self.in_unchecked_block {
@@ -4,7 +4,7 @@ use crate::{
     ty::{Gcx, Ty, TyFnPtr, TyKind},
 };
 use solar_ast::{DataLocation, ElementaryType, StateMutability as SM};
-use solar_data_structures::BumpExt;
+use solar_data_structures::{BumpExt, map::FxHashSet};
 use solar_interface::Symbol;
 
 pub type MemberList<'gcx> = &'gcx [Member<'gcx>];
@@ -26,7 +26,7 @@ pub(crate) fn native_members<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>) -> MemberList<'
         },
         TyKind::StringLiteral(_utf8, _size) => Default::default(),
         TyKind::IntLiteral(_negative, _size) => Default::default(),
-        TyKind::Ref(inner, loc) => reference(gcx, ty, inner, loc),
+        TyKind::Ref(inner, loc) => reference(gcx, inner, loc),
         TyKind::DynArray(_ty) => expected_ref(),
         TyKind::Array(_ty, _len) => expected_ref(),
         TyKind::Slice(_ty) => Default::default(),
@@ -62,6 +62,108 @@ pub(crate) fn native_members<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>) -> MemberList<'
     })
 }
 
+/// Returns the native members of `ty`, followed by the functions attached to it with `using for`
+/// directives that are visible in the given scope.
+pub(crate) fn members_in_scope<'gcx>(
+    gcx: Gcx<'gcx>,
+    ty: Ty<'gcx>,
+    source: hir::SourceId,
+    contract: Option<hir::ContractId>,
+) -> MemberList<'gcx> {
+    let native = gcx.native_members(ty);
+    let attached = attached_functions(gcx, ty, source, contract);
+    if attached.is_empty() {
+        return native;
+    }
+    gcx.bump().alloc_from_iter(native.iter().copied().chain(attached))
+}
+
+/// Returns the functions attached to `ty` with `using for` directives, with their first
+/// parameter bound.
+///
+/// The directives are looked up in the contract, in the source file, and, for user-defined types,
+/// the `global` directives in the source file that defines the type.
+///
+/// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/ast/Types.cpp#L357>
+fn attached_functions<'gcx>(
+    gcx: Gcx<'gcx>,
+    ty: Ty<'gcx>,
+    source: hir::SourceId,
+    contract: Option<hir::ContractId>,
+) -> MemberListOwned<'gcx> {
+    let mut members = MemberListOwned::new();
+    if ty.references_error()
+        || matches!(
+            ty.kind,
+            TyKind::Type(_)
+                | TyKind::Meta(_)
+                | TyKind::Module(_)
+                | TyKind::BuiltinModule(_)
+                | TyKind::Error(..)
+                | TyKind::Event(..)
+                | TyKind::Tuple(_)
+        )
+    {
+        return members;
+    }
+
+    let peeled = ty.peel_refs();
+    let contract_usings = contract.map(|id| gcx.hir.contract(id).usings).unwrap_or_default();
+    let source_usings = gcx.hir.source(source).usings;
+    let type_source = match peeled.kind {
+        TyKind::Struct(id) => Some(gcx.hir.strukt(id).source),
+        TyKind::Enum(id) => Some(gcx.hir.enumm(id).source),
+        TyKind::Udvt(_, id) => Some(gcx.hir.udvt(id).source),
+        _ => None,
+    };
+    let global_usings = type_source
+        .filter(|&id| id != source)
+        .map(|id| gcx.hir.source(id).usings)
+        .unwrap_or_default()
+        .iter()
+        .filter(|using| using.global);
+
+    let mut seen = FxHashSet::default();
+    for using in contract_usings.iter().chain(source_usings).chain(global_usings) {
+        if let Some(using_ty) = &using.ty
+            && gcx.type_of_hir_ty(using_ty).peel_refs() != peeled
+        {
+            continue;
+        }
+
+        let mut attach = |f_id: hir::FunctionId| {
+            let TyKind::FnPtr(f) = gcx.type_of_item(f_id.into()).kind else { unreachable!() };
+            let Some((&self_ty, parameters)) = f.parameters.split_first() else { return };
+            if !ty.convert_implicit_to(self_ty, gcx) || !seen.insert(f_id) {
+                return;
+            }
+            let bound = gcx.mk_ty_fn_ptr(TyFnPtr { parameters, function_id: None, ..*f });
+            members.push(Member::with_res(
+                gcx.item_name(f_id).name,
+                bound,
+                hir::ItemId::from(f_id),
+            ));
+        };
+        match using.list {
+            hir::UsingList::Library(id) => {
+                for f_id in gcx.hir.contract(id).functions() {
+                    let f = gcx.hir.function(f_id);
+                    if f.is_ordinary() && f.visibility >= hir::Visibility::Internal {
+                        attach(f_id);
+                    }
+                }
+            }
+            hir::UsingList::Functions(functions) => {
+                // Functions bound to operators are not attached as members.
+                for &(f_id, _) in functions.iter().filter(|(_, op)| op.is_none()) {
+                    attach(f_id);
+                }
+            }
+        }
+    }
+    members
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Member<'gcx> {
     pub name: Symbol,
@@ -83,7 +185,7 @@ impl<'gcx> Member<'gcx> {
     }
 
     pub fn of_builtin(gcx: Gcx<'gcx>, builtin: Builtin) -> Self {
-        Self::new(builtin.name(), builtin.ty(gcx))
+        Self::with_builtin(builtin, builtin.ty(gcx))
     }
 
     pub fn of_builtins(
@@ -151,12 +253,7 @@ fn function<'gcx>(gcx: Gcx<'gcx>, f: &'gcx TyFnPtr<'gcx>) -> MemberListOwned<'gc
     members
 }
 
-fn reference<'gcx>(
-    gcx: Gcx<'gcx>,
-    this: Ty<'gcx>,
-    inner: Ty<'gcx>,
-    loc: DataLocation,
-) -> MemberListOwned<'gcx> {
+fn reference<'gcx>(gcx: Gcx<'gcx>, inner: Ty<'gcx>, loc: DataLocation) -> MemberListOwned<'gcx> {
     match (&inner.kind, loc) {
         (&TyKind::Struct(id), _) => {
             let fields = gcx.hir.strukt(id).fields;
@@ -172,24 +269,25 @@ fn reference<'gcx>(
             TyKind::DynArray(_) | TyKind::Elementary(ElementaryType::Bytes),
             DataLocation::Storage,
         ) => {
-            let inner = if let TyKind::DynArray(inner) = inner.kind {
-                inner.with_loc_if_ref(gcx, loc)
+            let (inner, value) = if let TyKind::DynArray(inner) = inner.kind {
+                // Pushed values are copied into storage from any data location.
+                (inner.with_loc_if_ref(gcx, loc), inner.with_loc_if_ref(gcx, DataLocation::Memory))
             } else {
-                gcx.types.fixed_bytes(1)
+                (gcx.types.fixed_bytes(1), gcx.types.fixed_bytes(1))
             };
             vec![
                 Member::of_builtin(gcx, Builtin::ArrayLength),
                 Member::with_builtin(
                     Builtin::ArrayPush0,
-                    gcx.mk_builtin_fn(&[this, inner], SM::NonPayable, &[]),
+                    gcx.mk_builtin_fn(&[], SM::NonPayable, &[inner]),
                 ),
                 Member::with_builtin(
                     Builtin::ArrayPush,
-                    gcx.mk_builtin_fn(&[this], SM::NonPayable, &[inner]),
+                    gcx.mk_builtin_fn(&[value], SM::NonPayable, &[]),
                 ),
                 Member::with_builtin(
                     Builtin::ArrayPop,
-                    gcx.mk_builtin_fn(&[this], SM::NonPayable, &[]),
+                    gcx.mk_builtin_fn(&[], SM::NonPayable, &[]),
                 ),
             ]
         }
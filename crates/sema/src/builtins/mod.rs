@@ -94,7 +94,7 @@ declare_builtins! {
     Revert                 => kw::Revert
                            => gcx.mk_builtin_fn(&[], SM::Pure, &[]);
     RevertMsg              => kw::Revert
-                           => gcx.mk_builtin_fn(&[gcx.types.string], SM::Pure, &[]);
+                           => gcx.mk_builtin_fn(&[gcx.types.string_ref.memory], SM::Pure, &[]);
 
     AddMod                 => kw::Addmod
                            => gcx.mk_builtin_fn(&[gcx.types.uint(256), gcx.types.uint(256), gcx.types.uint(256)], SM::Pure, &[gcx.types.uint(256)]);
@@ -162,22 +162,22 @@ declare_builtins! {
                            => gcx.types.uint(256);
 
     // `abi`
-    // TODO                => `(T...) pure returns(bytes memory)`
+    // Checked by the type checker: `(T...) pure returns(bytes memory)`
     AbiEncode              => sym::encode
                            => gcx.mk_builtin_fn(&[], SM::Pure, &[gcx.types.bytes_ref.memory]);
-    // TODO                => `(T...) pure returns(bytes memory)`
+    // Checked by the type checker: `(T...) pure returns(bytes memory)`
     AbiEncodePacked        => sym::encodePacked
                            => gcx.mk_builtin_fn(&[], SM::Pure, &[gcx.types.bytes_ref.memory]);
-    // TODO                => `(bytes4, T...) pure returns(bytes memory)`
+    // Checked by the type checker: `(bytes4, T...) pure returns(bytes memory)`
     AbiEncodeWithSelector  => sym::encodeWithSelector
                            => gcx.mk_builtin_fn(&[], SM::Pure, &[gcx.types.bytes_ref.memory]);
-    // TODO                => `(F, T...) pure returns(bytes memory)`
+    // Checked by the type checker: `(F, (T...)) pure returns(bytes memory)`
     AbiEncodeCall          => sym::encodeCall
                            => gcx.mk_builtin_fn(&[], SM::Pure, &[gcx.types.bytes_ref.memory]);
-    // TODO                => `(string memory, T...) pure returns(bytes memory)`
+    // Checked by the type checker: `(string memory, T...) pure returns(bytes memory)`
     AbiEncodeWithSignature => sym::encodeWithSignature
                            => gcx.mk_builtin_fn(&[], SM::Pure, &[gcx.types.bytes_ref.memory]);
-    // TODO                => `(bytes memory, (T...)) pure returns(T...)`
+    // Checked by the type checker: `(bytes memory, (T...)) pure returns(T...)`
     AbiDecode              => sym::decode
                            => gcx.mk_builtin_fn(&[], SM::Pure, &[]);
 
@@ -190,9 +190,9 @@ declare_builtins! {
     AddressCodehash        => sym::codehash
                            => gcx.types.fixed_bytes(32);
     AddressCall            => kw::Call
-                           => gcx.mk_builtin_fn(&[gcx.types.bytes_ref.memory], SM::View, &[gcx.types.bytes_ref.memory]);
+                           => gcx.mk_builtin_fn(&[gcx.types.bytes_ref.memory], SM::Payable, &[gcx.types.bytes_ref.memory]);
     AddressDelegatecall    => kw::Delegatecall
-                           => gcx.mk_builtin_fn(&[gcx.types.bytes_ref.memory], SM::View, &[gcx.types.bytes_ref.memory]);
+                           => gcx.mk_builtin_fn(&[gcx.types.bytes_ref.memory], SM::NonPayable, &[gcx.types.bytes_ref.memory]);
     AddressStaticcall      => kw::Staticcall
                            => gcx.mk_builtin_fn(&[gcx.types.bytes_ref.memory], SM::View, &[gcx.types.bytes_ref.memory]);
 
@@ -235,11 +235,11 @@ declare_builtins! {
     UdvtWrap               => sym::wrap   => unreachable!();
     UdvtUnwrap             => sym::unwrap => unreachable!();
 
-    // TODO                => `(string memory...) pure returns(string memory)`
+    // Checked by the type checker: `(string memory...) pure returns(string memory)`
     StringConcat           => sym::concat
                            => gcx.mk_builtin_fn(&[], SM::Pure, &[gcx.types.string_ref.memory]);
 
-    // TODO                => `(bytes memory...) pure returns(bytes memory)`
+    // Checked by the type checker: `(bytes memory...) pure returns(bytes memory)`
     BytesConcat            => sym::concat
                            => gcx.mk_builtin_fn(&[], SM::Pure, &[gcx.types.bytes_ref.memory]);
 }
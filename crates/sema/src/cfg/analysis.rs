@@ -0,0 +1,166 @@
+use super::{Cfg, NodeId, Occurrence, OccurrenceKind};
+use crate::{hir, ty::Gcx};
+use solar_ast::DataLocation;
+use solar_data_structures::{
+    index::IndexVec,
+    map::{FxHashSet, FxIndexSet},
+};
+use solar_interface::error_code;
+
+/// Analyzes the control flow graph of the given function.
+pub(super) fn check<'gcx>(gcx: Gcx<'gcx>, func: &'gcx hir::Function<'gcx>, cfg: &Cfg) {
+    check_unreachable(gcx, cfg);
+    check_uninitialized_access(gcx, func, cfg);
+}
+
+/// Warns about code that can never be executed.
+fn check_unreachable(gcx: Gcx<'_>, cfg: &Cfg) {
+    let reachable = cfg.reachable();
+    let preds = cfg.predecessors();
+
+    // Walk backwards from the exits, so that only code that is part of some path is reported.
+    let mut visited = IndexVec::from_vec(vec![false; cfg.nodes.len()]);
+    let mut stack = vec![cfg.exit, cfg.revert, cfg.transaction_return];
+    let mut unreachable = Vec::new();
+    while let Some(id) = stack.pop() {
+        if std::mem::replace(&mut visited[id], true) {
+            continue;
+        }
+        if !reachable[id]
+            && let Some(span) = cfg.nodes[id].span
+        {
+            unreachable.push(span);
+        }
+        stack.extend(preds[id].iter().copied());
+    }
+
+    unreachable.sort_by_key(|span| (span.lo(), span.hi()));
+    let mut spans = unreachable.into_iter().peekable();
+    while let Some(mut span) = spans.next() {
+        // Merge overlapping spans.
+        while let Some(&next) = spans.peek()
+            && next.lo() <= span.hi()
+        {
+            span = span.to(next);
+            spans.next();
+        }
+        gcx.dcx().warn("unreachable code").code(error_code!(5740)).span(span).emit();
+    }
+}
+
+/// The data flow state of a node in [`check_uninitialized_access`].
+#[derive(Clone, Default)]
+struct NodeInfo {
+    /// The variables that may be unassigned when entering the node.
+    unassigned_at_entry: FxHashSet<hir::VariableId>,
+    /// The variables that may be unassigned when leaving the node.
+    unassigned_at_exit: FxHashSet<hir::VariableId>,
+    /// The accesses of unassigned variables on the paths that lead through the node.
+    accesses: FxHashSet<(NodeId, usize)>,
+}
+
+impl NodeInfo {
+    /// Merges the state at the exit of a predecessor into the state at the entry of this node.
+    /// Returns `true` if the state changed.
+    fn propagate_from(&mut self, pred: &Self) -> bool {
+        let prev = (self.unassigned_at_entry.len(), self.accesses.len());
+        self.unassigned_at_entry.extend(pred.unassigned_at_exit.iter().copied());
+        self.accesses.extend(pred.accesses.iter().copied());
+        prev != (self.unassigned_at_entry.len(), self.accesses.len())
+    }
+}
+
+/// Reports storage and calldata pointers that can be used before being assigned, and unnamed
+/// return variables that can remain unassigned.
+///
+/// Only paths that return from the function are considered, as reverting discards any effects of
+/// the uninitialized accesses.
+fn check_uninitialized_access<'gcx>(gcx: Gcx<'gcx>, func: &'gcx hir::Function<'gcx>, cfg: &Cfg) {
+    let mut infos: IndexVec<NodeId, Option<NodeInfo>> =
+        IndexVec::from_vec(vec![None; cfg.nodes.len()]);
+    infos[cfg.entry] = Some(NodeInfo::default());
+    let mut worklist = FxIndexSet::default();
+    worklist.insert(cfg.entry);
+    while let Some(id) = worklist.pop() {
+        let info = infos[id].as_mut().unwrap();
+        let mut unassigned = info.unassigned_at_entry.clone();
+        for (i, occurrence) in cfg.nodes[id].occurrences.iter().enumerate() {
+            match occurrence.kind {
+                OccurrenceKind::Declaration => {
+                    unassigned.insert(occurrence.var);
+                }
+                OccurrenceKind::Assignment => {
+                    unassigned.remove(&occurrence.var);
+                }
+                OccurrenceKind::Access | OccurrenceKind::Return => {
+                    if unassigned.contains(&occurrence.var) {
+                        info.accesses.insert((id, i));
+                    }
+                }
+            }
+        }
+        info.unassigned_at_exit = unassigned;
+
+        let info = info.clone();
+        for &succ in &cfg.nodes[id].succs {
+            let changed = match &mut infos[succ] {
+                Some(succ_info) => succ_info.propagate_from(&info),
+                succ_info @ None => {
+                    let mut new = NodeInfo::default();
+                    new.propagate_from(&info);
+                    *succ_info = Some(new);
+                    true
+                }
+            };
+            if changed {
+                worklist.insert(succ);
+            }
+        }
+    }
+
+    let Some(exit) = &infos[cfg.exit] else { return };
+    let mut accesses: Vec<&Occurrence> =
+        exit.accesses.iter().map(|&(node, i)| &cfg.nodes[node].occurrences[i]).collect();
+    let span_of = |occurrence: &Occurrence| {
+        occurrence.span.unwrap_or_else(|| gcx.hir.variable(occurrence.var).span)
+    };
+    accesses.sort_by_key(|&occurrence| {
+        let span = span_of(occurrence);
+        (span.lo(), span.hi(), occurrence.kind == OccurrenceKind::Return)
+    });
+
+    let empty_body = func.body.is_none_or(|body| body.stmts.is_empty());
+    let mut warned = FxHashSet::default();
+    for occurrence in accesses {
+        let var = gcx.hir.variable(occurrence.var);
+        let ty = gcx.type_of_item(occurrence.var.into());
+        let location = if ty.data_stored_in(DataLocation::Storage) {
+            "storage"
+        } else if ty.data_stored_in(DataLocation::Calldata) {
+            "calldata"
+        } else {
+            if var.name.is_none() && !empty_body && warned.insert(occurrence.var) {
+                gcx.dcx()
+                    .warn("unnamed return variable can remain unassigned")
+                    .code(error_code!(6321))
+                    .span(var.span)
+                    .help(
+                        "add an explicit return with value to all non-reverting code paths or name the variable",
+                    )
+                    .emit();
+            }
+            continue;
+        };
+        let action =
+            if occurrence.kind == OccurrenceKind::Return { "returned" } else { "accessed" };
+        let msg = format!(
+            "this variable is of {location} pointer type and can be {action} without prior assignment, \
+             which would lead to undefined behaviour"
+        );
+        let mut diag = gcx.dcx().err(msg).code(error_code!(3464)).span(span_of(occurrence));
+        if occurrence.span.is_some() {
+            diag = diag.span_note(var.span, "the variable was declared here");
+        }
+        diag.emit();
+    }
+}
@@ -0,0 +1,517 @@
+use super::{Cfg, Node, NodeId, Occurrence, OccurrenceKind};
+use crate::{
+    builtins::Builtin,
+    hir::{self, yul},
+    ty::Gcx,
+};
+use solar_ast::BinOpKind;
+use solar_interface::Span;
+
+/// Builds the control flow graph of the given function.
+pub(super) fn build<'gcx>(gcx: Gcx<'gcx>, func: &'gcx hir::Function<'gcx>) -> Cfg {
+    let cfg = Cfg::new();
+    let entry = cfg.entry;
+    let exit = cfg.exit;
+    let mut builder = CfgBuilder {
+        gcx,
+        cfg,
+        current: entry,
+        return_node: exit,
+        returns: func.returns,
+        placeholder: None,
+        loops: Vec::new(),
+    };
+    builder.function(func);
+    builder.cfg
+}
+
+struct CfgBuilder<'gcx> {
+    gcx: Gcx<'gcx>,
+    cfg: Cfg,
+    /// The node that is currently being built.
+    current: NodeId,
+    /// The node that `return` statements flow into.
+    return_node: NodeId,
+    /// The return variables of the function.
+    returns: &'gcx [hir::VariableId],
+    /// The entry and exit nodes of the `_` placeholder, when building the body of a modifier.
+    placeholder: Option<(NodeId, NodeId)>,
+    /// The `continue` and `break` targets of the enclosing loops.
+    loops: Vec<(NodeId, NodeId)>,
+}
+
+impl<'gcx> CfgBuilder<'gcx> {
+    fn function(&mut self, func: &'gcx hir::Function<'gcx>) {
+        for &ret in func.returns {
+            self.occurrence(ret, OccurrenceKind::Declaration, None);
+            let occurrence = Occurrence { var: ret, kind: OccurrenceKind::Return, span: None };
+            self.cfg.nodes[self.return_node].occurrences.push(occurrence);
+        }
+
+        // Modifiers wrap the rest of the function: the code before `_` runs first, and `return`s
+        // in the inner code flow into the code after `_`.
+        for modifier in func.modifiers {
+            self.call_args(&modifier.args);
+            let hir::ItemId::Function(id) = modifier.id else { continue };
+            let modifier = self.gcx.hir.function(id);
+            let Some(body) = modifier.body else { continue };
+            let placeholder = (self.new_node(), self.new_node());
+            self.placeholder = Some(placeholder);
+            // Start a new node so that nodes never span multiple sources.
+            let node = self.new_node();
+            self.connect(self.current, node);
+            self.current = node;
+            self.block(body.stmts);
+            self.connect(self.current, self.return_node);
+            (self.current, self.return_node) = placeholder;
+            self.placeholder = None;
+        }
+
+        if let Some(body) = func.body {
+            self.block(body.stmts);
+        }
+        self.connect(self.current, self.return_node);
+    }
+
+    fn block(&mut self, stmts: &'gcx [hir::Stmt<'gcx>]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'gcx hir::Stmt<'gcx>) {
+        self.cover(stmt.span);
+        match stmt.kind {
+            hir::StmtKind::DeclSingle(id) => {
+                let var = self.gcx.hir.variable(id);
+                self.occurrence(id, OccurrenceKind::Declaration, Some(var.span));
+                if let Some(init) = var.initializer {
+                    self.expr(init);
+                    self.occurrence(id, OccurrenceKind::Assignment, Some(var.span));
+                }
+            }
+            hir::StmtKind::DeclMulti(vars, expr) => {
+                let vars = vars.iter().flatten().copied();
+                for id in vars.clone() {
+                    let span = self.gcx.hir.variable(id).span;
+                    self.occurrence(id, OccurrenceKind::Declaration, Some(span));
+                }
+                self.expr(expr);
+                for id in vars {
+                    let span = self.gcx.hir.variable(id).span;
+                    self.occurrence(id, OccurrenceKind::Assignment, Some(span));
+                }
+            }
+            hir::StmtKind::Block(block) | hir::StmtKind::UncheckedBlock(block) => {
+                self.block(block.stmts)
+            }
+            hir::StmtKind::Emit(expr) | hir::StmtKind::Expr(expr) => self.expr(expr),
+            hir::StmtKind::Revert(expr) => {
+                self.expr(expr);
+                self.terminate(self.cfg.revert);
+            }
+            hir::StmtKind::Return(expr) => {
+                if let Some(expr) = expr {
+                    self.expr(expr);
+                    for &ret in self.returns {
+                        self.occurrence(ret, OccurrenceKind::Assignment, Some(stmt.span));
+                    }
+                }
+                self.terminate(self.return_node);
+            }
+            hir::StmtKind::Break => {
+                if let Some(&(_, brk)) = self.loops.last() {
+                    self.terminate(brk);
+                }
+            }
+            hir::StmtKind::Continue => {
+                if let Some(&(cont, _)) = self.loops.last() {
+                    self.terminate(cont);
+                }
+            }
+            hir::StmtKind::Loop(block, source) => self.loop_(block, source),
+            hir::StmtKind::If(cond, true_, false_) => {
+                self.expr(cond);
+                let after = self.new_node();
+                let from = self.current;
+                self.branch(from, after, |this| this.stmt(true_));
+                match false_ {
+                    Some(false_) => self.branch(from, after, |this| this.stmt(false_)),
+                    None => self.connect(from, after),
+                }
+                self.current = after;
+            }
+            hir::StmtKind::Try(try_) => {
+                self.expr(&try_.expr);
+                let after = self.new_node();
+                let from = self.current;
+                for clause in try_.clauses {
+                    self.branch(from, after, |this| this.block(clause.block.stmts));
+                }
+                self.current = after;
+            }
+            hir::StmtKind::Assembly(assembly) => self.yul_block(&assembly.code.block),
+            hir::StmtKind::Placeholder => {
+                if let Some((entry, exit)) = self.placeholder {
+                    self.connect(self.current, entry);
+                    self.current = exit;
+                }
+            }
+            hir::StmtKind::Err(_) => {}
+        }
+    }
+
+    /// Builds a loop, which has already been desugared by the lowering.
+    ///
+    /// See `codegen::function::FunctionCx::loop_`.
+    fn loop_(&mut self, block: hir::Block<'gcx>, source: hir::LoopSource) {
+        let (cond, body, next) = match source {
+            hir::LoopSource::While | hir::LoopSource::For => {
+                // `[if (<cond>) <body> else break]` or `[<body>]`.
+                let (cond, mut body) = match block.stmts {
+                    [
+                        hir::Stmt {
+                            kind:
+                                hir::StmtKind::If(
+                                    cond,
+                                    body,
+                                    Some(hir::Stmt { kind: hir::StmtKind::Break, .. }),
+                                ),
+                            ..
+                        },
+                    ] => (Some(*cond), std::slice::from_ref(*body)),
+                    stmts => (None, stmts),
+                };
+                // `{ <body>; <next>; }`
+                let mut next = None;
+                if source == hir::LoopSource::For
+                    && let [hir::Stmt { kind: hir::StmtKind::Block(inner), .. }] = body
+                    && inner.span == block.span
+                    && let [stmt, hir::Stmt { kind: hir::StmtKind::Expr(expr), .. }] = inner.stmts
+                {
+                    body = std::slice::from_ref(stmt);
+                    next = Some(*expr);
+                }
+                (cond, body, next)
+            }
+            hir::LoopSource::DoWhile => {
+                // `[{ <body> }, if (<cond>) continue else break]`
+                let [body, hir::Stmt { kind: hir::StmtKind::If(cond, ..), .. }] = block.stmts
+                else {
+                    unreachable!("invalid do-while loop: {block:?}")
+                };
+                (Some(*cond), std::slice::from_ref(body), None)
+            }
+        };
+        let do_while = source == hir::LoopSource::DoWhile;
+
+        let head = self.new_node();
+        self.connect(self.current, head);
+        let after = self.new_node();
+        let cont = if next.is_some() || do_while { self.new_node() } else { head };
+
+        self.current = head;
+        if !do_while && let Some(cond) = cond {
+            self.expr(cond);
+            self.connect(self.current, after);
+        }
+
+        self.loops.push((cont, after));
+        self.block(body);
+        self.loops.pop();
+        self.connect(self.current, cont);
+
+        if cont != head {
+            self.current = cont;
+            if let Some(next) = next {
+                self.expr(next);
+            }
+            if do_while && let Some(cond) = cond {
+                self.expr(cond);
+                self.connect(self.current, after);
+            }
+            self.connect(self.current, head);
+        }
+        self.current = after;
+    }
+
+    fn expr(&mut self, expr: &'gcx hir::Expr<'gcx>) {
+        self.cover(expr.span);
+        match expr.kind {
+            hir::ExprKind::Assign(lhs, op, rhs) => {
+                self.expr(rhs);
+                if op.is_some() {
+                    self.expr(lhs);
+                }
+                self.assign(lhs);
+            }
+            hir::ExprKind::Delete(expr) => self.assign(expr),
+            hir::ExprKind::Unary(op, expr) if op.kind.has_side_effects() => {
+                self.expr(expr);
+                self.assign(expr);
+            }
+            hir::ExprKind::Binary(lhs, op, rhs)
+                if matches!(op.kind, BinOpKind::And | BinOpKind::Or) =>
+            {
+                self.expr(lhs);
+                let after = self.new_node();
+                let from = self.current;
+                self.branch(from, after, |this| this.expr(rhs));
+                self.connect(from, after);
+                self.current = after;
+            }
+            hir::ExprKind::Ternary(cond, true_, false_) => {
+                self.expr(cond);
+                let after = self.new_node();
+                let from = self.current;
+                self.branch(from, after, |this| this.expr(true_));
+                self.branch(from, after, |this| this.expr(false_));
+                self.current = after;
+            }
+            hir::ExprKind::Call(callee, ref args, opts) => {
+                self.expr(callee);
+                for opt in opts.into_iter().flatten() {
+                    self.expr(&opt.value);
+                }
+                self.call_args(args);
+                self.call(callee);
+            }
+            hir::ExprKind::Ident(res) => {
+                if let [hir::Res::Item(hir::ItemId::Variable(id))] = *res {
+                    self.occurrence(id, OccurrenceKind::Access, Some(expr.span));
+                }
+            }
+            hir::ExprKind::Unary(_, expr)
+            | hir::ExprKind::Member(expr, _)
+            | hir::ExprKind::Payable(expr) => self.expr(expr),
+            hir::ExprKind::Binary(lhs, _, rhs) => {
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            hir::ExprKind::Index(expr, index) => {
+                self.expr(expr);
+                if let Some(index) = index {
+                    self.expr(index);
+                }
+            }
+            hir::ExprKind::Slice(expr, start, end) => {
+                self.expr(expr);
+                for expr in [start, end].into_iter().flatten() {
+                    self.expr(expr);
+                }
+            }
+            hir::ExprKind::Array(exprs) => {
+                for expr in exprs {
+                    self.expr(expr);
+                }
+            }
+            hir::ExprKind::Tuple(exprs) => {
+                for expr in exprs.iter().copied().flatten() {
+                    self.expr(expr);
+                }
+            }
+            hir::ExprKind::Lit(_)
+            | hir::ExprKind::New(_)
+            | hir::ExprKind::TypeCall(_)
+            | hir::ExprKind::Type(_)
+            | hir::ExprKind::Err(_) => {}
+        }
+    }
+
+    /// Records a write to the given expression, which has already been evaluated if needed.
+    ///
+    /// Only variables that are written to directly are assigned. The base of an index or member
+    /// access is read instead.
+    fn assign(&mut self, expr: &'gcx hir::Expr<'gcx>) {
+        match expr.kind {
+            hir::ExprKind::Ident(&[hir::Res::Item(hir::ItemId::Variable(id))]) => {
+                self.occurrence(id, OccurrenceKind::Assignment, Some(expr.span));
+            }
+            hir::ExprKind::Tuple(exprs) => {
+                for expr in exprs.iter().copied().flatten() {
+                    self.assign(expr);
+                }
+            }
+            _ => self.expr(expr),
+        }
+    }
+
+    fn call_args(&mut self, args: &'gcx hir::CallArgs<'gcx>) {
+        for expr in args.kind.exprs() {
+            self.expr(expr);
+        }
+    }
+
+    /// Handles the control flow of a call to `callee`, after its arguments have been evaluated.
+    fn call(&mut self, callee: &'gcx hir::Expr<'gcx>) {
+        let hir::ExprKind::Ident(res) = callee.peel_parens().kind else { return };
+        if !res.is_empty()
+            && res
+                .iter()
+                .all(|res| matches!(res, hir::Res::Builtin(Builtin::Revert | Builtin::RevertMsg)))
+        {
+            self.terminate(self.cfg.revert);
+        } else if let [
+            hir::Res::Builtin(Builtin::Require | Builtin::RequireMsg | Builtin::Assert),
+        ] = *res
+        {
+            self.connect(self.current, self.cfg.revert);
+        } else if let [hir::Res::Item(hir::ItemId::Function(id))] = *res
+            && self.gcx.hir.function(id).body.is_some()
+        {
+            // Split the node, so that the call can be pruned if the callee never returns.
+            // Virtual functions are resolved in the context of the contract being analyzed, which
+            // is the one that defines the function.
+            self.cfg.nodes[self.current].call = Some(id);
+            let next = self.new_node();
+            self.connect(self.current, next);
+            self.current = next;
+        }
+    }
+
+    fn yul_block(&mut self, block: &'gcx yul::Block<'gcx>) {
+        for stmt in block.stmts {
+            self.yul_stmt(stmt);
+        }
+    }
+
+    fn yul_stmt(&mut self, stmt: &'gcx yul::Stmt<'gcx>) {
+        self.cover(stmt.span);
+        match stmt.kind {
+            yul::StmtKind::Block(ref block) => self.yul_block(block),
+            yul::StmtKind::Assign(lhs, rhs) => {
+                self.yul_expr(rhs);
+                for expr in lhs {
+                    if let yul::ExprKind::External(ext) = expr.kind
+                        && let hir::Res::Item(hir::ItemId::Variable(id)) = ext.res
+                    {
+                        self.occurrence(id, OccurrenceKind::Assignment, Some(expr.span));
+                    }
+                }
+            }
+            yul::StmtKind::Expr(expr) => self.yul_expr(expr),
+            yul::StmtKind::VarDecl(_, value) => {
+                if let Some(value) = value {
+                    self.yul_expr(value);
+                }
+            }
+            yul::StmtKind::If(cond, ref block) => {
+                self.yul_expr(cond);
+                let after = self.new_node();
+                let from = self.current;
+                self.branch(from, after, |this| this.yul_block(block));
+                self.connect(from, after);
+                self.current = after;
+            }
+            yul::StmtKind::For(for_) => {
+                self.yul_block(&for_.init);
+                let head = self.new_node();
+                self.connect(self.current, head);
+                let after = self.new_node();
+                let step = self.new_node();
+
+                self.current = head;
+                self.yul_expr(&for_.cond);
+                self.connect(self.current, after);
+
+                self.loops.push((step, after));
+                self.yul_block(&for_.body);
+                self.loops.pop();
+                self.connect(self.current, step);
+
+                self.current = step;
+                self.yul_block(&for_.step);
+                self.connect(self.current, head);
+                self.current = after;
+            }
+            yul::StmtKind::Switch(switch) => {
+                self.yul_expr(&switch.selector);
+                let after = self.new_node();
+                let from = self.current;
+                for case in switch.cases {
+                    self.branch(from, after, |this| this.yul_block(&case.body));
+                }
+                if switch.cases.iter().all(|case| case.constant.is_some()) {
+                    self.connect(from, after);
+                }
+                self.current = after;
+            }
+            yul::StmtKind::Break => {
+                if let Some(&(_, brk)) = self.loops.last() {
+                    self.terminate(brk);
+                }
+            }
+            yul::StmtKind::Continue => {
+                if let Some(&(cont, _)) = self.loops.last() {
+                    self.terminate(cont);
+                }
+            }
+            // Only valid in functions, which are not part of the control flow.
+            yul::StmtKind::Leave | yul::StmtKind::FunctionDef(_) => {}
+        }
+    }
+
+    fn yul_expr(&mut self, expr: &'gcx yul::Expr<'gcx>) {
+        self.cover(expr.span);
+        match expr.kind {
+            yul::ExprKind::Call(ref call) => {
+                for arg in call.arguments.iter().rev() {
+                    self.yul_expr(arg);
+                }
+                if call.callee == yul::Callee::Builtin {
+                    match call.name.as_str() {
+                        "revert" | "invalid" => self.terminate(self.cfg.revert),
+                        "return" | "stop" => self.terminate(self.cfg.transaction_return),
+                        _ => {}
+                    }
+                }
+            }
+            yul::ExprKind::External(ext) => {
+                if let hir::Res::Item(hir::ItemId::Variable(id)) = ext.res {
+                    self.occurrence(id, OccurrenceKind::Access, Some(expr.span));
+                }
+            }
+            yul::ExprKind::Var(_) | yul::ExprKind::Lit(_) | yul::ExprKind::Err(_) => {}
+        }
+    }
+
+    /// Builds a branch from `from` that flows into `to`.
+    fn branch(&mut self, from: NodeId, to: NodeId, f: impl FnOnce(&mut Self)) {
+        self.current = self.new_node();
+        self.connect(from, self.current);
+        f(self);
+        self.connect(self.current, to);
+    }
+
+    /// Makes the current node flow into `to`, and continues in a new unreachable node.
+    fn terminate(&mut self, to: NodeId) {
+        self.connect(self.current, to);
+        self.current = self.new_node();
+    }
+
+    fn new_node(&mut self) -> NodeId {
+        self.cfg.nodes.push(Node::default())
+    }
+
+    fn connect(&mut self, from: NodeId, to: NodeId) {
+        let succs = &mut self.cfg.nodes[from].succs;
+        if !succs.contains(&to) {
+            succs.push(to);
+        }
+    }
+
+    /// Extends the span of the current node to cover `span`.
+    fn cover(&mut self, span: Span) {
+        if span.is_dummy() {
+            return;
+        }
+        let node = &mut self.cfg.nodes[self.current];
+        node.span = Some(node.span.map_or(span, |s| s.to(span)));
+    }
+
+    fn occurrence(&mut self, var: hir::VariableId, kind: OccurrenceKind, span: Option<Span>) {
+        if self.gcx.hir.variable(var).is_local_or_return() {
+            self.cfg.nodes[self.current].occurrences.push(Occurrence { var, kind, span });
+        }
+    }
+}
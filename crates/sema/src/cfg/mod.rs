@@ -0,0 +1,190 @@
+//! Control flow analysis.
+//!
+//! Builds a control flow graph for every implemented function, with its modifiers inlined, and
+//! checks for:
+//! - unreachable code
+//! - storage and calldata pointers that are accessed or returned before being assigned
+//! - unnamed return variables that can remain unassigned
+//!
+//! Reference: solc `libsolidity/analysis/ControlFlowGraph.cpp`, `ControlFlowBuilder.cpp`,
+//! `ControlFlowRevertPruner.cpp`, and `ControlFlowAnalyzer.cpp`
+
+use crate::{hir, ty::Gcx};
+use rayon::prelude::*;
+use solar_data_structures::{index::IndexVec, map::FxHashSet, newtype_index, smallvec::SmallVec};
+use solar_interface::Span;
+
+mod analysis;
+mod builder;
+
+newtype_index! {
+    /// A [`Node`] ID. Indexes into [`Cfg::nodes`].
+    pub(crate) struct NodeId;
+}
+
+/// The control flow graph of a function.
+#[derive(Debug)]
+pub(crate) struct Cfg {
+    pub(crate) nodes: IndexVec<NodeId, Node>,
+    /// The entry of the function.
+    pub(crate) entry: NodeId,
+    /// Reached when the function returns to its caller.
+    pub(crate) exit: NodeId,
+    /// Reached when the function reverts.
+    pub(crate) revert: NodeId,
+    /// Reached when the function ends the transaction without reverting, e.g. with the `return`
+    /// or `stop` assembly builtins.
+    pub(crate) transaction_return: NodeId,
+}
+
+/// A node of a [`Cfg`]: a sequence of code without any branches.
+#[derive(Debug, Default)]
+pub(crate) struct Node {
+    /// The nodes that control can flow to from this node.
+    pub(crate) succs: SmallVec<[NodeId; 2]>,
+    /// The smallest span that covers the code in this node, if any.
+    pub(crate) span: Option<Span>,
+    /// The local variable occurrences in this node, in execution order.
+    pub(crate) occurrences: Vec<Occurrence>,
+    /// The internal function that is called at the end of this node, if it is known statically.
+    pub(crate) call: Option<hir::FunctionId>,
+}
+
+/// An occurrence of a local or return variable.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Occurrence {
+    pub(crate) var: hir::VariableId,
+    pub(crate) kind: OccurrenceKind,
+    /// The span of the occurrence. `None` for implicit occurrences, like returning from the
+    /// function.
+    pub(crate) span: Option<Span>,
+}
+
+/// The kind of an [`Occurrence`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OccurrenceKind {
+    /// The variable is declared, without a value.
+    Declaration,
+    /// The variable is assigned to.
+    Assignment,
+    /// The variable is read.
+    Access,
+    /// The variable is returned from the function.
+    Return,
+}
+
+impl Cfg {
+    fn new() -> Self {
+        let mut nodes = IndexVec::new();
+        let entry = nodes.push(Node::default());
+        let exit = nodes.push(Node::default());
+        let revert = nodes.push(Node::default());
+        let transaction_return = nodes.push(Node::default());
+        Self { nodes, entry, exit, revert, transaction_return }
+    }
+
+    /// Returns the predecessors of every node.
+    pub(crate) fn predecessors(&self) -> IndexVec<NodeId, SmallVec<[NodeId; 2]>> {
+        let mut preds = IndexVec::from_vec(vec![SmallVec::new(); self.nodes.len()]);
+        for (id, node) in self.nodes.iter_enumerated() {
+            for &succ in &node.succs {
+                preds[succ].push(id);
+            }
+        }
+        preds
+    }
+
+    /// Returns the nodes that are reachable from the entry.
+    pub(crate) fn reachable(&self) -> IndexVec<NodeId, bool> {
+        let mut reachable = IndexVec::from_vec(vec![false; self.nodes.len()]);
+        let mut stack = vec![self.entry];
+        while let Some(id) = stack.pop() {
+            if std::mem::replace(&mut reachable[id], true) {
+                continue;
+            }
+            stack.extend(self.nodes[id].succs.iter().copied());
+        }
+        reachable
+    }
+
+    /// Makes the nodes that call one of the given functions flow into the revert node.
+    fn prune_calls(&mut self, reverting: &FxHashSet<hir::FunctionId>) {
+        let revert = self.revert;
+        for node in self.nodes.iter_mut() {
+            if node.call.is_some_and(|callee| reverting.contains(&callee)) {
+                node.succs.clear();
+                node.succs.push(revert);
+                node.call = None;
+            }
+        }
+    }
+}
+
+/// Builds the control flow graphs of all implemented functions, and analyzes them.
+pub(crate) fn check(gcx: Gcx<'_>) {
+    let mut cfgs: IndexVec<hir::FunctionId, Option<Cfg>> = IndexVec::from_vec(
+        gcx.hir
+            .par_function_ids()
+            .map(|id| {
+                let func = gcx.hir.function(id);
+                (!func.kind.is_modifier() && func.body.is_some()).then(|| builder::build(gcx, func))
+            })
+            .collect(),
+    );
+
+    // Calls to internal functions that never return are treated as reverts.
+    let reverting = reverting_functions(&cfgs);
+    if !reverting.is_empty() {
+        cfgs.as_raw_slice_mut()
+            .par_iter_mut()
+            .flatten()
+            .for_each(|cfg| cfg.prune_calls(&reverting));
+    }
+
+    cfgs.as_raw_slice().par_iter().enumerate().for_each(|(i, cfg)| {
+        if let Some(cfg) = cfg {
+            analysis::check(gcx, gcx.hir.function(hir::FunctionId::from_usize(i)), cfg);
+        }
+    });
+}
+
+/// Returns the functions whose exit is unreachable, including through calls to other such
+/// functions.
+fn reverting_functions(
+    cfgs: &IndexVec<hir::FunctionId, Option<Cfg>>,
+) -> FxHashSet<hir::FunctionId> {
+    let mut reverting = FxHashSet::default();
+    loop {
+        let mut changed = false;
+        for (id, cfg) in cfgs.iter_enumerated() {
+            let Some(cfg) = cfg else { continue };
+            if reverting.contains(&id) {
+                continue;
+            }
+            let mut visited = FxHashSet::default();
+            let mut stack = vec![cfg.entry];
+            let mut returns = false;
+            while let Some(node) = stack.pop() {
+                if node == cfg.exit {
+                    returns = true;
+                    break;
+                }
+                if !visited.insert(node) {
+                    continue;
+                }
+                let node = &cfg.nodes[node];
+                if node.call.is_some_and(|callee| reverting.contains(&callee)) {
+                    continue;
+                }
+                stack.extend(node.succs.iter().copied());
+            }
+            if !returns {
+                reverting.insert(id);
+                changed = true;
+            }
+        }
+        if !changed {
+            return reverting;
+        }
+    }
+}
@@ -0,0 +1,429 @@
+//! ABI encoding and decoding helpers.
+//!
+//! Values are always converted to memory before being encoded, so the encoders only need to
+//! handle value types and memory references.
+//!
+//! Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/codegen/ABIFunctions.cpp>
+
+use super::{
+    ObjectCx,
+    utils::{self, MAX_LEN, Repr, num},
+    yul::template,
+};
+use crate::ty::{Ty, TyKind};
+use alloy_primitives::U256;
+use solar_ast::{DataLocation, ElementaryType};
+
+impl<'gcx> ObjectCx<'_, 'gcx> {
+    /// Returns the type that a value of type `ty` is converted to before being ABI-encoded.
+    pub(crate) fn abi_encoding_type(&self, ty: Ty<'gcx>) -> Ty<'gcx> {
+        match ty.kind {
+            TyKind::Ref(..) => ty.with_loc(self.gcx, DataLocation::Memory),
+            TyKind::StringLiteral(..) => self.gcx.types.string_ref.memory,
+            TyKind::IntLiteral(..) => ty.mobile(self.gcx).unwrap_or(ty),
+            TyKind::Udvt(inner, _) => inner,
+            _ => ty,
+        }
+    }
+
+    /// Returns `true` if a value of the given type can be ABI-encoded.
+    pub(crate) fn is_abi_encodable(&self, ty: Ty<'gcx>) -> bool {
+        match ty.kind {
+            TyKind::Ref(inner, loc) => {
+                let from_calldata = loc != DataLocation::Calldata || self.is_calldata_copyable(ty);
+                from_calldata && !inner.has_mapping() && self.is_abi_encodable(inner)
+            }
+            TyKind::Array(elem, _) | TyKind::DynArray(elem) => self.is_abi_encodable(elem),
+            TyKind::Struct(id) => {
+                self.gcx.struct_field_types(id).iter().all(|&ty| self.is_abi_encodable(ty))
+            }
+            TyKind::StringLiteral(..) | TyKind::IntLiteral(..) => true,
+            TyKind::Udvt(inner, _) => self.is_abi_encodable(inner),
+            TyKind::Elementary(ElementaryType::Fixed(..) | ElementaryType::UFixed(..)) => false,
+            _ => utils::repr(self.gcx, ty).is_some() || ty.is_array_like(),
+        }
+    }
+
+    /// Returns `true` if the given calldata reference can be copied to memory.
+    pub(crate) fn is_calldata_copyable(&self, ty: Ty<'gcx>) -> bool {
+        match ty.peel_refs().kind {
+            TyKind::Elementary(ElementaryType::Bytes | ElementaryType::String) => true,
+            TyKind::DynArray(elem) | TyKind::Array(elem, _) => elem.is_value_type(),
+            TyKind::Struct(id) => {
+                self.gcx.struct_field_types(id).iter().all(|ty| ty.is_value_type())
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if a value of the given type can be ABI-decoded.
+    pub(crate) fn is_abi_decodable(&self, ty: Ty<'gcx>) -> bool {
+        match ty.kind {
+            TyKind::Ref(..) => self.is_calldata_copyable(ty),
+            _ => utils::repr(self.gcx, ty).is_some(),
+        }
+    }
+
+    /// Returns the size of the static part of the ABI encoding of the given memory type.
+    fn abi_head_size(&self, ty: Ty<'gcx>) -> U256 {
+        if ty.peel_refs().is_dynamically_encoded(self.gcx) {
+            return U256::from(32);
+        }
+        match ty.peel_refs().kind {
+            TyKind::Array(elem, len) => len.saturating_mul(self.abi_head_size(elem)),
+            TyKind::Struct(id) => {
+                let fields = self.gcx.struct_field_types(id);
+                fields
+                    .iter()
+                    .fold(U256::ZERO, |acc, &ty| acc.saturating_add(self.abi_head_size(ty)))
+            }
+            _ => U256::from(32),
+        }
+    }
+
+    // --- Encoding ---
+
+    /// `abi_encode_tuple_<tys>(headStart, value0, ...) -> tail`.
+    ///
+    /// The values must already be converted to their [encoding types](Self::abi_encoding_type).
+    pub(crate) fn abi_encode_tuple_fn(&mut self, tys: &[Ty<'gcx>]) -> String {
+        let name = format!("abi_encode_tuple_{}", utils::types_id(self.gcx, tys));
+        let tys = tys.to_vec();
+        self.helper(name.clone(), |cx| {
+            let mut params = vec!["headStart".to_string()];
+            let mut head_size = U256::ZERO;
+            let mut body = String::new();
+            for (i, &ty) in tys.iter().enumerate() {
+                let value = format!("value{i}");
+                let head = format!("add(headStart, {})", num(head_size));
+                if ty.peel_refs().is_dynamically_encoded(cx.gcx) {
+                    body += &format!(
+                        "mstore({head}, sub(tail, headStart))\ntail := {}({value}, tail)\n",
+                        cx.abi_encode_fn(ty)
+                    );
+                } else {
+                    body += &format!("pop({}({value}, {head}))\n", cx.abi_encode_fn(ty));
+                }
+                head_size = head_size.saturating_add(cx.abi_head_size(ty));
+                params.push(value);
+            }
+            template!(
+                "function <name>(<params>) -> tail {
+                    tail := add(headStart, <head_size>)
+                    <body>
+                }",
+                name = name,
+                params = params.join(", "),
+                head_size = num(head_size),
+                body = body,
+            )
+        })
+    }
+
+    /// `abi_encode_<ty>(value, pos) -> end`: writes the full encoding of a value at `pos`.
+    pub(crate) fn abi_encode_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let name = format!("abi_encode_{}", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            let body = match ty.peel_refs().kind {
+                _ if ty.is_value_type() => {
+                    let repr = cx.value_repr(ty);
+                    format!(
+                        "mstore(pos, {})\nend := add(pos, 32)",
+                        utils::cleanup_expr(repr, "value")
+                    )
+                }
+                TyKind::Elementary(_) => template!(
+                    "let length := mload(value)
+                    mstore(pos, length)
+                    <copy>(add(value, 32), add(pos, 32), length)
+                    end := add(add(pos, 32), <round_up>(length))",
+                    copy = cx.copy_memory_fn(),
+                    round_up = cx.round_up_fn(),
+                ),
+                TyKind::DynArray(elem) => {
+                    let elems =
+                        cx.abi_encode_elements(elem, "add(value, 32)", "length", "headStart");
+                    template!(
+                        "let length := mload(value)
+                        mstore(pos, length)
+                        let headStart := add(pos, 32)
+                        <elems>",
+                        elems = elems,
+                    )
+                }
+                TyKind::Array(elem, len) => cx.abi_encode_elements(elem, "value", &num(len), "pos"),
+                TyKind::Struct(id) => {
+                    let fields = cx.gcx.struct_field_types(id).to_vec();
+                    let mut head_size = U256::ZERO;
+                    for &field in &fields {
+                        head_size = head_size.saturating_add(cx.abi_head_size(field));
+                    }
+                    let mut body = format!("let tail := add(pos, {})\n", num(head_size));
+                    let mut offset = U256::ZERO;
+                    for (i, &field) in fields.iter().enumerate() {
+                        let field = field.with_loc_if_ref(cx.gcx, DataLocation::Memory);
+                        let value = format!("mload(add(value, {}))", i * 32);
+                        let head = format!("add(pos, {})", num(offset));
+                        if field.peel_refs().is_dynamically_encoded(cx.gcx) {
+                            body += &format!(
+                                "mstore({head}, sub(tail, pos))\ntail := {}({value}, tail)\n",
+                                cx.abi_encode_fn(field)
+                            );
+                        } else {
+                            body += &format!("pop({}({value}, {head}))\n", cx.abi_encode_fn(field));
+                        }
+                        offset = offset.saturating_add(cx.abi_head_size(field));
+                    }
+                    body + "end := tail"
+                }
+                _ => unreachable!("cannot ABI-encode {ty:?}"),
+            };
+            template!("function <name>(value, pos) -> end { <body> }", name = name, body = body)
+        })
+    }
+
+    /// Returns the code that encodes `length` array elements stored in memory at `data`, with the
+    /// head starting at `head_start`. Assigns `end`.
+    fn abi_encode_elements(
+        &mut self,
+        elem: Ty<'gcx>,
+        data: &str,
+        length: &str,
+        head_start: &str,
+    ) -> String {
+        let elem = elem.with_loc_if_ref(self.gcx, DataLocation::Memory);
+        let encode = self.abi_encode_fn(elem);
+        if elem.peel_refs().is_dynamically_encoded(self.gcx) {
+            format!(
+                "let tail := add({head_start}, mul({length}, 32))
+                for {{ let i := 0 }} lt(i, {length}) {{ i := add(i, 1) }} {{
+                    mstore(add({head_start}, mul(i, 32)), sub(tail, {head_start}))
+                    tail := {encode}(mload(add({data}, mul(i, 32))), tail)
+                }}
+                end := tail"
+            )
+        } else {
+            format!(
+                "end := {head_start}
+                for {{ let i := 0 }} lt(i, {length}) {{ i := add(i, 1) }} {{
+                    end := {encode}(mload(add({data}, mul(i, 32))), end)
+                }}"
+            )
+        }
+    }
+
+    /// `abi_encode_packed_<ty>(value, pos) -> end`.
+    pub(crate) fn abi_encode_packed_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let name = format!("abi_encode_packed_{}", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            let body = match ty.peel_refs().kind {
+                _ if ty.is_value_type() => {
+                    let repr = cx.value_repr(ty);
+                    let cleaned = utils::cleanup_expr(repr, "value");
+                    let aligned = match repr {
+                        Repr::FixedBytes(_) | Repr::Unsigned(256) | Repr::Signed(256) => cleaned,
+                        _ => format!("shl({}, {cleaned})", 256 - repr.bytes() as u32 * 8),
+                    };
+                    format!("mstore(pos, {aligned})\nend := add(pos, {})", repr.bytes())
+                }
+                TyKind::Elementary(_) => template!(
+                    "let length := mload(value)
+                    <copy>(add(value, 32), pos, length)
+                    end := add(pos, length)",
+                    copy = cx.copy_memory_fn(),
+                ),
+                TyKind::DynArray(elem) | TyKind::Array(elem, _) => {
+                    let (data, length) = match ty.peel_refs().kind {
+                        TyKind::Array(_, len) => ("value".to_string(), num(len)),
+                        _ => ("add(value, 32)".to_string(), "mload(value)".to_string()),
+                    };
+                    let encode = cx.abi_encode_fn(elem);
+                    format!(
+                        "let length := {length}
+                        end := pos
+                        for {{ let i := 0 }} lt(i, length) {{ i := add(i, 1) }} {{
+                            end := {encode}(mload(add({data}, mul(i, 32))), end)
+                        }}"
+                    )
+                }
+                _ => unreachable!("cannot ABI-encode {ty:?} in packed mode"),
+            };
+            template!("function <name>(value, pos) -> end { <body> }", name = name, body = body)
+        })
+    }
+
+    // --- Decoding ---
+
+    /// `abi_decode_tuple_<tys>(headStart, dataEnd) -> value0, ...`.
+    ///
+    /// Decodes from calldata, or from memory if `from_memory` is set, in which case all reference
+    /// types are decoded into memory.
+    pub(crate) fn abi_decode_tuple_fn(&mut self, tys: &[Ty<'gcx>], from_memory: bool) -> String {
+        let suffix = if from_memory { "_fromMemory" } else { "" };
+        let name = format!("abi_decode_tuple_{}{suffix}", utils::types_id(self.gcx, tys));
+        let tys = tys.to_vec();
+        let load = if from_memory { "mload" } else { "calldataload" };
+        self.helper(name.clone(), |cx| {
+            let mut rets = Vec::new();
+            let mut head_size = U256::ZERO;
+            let mut body = String::new();
+            for (i, &ty) in tys.iter().enumerate() {
+                let values: Vec<_> = (0..utils::stack_size(ty))
+                    .map(|j| if j == 0 { format!("value{i}") } else { format!("value{i}_{j}") })
+                    .collect();
+                let decode = cx.abi_decode_fn(ty, from_memory);
+                let offset = if ty.peel_refs().is_dynamically_encoded(cx.gcx) {
+                    format!(
+                        "let offset := {load}(add(headStart, {}))
+                        if gt(offset, {MAX_LEN}) {{ revert(0, 0) }}",
+                        num(head_size)
+                    )
+                } else {
+                    format!("let offset := {}", num(head_size))
+                };
+                body += &format!(
+                    "{{\n{offset}\n{} := {decode}(add(headStart, offset), dataEnd)\n}}\n",
+                    values.join(", ")
+                );
+                head_size = head_size.saturating_add(cx.abi_head_size(ty));
+                rets.extend(values);
+            }
+            let rets =
+                if rets.is_empty() { String::new() } else { format!("-> {}", rets.join(", ")) };
+            template!(
+                "function <name>(headStart, dataEnd) <rets> {
+                    if slt(sub(dataEnd, headStart), <head_size>) { revert(0, 0) }
+                    <body>
+                }",
+                name = name,
+                rets = rets,
+                head_size = num(head_size),
+                body = body,
+            )
+        })
+    }
+
+    /// `abi_decode_<ty>(offset, end) -> value...`: decodes a single value at `offset`.
+    fn abi_decode_fn(&mut self, ty: Ty<'gcx>, from_memory: bool) -> String {
+        let suffix = if from_memory { "_fromMemory" } else { "" };
+        let name = format!("abi_decode_{}{suffix}", self.type_id(ty));
+        let load = if from_memory { "mload" } else { "calldataload" };
+        self.helper(name.clone(), |cx| {
+            let (rets, body) = match ty.kind {
+                _ if ty.is_value_type() => (
+                    "value",
+                    format!("value := {load}(offset)\n{}(value)", cx.validator_fn(ty)),
+                ),
+                TyKind::Ref(inner, DataLocation::Calldata) => {
+                    let elem_size = match inner.kind {
+                        TyKind::Elementary(_) => 1,
+                        _ => 32,
+                    };
+                    match inner.kind {
+                        TyKind::Array(_, len) => (
+                            "value",
+                            format!(
+                                "value := offset
+                                if gt(add(offset, {}), end) {{ revert(0, 0) }}",
+                                num(len.saturating_mul(U256::from(32)))
+                            ),
+                        ),
+                        TyKind::Struct(id) => (
+                            "value",
+                            format!(
+                                "value := offset
+                                if gt(add(offset, {}), end) {{ revert(0, 0) }}",
+                                cx.gcx.struct_field_types(id).len() * 32
+                            ),
+                        ),
+                        _ => (
+                            "arrayPos, length",
+                            format!(
+                                "if iszero(slt(add(offset, 0x1f), end)) {{ revert(0, 0) }}
+                                length := calldataload(offset)
+                                if gt(length, {MAX_LEN}) {{ revert(0, 0) }}
+                                arrayPos := add(offset, 0x20)
+                                if gt(add(arrayPos, mul(length, {elem_size})), end) {{ revert(0, 0) }}"
+                            ),
+                        ),
+                    }
+                }
+                TyKind::Ref(inner, _) => {
+                    // Decode into memory.
+                    let body = match inner.kind {
+                        TyKind::Elementary(_) => template!(
+                            "if iszero(slt(add(offset, 0x1f), end)) { revert(0, 0) }
+                            let length := <load>(offset)
+                            if gt(add(add(offset, 0x20), length), end) { revert(0, 0) }
+                            value := <allocate>(length)
+                            let dst := add(value, 0x20)
+                            <copy>
+                            mstore(add(dst, length), 0)",
+                            load = load,
+                            allocate = cx.allocate_array_fn(inner),
+                            copy = if from_memory {
+                                format!("{}(add(offset, 0x20), dst, length)", cx.copy_memory_fn())
+                            } else {
+                                "calldatacopy(dst, add(offset, 0x20), length)".to_string()
+                            },
+                        ),
+                        TyKind::DynArray(elem) => template!(
+                            "if iszero(slt(add(offset, 0x1f), end)) { revert(0, 0) }
+                            let length := <load>(offset)
+                            let src := add(offset, 0x20)
+                            if gt(add(src, mul(length, 0x20)), end) { revert(0, 0) }
+                            value := <allocate>(length)
+                            for { let i := 0 } lt(i, length) { i := add(i, 1) } {
+                                let elem := <load>(add(src, mul(i, 0x20)))
+                                <validator>(elem)
+                                mstore(add(value, mul(add(i, 1), 0x20)), elem)
+                            }",
+                            load = load,
+                            allocate = cx.allocate_array_fn(inner),
+                            validator = cx.validator_fn(elem),
+                        ),
+                        TyKind::Array(elem, len) => template!(
+                            "if gt(add(offset, <size>), end) { revert(0, 0) }
+                            value := <allocate>(<size>)
+                            for { let i := 0 } lt(i, <len>) { i := add(i, 1) } {
+                                let elem := <load>(add(offset, mul(i, 0x20)))
+                                <validator>(elem)
+                                mstore(add(value, mul(i, 0x20)), elem)
+                            }",
+                            size = num(len.saturating_mul(U256::from(32))),
+                            allocate = cx.allocate_memory_fn(),
+                            len = num(len),
+                            load = load,
+                            validator = cx.validator_fn(elem),
+                        ),
+                        TyKind::Struct(id) => {
+                            let fields = cx.gcx.struct_field_types(id).to_vec();
+                            let mut body = format!(
+                                "if gt(add(offset, {0}), end) {{ revert(0, 0) }}\nvalue := {1}({0})\n",
+                                fields.len() * 32,
+                                cx.allocate_memory_fn()
+                            );
+                            for (i, &field) in fields.iter().enumerate() {
+                                body += &format!(
+                                    "{{\nlet elem := {load}(add(offset, {0}))\n{1}(elem)\nmstore(add(value, {0}), elem)\n}}\n",
+                                    i * 32,
+                                    cx.validator_fn(field)
+                                );
+                            }
+                            body
+                        }
+                        _ => unreachable!("cannot ABI-decode {ty:?}"),
+                    };
+                    ("value", body)
+                }
+                _ => unreachable!("cannot ABI-decode {ty:?}"),
+            };
+            template!(
+                "function <name>(offset, end) -> <rets> { <body> }",
+                name = name,
+                rets = rets,
+                body = body,
+            )
+        })
+    }
+}
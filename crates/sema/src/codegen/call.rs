@@ -0,0 +1,945 @@
+//! Call and builtin lowering.
+
+use super::{
+    contract,
+    expr::memory_field_index,
+    function::{FunctionCx, tuple_types},
+    utils::{self, Repr, num},
+    yul::template,
+};
+use crate::{
+    builtins::Builtin,
+    hir,
+    ty::{Ty, TyKind},
+};
+use alloy_primitives::U256;
+use solar_ast::{DataLocation, ElementaryType};
+use solar_interface::Span;
+
+/// The selector of `Error(string)`.
+const ERROR_STRING_SELECTOR: u32 = 0x08c379a0;
+
+impl<'gcx> FunctionCx<'_, '_, 'gcx> {
+    /// Lowers a call expression.
+    pub(crate) fn call(
+        &mut self,
+        expr: &'gcx hir::Expr<'gcx>,
+        callee: &'gcx hir::Expr<'gcx>,
+        args: &'gcx hir::CallArgs<'gcx>,
+        opts: Option<&'gcx [hir::NamedArg<'gcx>]>,
+        ty: Ty<'gcx>,
+    ) -> Vec<String> {
+        let gcx = self.cx.gcx;
+        if let Some(hir::Res::Builtin(builtin)) = self.cx.res_of(callee) {
+            let base = match callee.kind {
+                hir::ExprKind::Member(base, _) => Some(base),
+                _ => None,
+            };
+            return self.builtin_call(expr, builtin, base, args, opts, ty);
+        }
+
+        let callee_ty = self.cx.ty_of(callee);
+        match callee_ty.kind {
+            TyKind::Type(to) => {
+                if let TyKind::Struct(id) = to.kind {
+                    return self.struct_constructor(id, args);
+                }
+                let arg = args.exprs().next().expect("conversion without argument");
+                self.expr_to(arg, ty)
+            }
+            TyKind::FnPtr(f) => {
+                if let hir::ExprKind::New(_) = callee.kind {
+                    return self.new_expr(expr, args, opts, ty, f.parameters);
+                }
+                // Functions attached with `using for` are resolved to the library function.
+                if let hir::ExprKind::Member(base, _) = callee.kind
+                    && f.function_id.is_none()
+                    && let Some(hir::Res::Item(hir::ItemId::Function(id))) = self.cx.res_of(callee)
+                {
+                    return self.library_call(expr, id, Some(base), args, ty);
+                }
+                let Some(id) = f.function_id else {
+                    self.cx.unsupported(expr.span, "calling function pointers");
+                    return vec!["0".into(); utils::stack_size(ty)];
+                };
+                match callee.kind {
+                    hir::ExprKind::Ident(_) => {
+                        let id = self.cx.resolve_virtual(id, None);
+                        self.internal_call(id, None, args, ty)
+                    }
+                    hir::ExprKind::Member(base, _) => {
+                        let base_ty = self.cx.ty_of(base);
+                        if let hir::ExprKind::Ident(_) = base.kind
+                            && let Some(hir::Res::Builtin(Builtin::Super)) = self.cx.res_of(base)
+                        {
+                            let id = self.cx.resolve_virtual(id, self.contract);
+                            return self.internal_call(id, None, args, ty);
+                        }
+                        match base_ty.kind {
+                            TyKind::Contract(_) => self.external_call(
+                                expr,
+                                id,
+                                base,
+                                args,
+                                opts,
+                                f.parameters,
+                                f.returns,
+                            ),
+                            TyKind::Type(lib) if matches!(lib.kind, TyKind::Contract(c) if gcx.hir.contract(c).kind.is_library()) => {
+                                self.library_call(expr, id, None, args, ty)
+                            }
+                            // `Base.f()`, which bypasses virtual lookup.
+                            TyKind::Type(_) => self.internal_call(id, None, args, ty),
+                            _ => {
+                                self.cx.unsupported(expr.span, "calling function pointers");
+                                vec!["0".into(); utils::stack_size(ty)]
+                            }
+                        }
+                    }
+                    _ => {
+                        self.cx.unsupported(expr.span, "calling function pointers");
+                        vec!["0".into(); utils::stack_size(ty)]
+                    }
+                }
+            }
+            TyKind::Err(guar) => {
+                self.cx.set_error(guar);
+                vec!["0".into(); utils::stack_size(ty)]
+            }
+            _ => {
+                self.cx.unsupported(expr.span, "this call");
+                vec!["0".into(); utils::stack_size(ty)]
+            }
+        }
+    }
+
+    /// Returns the arguments in the order of the given parameters.
+    fn ordered_args(
+        &self,
+        args: &'gcx hir::CallArgs<'gcx>,
+        params: &[hir::VariableId],
+    ) -> Vec<&'gcx hir::Expr<'gcx>> {
+        match args.kind {
+            hir::CallArgsKind::Unnamed(exprs) => exprs.iter().collect(),
+            hir::CallArgsKind::Named(named) => params
+                .iter()
+                .filter_map(|&param| {
+                    let name = self.cx.gcx.hir.variable(param).name?;
+                    named.iter().find(|arg| arg.name.name == name.name)
+                })
+                .map(|arg| &arg.value)
+                .collect(),
+        }
+    }
+
+    /// Evaluates the arguments of a call, converting them to the parameter types.
+    fn args_to(&mut self, args: &[&'gcx hir::Expr<'gcx>], tys: &[Ty<'gcx>]) -> Vec<String> {
+        let mut values = Vec::new();
+        for (&arg, &ty) in args.iter().zip(tys) {
+            values.extend(self.expr_to(arg, ty));
+        }
+        values
+    }
+
+    fn internal_call(
+        &mut self,
+        id: hir::FunctionId,
+        bound: Option<&'gcx hir::Expr<'gcx>>,
+        args: &'gcx hir::CallArgs<'gcx>,
+        ty: Ty<'gcx>,
+    ) -> Vec<String> {
+        let gcx = self.cx.gcx;
+        let f = gcx.hir.function(id);
+        let param_tys = gcx.item_parameter_types(id);
+        let mut params = f.parameters;
+        let mut values = Vec::new();
+        if let Some(bound) = bound {
+            values.extend(self.expr_to(bound, param_tys[0]));
+            params = &params[1..];
+        }
+        let args = self.ordered_args(args, params);
+        values.extend(self.args_to(&args, &param_tys[param_tys.len() - params.len()..]));
+        let name = self.cx.internal_function(id);
+        self.bind_call(format!("{name}({})", values.join(", ")), utils::stack_size(ty))
+    }
+
+    /// Calls a library function, either directly or attached with `using for`.
+    fn library_call(
+        &mut self,
+        expr: &'gcx hir::Expr<'gcx>,
+        id: hir::FunctionId,
+        bound: Option<&'gcx hir::Expr<'gcx>>,
+        args: &'gcx hir::CallArgs<'gcx>,
+        ty: Ty<'gcx>,
+    ) -> Vec<String> {
+        let gcx = self.cx.gcx;
+        let f = gcx.hir.function(id);
+        if f.visibility >= hir::Visibility::Public
+            && f.contract.is_some_and(|c| gcx.hir.contract(c).kind.is_library())
+        {
+            self.cx.unsupported(expr.span, "external library calls");
+            return vec!["0".into(); utils::stack_size(ty)];
+        }
+        self.internal_call(id, bound, args, ty)
+    }
+
+    /// Returns the value of the given call option.
+    fn call_option(
+        &mut self,
+        opts: Option<&'gcx [hir::NamedArg<'gcx>]>,
+        name: &str,
+    ) -> Option<String> {
+        let arg = opts?.iter().find(|arg| arg.name.as_str() == name)?;
+        let ty = match name {
+            "salt" => self.cx.gcx.types.fixed_bytes(32),
+            _ => self.cx.gcx.types.uint(256),
+        };
+        Some(self.expr_to(&arg.value, ty).remove(0))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn external_call(
+        &mut self,
+        expr: &'gcx hir::Expr<'gcx>,
+        id: hir::FunctionId,
+        base: &'gcx hir::Expr<'gcx>,
+        args: &'gcx hir::CallArgs<'gcx>,
+        opts: Option<&'gcx [hir::NamedArg<'gcx>]>,
+        param_tys: &'gcx [Ty<'gcx>],
+        return_tys: &'gcx [Ty<'gcx>],
+    ) -> Vec<String> {
+        let gcx = self.cx.gcx;
+        let f = gcx.hir.function(id);
+        let address = self.expr(base).remove(0);
+        let enc_tys: Vec<_> = param_tys.iter().map(|&ty| self.cx.abi_encoding_type(ty)).collect();
+        let dec_tys: Vec<_> = return_tys.iter().map(|&ty| self.cx.abi_encoding_type(ty)).collect();
+        if !enc_tys.iter().all(|&ty| self.cx.is_abi_encodable(ty))
+            || !dec_tys.iter().all(|&ty| self.cx.is_abi_decodable(ty))
+        {
+            self.cx.unsupported(expr.span, "external calls with these parameter types");
+            return vec!["0".into(); return_tys.iter().map(|&ty| utils::stack_size(ty)).sum()];
+        }
+        let args = self.ordered_args(args, f.parameters);
+        let values = self.args_to(&args, &enc_tys);
+        let value = self.call_option(opts, "value");
+        let gas = self.call_option(opts, "gas").unwrap_or_else(|| "gas()".into());
+
+        if return_tys.is_empty() {
+            self.line(format_args!("if iszero(extcodesize({address})) {{ revert(0, 0) }}"));
+        }
+        let pos = self.allocate_unbounded();
+        let selector = gcx.function_selector(id);
+        self.line(format_args!("mstore({pos}, {})", num(utils::left_word(selector.as_slice()))));
+        let encode = self.cx.abi_encode_tuple_fn(&enc_tys);
+        let mut encode_args = vec![format!("add({pos}, 4)")];
+        encode_args.extend(values);
+        let end = self.bind(format!("{encode}({})", encode_args.join(", ")));
+        let success = if f.state_mutability <= hir::StateMutability::View && value.is_none() {
+            format!("staticcall({gas}, {address}, {pos}, sub({end}, {pos}), {pos}, 0)")
+        } else {
+            let value = value.as_deref().unwrap_or("0");
+            format!("call({gas}, {address}, {value}, {pos}, sub({end}, {pos}), {pos}, 0)")
+        };
+        let success = self.bind(success);
+        let revert_forward = self.cx.revert_forward_fn();
+        self.line(format_args!("if iszero({success}) {{ {revert_forward}() }}"));
+        if dec_tys.is_empty() {
+            return Vec::new();
+        }
+        self.line(format_args!("returndatacopy({pos}, 0, returndatasize())"));
+        let finalize = self.cx.finalize_allocation_fn();
+        self.line(format_args!("{finalize}({pos}, returndatasize())"));
+        let decode = self.cx.abi_decode_tuple_fn(&dec_tys, true);
+        let n = dec_tys.iter().map(|&ty| utils::stack_size(ty)).sum();
+        let values = self.bind_call(format!("{decode}({pos}, add({pos}, returndatasize()))"), n);
+        let mut values = values.into_iter();
+        let mut converted = Vec::new();
+        for (&from, &to) in dec_tys.iter().zip(return_tys) {
+            let component = values.by_ref().take(utils::stack_size(from)).collect();
+            converted.extend(self.convert(component, from, to));
+        }
+        converted
+    }
+
+    /// `new C(...)` and `new T[](length)`.
+    fn new_expr(
+        &mut self,
+        expr: &'gcx hir::Expr<'gcx>,
+        args: &'gcx hir::CallArgs<'gcx>,
+        opts: Option<&'gcx [hir::NamedArg<'gcx>]>,
+        ty: Ty<'gcx>,
+        param_tys: &'gcx [Ty<'gcx>],
+    ) -> Vec<String> {
+        let gcx = self.cx.gcx;
+        let TyKind::Contract(id) = ty.kind else {
+            let length = args.exprs().next().expect("array allocation without length");
+            let length = self.expr_to(length, gcx.types.uint(256)).remove(0);
+            let f = self.allocate_zeroed_array_fn(ty);
+            return vec![self.bind(format!("{f}({length})"))];
+        };
+        let enc_tys: Vec<_> = param_tys.iter().map(|&ty| self.cx.abi_encoding_type(ty)).collect();
+        if !enc_tys.iter().all(|&ty| self.cx.is_abi_encodable(ty)) {
+            self.cx.unsupported(expr.span, "contract creation with these parameter types");
+            return vec!["0".into()];
+        }
+        let ctor_params = gcx.hir.contract(id).ctor.map(|ctor| gcx.hir.function(ctor).parameters);
+        let args = self.ordered_args(args, ctor_params.unwrap_or_default());
+        let values = self.args_to(&args, &enc_tys);
+        let value = self.call_option(opts, "value").unwrap_or_else(|| "0".into());
+        let salt = self.call_option(opts, "salt");
+
+        self.cx.created.push(id);
+        let object = contract::object_name(gcx, id);
+        let size = self.bind(format!("datasize(\"{object}\")"));
+        let pos = self.allocate_unbounded();
+        self.line(format_args!("datacopy({pos}, dataoffset(\"{object}\"), {size})"));
+        let encode = self.cx.abi_encode_tuple_fn(&enc_tys);
+        let mut encode_args = vec![format!("add({pos}, {size})")];
+        encode_args.extend(values);
+        let end = self.bind(format!("{encode}({})", encode_args.join(", ")));
+        let address = match salt {
+            Some(salt) => {
+                if !gcx.sess.opts.evm_version.has_create2() {
+                    self.evm_unavailable(expr.span, "`create2`");
+                }
+                format!("create2({value}, {pos}, sub({end}, {pos}), {salt})")
+            }
+            None => format!("create({value}, {pos}, sub({end}, {pos}))"),
+        };
+        let address = self.bind(address);
+        let revert_forward = self.cx.revert_forward_fn();
+        self.line(format_args!("if iszero({address}) {{ {revert_forward}() }}"));
+        vec![address]
+    }
+
+    /// `allocate_and_zero_memory_array_<ty>(length) -> memPtr`.
+    fn allocate_zeroed_array_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let gcx = self.cx.gcx;
+        let inner = ty.peel_refs();
+        let name = format!("allocate_and_zero_memory_array_{}", self.cx.type_id(inner));
+        self.cx.helper(name.clone(), |cx| {
+            let zero = match inner.kind {
+                TyKind::DynArray(elem) if !elem.is_value_type() => {
+                    let zero = cx.zero_value(elem.with_loc_if_ref(gcx, DataLocation::Memory));
+                    format!(
+                        "for {{ let i := 0 }} lt(i, length) {{ i := add(i, 1) }} {{
+                            mstore(add(add(memPtr, 32), mul(i, 32)), {})
+                        }}",
+                        zero[0]
+                    )
+                }
+                TyKind::DynArray(_) => {
+                    "calldatacopy(add(memPtr, 32), calldatasize(), mul(length, 32))".to_string()
+                }
+                _ => format!(
+                    "calldatacopy(add(memPtr, 32), calldatasize(), {}(length))",
+                    cx.round_up_fn()
+                ),
+            };
+            template!(
+                "function <name>(length) -> memPtr {
+                    memPtr := <allocate>(length)
+                    <zero>
+                }",
+                name = name,
+                allocate = cx.allocate_array_fn(inner),
+                zero = zero,
+            )
+        })
+    }
+
+    fn struct_constructor(
+        &mut self,
+        id: hir::StructId,
+        args: &'gcx hir::CallArgs<'gcx>,
+    ) -> Vec<String> {
+        let gcx = self.cx.gcx;
+        let fields = gcx.hir.strukt(id).fields;
+        let args = self.ordered_args(args, fields);
+        let field_tys: Vec<_> = gcx
+            .struct_field_types(id)
+            .iter()
+            .map(|ty| ty.with_loc_if_ref(gcx, DataLocation::Memory))
+            .collect();
+        let values = self.args_to(&args, &field_tys);
+        let size = utils::memory_elements(gcx, gcx.mk_ty(TyKind::Struct(id))).len() * 32;
+        let allocate = self.cx.allocate_memory_fn();
+        let ptr = self.bind(format!("{allocate}({size})"));
+        for (i, value) in values.iter().enumerate() {
+            let offset = memory_field_index(gcx, id, i) * 32;
+            self.line(format_args!("mstore(add({ptr}, {offset}), {value})"));
+        }
+        vec![ptr]
+    }
+
+    // --- Builtins ---
+
+    /// Evaluates a builtin that is not called, like `msg.sender`.
+    pub(crate) fn builtin_member(
+        &mut self,
+        builtin: Builtin,
+        base: &'gcx hir::Expr<'gcx>,
+        expr: &'gcx hir::Expr<'gcx>,
+    ) -> Vec<String> {
+        let gcx = self.cx.gcx;
+        let evm = gcx.sess.opts.evm_version;
+        let opcode = |name: &str| vec![format!("{name}()")];
+        match builtin {
+            Builtin::BlockCoinbase => opcode("coinbase"),
+            Builtin::BlockTimestamp => opcode("timestamp"),
+            Builtin::BlockDifficulty | Builtin::BlockPrevrandao => {
+                opcode(if evm.has_prev_randao() { "prevrandao" } else { "difficulty" })
+            }
+            Builtin::BlockNumber => opcode("number"),
+            Builtin::BlockGaslimit => opcode("gaslimit"),
+            Builtin::BlockChainid => {
+                if !evm.has_chain_id() {
+                    self.evm_unavailable(expr.span, "`block.chainid`");
+                }
+                opcode("chainid")
+            }
+            Builtin::BlockBasefee => {
+                if !evm.has_base_fee() {
+                    self.evm_unavailable(expr.span, "`block.basefee`");
+                }
+                opcode("basefee")
+            }
+            Builtin::BlockBlobbasefee => {
+                if !evm.has_blob_base_fee() {
+                    self.evm_unavailable(expr.span, "`block.blobbasefee`");
+                }
+                opcode("blobbasefee")
+            }
+            Builtin::MsgSender => opcode("caller"),
+            Builtin::MsgGas => opcode("gas"),
+            Builtin::MsgValue => opcode("callvalue"),
+            Builtin::MsgData => vec!["0".into(), "calldatasize()".into()],
+            Builtin::MsgSig => {
+                vec![self.bind(format!("and(calldataload(0), {})", num(utils::high_mask(4))))]
+            }
+            Builtin::TxOrigin => opcode("origin"),
+            Builtin::TxGasPrice => opcode("gasprice"),
+            Builtin::AddressBalance => {
+                let address = self.expr(base).remove(0);
+                let is_this =
+                    matches!(self.cx.res_of(base), Some(hir::Res::Builtin(Builtin::This)));
+                if evm.has_self_balance() && is_this {
+                    opcode("selfbalance")
+                } else {
+                    vec![self.bind(format!("balance({address})"))]
+                }
+            }
+            Builtin::AddressCode => {
+                let address = self.expr(base).remove(0);
+                let size = self.bind(format!("extcodesize({address})"));
+                let allocate = self.cx.allocate_array_fn(gcx.types.bytes);
+                let ptr = self.bind(format!("{allocate}({size})"));
+                self.line(format_args!("extcodecopy({address}, add({ptr}, 32), 0, {size})"));
+                vec![ptr]
+            }
+            Builtin::AddressCodehash => {
+                if !evm.has_ext_code_hash() {
+                    self.evm_unavailable(expr.span, "`address.codehash`");
+                }
+                let address = self.expr(base).remove(0);
+                vec![self.bind(format!("extcodehash({address})"))]
+            }
+            Builtin::FixedBytesLength => {
+                let ty = self.cx.ty_of(base);
+                let Some(Repr::FixedBytes(bytes)) = utils::repr(gcx, ty) else { unreachable!() };
+                vec![bytes.to_string()]
+            }
+            Builtin::ArrayLength => {
+                let ty = self.cx.ty_of(base);
+                let values = self.expr(base);
+                let length = match (ty.peel_refs().kind, ty.loc()) {
+                    (TyKind::Array(_, len), _) => num(len),
+                    (_, Some(DataLocation::Storage | DataLocation::Transient)) => {
+                        let f = self.cx.storage_array_length_fn(ty);
+                        format!("{f}({})", values[0])
+                    }
+                    (_, Some(DataLocation::Calldata)) => values[1].clone(),
+                    _ => format!("mload({})", values[0]),
+                };
+                vec![self.bind(length)]
+            }
+            Builtin::FunctionSelector => {
+                let selector = match self.cx.res_of(base) {
+                    Some(hir::Res::Item(
+                        id @ (hir::ItemId::Function(_) | hir::ItemId::Error(_)),
+                    )) => gcx.function_selector(id),
+                    _ => {
+                        self.cx.unsupported(expr.span, "this function selector");
+                        return vec!["0".into()];
+                    }
+                };
+                vec![num(utils::left_word(selector.as_slice()))]
+            }
+            Builtin::EventSelector => match self.cx.res_of(base) {
+                Some(hir::Res::Item(hir::ItemId::Event(id))) => {
+                    vec![num(U256::from_be_bytes(gcx.event_selector(id).0))]
+                }
+                _ => {
+                    self.cx.unsupported(expr.span, "this event selector");
+                    vec!["0".into()]
+                }
+            },
+            Builtin::InterfaceId | Builtin::ContractName => {
+                let TyKind::Meta(ty) = self.cx.ty_of(base).kind else { unreachable!() };
+                let TyKind::Contract(id) = ty.kind else { unreachable!() };
+                if builtin == Builtin::InterfaceId {
+                    vec![num(utils::left_word(gcx.interface_id(id).as_slice()))]
+                } else {
+                    let name = gcx.hir.contract(id).name.to_string();
+                    let f = self.cx.string_literal_fn(name.as_bytes());
+                    vec![self.bind(format!("{f}()"))]
+                }
+            }
+            Builtin::TypeMin | Builtin::TypeMax => {
+                let TyKind::Meta(ty) = self.cx.ty_of(base).kind else { unreachable!() };
+                let (min, max) = match ty.kind {
+                    TyKind::Enum(id) => {
+                        (U256::ZERO, U256::from(gcx.hir.enumm(id).variants.len() - 1))
+                    }
+                    _ => {
+                        let repr = self.cx.value_repr(ty);
+                        super::ObjectCx::int_bounds(repr)
+                    }
+                };
+                vec![num(if builtin == Builtin::TypeMin { min } else { max })]
+            }
+            Builtin::This => vec!["address()".into()],
+            _ => {
+                self.cx.unsupported(expr.span, "this builtin");
+                vec!["0".into(); utils::stack_size(self.cx.ty_of(expr))]
+            }
+        }
+    }
+
+    /// Lowers a call to a builtin function.
+    fn builtin_call(
+        &mut self,
+        expr: &'gcx hir::Expr<'gcx>,
+        builtin: Builtin,
+        base: Option<&'gcx hir::Expr<'gcx>>,
+        args: &'gcx hir::CallArgs<'gcx>,
+        opts: Option<&'gcx [hir::NamedArg<'gcx>]>,
+        ty: Ty<'gcx>,
+    ) -> Vec<String> {
+        let gcx = self.cx.gcx;
+        let args: Vec<_> = args.exprs().collect();
+        let uint256 = gcx.types.uint(256);
+        let bytes = gcx.types.bytes_ref.memory;
+        let string = gcx.types.string_ref.memory;
+        match builtin {
+            Builtin::Gasleft => vec![self.bind("gas()".into())],
+            Builtin::Blockhash => {
+                let n = self.expr_to(args[0], uint256).remove(0);
+                vec![self.bind(format!("blockhash({n})"))]
+            }
+            Builtin::Blobhash => {
+                if !gcx.sess.opts.evm_version.has_blob_base_fee() {
+                    self.evm_unavailable(expr.span, "`blobhash`");
+                }
+                let n = self.expr_to(args[0], uint256).remove(0);
+                vec![self.bind(format!("blobhash({n})"))]
+            }
+            Builtin::Selfdestruct => {
+                let address = self.expr_to(args[0], gcx.types.address_payable).remove(0);
+                self.line(format_args!("selfdestruct({address})"));
+                Vec::new()
+            }
+            Builtin::Assert => {
+                let cond = self.expr_to(args[0], gcx.types.bool).remove(0);
+                let panic = self.cx.panic_fn(0x01);
+                self.line(format_args!("if iszero({cond}) {{ {panic}() }}"));
+                Vec::new()
+            }
+            Builtin::Require => {
+                let cond = self.expr_to(args[0], gcx.types.bool).remove(0);
+                self.line(format_args!("if iszero({cond}) {{ revert(0, 0) }}"));
+                Vec::new()
+            }
+            Builtin::RequireMsg => {
+                let cond = self.expr_to(args[0], gcx.types.bool).remove(0);
+                let msg = self.expr_to(args[1], string).remove(0);
+                self.line(format_args!("if iszero({cond})"));
+                self.line("{");
+                self.revert_with(ERROR_STRING_SELECTOR, &[string], &[msg]);
+                self.line("}");
+                Vec::new()
+            }
+            Builtin::Revert => {
+                self.line("revert(0, 0)");
+                Vec::new()
+            }
+            Builtin::RevertMsg => {
+                let msg = self.expr_to(args[0], string).remove(0);
+                self.revert_with(ERROR_STRING_SELECTOR, &[string], &[msg]);
+                Vec::new()
+            }
+            Builtin::AddMod | Builtin::MulMod => {
+                let values: Vec<_> =
+                    args.iter().map(|&arg| self.expr_to(arg, uint256).remove(0)).collect();
+                let panic = self.cx.panic_fn(0x12);
+                self.line(format_args!("if iszero({}) {{ {panic}() }}", values[2]));
+                let op = if builtin == Builtin::AddMod { "addmod" } else { "mulmod" };
+                vec![self.bind(format!("{op}({}, {}, {})", values[0], values[1], values[2]))]
+            }
+            Builtin::Keccak256 => {
+                let data = self.expr_to(args[0], bytes).remove(0);
+                vec![self.bind(format!("keccak256(add({data}, 32), mload({data}))"))]
+            }
+            Builtin::Sha256 | Builtin::Ripemd160 => {
+                let data = self.expr_to(args[0], bytes).remove(0);
+                let (address, result) = if builtin == Builtin::Sha256 {
+                    (2, "mload(0)")
+                } else {
+                    (3, "shl(96, mload(0))")
+                };
+                let success = self.bind(format!(
+                    "staticcall(gas(), {address}, add({data}, 32), mload({data}), 0, 32)"
+                ));
+                let revert_forward = self.cx.revert_forward_fn();
+                self.line(format_args!("if iszero({success}) {{ {revert_forward}() }}"));
+                vec![self.bind(result.into())]
+            }
+            Builtin::EcRecover => {
+                let tys = [
+                    gcx.types.fixed_bytes(32),
+                    gcx.types.uint(8),
+                    gcx.types.fixed_bytes(32),
+                    gcx.types.fixed_bytes(32),
+                ];
+                let values: Vec<_> = args
+                    .iter()
+                    .zip(tys)
+                    .map(|(&arg, ty)| self.expr_to(arg, ty).remove(0))
+                    .collect();
+                let pos = self.allocate_unbounded();
+                for (i, value) in values.iter().enumerate() {
+                    self.line(format_args!("mstore(add({pos}, {}), {value})", i * 32));
+                }
+                self.line("mstore(0, 0)");
+                let success = self.bind(format!("staticcall(gas(), 1, {pos}, 128, 0, 32)"));
+                let revert_forward = self.cx.revert_forward_fn();
+                self.line(format_args!("if iszero({success}) {{ {revert_forward}() }}"));
+                vec![self.bind("mload(0)".into())]
+            }
+            Builtin::AbiEncode
+            | Builtin::AbiEncodePacked
+            | Builtin::AbiEncodeWithSelector
+            | Builtin::AbiEncodeWithSignature => {
+                let (selector, args) = match builtin {
+                    Builtin::AbiEncodeWithSelector => {
+                        let selector = self.expr_to(args[0], gcx.types.fixed_bytes(4)).remove(0);
+                        (Some(selector), &args[1..])
+                    }
+                    Builtin::AbiEncodeWithSignature => {
+                        let signature = self.expr_to(args[0], string).remove(0);
+                        let selector = format!(
+                            "and(keccak256(add({signature}, 32), mload({signature})), {})",
+                            num(utils::high_mask(4))
+                        );
+                        (Some(self.bind(selector)), &args[1..])
+                    }
+                    _ => (None, &args[..]),
+                };
+                let packed = builtin == Builtin::AbiEncodePacked;
+                let Some((tys, values)) = self.abi_args(expr.span, args) else {
+                    return vec!["0".into()];
+                };
+                vec![self.abi_encode(selector, &tys, &values, packed)]
+            }
+            Builtin::AbiEncodeCall => {
+                self.cx.unsupported(expr.span, "`abi.encodeCall`");
+                vec!["0".into()]
+            }
+            Builtin::AbiDecode => {
+                let data = self.expr_to(args[0], bytes).remove(0);
+                let tys: Vec<_> = tuple_types(ty)
+                    .into_iter()
+                    .filter(|&ty| !ty.is_unit())
+                    .map(|ty| ty.with_loc_if_ref(gcx, DataLocation::Memory))
+                    .collect();
+                if !tys.iter().all(|&ty| self.cx.is_abi_decodable(ty)) {
+                    self.cx.unsupported(expr.span, "decoding these types");
+                    return vec!["0".into(); utils::stack_size(ty)];
+                }
+                let decode = self.cx.abi_decode_tuple_fn(&tys, true);
+                let call =
+                    format!("{decode}(add({data}, 32), add(add({data}, 32), mload({data})))");
+                self.bind_call(call, tys.iter().map(|&ty| utils::stack_size(ty)).sum())
+            }
+            Builtin::StringConcat | Builtin::BytesConcat => {
+                let mut tys = Vec::new();
+                let mut values = Vec::new();
+                for &arg in &args {
+                    let arg_ty = self.cx.ty_of(arg);
+                    let to = match utils::repr(gcx, arg_ty) {
+                        Some(Repr::FixedBytes(_)) => arg_ty,
+                        _ if builtin == Builtin::StringConcat => string,
+                        _ => bytes,
+                    };
+                    values.push(self.expr_to(arg, to).remove(0));
+                    tys.push(to);
+                }
+                vec![self.abi_encode(None, &tys, &values, true)]
+            }
+            Builtin::AddressCall | Builtin::AddressDelegatecall | Builtin::AddressStaticcall => {
+                let address = self.expr(base.unwrap()).remove(0);
+                let data = self.expr_to(args[0], bytes).remove(0);
+                let gas = self.call_option(opts, "gas").unwrap_or_else(|| "gas()".into());
+                let input = format!("add({data}, 32), mload({data})");
+                let call = match builtin {
+                    Builtin::AddressCall => {
+                        let value = self.call_option(opts, "value").unwrap_or_else(|| "0".into());
+                        format!("call({gas}, {address}, {value}, {input}, 0, 0)")
+                    }
+                    Builtin::AddressDelegatecall => {
+                        format!("delegatecall({gas}, {address}, {input}, 0, 0)")
+                    }
+                    _ => format!("staticcall({gas}, {address}, {input}, 0, 0)"),
+                };
+                let success = self.bind(call);
+                let data = self.extract_returndata();
+                match tuple_types(ty).len() {
+                    2 => vec![success, data],
+                    _ => vec![data],
+                }
+            }
+            Builtin::AddressPayableTransfer | Builtin::AddressPayableSend => {
+                let address = self.expr(base.unwrap()).remove(0);
+                let amount = self.expr_to(args[0], uint256).remove(0);
+                let success = self.bind(format!(
+                    "call(mul(iszero({amount}), 2300), {address}, {amount}, 0, 0, 0, 0)"
+                ));
+                if builtin == Builtin::AddressPayableSend {
+                    return vec![success];
+                }
+                let revert_forward = self.cx.revert_forward_fn();
+                self.line(format_args!("if iszero({success}) {{ {revert_forward}() }}"));
+                Vec::new()
+            }
+            Builtin::ArrayPush0 | Builtin::ArrayPush | Builtin::ArrayPop => {
+                let base = base.unwrap();
+                let array_ty = self.cx.ty_of(base);
+                let TyKind::DynArray(elem) = array_ty.peel_refs().kind else {
+                    self.cx.unsupported(expr.span, "`push` and `pop` on byte arrays");
+                    return vec!["0".into(); utils::stack_size(ty)];
+                };
+                let array = self.expr(base).remove(0);
+                if builtin == Builtin::ArrayPop {
+                    let f = self.cx.storage_pop_fn(array_ty);
+                    self.line(format_args!("{f}({array})"));
+                    return Vec::new();
+                }
+                let value_ty = if elem.is_value_type() {
+                    elem
+                } else {
+                    elem.with_loc_if_ref(gcx, DataLocation::Memory)
+                };
+                let value = args.first().map(|&arg| self.expr_to(arg, value_ty).remove(0));
+                let f = self.cx.storage_push_fn(array_ty);
+                let [slot, offset] = [self.tmp(), self.tmp()];
+                self.line(format_args!("let {slot}, {offset} := {f}({array})"));
+                let elem = elem.with_loc_if_ref(gcx, DataLocation::Storage);
+                match value {
+                    Some(value) if elem.is_value_type() => {
+                        let f = self.cx.storage_write_fn(elem, None);
+                        self.line(format_args!("{f}({slot}, {offset}, {value})"));
+                    }
+                    Some(value) => {
+                        let f = self.cx.memory_to_storage_fn(elem);
+                        self.line(format_args!("{f}({slot}, {value})"));
+                    }
+                    None => {}
+                }
+                match builtin {
+                    Builtin::ArrayPush => Vec::new(),
+                    _ if elem.is_value_type() => {
+                        let f = self.cx.storage_read_fn(elem, None);
+                        vec![self.bind(format!("{f}({slot}, {offset})"))]
+                    }
+                    _ => vec![slot],
+                }
+            }
+            Builtin::UdvtWrap | Builtin::UdvtUnwrap => self.expr_to(args[0], ty),
+            _ => {
+                self.cx.unsupported(expr.span, "this builtin function");
+                vec!["0".into(); utils::stack_size(ty)]
+            }
+        }
+    }
+
+    /// Evaluates the arguments of an ABI encoding function, returning their encoding types.
+    fn abi_args(
+        &mut self,
+        span: Span,
+        args: &[&'gcx hir::Expr<'gcx>],
+    ) -> Option<(Vec<Ty<'gcx>>, Vec<String>)> {
+        let mut tys = Vec::new();
+        let mut values = Vec::new();
+        for &arg in args {
+            let ty = self.cx.abi_encoding_type(self.cx.ty_of(arg));
+            if !self.cx.is_abi_encodable(ty) {
+                self.cx.unsupported(span, "ABI-encoding this type");
+                return None;
+            }
+            values.push(self.expr_to(arg, ty).remove(0));
+            tys.push(ty);
+        }
+        Some((tys, values))
+    }
+
+    /// Encodes the given values into a new `bytes memory` array, optionally prefixed with a
+    /// left-aligned selector.
+    fn abi_encode(
+        &mut self,
+        selector: Option<String>,
+        tys: &[Ty<'gcx>],
+        values: &[String],
+        packed: bool,
+    ) -> String {
+        let pos = self.allocate_unbounded();
+        let mut start = format!("add({pos}, 32)");
+        if let Some(selector) = selector {
+            self.line(format_args!("mstore({start}, {selector})"));
+            start = format!("add({pos}, 36)");
+        }
+        let end = if packed {
+            let end = self.tmp();
+            self.line(format_args!("let {end} := {start}"));
+            for (&ty, value) in tys.iter().zip(values) {
+                let encode = self.cx.abi_encode_packed_fn(ty);
+                self.line(format_args!("{end} := {encode}({value}, {end})"));
+            }
+            end
+        } else {
+            let encode = self.cx.abi_encode_tuple_fn(tys);
+            let mut args = vec![start];
+            args.extend(values.iter().cloned());
+            self.bind(format!("{encode}({})", args.join(", ")))
+        };
+        self.line(format_args!("mstore({pos}, sub({end}, add({pos}, 32)))"));
+        let finalize = self.cx.finalize_allocation_fn();
+        self.line(format_args!("{finalize}({pos}, sub({end}, {pos}))"));
+        pos
+    }
+
+    /// Copies the return data of the last call into a new `bytes memory` array.
+    fn extract_returndata(&mut self) -> String {
+        let f = self.cx.helper("extract_returndata".into(), |cx| {
+            template!(
+                "function extract_returndata() -> data {
+                    switch returndatasize()
+                    case 0 { data := 96 }
+                    default {
+                        data := <allocate>(returndatasize())
+                        returndatacopy(add(data, 32), 0, returndatasize())
+                    }
+                }",
+                allocate = cx.allocate_array_fn(cx.gcx.types.bytes),
+            )
+        });
+        self.bind(format!("{f}()"))
+    }
+
+    /// Returns a pointer to the free memory, without allocating it.
+    pub(crate) fn allocate_unbounded(&mut self) -> String {
+        let f = self.cx.allocate_unbounded_fn();
+        self.bind(format!("{f}()"))
+    }
+
+    /// Reverts with the given selector and ABI-encoded arguments.
+    fn revert_with(&mut self, selector: u32, tys: &[Ty<'gcx>], values: &[String]) {
+        let pos = self.allocate_unbounded();
+        self.line(format_args!("mstore({pos}, shl(224, {selector:#010x}))"));
+        let encode = self.cx.abi_encode_tuple_fn(tys);
+        let mut args = vec![format!("add({pos}, 4)")];
+        args.extend(values.iter().cloned());
+        let end = self.bind(format!("{encode}({})", args.join(", ")));
+        self.line(format_args!("revert({pos}, sub({end}, {pos}))"));
+    }
+
+    /// Emits an error for an instruction that is not available in the target EVM version.
+    fn evm_unavailable(&mut self, span: Span, what: &str) {
+        let msg = format!(
+            "{what} is not available in the `{}` EVM version",
+            self.cx.gcx.sess.opts.evm_version
+        );
+        let guar = self.cx.gcx.dcx().err(msg).span(span).emit();
+        self.cx.set_error(guar);
+    }
+
+    // --- Events and errors ---
+
+    /// Lowers an `emit` statement.
+    pub(crate) fn emit_event(&mut self, expr: &'gcx hir::Expr<'gcx>) {
+        let gcx = self.cx.gcx;
+        let hir::ExprKind::Call(callee, ref args, _) = expr.kind else { unreachable!() };
+        let TyKind::Event(param_tys, id) = self.cx.ty_of(callee).kind else {
+            self.cx.unsupported(expr.span, "this event");
+            return;
+        };
+        let event = gcx.hir.event(id);
+        let args = self.ordered_args(args, event.parameters);
+        let mut topics = Vec::new();
+        if !event.anonymous {
+            topics.push(num(U256::from_be_bytes(gcx.event_selector(id).0)));
+        }
+        let mut data_tys = Vec::new();
+        let mut data_values = Vec::new();
+        for ((&param, &param_ty), &arg) in event.parameters.iter().zip(param_tys).zip(&args) {
+            let ty = self.cx.abi_encoding_type(param_ty);
+            if !self.cx.is_abi_encodable(ty) {
+                self.cx.unsupported(arg.span, "ABI-encoding this type");
+                return;
+            }
+            let value = self.expr_to(arg, ty).remove(0);
+            if !gcx.hir.variable(param).indexed {
+                data_tys.push(ty);
+                data_values.push(value);
+            } else if ty.is_value_type() {
+                let repr = self.cx.value_repr(ty);
+                topics.push(self.bind(utils::cleanup_expr(repr, &value)));
+            } else if let TyKind::Elementary(ElementaryType::Bytes | ElementaryType::String) =
+                ty.peel_refs().kind
+            {
+                topics.push(self.bind(format!("keccak256(add({value}, 32), mload({value}))")));
+            } else {
+                self.cx.unsupported(arg.span, "indexed event parameters of this type");
+                return;
+            }
+        }
+        let pos = self.allocate_unbounded();
+        let encode = self.cx.abi_encode_tuple_fn(&data_tys);
+        let mut encode_args = vec![pos.clone()];
+        encode_args.extend(data_values);
+        let end = self.bind(format!("{encode}({})", encode_args.join(", ")));
+        let mut log_args = vec![pos.clone(), format!("sub({end}, {pos})")];
+        log_args.extend(topics.iter().cloned());
+        self.line(format_args!("log{}({})", topics.len(), log_args.join(", ")));
+    }
+
+    /// Lowers a `revert` statement with a custom error.
+    pub(crate) fn revert_error(&mut self, expr: &'gcx hir::Expr<'gcx>) {
+        let gcx = self.cx.gcx;
+        let hir::ExprKind::Call(callee, ref args, _) = expr.kind else { unreachable!() };
+        let TyKind::Error(param_tys, id) = self.cx.ty_of(callee).kind else {
+            // `revert(...)` builtin calls are lowered as expressions.
+            let _ = self.expr(expr);
+            return;
+        };
+        let error = gcx.hir.error(id);
+        let args = self.ordered_args(args, error.parameters);
+        let tys: Vec<_> = param_tys.iter().map(|&ty| self.cx.abi_encoding_type(ty)).collect();
+        if !tys.iter().all(|&ty| self.cx.is_abi_encodable(ty)) {
+            self.cx.unsupported(expr.span, "ABI-encoding these error parameters");
+            return;
+        }
+        let values = self.args_to(&args, &tys);
+        let selector = u32::from_be_bytes(gcx.function_selector(id).0);
+        self.revert_with(selector, &tys, &values);
+    }
+}
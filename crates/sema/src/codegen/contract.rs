@@ -0,0 +1,298 @@
+//! Contract lowering: creation code, constructors and the function dispatcher.
+
+use super::{
+    ObjectCx,
+    function::{FunctionCx, param_names},
+    utils, yul,
+};
+use crate::{hir, ty::Gcx, typeck::TypeckResults};
+use solar_ast::DataLocation;
+use solar_interface::diagnostics::ErrorGuaranteed;
+use std::fmt::Write;
+
+/// Returns the name of the creation object of the given contract.
+pub(crate) fn object_name(gcx: Gcx<'_>, id: hir::ContractId) -> String {
+    format!("{}_{}", gcx.hir.contract(id).name, id.index())
+}
+
+/// Returns the immutable variables of the given contract, including inherited ones, in the order
+/// in which they are stored in memory during creation.
+fn immutables(gcx: Gcx<'_>, id: hir::ContractId) -> Vec<hir::VariableId> {
+    let c = gcx.hir.contract(id);
+    c.linearized_bases
+        .iter()
+        .rev()
+        .flat_map(|&base| gcx.hir.contract(base).variables())
+        .filter(|&var| gcx.hir.variable(var).is_immutable())
+        .collect()
+}
+
+/// The memory offset where immutables start during creation. This is also the initial value of
+/// the free memory pointer in the deployed code.
+const IMMUTABLES_START: usize = 128;
+
+impl ObjectCx<'_, '_> {
+    /// Returns the memory offset of the given immutable during creation.
+    pub(crate) fn immutable_offset(&self, var: hir::VariableId) -> usize {
+        let index = immutables(self.gcx, self.contract)
+            .iter()
+            .position(|&v| v == var)
+            .expect("immutable not found in contract");
+        IMMUTABLES_START + index * 32
+    }
+}
+
+/// Generates the creation object of the given contract, with the deployed object as a
+/// sub-object.
+pub(crate) fn creation_object<'gcx>(
+    gcx: Gcx<'gcx>,
+    results: &TypeckResults<'gcx>,
+    id: hir::ContractId,
+) -> Result<yul::Object, ErrorGuaranteed> {
+    creation_object_inner(gcx, results, id, &mut Vec::new())
+}
+
+fn creation_object_inner<'gcx>(
+    gcx: Gcx<'gcx>,
+    results: &TypeckResults<'gcx>,
+    id: hir::ContractId,
+    stack: &mut Vec<hir::ContractId>,
+) -> Result<yul::Object, ErrorGuaranteed> {
+    stack.push(id);
+    let name = object_name(gcx, id);
+
+    let mut cx = ObjectCx::new(gcx, results, id, true);
+    let code = constructor_code(&mut cx, &name);
+    let creation = cx.finish(yul::parse_stmts(&code));
+
+    let mut cx = ObjectCx::new(gcx, results, id, false);
+    let code = dispatcher_code(&mut cx);
+    let deployed = cx.finish(yul::parse_stmts(&code));
+
+    let (creation, deployed) = (creation?, deployed?);
+    let mut deployed_object = yul::Object {
+        name: format!("{name}_deployed"),
+        code: deployed.code,
+        children: Vec::new(),
+        data: Vec::new(),
+    };
+    deployed_object.children = created_objects(gcx, results, id, deployed.created, stack)?;
+    let mut children = vec![deployed_object];
+    children.extend(created_objects(gcx, results, id, creation.created, stack)?);
+    stack.pop();
+    Ok(yul::Object { name, code: creation.code, children, data: Vec::new() })
+}
+
+/// Generates the creation objects of the contracts created with `new`.
+fn created_objects<'gcx>(
+    gcx: Gcx<'gcx>,
+    results: &TypeckResults<'gcx>,
+    id: hir::ContractId,
+    created: Vec<hir::ContractId>,
+    stack: &mut Vec<hir::ContractId>,
+) -> Result<Vec<yul::Object>, ErrorGuaranteed> {
+    created
+        .into_iter()
+        .map(|created| {
+            if stack.contains(&created) {
+                let c = gcx.hir.contract(id);
+                let msg = format!(
+                    "circular reference to contract `{}` in contract creation",
+                    gcx.hir.contract(created).name
+                );
+                return Err(gcx.dcx().err(msg).span(c.name.span).emit());
+            }
+            creation_object_inner(gcx, results, created, stack)
+        })
+        .collect()
+}
+
+/// Returns the top-level code of the creation object.
+fn constructor_code(cx: &mut ObjectCx<'_, '_>, name: &str) -> String {
+    let gcx = cx.gcx;
+    let id = cx.contract;
+    let c = gcx.hir.contract(id);
+    let mut code = String::new();
+    let free_memory = IMMUTABLES_START + immutables(gcx, id).len() * 32;
+    let _ = writeln!(code, "mstore(64, {free_memory})");
+
+    let ctor = c.ctor.map(|ctor| gcx.hir.function(ctor));
+    if !ctor.is_some_and(|ctor| ctor.state_mutability.is_payable()) {
+        code += "if callvalue() { revert(0, 0) }\n";
+    }
+
+    // Decode the constructor arguments, which are appended to the creation code.
+    let mut fcx = FunctionCx::new(cx, Some(id));
+    if let Some(ctor) = ctor
+        && !ctor.parameters.is_empty()
+    {
+        let tys = gcx.item_parameter_types(c.ctor.unwrap());
+        let names = param_names(fcx.cx, ctor.parameters);
+        let decode = fcx.cx.abi_decode_tuple_fn(tys, true);
+        let allocate = fcx.cx.allocate_memory_fn();
+        fcx.line(format_args!(
+            "let programSize := datasize(\"{name}\")
+            let argSize := sub(codesize(), programSize)
+            let memoryDataOffset := {allocate}(argSize)
+            codecopy(memoryDataOffset, programSize, argSize)
+            let {} := {decode}(memoryDataOffset, add(memoryDataOffset, argSize))",
+            names.join(", ")
+        ));
+    }
+
+    // Evaluate the base constructor arguments, from the most derived contract to the most base
+    // one, so that the parameters they reference are always declared.
+    for (&base, args) in c.linearized_bases[1..].iter().zip(c.linearized_bases_args) {
+        let Some(base_ctor) = gcx.hir.contract(base).ctor else { continue };
+        let Some(args) = args else { continue };
+        let f = gcx.hir.function(base_ctor);
+        let tys = gcx.item_parameter_types(base_ctor);
+        for ((&param, &ty), arg) in f.parameters.iter().zip(tys).zip(args.args.exprs()) {
+            let values = fcx.expr_to(arg, ty);
+            let names = utils::var_names(gcx, param, ty);
+            for (name, value) in names.iter().zip(values) {
+                fcx.line(format_args!("let {name} := {value}"));
+            }
+        }
+    }
+
+    // Run the state variable initializers and constructors, from the most base contract.
+    for &base in c.linearized_bases.iter().rev() {
+        let b = gcx.hir.contract(base);
+        fcx.contract = Some(base);
+        for var in b.variables() {
+            fcx.init_state_var(var);
+        }
+        if let Some(base_ctor) = b.ctor {
+            let f = gcx.hir.function(base_ctor);
+            let name = fcx.cx.internal_function(base_ctor);
+            let params = param_names(fcx.cx, f.parameters);
+            fcx.line(format_args!("{name}({})", params.join(", ")));
+        }
+    }
+    code += &fcx.into_code();
+
+    // Copy the deployed code to memory, fill in the immutables and return it.
+    let _ = write!(
+        code,
+        "let pos := mload(64)
+        let size := datasize(\"{name}_deployed\")
+        codecopy(pos, dataoffset(\"{name}_deployed\"), size)\n"
+    );
+    for var in immutables(gcx, id) {
+        let offset = cx.immutable_offset(var);
+        let _ = writeln!(code, "setimmutable(pos, \"{}\", mload({offset}))", var.index());
+    }
+    code += "return(pos, size)\n";
+    code
+}
+
+/// Returns the top-level code of the deployed object.
+fn dispatcher_code(cx: &mut ObjectCx<'_, '_>) -> String {
+    let gcx = cx.gcx;
+    let id = cx.contract;
+    let c = gcx.hir.contract(id);
+    let mut code = format!("mstore(64, {IMMUTABLES_START})\n");
+
+    let functions = gcx.interface_functions(id).all();
+    if !functions.is_empty() {
+        code += "if iszero(lt(calldatasize(), 4))\n{\nlet selector := shr(224, calldataload(0))\nswitch selector\n";
+        for f in functions {
+            let wrapper = external_function(cx, f.id);
+            let selector = u32::from_be_bytes(f.selector.0);
+            let _ = writeln!(code, "case {selector:#010x} {{ {wrapper}() }}");
+        }
+        code += "default { }\n}\n";
+    }
+
+    if let Some(receive) = c.receive {
+        let receive = cx.internal_function(receive);
+        let _ = writeln!(code, "if iszero(calldatasize()) {{ {receive}() stop() }}");
+    }
+    match c.fallback {
+        Some(fallback_id) => {
+            let fallback = gcx.hir.function(fallback_id);
+            let name = cx.internal_function(fallback_id);
+            if !fallback.state_mutability.is_payable() {
+                code += "if callvalue() { revert(0, 0) }\n";
+            }
+            if fallback.parameters.is_empty() {
+                let _ = writeln!(code, "{name}()\nstop()");
+            } else {
+                let _ = writeln!(
+                    code,
+                    "let output := {name}(0, calldatasize())
+                    return(add(output, 32), mload(output))"
+                );
+            }
+        }
+        None => code += "revert(0, 0)\n",
+    }
+    code
+}
+
+/// Generates the ABI wrapper of an externally callable function and returns its name.
+fn external_function(cx: &mut ObjectCx<'_, '_>, id: hir::FunctionId) -> String {
+    let gcx = cx.gcx;
+    let f = gcx.hir.function(id);
+    let name = format!("external_{}", cx.function_name(id));
+    let param_tys = gcx.item_parameter_types(id);
+    let return_tys: Vec<_> = f.returns.iter().map(|&ret| gcx.type_of_item(ret.into())).collect();
+    let enc_tys: Vec<_> = return_tys.iter().map(|&ty| cx.abi_encoding_type(ty)).collect();
+    if !param_tys.iter().all(|&ty| cx.is_abi_decodable(ty))
+        || !enc_tys.iter().all(|&ty| cx.is_abi_encodable(ty))
+    {
+        cx.unsupported(f.span, "external functions with these parameter types");
+        return name;
+    }
+
+    let mut fcx = FunctionCx::new(cx, f.contract);
+    if !f.state_mutability.is_payable() {
+        fcx.line("if callvalue() { revert(0, 0) }");
+    }
+    let params: Vec<_> = param_tys
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &ty)| (0..utils::stack_size(ty)).map(move |j| format!("param_{i}_{j}")))
+        .collect();
+    let decode = fcx.cx.abi_decode_tuple_fn(param_tys, false);
+    if params.is_empty() {
+        fcx.line(format_args!("{decode}(4, calldatasize())"));
+    } else {
+        fcx.line(format_args!("let {} := {decode}(4, calldatasize())", params.join(", ")));
+    }
+    let function = fcx.cx.internal_function(id);
+    let n_returns = return_tys.iter().map(|&ty| utils::stack_size(ty)).sum();
+    let values = fcx.bind_call(format!("{function}({})", params.join(", ")), n_returns);
+    let mut values = values.into_iter();
+    let mut encoded = Vec::new();
+    for (&from, &to) in return_tys.iter().zip(&enc_tys) {
+        let component = values.by_ref().take(utils::stack_size(from)).collect();
+        encoded.extend(fcx.convert(component, from, to));
+    }
+    let pos = fcx.allocate_unbounded();
+    let encode = fcx.cx.abi_encode_tuple_fn(&enc_tys);
+    let mut encode_args = vec![pos.clone()];
+    encode_args.extend(encoded);
+    let end = fcx.bind(format!("{encode}({})", encode_args.join(", ")));
+    fcx.line(format_args!("return({pos}, sub({end}, {pos}))"));
+    let code = fcx.into_code();
+    super::function::add_function(cx, &name, &[], &[], &code);
+    name
+}
+
+impl<'gcx> FunctionCx<'_, '_, 'gcx> {
+    /// Assigns the initial value of a state variable, if it has one.
+    pub(crate) fn init_state_var(&mut self, id: hir::VariableId) {
+        let gcx = self.cx.gcx;
+        let var = gcx.hir.variable(id);
+        if var.is_constant() {
+            return;
+        }
+        let Some(init) = var.initializer else { return };
+        let ty = gcx.type_of_item(id.into());
+        let value_ty = if ty.is_value_type() { ty } else { ty.with_loc(gcx, DataLocation::Memory) };
+        let values = self.expr_to(init, value_ty);
+        self.assign_var(id, &values);
+    }
+}
@@ -0,0 +1,150 @@
+//! EVM assembly and bytecode layout.
+
+use super::instructions::op;
+use alloy_primitives::U256;
+use std::collections::BTreeMap;
+
+/// A jump destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Tag(pub(crate) usize);
+
+/// An assembly item.
+#[derive(Clone, Debug)]
+pub(crate) enum Item {
+    /// A plain instruction.
+    Op(u8),
+    /// Pushes a constant.
+    Push(U256),
+    /// Pushes the code offset of a tag.
+    PushTag(Tag),
+    /// A jump destination.
+    Tag(Tag),
+    /// Pushes the size of the object that is being assembled, including its sub-objects.
+    PushProgramSize,
+    /// Pushes the size of a sub-object.
+    PushSubSize(usize),
+    /// Pushes the offset of a sub-object in the assembled object.
+    PushSubOffset(usize),
+    /// Pushes the value of an immutable, which is filled in at deploy time.
+    PushImmutable(String),
+}
+
+/// An assembled object.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Assembled {
+    /// The bytecode, including the sub-objects.
+    pub(crate) bytecode: Vec<u8>,
+    /// The offsets of the 32-byte placeholders of each immutable in the bytecode.
+    pub(crate) immutables: BTreeMap<String, Vec<usize>>,
+    /// The assembled sub-objects, by name.
+    pub(crate) children: Vec<(String, Self)>,
+}
+
+impl Assembled {
+    /// Returns the sub-object with the given name.
+    pub(crate) fn child(&self, name: &str) -> Option<&Self> {
+        self.children.iter().find(|(n, _)| n == name).map(|(_, child)| child)
+    }
+}
+
+/// Assembles the given items and appends the sub-objects.
+pub(crate) fn assemble(
+    items: &[Item],
+    children: Vec<(String, Assembled)>,
+    push0: bool,
+) -> Assembled {
+    let data_size: usize = children.iter().map(|(_, c)| c.bytecode.len()).sum();
+    // Tags and sizes are pushed with a fixed width, which must fit the total size.
+    let mut width = 1;
+    let code_size = loop {
+        let code_size: usize = items.iter().map(|item| item_size(item, width, push0)).sum();
+        let total = code_size + usize::from(!children.is_empty()) + data_size;
+        if total < 1 << (8 * width) {
+            break code_size;
+        }
+        width += 1;
+    };
+
+    let mut tags = Vec::new();
+    let mut pc = 0;
+    for item in items {
+        if let Item::Tag(tag) = item {
+            if tags.len() <= tag.0 {
+                tags.resize(tag.0 + 1, 0);
+            }
+            tags[tag.0] = pc;
+        }
+        pc += item_size(item, width, push0);
+    }
+
+    let data_start = code_size + usize::from(!children.is_empty());
+    let total_size = data_start + data_size;
+    let sub_offsets: Vec<usize> = children
+        .iter()
+        .scan(data_start, |offset, (_, child)| {
+            let start = *offset;
+            *offset += child.bytecode.len();
+            Some(start)
+        })
+        .collect();
+
+    let mut code = Vec::with_capacity(total_size);
+    let mut immutables = BTreeMap::<String, Vec<usize>>::new();
+    let push_fixed = |code: &mut Vec<u8>, value: usize| {
+        code.push(op::PUSH1 + width as u8 - 1);
+        code.extend_from_slice(&value.to_be_bytes()[size_of::<usize>() - width..]);
+    };
+    for item in items {
+        match item {
+            Item::Op(opcode) => code.push(*opcode),
+            Item::Push(value) => push_value(&mut code, *value, push0),
+            Item::PushTag(tag) => push_fixed(&mut code, tags[tag.0]),
+            Item::Tag(_) => code.push(op::JUMPDEST),
+            Item::PushProgramSize => push_fixed(&mut code, total_size),
+            Item::PushSubSize(i) => push_fixed(&mut code, children[*i].1.bytecode.len()),
+            Item::PushSubOffset(i) => push_fixed(&mut code, sub_offsets[*i]),
+            Item::PushImmutable(name) => {
+                code.push(op::PUSH32);
+                immutables.entry(name.clone()).or_default().push(code.len());
+                code.extend_from_slice(&[0; 32]);
+            }
+        }
+    }
+    debug_assert_eq!(code.len(), code_size);
+    if !children.is_empty() {
+        // Separates the code from the data, so that it cannot be executed by falling through.
+        code.push(op::INVALID);
+    }
+    for (_, child) in &children {
+        code.extend_from_slice(&child.bytecode);
+    }
+    Assembled { bytecode: code, immutables, children }
+}
+
+fn item_size(item: &Item, width: usize, push0: bool) -> usize {
+    match item {
+        Item::Op(_) | Item::Tag(_) => 1,
+        Item::Push(value) => 1 + push_width(*value, push0),
+        Item::PushTag(_)
+        | Item::PushProgramSize
+        | Item::PushSubSize(_)
+        | Item::PushSubOffset(_) => 1 + width,
+        Item::PushImmutable(_) => 33,
+    }
+}
+
+/// Returns the number of immediate bytes required to push the given value.
+fn push_width(value: U256, push0: bool) -> usize {
+    let bytes = value.byte_len();
+    if bytes == 0 && !push0 { 1 } else { bytes }
+}
+
+fn push_value(code: &mut Vec<u8>, value: U256, push0: bool) {
+    let width = push_width(value, push0);
+    if width == 0 {
+        code.push(op::PUSH0);
+        return;
+    }
+    code.push(op::PUSH1 + width as u8 - 1);
+    code.extend_from_slice(&value.to_be_bytes::<32>()[32 - width..]);
+}
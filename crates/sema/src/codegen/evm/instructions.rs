@@ -0,0 +1,166 @@
+//! EVM instructions that are available as Yul builtin functions.
+
+use solar_interface::config::EvmVersion;
+
+/// An EVM instruction.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Instruction {
+    /// The name of the Yul builtin.
+    pub(crate) name: &'static str,
+    /// The opcode.
+    pub(crate) opcode: u8,
+    /// The number of stack items consumed.
+    pub(crate) inputs: u8,
+    /// The number of stack items produced.
+    pub(crate) outputs: u8,
+    /// The first EVM version in which the instruction is available.
+    pub(crate) since: EvmVersion,
+}
+
+impl Instruction {
+    /// Returns `true` if the instruction is available in the given EVM version.
+    pub(crate) fn is_available(&self, evm: EvmVersion) -> bool {
+        match self.name {
+            "returndatasize" | "returndatacopy" => evm.supports_returndata(),
+            "staticcall" => evm.has_static_call(),
+            "shl" | "shr" | "sar" => evm.has_bitwise_shifting(),
+            "create2" => evm.has_create2(),
+            "extcodehash" => evm.has_ext_code_hash(),
+            "chainid" => evm.has_chain_id(),
+            "selfbalance" => evm.has_self_balance(),
+            "basefee" => evm.has_base_fee(),
+            "blobbasefee" => evm.has_blob_base_fee(),
+            "prevrandao" => evm.has_prev_randao(),
+            "blobhash" => evm.has_blob_hash(),
+            "mcopy" => evm.has_mcopy(),
+            "tload" | "tstore" => evm.supports_transient_storage(),
+            _ => evm >= self.since,
+        }
+    }
+}
+
+macro_rules! instructions {
+    ($($name:literal = $opcode:literal, $inputs:literal, $outputs:literal $(, $since:ident)?;)*) => {
+        /// All the instructions that can be called from Yul, sorted by name.
+        static INSTRUCTIONS: &[Instruction] = &[$(
+            Instruction {
+                name: $name,
+                opcode: $opcode,
+                inputs: $inputs,
+                outputs: $outputs,
+                since: instructions!(@since $($since)?),
+            },
+        )*];
+    };
+    (@since) => { EvmVersion::Homestead };
+    (@since $since:ident) => { EvmVersion::$since };
+}
+
+instructions! {
+    "add" = 0x01, 2, 1;
+    "addmod" = 0x08, 3, 1;
+    "address" = 0x30, 0, 1;
+    "and" = 0x16, 2, 1;
+    "balance" = 0x31, 1, 1;
+    "basefee" = 0x48, 0, 1, London;
+    "blobbasefee" = 0x4a, 0, 1, Cancun;
+    "blobhash" = 0x49, 1, 1, Cancun;
+    "blockhash" = 0x40, 1, 1;
+    "byte" = 0x1a, 2, 1;
+    "call" = 0xf1, 7, 1;
+    "callcode" = 0xf2, 7, 1;
+    "calldatacopy" = 0x37, 3, 0;
+    "calldataload" = 0x35, 1, 1;
+    "calldatasize" = 0x36, 0, 1;
+    "caller" = 0x33, 0, 1;
+    "callvalue" = 0x34, 0, 1;
+    "chainid" = 0x46, 0, 1, Istanbul;
+    "codecopy" = 0x39, 3, 0;
+    "codesize" = 0x38, 0, 1;
+    "coinbase" = 0x41, 0, 1;
+    "create" = 0xf0, 3, 1;
+    "create2" = 0xf5, 4, 1, Constantinople;
+    "delegatecall" = 0xf4, 6, 1;
+    "difficulty" = 0x44, 0, 1;
+    "div" = 0x04, 2, 1;
+    "eq" = 0x14, 2, 1;
+    "exp" = 0x0a, 2, 1;
+    "extcodecopy" = 0x3c, 4, 0;
+    "extcodehash" = 0x3f, 1, 1, Constantinople;
+    "extcodesize" = 0x3b, 1, 1;
+    "gas" = 0x5a, 0, 1;
+    "gaslimit" = 0x45, 0, 1;
+    "gasprice" = 0x3a, 0, 1;
+    "gt" = 0x11, 2, 1;
+    "invalid" = 0xfe, 0, 0;
+    "iszero" = 0x15, 1, 1;
+    "keccak256" = 0x20, 2, 1;
+    "log0" = 0xa0, 2, 0;
+    "log1" = 0xa1, 3, 0;
+    "log2" = 0xa2, 4, 0;
+    "log3" = 0xa3, 5, 0;
+    "log4" = 0xa4, 6, 0;
+    "lt" = 0x10, 2, 1;
+    "mcopy" = 0x5e, 3, 0, Cancun;
+    "mload" = 0x51, 1, 1;
+    "mod" = 0x06, 2, 1;
+    "msize" = 0x59, 0, 1;
+    "mstore" = 0x52, 2, 0;
+    "mstore8" = 0x53, 2, 0;
+    "mul" = 0x02, 2, 1;
+    "mulmod" = 0x09, 3, 1;
+    "not" = 0x19, 1, 1;
+    "number" = 0x43, 0, 1;
+    "or" = 0x17, 2, 1;
+    "origin" = 0x32, 0, 1;
+    "pop" = 0x50, 1, 0;
+    "prevrandao" = 0x44, 0, 1, Paris;
+    "return" = 0xf3, 2, 0;
+    "returndatacopy" = 0x3e, 3, 0, Byzantium;
+    "returndatasize" = 0x3d, 0, 1, Byzantium;
+    "revert" = 0xfd, 2, 0, Byzantium;
+    "sar" = 0x1d, 2, 1, Constantinople;
+    "sdiv" = 0x05, 2, 1;
+    "selfbalance" = 0x47, 0, 1, Istanbul;
+    "selfdestruct" = 0xff, 1, 0;
+    "sgt" = 0x13, 2, 1;
+    "shl" = 0x1b, 2, 1, Constantinople;
+    "shr" = 0x1c, 2, 1, Constantinople;
+    "signextend" = 0x0b, 2, 1;
+    "sload" = 0x54, 1, 1;
+    "slt" = 0x12, 2, 1;
+    "smod" = 0x07, 2, 1;
+    "sstore" = 0x55, 2, 0;
+    "staticcall" = 0xfa, 6, 1, Byzantium;
+    "stop" = 0x00, 0, 0;
+    "sub" = 0x03, 2, 1;
+    "timestamp" = 0x42, 0, 1;
+    "tload" = 0x5c, 1, 1, Cancun;
+    "tstore" = 0x5d, 2, 0, Cancun;
+    "xor" = 0x18, 2, 1;
+}
+
+/// Returns the instruction with the given Yul builtin name.
+pub(crate) fn by_name(name: &str) -> Option<&'static Instruction> {
+    INSTRUCTIONS.binary_search_by(|i| i.name.cmp(name)).ok().map(|i| &INSTRUCTIONS[i])
+}
+
+/// Opcodes used by the code transform and the assembler.
+pub(crate) mod op {
+    pub(crate) const STOP: u8 = 0x00;
+    pub(crate) const ADD: u8 = 0x01;
+    pub(crate) const POP: u8 = 0x50;
+    pub(crate) const MSTORE: u8 = 0x52;
+    pub(crate) const JUMP: u8 = 0x56;
+    pub(crate) const JUMPI: u8 = 0x57;
+    pub(crate) const JUMPDEST: u8 = 0x5b;
+    pub(crate) const PUSH0: u8 = 0x5f;
+    pub(crate) const PUSH1: u8 = 0x60;
+    pub(crate) const PUSH32: u8 = 0x7f;
+    pub(crate) const DUP1: u8 = 0x80;
+    pub(crate) const SWAP1: u8 = 0x90;
+    pub(crate) const ISZERO: u8 = 0x15;
+    pub(crate) const EQ: u8 = 0x14;
+    pub(crate) const CODECOPY: u8 = 0x39;
+    pub(crate) const INVALID: u8 = 0xfe;
+}
@@ -0,0 +1,26 @@
+//! EVM bytecode generation from the Yul IR.
+
+use super::yul;
+use solar_interface::config::EvmVersion;
+
+mod assembly;
+pub(crate) mod instructions;
+mod transform;
+
+pub(crate) use assembly::Assembled;
+
+/// Assembles the given Yul object and its sub-objects into EVM bytecode.
+///
+/// Returns the error messages if the object could not be assembled.
+pub(crate) fn assemble(object: &yul::Object, evm: EvmVersion) -> Result<Assembled, Vec<String>> {
+    let mut children = Vec::with_capacity(object.children.len() + object.data.len());
+    for child in &object.children {
+        children.push((child.name.clone(), assemble(child, evm)?));
+    }
+    // Data sections are appended like sub-objects.
+    for (name, data) in &object.data {
+        children.push((name.clone(), Assembled { bytecode: data.clone(), ..Default::default() }));
+    }
+    let items = transform::transform(object, &children, evm)?;
+    Ok(assembly::assemble(&items, children, evm.has_push0()))
+}
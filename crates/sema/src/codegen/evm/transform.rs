@@ -0,0 +1,666 @@
+//! Transformation of the Yul tree into EVM assembly.
+//!
+//! Variables are kept on the stack and accessed with `DUP` and `SWAP`, similarly to solc's legacy
+//! Yul code transform. The slots of variables that are no longer referenced are reused by later
+//! declarations, or popped if they are on top of the stack.
+
+use super::{
+    assembly::{Assembled, Item, Tag},
+    instructions::{self, op},
+};
+use crate::codegen::yul::{Block, Expr, For, Function, Object, Stmt, Switch};
+use alloy_primitives::U256;
+use solar_data_structures::map::FxHashMap;
+use solar_interface::config::EvmVersion;
+
+/// The maximum depth that can be reached with `DUP` and `SWAP`.
+const MAX_DEPTH: usize = 16;
+
+/// Transforms the code of the given object into assembly items.
+///
+/// `children` are the already assembled sub-objects of `object`.
+pub(crate) fn transform(
+    object: &Object,
+    children: &[(String, Assembled)],
+    evm: EvmVersion,
+) -> Result<Vec<Item>, Vec<String>> {
+    let mut t = Transform {
+        evm,
+        object,
+        children,
+        items: Vec::new(),
+        next_tag: 0,
+        errors: Vec::new(),
+        functions: Vec::new(),
+        frame: Frame::default(),
+    };
+    t.frame.scopes.push(Scope { height: 0, vars: 0 });
+    t.stmts(&object.code.stmts);
+    t.op(op::STOP, 0, 0);
+    if t.errors.is_empty() { Ok(t.items) } else { Err(t.errors) }
+}
+
+struct Transform<'a> {
+    evm: EvmVersion,
+    object: &'a Object,
+    children: &'a [(String, Assembled)],
+    items: Vec<Item>,
+    next_tag: usize,
+    errors: Vec<String>,
+    /// The visible functions, by block, innermost last.
+    functions: Vec<FxHashMap<&'a str, FunctionInfo>>,
+    /// The stack layout of the function that is being transformed.
+    frame: Frame<'a>,
+}
+
+#[derive(Clone, Copy)]
+struct FunctionInfo {
+    tag: Tag,
+    params: usize,
+    returns: usize,
+}
+
+/// The stack layout of a function, or of the top-level code of an object.
+#[derive(Default)]
+struct Frame<'a> {
+    /// The name of the function, if any.
+    name: Option<&'a str>,
+    /// The contents of the stack, bottom first.
+    stack: Vec<Slot>,
+    /// The variables in scope, in declaration order.
+    vars: Vec<Var<'a>>,
+    /// The scopes of the blocks that are being transformed, innermost last.
+    scopes: Vec<Scope>,
+    /// The loops that are being transformed, innermost last.
+    loops: Vec<Loop>,
+    /// The tag that `leave` jumps to and the height of the stack that it expects.
+    exit: Option<(Tag, usize)>,
+    /// The scope whose variables must be kept until its end, used for loop initializers.
+    keep_scope: Option<usize>,
+    /// Whether a "stack too deep" error was already reported, which is only done once per frame.
+    too_deep: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    /// An intermediate value.
+    Value,
+    /// A variable, by its index in [`Frame::vars`].
+    Var(usize),
+    /// The slot of a variable that is no longer referenced.
+    Free,
+}
+
+struct Var<'a> {
+    name: &'a str,
+    slot: usize,
+    /// The number of references that have not been transformed yet.
+    refs: usize,
+    /// Whether the variable must be kept until the end of its scope.
+    keep: bool,
+}
+
+#[derive(Clone, Copy)]
+struct Scope {
+    /// The height of the stack at the start of the scope.
+    height: usize,
+    /// The index of the first variable declared in the scope.
+    vars: usize,
+}
+
+#[derive(Clone, Copy)]
+struct Loop {
+    continue_tag: Tag,
+    break_tag: Tag,
+    height: usize,
+}
+
+impl<'a> Transform<'a> {
+    fn new_tag(&mut self) -> Tag {
+        self.next_tag += 1;
+        Tag(self.next_tag - 1)
+    }
+
+    fn height(&self) -> usize {
+        self.frame.stack.len()
+    }
+
+    /// Emits an instruction that consumes `inputs` values and produces `outputs` values.
+    fn op(&mut self, opcode: u8, inputs: usize, outputs: usize) {
+        self.items.push(Item::Op(opcode));
+        let height = self.height();
+        self.frame.stack.truncate(height - inputs);
+        self.frame.stack.extend(std::iter::repeat_n(Slot::Value, outputs));
+    }
+
+    /// Emits an item that pushes one value.
+    fn push(&mut self, item: Item) {
+        self.items.push(item);
+        self.frame.stack.push(Slot::Value);
+    }
+
+    fn pop(&mut self) {
+        self.op(op::POP, 1, 0);
+    }
+
+    /// Duplicates the given stack slot onto the top of the stack.
+    fn dup(&mut self, slot: usize, what: &str) {
+        let depth = self.height() - slot;
+        self.check_depth(depth, what);
+        self.items.push(Item::Op(op::DUP1 + depth.clamp(1, MAX_DEPTH) as u8 - 1));
+        self.frame.stack.push(Slot::Value);
+    }
+
+    /// Moves the top of the stack into the given slot.
+    fn swap_pop(&mut self, slot: usize, what: &str) {
+        let depth = self.height() - 1 - slot;
+        self.check_depth(depth, what);
+        self.items.push(Item::Op(op::SWAP1 + depth.clamp(1, MAX_DEPTH) as u8 - 1));
+        self.pop();
+    }
+
+    fn check_depth(&mut self, depth: usize, what: &str) {
+        if depth > MAX_DEPTH && !self.frame.too_deep {
+            self.frame.too_deep = true;
+            let location = match self.frame.name {
+                Some(name) => format!("in function `{name}`"),
+                None => format!("in the code of object `{}`", self.object.name),
+            };
+            self.errors.push(format!(
+                "stack too deep: {what} {location} is {} slot(s) too deep to be reached",
+                depth - MAX_DEPTH
+            ));
+        }
+    }
+
+    /// Pops the stack down to the given height, without changing the stack model.
+    ///
+    /// Used before jumps, after which the code is unreachable.
+    fn pop_for_jump(&mut self, height: usize, tag: Tag) {
+        let stack = self.frame.stack.clone();
+        while self.height() > height {
+            self.pop();
+        }
+        self.push(Item::PushTag(tag));
+        self.op(op::JUMP, 1, 0);
+        self.frame.stack = stack;
+    }
+
+    fn lookup_var(&self, name: &str) -> Option<usize> {
+        self.frame.vars.iter().rposition(|var| var.name == name)
+    }
+
+    fn lookup_function(&self, name: &str) -> Option<FunctionInfo> {
+        self.functions.iter().rev().find_map(|scope| scope.get(name).copied())
+    }
+
+    /// Declares a variable in the given slot, which must be on the stack already.
+    fn declare(&mut self, name: &'a str, slot: usize, refs: usize, keep: bool) {
+        let keep = keep || self.frame.keep_scope.is_some_and(|s| s + 1 == self.frame.scopes.len());
+        let index = self.frame.vars.len();
+        self.frame.vars.push(Var { name, slot, refs, keep });
+        self.frame.stack[slot] = Slot::Var(index);
+    }
+
+    fn stmts(&mut self, stmts: &'a [Stmt]) {
+        // Functions are visible in the whole block.
+        let mut functions = FxHashMap::default();
+        for stmt in stmts {
+            if let Stmt::Function(f) = stmt {
+                let info = FunctionInfo {
+                    tag: self.new_tag(),
+                    params: f.params.len(),
+                    returns: f.returns.len(),
+                };
+                functions.insert(f.name.as_str(), info);
+            }
+        }
+        self.functions.push(functions);
+
+        let mut i = 0;
+        while i < stmts.len() {
+            if let Stmt::Function(_) = &stmts[i] {
+                // Jump over consecutive function definitions.
+                let skip = self.new_tag();
+                self.push(Item::PushTag(skip));
+                self.op(op::JUMP, 1, 0);
+                while let Some(Stmt::Function(f)) = stmts.get(i) {
+                    self.function(f);
+                    i += 1;
+                }
+                self.items.push(Item::Tag(skip));
+                continue;
+            }
+            self.stmt(&stmts[i], &stmts[i + 1..]);
+            self.free_unused();
+            i += 1;
+        }
+
+        self.functions.pop();
+    }
+
+    /// Frees the slots of the variables of the current scope that are no longer referenced, and
+    /// pops them if they are on top of the stack.
+    fn free_unused(&mut self) {
+        let scope = *self.frame.scopes.last().unwrap();
+        for index in scope.vars..self.frame.vars.len() {
+            let var = &self.frame.vars[index];
+            if !var.keep
+                && var.refs == 0
+                && self.frame.stack.get(var.slot) == Some(&Slot::Var(index))
+            {
+                let slot = var.slot;
+                self.frame.stack[slot] = Slot::Free;
+            }
+        }
+        while self.height() > scope.height && self.frame.stack.last() == Some(&Slot::Free) {
+            self.pop();
+        }
+    }
+
+    fn block(&mut self, block: &'a Block) {
+        self.enter_scope();
+        self.stmts(&block.stmts);
+        self.exit_scope();
+    }
+
+    fn enter_scope(&mut self) {
+        let scope = Scope { height: self.height(), vars: self.frame.vars.len() };
+        self.frame.scopes.push(scope);
+    }
+
+    fn exit_scope(&mut self) {
+        let scope = self.frame.scopes.pop().unwrap();
+        while self.height() > scope.height {
+            self.pop();
+        }
+        // Variables that reused a slot of an outer scope free it again.
+        for index in scope.vars..self.frame.vars.len() {
+            let slot = self.frame.vars[index].slot;
+            if self.frame.stack.get(slot) == Some(&Slot::Var(index)) {
+                self.frame.stack[slot] = Slot::Free;
+            }
+        }
+        self.frame.vars.truncate(scope.vars);
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt, rest: &'a [Stmt]) {
+        match stmt {
+            Stmt::Block(block) => self.block(block),
+            Stmt::Let(names, value) => self.let_(names, value.as_ref(), rest),
+            Stmt::Assign(names, value) => {
+                let n = self.expr(value);
+                debug_assert_eq!(n, names.len());
+                for name in names.iter().rev() {
+                    let Some(index) = self.lookup_var(name) else {
+                        self.errors.push(format!("unknown variable `{name}`"));
+                        self.pop();
+                        continue;
+                    };
+                    let var = &mut self.frame.vars[index];
+                    var.refs = var.refs.saturating_sub(1);
+                    let slot = var.slot;
+                    self.swap_pop(slot, &format!("variable `{name}`"));
+                }
+            }
+            Stmt::Expr(expr) => {
+                let n = self.expr(expr);
+                for _ in 0..n {
+                    self.pop();
+                }
+            }
+            Stmt::If(cond, body) => {
+                let end = self.new_tag();
+                self.expr(cond);
+                self.op(op::ISZERO, 1, 1);
+                self.push(Item::PushTag(end));
+                self.op(op::JUMPI, 2, 0);
+                self.block(body);
+                self.items.push(Item::Tag(end));
+            }
+            Stmt::Switch(switch) => self.switch(switch),
+            Stmt::For(for_) => self.for_(for_),
+            Stmt::Break | Stmt::Continue => {
+                let Some(&l) = self.frame.loops.last() else {
+                    self.errors.push("`break` or `continue` outside of a loop".into());
+                    return;
+                };
+                let tag = if let Stmt::Break = stmt { l.break_tag } else { l.continue_tag };
+                self.pop_for_jump(l.height, tag);
+            }
+            Stmt::Leave => {
+                let Some((tag, height)) = self.frame.exit else {
+                    self.errors.push("`leave` outside of a function".into());
+                    return;
+                };
+                self.pop_for_jump(height, tag);
+            }
+            Stmt::Function(_) => unreachable!("functions are handled in `stmts`"),
+        }
+    }
+
+    fn let_(&mut self, names: &'a [String], value: Option<&'a Expr>, rest: &'a [Stmt]) {
+        let Some(value) = value else {
+            for name in names {
+                self.push(Item::Push(U256::ZERO));
+                let slot = self.height() - 1;
+                self.declare(name, slot, count_refs(name, rest), false);
+            }
+            return;
+        };
+        let n = self.expr(value);
+        debug_assert_eq!(n, names.len());
+        if let [name] = names {
+            // Reuse the highest free slot that can be reached.
+            let top = self.height() - 1;
+            let free = (top.saturating_sub(MAX_DEPTH)..top)
+                .rev()
+                .find(|&slot| self.frame.stack[slot] == Slot::Free);
+            let slot = match free {
+                Some(slot) => {
+                    self.swap_pop(slot, "a free slot");
+                    slot
+                }
+                None => top,
+            };
+            self.declare(name, slot, count_refs(name, rest), false);
+            return;
+        }
+        let first = self.height() - names.len();
+        for (i, name) in names.iter().enumerate() {
+            self.declare(name, first + i, count_refs(name, rest), false);
+        }
+    }
+
+    fn switch(&mut self, switch: &'a Switch) {
+        let end = self.new_tag();
+        self.expr(&switch.selector);
+        let tags: Vec<_> = switch.cases.iter().map(|_| self.new_tag()).collect();
+        for ((value, _), &tag) in switch.cases.iter().zip(&tags) {
+            self.push(Item::Push(*value));
+            self.dup(self.height() - 2, "the switch selector");
+            self.op(op::EQ, 2, 1);
+            self.push(Item::PushTag(tag));
+            self.op(op::JUMPI, 2, 0);
+        }
+        if let Some(default) = &switch.default {
+            self.block(default);
+        }
+        self.push(Item::PushTag(end));
+        self.op(op::JUMP, 1, 0);
+        for ((_, body), &tag) in switch.cases.iter().zip(&tags) {
+            self.items.push(Item::Tag(tag));
+            self.block(body);
+            self.push(Item::PushTag(end));
+            self.op(op::JUMP, 1, 0);
+        }
+        self.items.push(Item::Tag(end));
+        self.pop();
+    }
+
+    fn for_(&mut self, for_: &'a For) {
+        let (start, continue_tag, break_tag) = (self.new_tag(), self.new_tag(), self.new_tag());
+        self.enter_scope();
+        // Variables declared in the initialization block are used by the rest of the loop.
+        let keep_scope = self.frame.keep_scope.replace(self.frame.scopes.len() - 1);
+        self.stmts(&for_.init.stmts);
+        self.frame.keep_scope = keep_scope;
+        let height = self.height();
+
+        self.items.push(Item::Tag(start));
+        if !matches!(for_.cond, Expr::Number(n) if !n.is_zero()) {
+            self.expr(&for_.cond);
+            self.op(op::ISZERO, 1, 1);
+            self.push(Item::PushTag(break_tag));
+            self.op(op::JUMPI, 2, 0);
+        }
+        self.frame.loops.push(Loop { continue_tag, break_tag, height });
+        self.block(&for_.body);
+        self.frame.loops.pop();
+        self.items.push(Item::Tag(continue_tag));
+        self.block(&for_.post);
+        self.push(Item::PushTag(start));
+        self.op(op::JUMP, 1, 0);
+        self.items.push(Item::Tag(break_tag));
+        self.exit_scope();
+    }
+
+    fn function(&mut self, f: &'a Function) {
+        let info = self.lookup_function(&f.name).unwrap();
+        let exit = self.new_tag();
+        let n = f.params.len();
+        let m = f.returns.len();
+        let outer = std::mem::take(&mut self.frame);
+        self.frame.name = Some(&f.name);
+
+        // The caller pushes the return address and the arguments in reverse order.
+        self.items.push(Item::Tag(info.tag));
+        self.frame.stack.push(Slot::Value);
+        self.frame.stack.extend(std::iter::repeat_n(Slot::Value, n));
+        for (i, param) in f.params.iter().enumerate() {
+            self.declare(param, n - i, count_refs(param, &f.body.stmts), false);
+        }
+        for ret in &f.returns {
+            self.push(Item::Push(U256::ZERO));
+            let slot = self.height() - 1;
+            self.declare(ret, slot, 0, true);
+        }
+        self.frame.exit = Some((exit, self.height()));
+        self.frame.scopes.push(Scope { height: self.height(), vars: 0 });
+        self.stmts(&f.body.stmts);
+        let height = self.frame.scopes.pop().unwrap().height;
+        while self.height() > height {
+            self.pop();
+        }
+        self.items.push(Item::Tag(exit));
+
+        // Move the return values to the bottom of the frame, followed by the return address.
+        let targets = std::iter::once(Some(m))
+            .chain(std::iter::repeat_n(None, n))
+            .chain((0..m).map(Some))
+            .collect();
+        self.shuffle(targets);
+        self.frame.stack.truncate(m + 1);
+        self.op(op::JUMP, 1, 0);
+        self.frame = outer;
+    }
+
+    /// Permutes the stack with `SWAP` and `POP` so that each slot ends up at its target
+    /// position, dropping the slots without a target.
+    ///
+    /// `targets` describes the top of the stack, bottom first.
+    fn shuffle(&mut self, mut targets: Vec<Option<usize>>) {
+        loop {
+            let top = targets.len() - 1;
+            let Some(target) = targets[top] else {
+                targets.pop();
+                self.items.push(Item::Op(op::POP));
+                continue;
+            };
+            let slot = if target != top {
+                target
+            } else if let Some(slot) = (0..top).find(|&i| targets[i] != Some(i)) {
+                slot
+            } else {
+                break;
+            };
+            self.check_depth(top - slot, "a return value");
+            self.items.push(Item::Op(op::SWAP1 + (top - slot).min(MAX_DEPTH) as u8 - 1));
+            targets.swap(slot, top);
+        }
+    }
+
+    /// Transforms an expression and returns the number of values it pushed.
+    fn expr(&mut self, expr: &'a Expr) -> usize {
+        match expr {
+            Expr::Number(value) => {
+                self.push(Item::Push(*value));
+                1
+            }
+            Expr::Str(s) => {
+                let mut word = [0u8; 32];
+                let bytes = s.as_bytes();
+                let len = bytes.len().min(32);
+                word[..len].copy_from_slice(&bytes[..len]);
+                self.push(Item::Push(U256::from_be_bytes(word)));
+                1
+            }
+            Expr::Ident(name) => {
+                let Some(index) = self.lookup_var(name) else {
+                    self.errors.push(format!("unknown variable `{name}`"));
+                    self.push(Item::Push(U256::ZERO));
+                    return 1;
+                };
+                let var = &mut self.frame.vars[index];
+                var.refs = var.refs.saturating_sub(1);
+                let slot = var.slot;
+                self.dup(slot, &format!("variable `{name}`"));
+                1
+            }
+            Expr::Call(name, args) => self.call(name, args),
+        }
+    }
+
+    fn call(&mut self, name: &'a str, args: &'a [Expr]) -> usize {
+        if let Some(n) = self.special_builtin(name, args) {
+            return n;
+        }
+        if let Some(f) = self.lookup_function(name) {
+            let ret = self.new_tag();
+            let height = self.height();
+            self.push(Item::PushTag(ret));
+            for arg in args.iter().rev() {
+                self.expr(arg);
+            }
+            self.push(Item::PushTag(f.tag));
+            self.op(op::JUMP, 1, 0);
+            self.items.push(Item::Tag(ret));
+            self.frame.stack.truncate(height);
+            self.frame.stack.extend(std::iter::repeat_n(Slot::Value, f.returns));
+            debug_assert_eq!(args.len(), f.params);
+            return f.returns;
+        }
+        let Some(instruction) = instructions::by_name(name) else {
+            self.errors.push(format!("unknown function `{name}`"));
+            return 0;
+        };
+        if !instruction.is_available(self.evm) {
+            self.errors.push(format!(
+                "the `{name}` instruction is only available for {}-compatible VMs \
+                 (you are currently compiling for `{}`)",
+                capitalize(instruction.since.to_str()),
+                self.evm,
+            ));
+        }
+        for arg in args.iter().rev() {
+            self.expr(arg);
+        }
+        let (inputs, outputs) = (instruction.inputs as usize, instruction.outputs as usize);
+        self.op(instruction.opcode, inputs, outputs);
+        outputs
+    }
+
+    /// Transforms the builtins that are not plain instructions.
+    fn special_builtin(&mut self, name: &str, args: &'a [Expr]) -> Option<usize> {
+        let literal = |i: usize| match args.get(i) {
+            Some(Expr::Str(s)) => s.as_str(),
+            _ => "",
+        };
+        match name {
+            "datasize" | "dataoffset" => {
+                let object = literal(0);
+                let item = if object == self.object.name {
+                    if name == "datasize" { Item::PushProgramSize } else { Item::Push(U256::ZERO) }
+                } else if let Some(i) = self.children.iter().position(|(n, _)| n == object) {
+                    if name == "datasize" { Item::PushSubSize(i) } else { Item::PushSubOffset(i) }
+                } else {
+                    self.errors.push(format!("unknown object `{object}`"));
+                    Item::Push(U256::ZERO)
+                };
+                self.push(item);
+                Some(1)
+            }
+            "datacopy" => {
+                for arg in args.iter().rev() {
+                    self.expr(arg);
+                }
+                self.op(op::CODECOPY, 3, 0);
+                Some(0)
+            }
+            "loadimmutable" => {
+                self.push(Item::PushImmutable(literal(0).to_string()));
+                Some(1)
+            }
+            "setimmutable" => {
+                let name = literal(1);
+                self.expr(&args[2]);
+                self.expr(&args[0]);
+                let offsets: Vec<usize> = self
+                    .children
+                    .iter()
+                    .filter_map(|(_, child)| child.immutables.get(name))
+                    .flatten()
+                    .copied()
+                    .collect();
+                let (value, offset) = (self.height() - 2, self.height() - 1);
+                for position in offsets {
+                    self.dup(value, "the immutable value");
+                    self.dup(offset, "the immutable offset");
+                    self.push(Item::Push(U256::from(position)));
+                    self.op(op::ADD, 2, 1);
+                    self.op(op::MSTORE, 2, 0);
+                }
+                self.pop();
+                self.pop();
+                Some(0)
+            }
+            "memoryguard" => Some(self.expr(&args[0])),
+            _ => None,
+        }
+    }
+}
+
+/// Counts the references to the given variable in the given statements.
+fn count_refs(name: &str, stmts: &[Stmt]) -> usize {
+    fn expr(name: &str, e: &Expr) -> usize {
+        match e {
+            Expr::Ident(n) => usize::from(n == name),
+            Expr::Call(_, args) => args.iter().map(|arg| expr(name, arg)).sum(),
+            Expr::Number(_) | Expr::Str(_) => 0,
+        }
+    }
+    fn stmt(name: &str, s: &Stmt) -> usize {
+        match s {
+            Stmt::Block(block) => count_refs(name, &block.stmts),
+            Stmt::Let(_, value) => value.as_ref().map_or(0, |value| expr(name, value)),
+            Stmt::Assign(names, value) => {
+                names.iter().filter(|n| *n == name).count() + expr(name, value)
+            }
+            Stmt::Expr(e) => expr(name, e),
+            Stmt::If(cond, body) => expr(name, cond) + count_refs(name, &body.stmts),
+            Stmt::Switch(switch) => {
+                expr(name, &switch.selector)
+                    + switch
+                        .cases
+                        .iter()
+                        .map(|(_, body)| count_refs(name, &body.stmts))
+                        .sum::<usize>()
+                    + switch.default.as_ref().map_or(0, |body| count_refs(name, &body.stmts))
+            }
+            Stmt::For(for_) => {
+                count_refs(name, &for_.init.stmts)
+                    + expr(name, &for_.cond)
+                    + count_refs(name, &for_.post.stmts)
+                    + count_refs(name, &for_.body.stmts)
+            }
+            // Functions cannot access outer variables.
+            Stmt::Break | Stmt::Continue | Stmt::Leave | Stmt::Function(_) => 0,
+        }
+    }
+    stmts.iter().map(|s| stmt(name, s)).sum()
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
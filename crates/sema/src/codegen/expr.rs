@@ -0,0 +1,1021 @@
+//! Expression lowering.
+
+use super::{
+    function::{FunctionCx, tuple_types},
+    utils::{self, Repr, num},
+    yul::template,
+};
+use crate::{
+    builtins::Builtin,
+    hir,
+    ty::{Ty, TyKind},
+};
+use alloy_primitives::{U256, U512};
+use solar_ast::{DataLocation, ElementaryType, LitKind, StrKind};
+use solar_interface::Ident;
+
+/// A location that can be assigned to.
+enum LValue<'gcx> {
+    /// Local variables.
+    Stack(Vec<String>, Ty<'gcx>),
+    /// A value in storage. If `ty` is a reference type, the value is the slot itself.
+    Storage { slot: String, offset: Offset, ty: Ty<'gcx> },
+    /// A word in memory.
+    Memory { ptr: String, ty: Ty<'gcx> },
+    /// A single byte in a memory byte array.
+    MemoryByte { ptr: String },
+    /// An immutable state variable.
+    Immutable { var: hir::VariableId, ty: Ty<'gcx> },
+    /// A tuple of locations. Empty components are skipped.
+    Tuple(Vec<Option<Self>>),
+    /// A value that cannot be assigned to.
+    Rvalue(Vec<String>),
+}
+
+/// The byte offset of a value in a storage slot.
+enum Offset {
+    Static(u8),
+    Dynamic(String),
+}
+
+/// An integer literal value, possibly larger than 256 bits.
+#[derive(Clone, Copy)]
+struct LitInt {
+    neg: bool,
+    abs: U512,
+}
+
+impl LitInt {
+    fn new(neg: bool, abs: U512) -> Self {
+        Self { neg: neg && !abs.is_zero(), abs }
+    }
+
+    fn from_u256(value: U256) -> Self {
+        Self::new(false, U512::from(value))
+    }
+
+    fn neg(self) -> Self {
+        Self::new(!self.neg, self.abs)
+    }
+
+    fn add(self, other: Self) -> Option<Self> {
+        if self.neg == other.neg {
+            return Some(Self::new(self.neg, self.abs.checked_add(other.abs)?));
+        }
+        Some(if self.abs >= other.abs {
+            Self::new(self.neg, self.abs - other.abs)
+        } else {
+            Self::new(other.neg, other.abs - self.abs)
+        })
+    }
+
+    fn mul(self, other: Self) -> Option<Self> {
+        Some(Self::new(self.neg != other.neg, self.abs.checked_mul(other.abs)?))
+    }
+
+    /// Returns the two's complement representation in 256 bits.
+    fn to_word(self) -> Option<U256> {
+        let abs = U256::checked_from_limbs_slice(&self.abs.as_limbs()[..4])
+            .filter(|_| self.abs >> 256 == U512::ZERO)?;
+        Some(if self.neg { (!abs).wrapping_add(U256::from(1)) } else { abs })
+    }
+}
+
+impl<'gcx> FunctionCx<'_, '_, 'gcx> {
+    /// Evaluates an expression, returning one identifier or literal per stack slot.
+    pub(crate) fn expr(&mut self, expr: &'gcx hir::Expr<'gcx>) -> Vec<String> {
+        let ty = self.cx.ty_of(expr);
+        match ty.kind {
+            TyKind::IntLiteral(..) => {
+                let Some(mobile) = ty.mobile(self.cx.gcx) else { return vec!["0".into()] };
+                return self.expr_to(expr, mobile);
+            }
+            TyKind::StringLiteral(..) => {
+                return self.expr_to(expr, self.cx.gcx.types.string_ref.memory);
+            }
+            _ => {}
+        }
+        let lvalue = self.lvalue(expr);
+        self.read(lvalue)
+    }
+
+    /// Evaluates an expression and converts it to the given type.
+    pub(crate) fn expr_to(&mut self, expr: &'gcx hir::Expr<'gcx>, to: Ty<'gcx>) -> Vec<String> {
+        let from = self.cx.ty_of(expr);
+        match from.kind {
+            TyKind::IntLiteral(..) => {
+                let value = self.int_literal(expr);
+                let to_repr = utils::repr(self.cx.gcx, to);
+                let Some(word) = value.and_then(LitInt::to_word) else {
+                    self.cx.unsupported(expr.span, "this integer literal");
+                    return vec!["0".into()];
+                };
+                let word = match to_repr {
+                    Some(Repr::FixedBytes(bytes)) => word << (256 - bytes as usize * 8),
+                    _ => word,
+                };
+                return vec![num(word)];
+            }
+            TyKind::StringLiteral(..) => {
+                let Some(value) = string_literal(expr) else {
+                    self.cx.unsupported(expr.span, "this string literal");
+                    return vec!["0".into()];
+                };
+                return match utils::repr(self.cx.gcx, to) {
+                    Some(Repr::FixedBytes(_)) => vec![num(utils::left_word(&value))],
+                    _ => {
+                        let f = self.cx.string_literal_fn(&value);
+                        vec![self.bind(format!("{f}()"))]
+                    }
+                };
+            }
+            TyKind::Tuple(tys) if matches!(to.kind, TyKind::Tuple(_)) => {
+                if let hir::ExprKind::Tuple(exprs) = expr.kind
+                    && exprs.len() == tys.len()
+                {
+                    let to_tys = tuple_types(to);
+                    let mut values = Vec::new();
+                    for (component, &to) in exprs.iter().zip(&to_tys) {
+                        if let Some(component) = component {
+                            values.extend(self.expr_to(component, to));
+                        }
+                    }
+                    return values;
+                }
+            }
+            _ => {}
+        }
+        if let hir::ExprKind::Tuple([Some(inner)]) = expr.kind {
+            return self.expr_to(inner, to);
+        }
+        let values = self.expr(expr);
+        self.convert(values, from, to)
+    }
+
+    /// Evaluates a constant integer literal expression.
+    fn int_literal(&self, expr: &'gcx hir::Expr<'gcx>) -> Option<LitInt> {
+        match expr.kind {
+            hir::ExprKind::Lit(lit) => match lit.kind {
+                LitKind::Number(n) => Some(LitInt::from_u256(n)),
+                LitKind::Address(a) => Some(LitInt::from_u256(U256::from_be_slice(a.as_slice()))),
+                _ => None,
+            },
+            hir::ExprKind::Tuple([Some(inner)]) => self.int_literal(inner),
+            hir::ExprKind::Unary(op, inner) => {
+                let value = self.int_literal(inner)?;
+                match op.kind {
+                    hir::UnOpKind::Neg => Some(value.neg()),
+                    _ => None,
+                }
+            }
+            hir::ExprKind::Binary(lhs, op, rhs) => {
+                let lhs = self.int_literal(lhs)?;
+                let rhs = self.int_literal(rhs)?;
+                use hir::BinOpKind::*;
+                match op.kind {
+                    Add => lhs.add(rhs),
+                    Sub => lhs.add(rhs.neg()),
+                    Mul => lhs.mul(rhs),
+                    Div if !rhs.abs.is_zero() => {
+                        Some(LitInt::new(lhs.neg != rhs.neg, lhs.abs / rhs.abs))
+                    }
+                    Rem if !rhs.abs.is_zero() => Some(LitInt::new(lhs.neg, lhs.abs % rhs.abs)),
+                    Pow if !rhs.neg => {
+                        let exp: usize = rhs.abs.try_into().ok()?;
+                        let mut result = LitInt::from_u256(U256::from(1));
+                        for _ in 0..exp.min(512) {
+                            result = result.mul(lhs)?;
+                        }
+                        (exp <= 512 || lhs.abs <= U512::from(1)).then_some(result)
+                    }
+                    Shl if !lhs.neg && !rhs.neg => {
+                        let shift: usize = rhs.abs.try_into().ok()?;
+                        let result = lhs.abs.checked_shl(shift)?;
+                        (result >> shift == lhs.abs).then(|| LitInt::new(false, result))
+                    }
+                    Shr if !lhs.neg && !rhs.neg => {
+                        let shift: usize = rhs.abs.try_into().unwrap_or(usize::MAX);
+                        Some(LitInt::new(false, lhs.abs.checked_shr(shift).unwrap_or_default()))
+                    }
+                    BitAnd if !lhs.neg && !rhs.neg => Some(LitInt::new(false, lhs.abs & rhs.abs)),
+                    BitOr if !lhs.neg && !rhs.neg => Some(LitInt::new(false, lhs.abs | rhs.abs)),
+                    BitXor if !lhs.neg && !rhs.neg => Some(LitInt::new(false, lhs.abs ^ rhs.abs)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts values of type `from` to type `to`.
+    pub(crate) fn convert(
+        &mut self,
+        values: Vec<String>,
+        from: Ty<'gcx>,
+        to: Ty<'gcx>,
+    ) -> Vec<String> {
+        if from == to || values.is_empty() {
+            return values;
+        }
+        let gcx = self.cx.gcx;
+        match (from.kind, to.kind) {
+            (TyKind::Udvt(inner, _), _) => return self.convert(values, inner, to),
+            (_, TyKind::Udvt(inner, _)) => return self.convert(values, from, inner),
+            (TyKind::IntLiteral(..) | TyKind::StringLiteral(..), _) => {
+                let mobile = from.mobile(gcx).unwrap_or(from);
+                return self.convert(values, mobile, to);
+            }
+            (TyKind::Tuple(froms), TyKind::Tuple(tos)) => {
+                let mut values = values.into_iter();
+                let mut converted = Vec::new();
+                for (&from, &to) in froms.iter().zip(tos) {
+                    let component = values.by_ref().take(utils::stack_size(from)).collect();
+                    converted.extend(self.convert(component, from, to));
+                }
+                return converted;
+            }
+            (TyKind::Ref(inner, from_loc), TyKind::Ref(_, to_loc)) => {
+                use DataLocation::*;
+                return match (from_loc, to_loc) {
+                    (Storage | Transient, Memory) => {
+                        let f = self.cx.storage_to_memory_fn(inner);
+                        vec![self.bind(format!("{f}({})", values[0]))]
+                    }
+                    (Calldata, Memory) => {
+                        if !self.cx.is_calldata_copyable(from) {
+                            self.cx.unsupported(self.span, "copying this type from calldata");
+                            return vec!["0".into()];
+                        }
+                        let f = self.cx.calldata_to_memory_fn(inner);
+                        vec![self.bind(format!("{f}({})", values.join(", ")))]
+                    }
+                    _ => values,
+                };
+            }
+            (TyKind::Array(..), TyKind::Ref(..)) => return values,
+            (TyKind::Slice(_), _) => {
+                self.cx.unsupported(self.span, "array slices");
+                return vec!["0".into(); utils::stack_size(to)];
+            }
+            _ => {}
+        }
+
+        let (Some(from_repr), Some(to_repr)) = (utils::repr(gcx, from), utils::repr(gcx, to))
+        else {
+            if utils::stack_size(to) == 0 {
+                return Vec::new();
+            }
+            if utils::stack_size(from) == utils::stack_size(to) {
+                return values;
+            }
+            self.cx.unsupported(self.span, "this type conversion");
+            return vec!["0".into(); utils::stack_size(to)];
+        };
+        let value = &values[0];
+        let is_int = |repr| matches!(repr, Repr::Unsigned(_) | Repr::Signed(_));
+        let converted = match (from_repr, to_repr) {
+            _ if from_repr == to_repr => value.clone(),
+            (Repr::FixedBytes(a), Repr::FixedBytes(b)) if b >= a => value.clone(),
+            (Repr::FixedBytes(a), to) if is_int(to) => {
+                if a == 32 {
+                    value.clone()
+                } else {
+                    format!("shr({}, {value})", 256 - a as u32 * 8)
+                }
+            }
+            (from, Repr::FixedBytes(b)) if is_int(from) => {
+                let value = utils::cleanup_expr(Repr::Unsigned(b as u16 * 8), value);
+                if b == 32 { value } else { format!("shl({}, {value})", 256 - b as u32 * 8) }
+            }
+            (Repr::Unsigned(a), Repr::Unsigned(b)) if b >= a => value.clone(),
+            (Repr::Signed(a), Repr::Signed(b)) if b >= a => value.clone(),
+            (Repr::Unsigned(a), Repr::Signed(b)) if b > a => value.clone(),
+            _ => utils::cleanup_expr(to_repr, value),
+        };
+        let converted = if let TyKind::Enum(id) = to.kind
+            && !matches!(from.kind, TyKind::Enum(_))
+        {
+            let name = format!("convert_to_{}", self.cx.type_id(to));
+            let variants = gcx.hir.enumm(id).variants.len();
+            let f = self.cx.helper(name.clone(), |cx| {
+                template!(
+                    "function <name>(value) -> converted {
+                        if iszero(lt(value, <variants>)) { <panic>() }
+                        converted := value
+                    }",
+                    name = name,
+                    variants = variants,
+                    panic = cx.panic_fn(0x21),
+                )
+            });
+            format!("{f}({converted})")
+        } else {
+            converted
+        };
+        vec![self.bind(converted)]
+    }
+
+    // --- Locations ---
+
+    /// Returns the location that the given expression refers to.
+    fn lvalue(&mut self, expr: &'gcx hir::Expr<'gcx>) -> LValue<'gcx> {
+        self.span = expr.span;
+        match expr.kind {
+            hir::ExprKind::Ident(_) => match self.cx.res_of(expr) {
+                Some(hir::Res::Item(hir::ItemId::Variable(id))) => self.variable(id),
+                Some(hir::Res::Item(hir::ItemId::Function(_))) => {
+                    self.cx.unsupported(expr.span, "function pointers");
+                    LValue::Rvalue(vec!["0".into()])
+                }
+                Some(hir::Res::Builtin(Builtin::This)) => LValue::Rvalue(vec!["address()".into()]),
+                _ => LValue::Rvalue(Vec::new()),
+            },
+            hir::ExprKind::Member(base, ident) => self.member(expr, base, ident),
+            hir::ExprKind::Index(base, Some(index)) => self.index(base, index),
+            hir::ExprKind::Tuple([Some(inner)]) => self.lvalue(inner),
+            hir::ExprKind::Tuple(exprs) => {
+                let components = exprs
+                    .iter()
+                    .map(|component| component.map(|component| self.lvalue(component)))
+                    .collect();
+                LValue::Tuple(components)
+            }
+            _ => {
+                let ty = self.cx.ty_of(expr);
+                let values = self.expr_kind(expr, ty);
+                debug_assert_eq!(
+                    values.len(),
+                    utils::stack_size(ty),
+                    "invalid number of values for {ty:?}: {expr:?}",
+                );
+                LValue::Rvalue(values)
+            }
+        }
+    }
+
+    /// Writes already converted values to a variable.
+    pub(crate) fn assign_var(&mut self, id: hir::VariableId, values: &[String]) {
+        let lvalue = self.variable(id);
+        self.write(&lvalue, values);
+    }
+
+    /// Returns the location of a variable.
+    fn variable(&mut self, id: hir::VariableId) -> LValue<'gcx> {
+        let gcx = self.cx.gcx;
+        let var = gcx.hir.variable(id);
+        let ty = gcx.type_of_item(id.into());
+        if !var.is_state_variable() {
+            return LValue::Stack(utils::var_names(gcx, id, ty), ty);
+        }
+        if var.is_constant() {
+            let f = self.cx.constant_function(id);
+            let values = self.bind_call(format!("{f}()"), utils::stack_size(ty));
+            return LValue::Rvalue(values);
+        }
+        if var.is_immutable() {
+            return LValue::Immutable { var: id, ty };
+        }
+        if var.data_location == Some(DataLocation::Transient) {
+            self.cx.unsupported(var.span, "transient storage");
+            return LValue::Rvalue(vec!["0".into(); utils::stack_size(ty)]);
+        }
+        let layout = gcx.storage_layout(self.cx.contract);
+        let item = layout.items.iter().find(|item| item.var == id).expect("variable not in layout");
+        LValue::Storage { slot: num(item.slot), offset: Offset::Static(item.offset), ty }
+    }
+
+    /// Returns the location of a member access expression.
+    fn member(
+        &mut self,
+        expr: &'gcx hir::Expr<'gcx>,
+        base: &'gcx hir::Expr<'gcx>,
+        ident: Ident,
+    ) -> LValue<'gcx> {
+        let gcx = self.cx.gcx;
+        match self.cx.res_of(expr) {
+            Some(hir::Res::Builtin(builtin)) => {
+                let values = self.builtin_member(builtin, base, expr);
+                return LValue::Rvalue(values);
+            }
+            Some(hir::Res::Item(hir::ItemId::Function(_))) => {
+                self.cx.unsupported(expr.span, "function pointers");
+                return LValue::Rvalue(vec!["0".into(); 2]);
+            }
+            Some(hir::Res::Item(hir::ItemId::Variable(id))) => return self.variable(id),
+            _ => {}
+        }
+        let base_ty = self.cx.ty_of(base);
+        match base_ty.kind {
+            TyKind::Type(ty) if let TyKind::Enum(id) = ty.kind => {
+                let variants = gcx.hir.enumm(id).variants;
+                let index = variants.iter().position(|v| v.name == ident.name).unwrap();
+                LValue::Rvalue(vec![index.to_string()])
+            }
+            TyKind::Ref(inner, loc) if let TyKind::Struct(id) = inner.kind => {
+                let fields = gcx.hir.strukt(id).fields;
+                let index = fields
+                    .iter()
+                    .position(|&f| gcx.item_name(f).name == ident.name)
+                    .expect("struct field not found");
+                let field_ty = gcx.struct_field_types(id)[index];
+                let base = self.expr(base).remove(0);
+                match loc {
+                    DataLocation::Storage | DataLocation::Transient => {
+                        let item = gcx.struct_storage_layout(id).items[index];
+                        let slot = self.bind(format!("add({base}, {})", num(item.slot)));
+                        let ty = field_ty.with_loc_if_ref(gcx, DataLocation::Storage);
+                        LValue::Storage { slot, offset: Offset::Static(item.offset), ty }
+                    }
+                    DataLocation::Memory => {
+                        let word = memory_field_index(gcx, id, index);
+                        let ptr = self.bind(format!("add({base}, {})", word * 32));
+                        LValue::Memory { ptr, ty: field_ty.with_loc_if_ref(gcx, loc) }
+                    }
+                    DataLocation::Calldata => {
+                        if !field_ty.is_value_type() || !self.cx.is_calldata_copyable(base_ty) {
+                            self.cx.unsupported(expr.span, "this calldata struct access");
+                            return LValue::Rvalue(vec!["0".into(); utils::stack_size(field_ty)]);
+                        }
+                        let value = self.bind(format!("calldataload(add({base}, {}))", index * 32));
+                        let validator = self.cx.validator_fn(field_ty);
+                        self.line(format_args!("{validator}({value})"));
+                        LValue::Rvalue(vec![value])
+                    }
+                }
+            }
+            _ => {
+                self.cx.unsupported(expr.span, "this member access");
+                let ty = self.cx.ty_of(expr);
+                LValue::Rvalue(vec!["0".into(); utils::stack_size(ty)])
+            }
+        }
+    }
+
+    /// Returns the location of an index access expression.
+    fn index(&mut self, base: &'gcx hir::Expr<'gcx>, index: &'gcx hir::Expr<'gcx>) -> LValue<'gcx> {
+        let gcx = self.cx.gcx;
+        let base_ty = self.cx.ty_of(base);
+        let loc = base_ty.loc();
+        let inner = base_ty.peel_refs();
+        let uint256 = gcx.types.uint(256);
+        match (inner.kind, loc) {
+            (TyKind::Mapping(key, value), _) => {
+                let slot = self.expr(base).remove(0);
+                let value_ty = value.with_loc_if_ref(gcx, DataLocation::Storage);
+                let data_slot = if key.is_reference_type() {
+                    let key_ty = key.with_loc(gcx, DataLocation::Memory);
+                    let key = self.expr_to(index, key_ty).remove(0);
+                    let f = self.cx.mapping_index_bytes_fn(key_ty);
+                    self.bind(format!("{f}({slot}, {key})"))
+                } else {
+                    let key_value = self.expr_to(index, key).remove(0);
+                    let f = self.cx.mapping_index_fn(key);
+                    self.bind(format!("{f}({slot}, {key_value})"))
+                };
+                LValue::Storage { slot: data_slot, offset: Offset::Static(0), ty: value_ty }
+            }
+            (
+                TyKind::Array(elem, _) | TyKind::DynArray(elem),
+                Some(DataLocation::Storage | DataLocation::Transient),
+            ) => {
+                let array = self.expr(base).remove(0);
+                let index = self.expr_to(index, uint256).remove(0);
+                let f = self.cx.storage_index_fn(inner);
+                let [slot, offset] = [self.tmp(), self.tmp()];
+                self.line(format_args!("let {slot}, {offset} := {f}({array}, {index})"));
+                let ty = elem.with_loc_if_ref(gcx, DataLocation::Storage);
+                LValue::Storage { slot, offset: Offset::Dynamic(offset), ty }
+            }
+            (TyKind::Array(elem, _) | TyKind::DynArray(elem), Some(DataLocation::Memory)) => {
+                let array = self.expr(base).remove(0);
+                let index = self.expr_to(index, uint256).remove(0);
+                let (length, data) = match inner.kind {
+                    TyKind::Array(_, len) => (num(len), array),
+                    _ => (format!("mload({array})"), format!("add({array}, 32)")),
+                };
+                let panic = self.cx.panic_fn(0x32);
+                self.line(format_args!("if iszero(lt({index}, {length})) {{ {panic}() }}"));
+                let ptr = self.bind(format!("add({data}, mul({index}, 32))"));
+                LValue::Memory { ptr, ty: elem.with_loc_if_ref(gcx, DataLocation::Memory) }
+            }
+            (TyKind::Elementary(ElementaryType::Bytes), Some(DataLocation::Memory)) => {
+                let array = self.expr(base).remove(0);
+                let index = self.expr_to(index, uint256).remove(0);
+                let panic = self.cx.panic_fn(0x32);
+                self.line(format_args!("if iszero(lt({index}, mload({array}))) {{ {panic}() }}"));
+                let ptr = self.bind(format!("add(add({array}, 32), {index})"));
+                LValue::MemoryByte { ptr }
+            }
+            (TyKind::Elementary(ElementaryType::Bytes), Some(DataLocation::Calldata)) => {
+                let array = self.expr(base);
+                let index = self.expr_to(index, uint256).remove(0);
+                let panic = self.cx.panic_fn(0x32);
+                self.line(format_args!("if iszero(lt({index}, {})) {{ {panic}() }}", array[1]));
+                let mask = num(utils::high_mask(1));
+                let value = format!("and(calldataload(add({}, {index})), {mask})", array[0]);
+                LValue::Rvalue(vec![self.bind(value)])
+            }
+            (TyKind::Array(elem, _) | TyKind::DynArray(elem), Some(DataLocation::Calldata))
+                if elem.is_value_type() =>
+            {
+                let array = self.expr(base);
+                let index = self.expr_to(index, uint256).remove(0);
+                let length = match inner.kind {
+                    TyKind::Array(_, len) => num(len),
+                    _ => array[1].clone(),
+                };
+                let panic = self.cx.panic_fn(0x32);
+                self.line(format_args!("if iszero(lt({index}, {length})) {{ {panic}() }}"));
+                let value = self.bind(format!("calldataload(add({}, mul({index}, 32)))", array[0]));
+                let validator = self.cx.validator_fn(elem);
+                self.line(format_args!("{validator}({value})"));
+                LValue::Rvalue(vec![value])
+            }
+            (TyKind::Elementary(ElementaryType::FixedBytes(size)), None) => {
+                let value = self.expr(base).remove(0);
+                let index = self.expr_to(index, uint256).remove(0);
+                let panic = self.cx.panic_fn(0x32);
+                self.line(format_args!("if iszero(lt({index}, {})) {{ {panic}() }}", size.bytes()));
+                let mask = num(utils::high_mask(1));
+                let byte = format!("and(shl(mul({index}, 8), {value}), {mask})");
+                LValue::Rvalue(vec![self.bind(byte)])
+            }
+            _ => {
+                self.cx.unsupported(base.span, "this index access");
+                LValue::Rvalue(vec!["0".into()])
+            }
+        }
+    }
+
+    /// Reads the value at the given location.
+    fn read(&mut self, lvalue: LValue<'gcx>) -> Vec<String> {
+        match lvalue {
+            LValue::Stack(names, _) => {
+                // Copy the values so that later assignments don't change them.
+                names
+                    .into_iter()
+                    .map(|name| {
+                        let tmp = self.tmp();
+                        self.line(format_args!("let {tmp} := {name}"));
+                        tmp
+                    })
+                    .collect()
+            }
+            LValue::Storage { slot, offset, ty } => {
+                if !ty.is_value_type() {
+                    return vec![slot];
+                }
+                let value = match offset {
+                    Offset::Static(offset) => {
+                        let f = self.cx.storage_read_fn(ty, Some(offset));
+                        format!("{f}({slot})")
+                    }
+                    Offset::Dynamic(offset) => {
+                        let f = self.cx.storage_read_fn(ty, None);
+                        format!("{f}({slot}, {offset})")
+                    }
+                };
+                vec![self.bind(value)]
+            }
+            LValue::Memory { ptr, .. } => vec![self.bind(format!("mload({ptr})"))],
+            LValue::MemoryByte { ptr } => {
+                let mask = num(utils::high_mask(1));
+                vec![self.bind(format!("and(mload({ptr}), {mask})"))]
+            }
+            LValue::Immutable { var, .. } => {
+                let value = if self.cx.creation {
+                    format!("mload({})", self.cx.immutable_offset(var))
+                } else {
+                    format!("loadimmutable(\"{}\")", var.index())
+                };
+                vec![self.bind(value)]
+            }
+            LValue::Tuple(components) => {
+                components.into_iter().flatten().flat_map(|lv| self.read(lv)).collect()
+            }
+            LValue::Rvalue(values) => values,
+        }
+    }
+
+    /// Returns the type of the values that must be written to the given location.
+    fn lvalue_ty(&self, lvalue: &LValue<'gcx>) -> Option<Ty<'gcx>> {
+        let gcx = self.cx.gcx;
+        match *lvalue {
+            LValue::Stack(_, ty) | LValue::Immutable { ty, .. } | LValue::Memory { ty, .. } => {
+                Some(ty)
+            }
+            LValue::Storage { ty, .. } => Some(if ty.is_value_type() {
+                ty
+            } else {
+                ty.with_loc_if_ref(gcx, DataLocation::Memory)
+            }),
+            LValue::MemoryByte { .. } => Some(gcx.types.fixed_bytes(1)),
+            LValue::Tuple(ref components) => {
+                let tys = components
+                    .iter()
+                    .map(|c| c.as_ref().and_then(|c| self.lvalue_ty(c)).unwrap_or(gcx.types.unit))
+                    .collect::<Vec<_>>();
+                Some(gcx.mk_ty_tuple(gcx.mk_tys(&tys)))
+            }
+            LValue::Rvalue(_) => None,
+        }
+    }
+
+    /// Writes `values`, already converted to [`Self::lvalue_ty`], to the given location.
+    fn write(&mut self, lvalue: &LValue<'gcx>, values: &[String]) {
+        match lvalue {
+            LValue::Stack(names, _) => {
+                for (name, value) in names.iter().zip(values) {
+                    self.line(format_args!("{name} := {value}"));
+                }
+            }
+            LValue::Storage { slot, offset, ty } => {
+                let ty = *ty;
+                if let TyKind::Mapping(..) = ty.peel_refs().kind {
+                    return;
+                }
+                if !ty.is_value_type() {
+                    let f = self.cx.memory_to_storage_fn(ty);
+                    self.line(format_args!("{f}({slot}, {})", values[0]));
+                    return;
+                }
+                match offset {
+                    Offset::Static(offset) => {
+                        let f = self.cx.storage_write_fn(ty, Some(*offset));
+                        self.line(format_args!("{f}({slot}, {})", values[0]));
+                    }
+                    Offset::Dynamic(offset) => {
+                        let f = self.cx.storage_write_fn(ty, None);
+                        self.line(format_args!("{f}({slot}, {offset}, {})", values[0]));
+                    }
+                }
+            }
+            LValue::Memory { ptr, .. } => self.line(format_args!("mstore({ptr}, {})", values[0])),
+            LValue::MemoryByte { ptr } => {
+                self.line(format_args!("mstore8({ptr}, byte(0, {}))", values[0]));
+            }
+            LValue::Immutable { var, .. } => {
+                if self.cx.creation {
+                    let offset = self.cx.immutable_offset(*var);
+                    self.line(format_args!("mstore({offset}, {})", values[0]));
+                } else {
+                    self.cx
+                        .unsupported(self.span, "assigning immutables outside of the constructor");
+                }
+            }
+            LValue::Tuple(components) => {
+                let mut values = values.iter();
+                for component in components.iter().flatten() {
+                    let n = self.lvalue_ty(component).map_or(0, utils::stack_size);
+                    let component_values: Vec<_> = values.by_ref().take(n).cloned().collect();
+                    self.write(component, &component_values);
+                }
+            }
+            LValue::Rvalue(_) => unreachable!("cannot assign to rvalue"),
+        }
+    }
+
+    // --- Expressions ---
+
+    /// Evaluates an expression that is not a location.
+    fn expr_kind(&mut self, expr: &'gcx hir::Expr<'gcx>, ty: Ty<'gcx>) -> Vec<String> {
+        let gcx = self.cx.gcx;
+        match expr.kind {
+            hir::ExprKind::Lit(lit) => match lit.kind {
+                LitKind::Bool(b) => vec![(b as u8).to_string()],
+                LitKind::Address(a) => vec![num(U256::from_be_slice(a.as_slice()))],
+                _ => self.expr_to(expr, ty),
+            },
+            hir::ExprKind::Array(exprs) => {
+                let TyKind::Array(elem, _) = ty.peel_refs().kind else { unreachable!() };
+                let elem = elem.with_loc_if_ref(gcx, DataLocation::Memory);
+                let values: Vec<_> =
+                    exprs.iter().map(|e| self.expr_to(e, elem).remove(0)).collect();
+                let allocate = self.cx.allocate_memory_fn();
+                let ptr = self.bind(format!("{allocate}({})", values.len() * 32));
+                for (i, value) in values.iter().enumerate() {
+                    self.line(format_args!("mstore(add({ptr}, {}), {value})", i * 32));
+                }
+                vec![ptr]
+            }
+            hir::ExprKind::Assign(lhs, op, rhs) => self.assign(lhs, op, rhs),
+            hir::ExprKind::Binary(lhs, op, rhs) => self.binary(expr, lhs, op, rhs, ty),
+            hir::ExprKind::Call(callee, ref args, opts) => self.call(expr, callee, args, opts, ty),
+            hir::ExprKind::Delete(inner) => {
+                let lvalue = self.lvalue(inner);
+                if let LValue::Storage { slot, ty, .. } = &lvalue
+                    && !ty.is_value_type()
+                {
+                    let clear = self.cx.clear_storage_element(*ty, slot, "0");
+                    self.line(clear);
+                    return Vec::new();
+                }
+                if let Some(lvalue_ty) = self.lvalue_ty(&lvalue) {
+                    let zero = self.cx.zero_value(lvalue_ty);
+                    let zero = self.bind_all(zero);
+                    self.write(&lvalue, &zero);
+                }
+                Vec::new()
+            }
+            hir::ExprKind::Payable(inner) => self.expr_to(inner, ty),
+            hir::ExprKind::Ternary(cond, then, else_) => {
+                let cond = self.expr(cond).remove(0);
+                let results: Vec<_> = (0..utils::stack_size(ty)).map(|_| self.tmp()).collect();
+                for result in &results {
+                    self.line(format_args!("let {result}"));
+                }
+                self.line(format_args!("switch {cond}"));
+                for (label, branch) in [("case 0", else_), ("default", then)] {
+                    self.line(label);
+                    self.line("{");
+                    let values = self.expr_to(branch, ty);
+                    for (result, value) in results.iter().zip(values) {
+                        self.line(format_args!("{result} := {value}"));
+                    }
+                    self.line("}");
+                }
+                results
+            }
+            hir::ExprKind::Tuple(exprs) => {
+                let mut values = Vec::new();
+                for component in exprs.iter().flatten() {
+                    values.extend(self.expr(component));
+                }
+                values
+            }
+            hir::ExprKind::Unary(op, inner) => self.unary(expr, op, inner, ty),
+            hir::ExprKind::Index(_, None)
+            | hir::ExprKind::Type(_)
+            | hir::ExprKind::TypeCall(_)
+            | hir::ExprKind::New(_) => Vec::new(),
+            hir::ExprKind::Slice(..) => {
+                self.cx.unsupported(expr.span, "array slices");
+                vec!["0".into(); utils::stack_size(ty)]
+            }
+            hir::ExprKind::Err(guar) => {
+                self.cx.set_error(guar);
+                vec!["0".into(); utils::stack_size(ty)]
+            }
+            hir::ExprKind::Ident(_)
+            | hir::ExprKind::Member(..)
+            | hir::ExprKind::Index(_, Some(_)) => unreachable!(),
+        }
+    }
+
+    fn assign(
+        &mut self,
+        lhs: &'gcx hir::Expr<'gcx>,
+        op: Option<hir::BinOp>,
+        rhs: &'gcx hir::Expr<'gcx>,
+    ) -> Vec<String> {
+        if let Some(op) = op {
+            let lvalue = self.lvalue(lhs);
+            let ty = self.lvalue_ty(&lvalue).unwrap();
+            let current = self.read(lvalue_ref(&lvalue));
+            let rhs_ty = self.cx.ty_of(rhs);
+            let rhs_value = if matches!(
+                op.kind,
+                hir::BinOpKind::Shl | hir::BinOpKind::Shr | hir::BinOpKind::Sar
+            ) {
+                let mobile = rhs_ty.mobile(self.cx.gcx).unwrap_or(rhs_ty);
+                self.expr_to(rhs, mobile).remove(0)
+            } else {
+                self.expr_to(rhs, ty).remove(0)
+            };
+            let rhs_ty = rhs_ty.mobile(self.cx.gcx).unwrap_or(rhs_ty);
+            let result = self.binary_op(op.kind, &current[0], &rhs_value, ty, rhs_ty);
+            self.write(&lvalue, std::slice::from_ref(&result));
+            return vec![result];
+        }
+        let lhs_ty = self.cx.ty_of(lhs);
+        // Evaluate the right hand side first, with the type it will be stored as.
+        let store_ty = match lhs_ty.kind {
+            TyKind::Ref(_, DataLocation::Storage) if !is_local_storage_pointer(self, lhs) => {
+                lhs_ty.with_loc(self.cx.gcx, DataLocation::Memory)
+            }
+            TyKind::Tuple(tys) => {
+                let tys: Vec<_> = tys
+                    .iter()
+                    .map(|&ty| match ty.kind {
+                        TyKind::Ref(_, DataLocation::Storage) => {
+                            ty.with_loc(self.cx.gcx, DataLocation::Memory)
+                        }
+                        _ => ty,
+                    })
+                    .collect();
+                self.cx.gcx.mk_ty_tuple(self.cx.gcx.mk_tys(&tys))
+            }
+            _ => lhs_ty,
+        };
+        let values = self.expr_to(rhs, store_ty);
+        let lvalue = self.lvalue(lhs);
+        self.write(&lvalue, &values);
+        values
+    }
+
+    fn unary(
+        &mut self,
+        expr: &'gcx hir::Expr<'gcx>,
+        op: hir::UnOp,
+        inner: &'gcx hir::Expr<'gcx>,
+        ty: Ty<'gcx>,
+    ) -> Vec<String> {
+        use hir::UnOpKind::*;
+        match op.kind {
+            PreInc | PreDec | PostInc | PostDec => {
+                let lvalue = self.lvalue(inner);
+                let old = self.read(lvalue_ref(&lvalue)).remove(0);
+                let bin_op = if matches!(op.kind, PreInc | PostInc) {
+                    hir::BinOpKind::Add
+                } else {
+                    hir::BinOpKind::Sub
+                };
+                let new = self.binary_op(bin_op, &old, "1", ty, ty);
+                self.write(&lvalue, std::slice::from_ref(&new));
+                vec![if matches!(op.kind, PreInc | PreDec) { new } else { old }]
+            }
+            Not => {
+                let value = self.expr(inner).remove(0);
+                vec![self.bind(format!("iszero({value})"))]
+            }
+            BitNot => {
+                let value = self.expr_to(inner, ty).remove(0);
+                let repr = self.cx.value_repr(ty);
+                vec![self.bind(utils::cleanup_expr(repr, &format!("not({value})")))]
+            }
+            Neg => {
+                if utils::repr(self.cx.gcx, ty).is_none() {
+                    self.cx.unsupported(expr.span, "this unary operator");
+                    return vec!["0".into()];
+                }
+                let value = self.expr_to(inner, ty).remove(0);
+                let result = self.binary_op(hir::BinOpKind::Sub, "0", &value, ty, ty);
+                vec![result]
+            }
+        }
+    }
+
+    fn binary(
+        &mut self,
+        expr: &'gcx hir::Expr<'gcx>,
+        lhs: &'gcx hir::Expr<'gcx>,
+        op: hir::BinOp,
+        rhs: &'gcx hir::Expr<'gcx>,
+        ty: Ty<'gcx>,
+    ) -> Vec<String> {
+        use hir::BinOpKind::*;
+        let gcx = self.cx.gcx;
+        let lhs_ty = self.cx.ty_of(lhs);
+        let rhs_ty = self.cx.ty_of(rhs);
+        if matches!(lhs_ty.peel_refs().kind, TyKind::Udvt(..))
+            || matches!(rhs_ty.peel_refs().kind, TyKind::Udvt(..))
+        {
+            self.cx.unsupported(expr.span, "user-defined operators");
+            return vec!["0".into()];
+        }
+        match op.kind {
+            And | Or => {
+                let result = self.tmp();
+                let lhs = self.expr(lhs).remove(0);
+                self.line(format_args!("let {result} := {lhs}"));
+                let cond =
+                    if op.kind == And { result.clone() } else { format!("iszero({result})") };
+                self.line(format_args!("if {cond}"));
+                self.line("{");
+                let rhs = self.expr(rhs).remove(0);
+                self.line(format_args!("{result} := {rhs}"));
+                self.line("}");
+                vec![result]
+            }
+            Lt | Le | Gt | Ge | Eq | Ne => {
+                let Some(common) = lhs_ty.common_type(rhs_ty, gcx) else {
+                    self.cx.unsupported(expr.span, "this comparison");
+                    return vec!["0".into()];
+                };
+                if utils::repr(gcx, common).is_none() {
+                    self.cx.unsupported(expr.span, "this comparison");
+                    return vec!["0".into()];
+                }
+                let lhs = self.expr_to(lhs, common).remove(0);
+                let rhs = self.expr_to(rhs, common).remove(0);
+                let signed = matches!(self.cx.value_repr(common), Repr::Signed(_));
+                let (lt, gt) = if signed { ("slt", "sgt") } else { ("lt", "gt") };
+                let result = match op.kind {
+                    Lt => format!("{lt}({lhs}, {rhs})"),
+                    Gt => format!("{gt}({lhs}, {rhs})"),
+                    Le => format!("iszero({gt}({lhs}, {rhs}))"),
+                    Ge => format!("iszero({lt}({lhs}, {rhs}))"),
+                    Eq => format!("eq({lhs}, {rhs})"),
+                    _ => format!("iszero(eq({lhs}, {rhs}))"),
+                };
+                vec![self.bind(result)]
+            }
+            _ => {
+                if utils::repr(gcx, ty).is_none() {
+                    self.cx.unsupported(expr.span, "this binary operator");
+                    return vec!["0".into()];
+                }
+                let lhs = self.expr_to(lhs, ty).remove(0);
+                let (rhs, rhs_ty) = if matches!(op.kind, Shl | Shr | Sar | Pow) {
+                    let mobile = rhs_ty.mobile(gcx).unwrap_or(rhs_ty);
+                    (self.expr_to(rhs, mobile).remove(0), mobile)
+                } else {
+                    (self.expr_to(rhs, ty).remove(0), ty)
+                };
+                vec![self.binary_op(op.kind, &lhs, &rhs, ty, rhs_ty)]
+            }
+        }
+    }
+
+    /// Applies an arithmetic or bitwise operator to two clean values.
+    fn binary_op(
+        &mut self,
+        op: hir::BinOpKind,
+        lhs: &str,
+        rhs: &str,
+        ty: Ty<'gcx>,
+        rhs_ty: Ty<'gcx>,
+    ) -> String {
+        use hir::BinOpKind::*;
+        let repr = self.cx.value_repr(ty);
+        let checked = !self.unchecked;
+        let result = match op {
+            Add | Sub | Mul | Div | Rem => {
+                let f = self.cx.arith_fn(op, ty, checked);
+                format!("{f}({lhs}, {rhs})")
+            }
+            Pow => {
+                let f = self.cx.exp_fn(ty, rhs_ty, checked);
+                format!("{f}({lhs}, {rhs})")
+            }
+            BitAnd => format!("and({lhs}, {rhs})"),
+            BitOr => format!("or({lhs}, {rhs})"),
+            BitXor => format!("xor({lhs}, {rhs})"),
+            Shl => utils::cleanup_expr(repr, &format!("shl({rhs}, {lhs})")),
+            Shr | Sar => match repr {
+                Repr::Signed(_) => format!("sar({rhs}, {lhs})"),
+                _ => utils::cleanup_expr(repr, &format!("shr({rhs}, {lhs})")),
+            },
+            _ => unreachable!("not an arithmetic operator: {op:?}"),
+        };
+        self.bind(result)
+    }
+}
+
+/// Returns a reading copy of an lvalue that refers to the same location.
+fn lvalue_ref<'gcx>(lvalue: &LValue<'gcx>) -> LValue<'gcx> {
+    match lvalue {
+        LValue::Stack(names, ty) => LValue::Stack(names.clone(), *ty),
+        LValue::Storage { slot, offset, ty } => LValue::Storage {
+            slot: slot.clone(),
+            offset: match offset {
+                Offset::Static(offset) => Offset::Static(*offset),
+                Offset::Dynamic(offset) => Offset::Dynamic(offset.clone()),
+            },
+            ty: *ty,
+        },
+        LValue::Memory { ptr, ty } => LValue::Memory { ptr: ptr.clone(), ty: *ty },
+        LValue::MemoryByte { ptr } => LValue::MemoryByte { ptr: ptr.clone() },
+        LValue::Immutable { var, ty } => LValue::Immutable { var: *var, ty: *ty },
+        LValue::Tuple(components) => {
+            LValue::Tuple(components.iter().map(|c| c.as_ref().map(lvalue_ref)).collect())
+        }
+        LValue::Rvalue(values) => LValue::Rvalue(values.clone()),
+    }
+}
+
+/// Returns `true` if the expression is a local variable with a storage pointer type.
+fn is_local_storage_pointer(fcx: &FunctionCx<'_, '_, '_>, expr: &hir::Expr<'_>) -> bool {
+    match fcx.cx.res_of(expr) {
+        Some(hir::Res::Item(hir::ItemId::Variable(id))) => {
+            !fcx.cx.gcx.hir.variable(id).is_state_variable()
+        }
+        _ => false,
+    }
+}
+
+/// Returns the index of the memory word of a struct field, skipping mappings.
+pub(crate) fn memory_field_index(
+    gcx: crate::ty::Gcx<'_>,
+    id: hir::StructId,
+    index: usize,
+) -> usize {
+    gcx.struct_field_types(id)[..index]
+        .iter()
+        .filter(|ty| !matches!(ty.peel_refs().kind, TyKind::Mapping(..)))
+        .count()
+}
+
+/// Returns the value of a string literal expression.
+pub(crate) fn string_literal(expr: &hir::Expr<'_>) -> Option<Vec<u8>> {
+    match expr.kind {
+        hir::ExprKind::Lit(lit) => match lit.kind {
+            LitKind::Str(StrKind::Str | StrKind::Unicode | StrKind::Hex, value, _) => {
+                Some(value.as_byte_str().to_vec())
+            }
+            _ => None,
+        },
+        hir::ExprKind::Tuple([Some(inner)]) => string_literal(inner),
+        _ => None,
+    }
+}
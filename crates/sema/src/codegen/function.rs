@@ -0,0 +1,438 @@
+//! Function and statement lowering.
+
+use super::{ObjectCx, utils, yul};
+use crate::{
+    hir,
+    ty::{Ty, TyKind},
+};
+use solar_interface::Span;
+use std::fmt::Write;
+
+/// The code generation context of a single Yul function.
+pub(crate) struct FunctionCx<'a, 'b, 'gcx> {
+    pub(crate) cx: &'b mut ObjectCx<'a, 'gcx>,
+    /// The body of the function being built.
+    code: String,
+    /// The counter for temporary variable names.
+    temps: usize,
+    /// Whether arithmetic is unchecked.
+    pub(crate) unchecked: bool,
+    /// The Yul return variables of the function.
+    returns: Vec<String>,
+    /// The types of the return parameters of the function.
+    return_types: Vec<Ty<'gcx>>,
+    /// The call that replaces the placeholder statement in modifiers.
+    placeholder: Option<String>,
+    /// The contract that the function is defined in, used for resolving `super`.
+    pub(crate) contract: Option<hir::ContractId>,
+    /// The span of the expression being lowered, for error reporting.
+    pub(crate) span: Span,
+}
+
+impl<'a, 'b, 'gcx> FunctionCx<'a, 'b, 'gcx> {
+    pub(crate) fn new(cx: &'b mut ObjectCx<'a, 'gcx>, contract: Option<hir::ContractId>) -> Self {
+        Self {
+            cx,
+            code: String::new(),
+            temps: 0,
+            unchecked: false,
+            returns: Vec::new(),
+            return_types: Vec::new(),
+            placeholder: None,
+            contract,
+            span: Span::DUMMY,
+        }
+    }
+
+    /// Returns the generated code.
+    pub(crate) fn into_code(self) -> String {
+        self.code
+    }
+
+    /// Appends a line of code.
+    pub(crate) fn line(&mut self, line: impl std::fmt::Display) {
+        let _ = writeln!(self.code, "{line}");
+    }
+
+    /// Returns a new temporary variable name.
+    pub(crate) fn tmp(&mut self) -> String {
+        let name = format!("_{}", self.temps);
+        self.temps += 1;
+        name
+    }
+
+    /// Binds an expression to a new variable, unless it is already an identifier or a literal.
+    pub(crate) fn bind(&mut self, expr: String) -> String {
+        if is_atom(&expr) {
+            return expr;
+        }
+        let name = self.tmp();
+        self.line(format_args!("let {name} := {expr}"));
+        name
+    }
+
+    /// Binds all the given expressions.
+    pub(crate) fn bind_all(&mut self, exprs: Vec<String>) -> Vec<String> {
+        exprs.into_iter().map(|expr| self.bind(expr)).collect()
+    }
+
+    /// Binds the result of a call returning `n` values.
+    pub(crate) fn bind_call(&mut self, call: String, n: usize) -> Vec<String> {
+        match n {
+            0 => {
+                self.line(call);
+                Vec::new()
+            }
+            1 => vec![self.bind(call)],
+            _ => {
+                let names: Vec<_> = (0..n).map(|_| self.tmp()).collect();
+                self.line(format_args!("let {} := {call}", names.join(", ")));
+                names
+            }
+        }
+    }
+
+    /// Declares `names` and initializes them with the zero value of `ty`.
+    fn declare_zero(&mut self, names: &[String], ty: Ty<'gcx>, declare: bool) {
+        let zero = self.cx.zero_value(ty);
+        for (name, zero) in names.iter().zip(zero) {
+            if declare {
+                self.line(format_args!("let {name} := {zero}"));
+            } else if zero != "0" {
+                self.line(format_args!("{name} := {zero}"));
+            }
+        }
+    }
+
+    // --- Statements ---
+
+    pub(crate) fn block(&mut self, block: &'gcx [hir::Stmt<'gcx>]) {
+        for stmt in block {
+            self.stmt(stmt);
+        }
+    }
+
+    fn nested_block(&mut self, block: &'gcx [hir::Stmt<'gcx>]) {
+        self.line("{");
+        self.block(block);
+        self.line("}");
+    }
+
+    fn nested_stmt(&mut self, stmt: &'gcx hir::Stmt<'gcx>) {
+        self.line("{");
+        self.stmt(stmt);
+        self.line("}");
+    }
+
+    pub(crate) fn stmt(&mut self, stmt: &'gcx hir::Stmt<'gcx>) {
+        match stmt.kind {
+            hir::StmtKind::DeclSingle(id) => {
+                let var = self.cx.gcx.hir.variable(id);
+                let ty = self.cx.gcx.type_of_item(id.into());
+                let names = utils::var_names(self.cx.gcx, id, ty);
+                if let Some(init) = var.initializer {
+                    let values = self.expr_to(init, ty);
+                    for (name, value) in names.iter().zip(values) {
+                        self.line(format_args!("let {name} := {value}"));
+                    }
+                } else {
+                    self.declare_zero(&names, ty, true);
+                }
+            }
+            hir::StmtKind::DeclMulti(vars, init) => {
+                let init_ty = self.cx.ty_of(init);
+                let values = self.expr(init);
+                let tys = tuple_types(init_ty);
+                let mut values = values.into_iter();
+                for (&var, ty) in vars.iter().zip(tys) {
+                    let component: Vec<_> = values.by_ref().take(utils::stack_size(ty)).collect();
+                    let Some(var) = var else { continue };
+                    let var_ty = self.cx.gcx.type_of_item(var.into());
+                    let converted = self.convert(component, ty, var_ty);
+                    let names = utils::var_names(self.cx.gcx, var, var_ty);
+                    for (name, value) in names.iter().zip(converted) {
+                        self.line(format_args!("let {name} := {value}"));
+                    }
+                }
+            }
+            hir::StmtKind::Block(block) => self.nested_block(block.stmts),
+            hir::StmtKind::UncheckedBlock(block) => {
+                let prev = std::mem::replace(&mut self.unchecked, true);
+                self.nested_block(block.stmts);
+                self.unchecked = prev;
+            }
+            hir::StmtKind::Emit(expr) => self.emit_event(expr),
+            hir::StmtKind::Revert(expr) => self.revert_error(expr),
+            hir::StmtKind::Return(expr) => {
+                if let Some(expr) = expr {
+                    self.return_values(expr);
+                }
+                self.line("leave");
+            }
+            hir::StmtKind::Break => self.line("break"),
+            hir::StmtKind::Continue => self.line("continue"),
+            hir::StmtKind::Loop(block, source) => self.loop_(block, source),
+            hir::StmtKind::If(cond, then, else_) => {
+                let cond = self.expr(cond).remove(0);
+                match else_ {
+                    Some(else_) => {
+                        self.line(format_args!("switch {cond}"));
+                        self.line("case 0");
+                        self.nested_stmt(else_);
+                        self.line("default");
+                        self.nested_stmt(then);
+                    }
+                    None => {
+                        self.line(format_args!("if {cond}"));
+                        self.nested_stmt(then);
+                    }
+                }
+            }
+            hir::StmtKind::Assembly(_) => {
+                self.cx.unsupported(stmt.span, "inline assembly");
+            }
+            hir::StmtKind::Try(_) => {
+                self.cx.unsupported(stmt.span, "`try` statements");
+            }
+            hir::StmtKind::Expr(expr) => {
+                let _ = self.expr(expr);
+            }
+            hir::StmtKind::Placeholder => {
+                let call = self.placeholder.clone().expect("placeholder outside of modifier");
+                if self.returns.is_empty() {
+                    self.line(call);
+                } else {
+                    self.line(format_args!("{} := {call}", self.returns.join(", ")));
+                }
+            }
+            hir::StmtKind::Err(guar) => self.cx.set_error(guar),
+        }
+    }
+
+    /// Assigns the values of a return expression to the return variables.
+    fn return_values(&mut self, expr: &'gcx hir::Expr<'gcx>) {
+        let from = self.cx.ty_of(expr);
+        let rets = std::mem::take(&mut self.returns);
+        let ret_tys = self.return_types.clone();
+        let values = if let [ret_ty] = ret_tys[..] {
+            self.expr_to(expr, ret_ty)
+        } else {
+            let values = self.expr(expr);
+            let mut values = values.into_iter();
+            let mut converted = Vec::new();
+            for (from, &to) in tuple_types(from).into_iter().zip(&ret_tys) {
+                let component: Vec<_> = values.by_ref().take(utils::stack_size(from)).collect();
+                converted.extend(self.convert(component, from, to));
+            }
+            converted
+        };
+        for (ret, value) in rets.iter().zip(values) {
+            self.line(format_args!("{ret} := {value}"));
+        }
+        self.returns = rets;
+    }
+
+    /// Lowers a desugared loop back into a Yul `for` loop.
+    fn loop_(&mut self, block: hir::Block<'gcx>, source: hir::LoopSource) {
+        match source {
+            hir::LoopSource::While | hir::LoopSource::For => {
+                // `[if (<cond>) <body> else break]` or `[<body>]`.
+                let (cond, mut body) = match block.stmts {
+                    [
+                        hir::Stmt {
+                            kind:
+                                hir::StmtKind::If(
+                                    cond,
+                                    body,
+                                    Some(hir::Stmt { kind: hir::StmtKind::Break, .. }),
+                                ),
+                            ..
+                        },
+                    ] => (Some(*cond), std::slice::from_ref(*body)),
+                    stmts => (None, stmts),
+                };
+                // `{ <body>; <next>; }`
+                let mut next = None;
+                if source == hir::LoopSource::For
+                    && let [hir::Stmt { kind: hir::StmtKind::Block(inner), .. }] = body
+                    && inner.span == block.span
+                    && let [stmt, hir::Stmt { kind: hir::StmtKind::Expr(expr), .. }] = inner.stmts
+                {
+                    body = std::slice::from_ref(stmt);
+                    next = Some(*expr);
+                }
+
+                let post = match next {
+                    Some(next) => self.in_block(|this| {
+                        let _ = this.expr(next);
+                    }),
+                    None => String::new(),
+                };
+                self.line(format_args!("for {{ }} 1 {{ {post} }}"));
+                self.line("{");
+                if let Some(cond) = cond {
+                    let cond = self.expr(cond).remove(0);
+                    self.line(format_args!("if iszero({cond}) {{ break }}"));
+                }
+                self.block(body);
+                self.line("}");
+            }
+            hir::LoopSource::DoWhile => {
+                // `[{ <body> }, if (<cond>) continue else break]`
+                let [body, hir::Stmt { kind: hir::StmtKind::If(cond, ..), .. }] = block.stmts
+                else {
+                    unreachable!("invalid do-while loop: {block:?}")
+                };
+                let first = self.tmp();
+                self.line(format_args!("for {{ let {first} := 1 }} 1 {{ {first} := 0 }}"));
+                self.line("{");
+                self.line(format_args!("if iszero({first})"));
+                self.line("{");
+                let cond = self.expr(cond).remove(0);
+                self.line(format_args!("if iszero({cond}) {{ break }}"));
+                self.line("}");
+                self.stmt(body);
+                self.line("}");
+            }
+        }
+    }
+
+    /// Runs `f` and returns the code it generated, instead of appending it.
+    fn in_block(&mut self, f: impl FnOnce(&mut Self)) -> String {
+        let prev = std::mem::take(&mut self.code);
+        f(self);
+        std::mem::replace(&mut self.code, prev)
+    }
+}
+
+/// Returns the component types of a tuple type, or the type itself.
+pub(crate) fn tuple_types<'gcx>(ty: Ty<'gcx>) -> Vec<Ty<'gcx>> {
+    match ty.kind {
+        TyKind::Tuple(tys) => tys.to_vec(),
+        _ => vec![ty],
+    }
+}
+
+/// Returns `true` if the expression is an identifier or a literal.
+fn is_atom(expr: &str) -> bool {
+    !expr.is_empty() && expr.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'$')
+}
+
+/// Generates the Yul function for an internal function, including its modifiers.
+pub(crate) fn generate_function(cx: &mut ObjectCx<'_, '_>, id: hir::FunctionId) {
+    let gcx = cx.gcx;
+    let f = gcx.hir.function(id);
+    let name = cx.function_name(id);
+    let params = param_names(cx, f.parameters);
+    let returns = param_names(cx, f.returns);
+    let Some(body) = f.body else {
+        cx.unsupported(f.span, "calling a function without implementation");
+        cx.add_function(yul::parse_function(&format!("function {name}() {{ }}")));
+        return;
+    };
+
+    // Base constructor calls are handled by the constructor code.
+    let modifiers: Vec<_> =
+        f.modifiers.iter().filter(|m| matches!(m.id, hir::ItemId::Function(_))).collect();
+
+    let inner_name = if modifiers.is_empty() { name.clone() } else { format!("{name}_inner") };
+    let mut next = inner_name.clone();
+    for (i, modifier) in modifiers.iter().enumerate().rev() {
+        let hir::ItemId::Function(modifier_id) = modifier.id else { unreachable!() };
+        let modifier_id = cx.resolve_virtual(modifier_id, None);
+        let m = gcx.hir.function(modifier_id);
+        let modifier_name = format!(
+            "modifier_{}_{i}_{}",
+            m.name.map(|n| n.to_string()).unwrap_or_default(),
+            id.index()
+        );
+        let mut fcx = FunctionCx::new(cx, f.contract);
+        fcx.returns = returns.clone();
+        fcx.return_types = f.returns.iter().map(|&r| gcx.type_of_item(r.into())).collect();
+        fcx.placeholder = Some(format!("{next}({})", params.join(", ")));
+        fcx.init_returns(f.returns);
+        let param_tys = gcx.item_parameter_types(modifier_id);
+        let args: Vec<_> = modifier.args.exprs().collect();
+        for ((&param, &param_ty), arg) in m.parameters.iter().zip(param_tys).zip(args) {
+            let values = fcx.expr_to(arg, param_ty);
+            let names = utils::var_names(gcx, param, param_ty);
+            for (name, value) in names.iter().zip(values) {
+                fcx.line(format_args!("let {name} := {value}"));
+            }
+        }
+        if let Some(body) = m.body {
+            fcx.block(body.stmts);
+        }
+        let code = fcx.into_code();
+        add_function(cx, &modifier_name, &params, &returns, &code);
+        next = modifier_name;
+    }
+    if !modifiers.is_empty() {
+        let call = format!("{next}({})", params.join(", "));
+        let code =
+            if returns.is_empty() { call } else { format!("{} := {call}", returns.join(", ")) };
+        add_function(cx, &name, &params, &returns, &code);
+    }
+
+    let mut fcx = FunctionCx::new(cx, f.contract);
+    fcx.returns = returns.clone();
+    fcx.return_types = f.returns.iter().map(|&r| gcx.type_of_item(r.into())).collect();
+    fcx.init_returns(f.returns);
+    fcx.block(body.stmts);
+    let code = fcx.into_code();
+    add_function(cx, &inner_name, &params, &returns, &code);
+}
+
+/// Generates the Yul function that returns the value of a constant.
+pub(crate) fn generate_constant(cx: &mut ObjectCx<'_, '_>, id: hir::VariableId) {
+    let gcx = cx.gcx;
+    let var = gcx.hir.variable(id);
+    let ty = gcx.type_of_item(id.into());
+    let name = cx.constant_function(id);
+    let returns: Vec<_> = (0..utils::stack_size(ty)).map(|i| format!("ret_{i}")).collect();
+    let mut fcx = FunctionCx::new(cx, var.contract);
+    if let Some(init) = var.initializer {
+        let values = fcx.expr_to(init, ty);
+        for (ret, value) in returns.iter().zip(values) {
+            fcx.line(format_args!("{ret} := {value}"));
+        }
+    }
+    let code = fcx.into_code();
+    add_function(cx, &name, &[], &returns, &code);
+}
+
+/// Returns the Yul names of the given parameters.
+pub(crate) fn param_names(cx: &ObjectCx<'_, '_>, params: &[hir::VariableId]) -> Vec<String> {
+    params
+        .iter()
+        .flat_map(|&param| {
+            let ty = cx.gcx.type_of_item(param.into());
+            utils::var_names(cx.gcx, param, ty)
+        })
+        .collect()
+}
+
+/// Parses and adds a function to the object.
+pub(crate) fn add_function(
+    cx: &mut ObjectCx<'_, '_>,
+    name: &str,
+    params: &[String],
+    returns: &[String],
+    body: &str,
+) {
+    let returns =
+        if returns.is_empty() { String::new() } else { format!("-> {}", returns.join(", ")) };
+    let src = format!("function {name}({}) {returns} {{\n{body}\n}}", params.join(", "));
+    cx.add_function(yul::parse_function(&src));
+}
+
+impl<'gcx> FunctionCx<'_, '_, 'gcx> {
+    /// Initializes the return variables that have a non-zero default value.
+    fn init_returns(&mut self, returns: &[hir::VariableId]) {
+        for &ret in returns {
+            let ty = self.cx.gcx.type_of_item(ret.into());
+            let names = utils::var_names(self.cx.gcx, ret, ty);
+            self.declare_zero(&names, ty, false);
+        }
+    }
+}
@@ -0,0 +1,421 @@
+//! Yul IR code generation.
+//!
+//! Lowers type-checked HIR contracts into a [Yul object tree](yul::Object), following the
+//! structure of the IR produced by solc's `--via-ir` pipeline: every contract is lowered into a
+//! creation object containing the constructor code, with the deployed code as its sub-object.
+//!
+//! Reference: <https://github.com/argotorg/solidity/tree/v0.8.30/libsolidity/codegen/ir>
+
+use crate::{
+    hir,
+    ty::{Gcx, Ty},
+    typeck::TypeckResults,
+};
+use solar_data_structures::map::FxHashSet;
+use solar_interface::{Span, diagnostics::ErrorGuaranteed};
+use std::collections::BTreeMap;
+
+mod abi;
+mod call;
+mod contract;
+pub(crate) mod evm;
+mod expr;
+mod function;
+mod utils;
+pub(crate) mod yul;
+
+/// Generates the Yul IR object of the given contract.
+///
+/// Returns `None` if the contract cannot be deployed, like interfaces and abstract contracts.
+pub(crate) fn generate<'gcx>(
+    gcx: Gcx<'gcx>,
+    results: &TypeckResults<'gcx>,
+    id: hir::ContractId,
+) -> Option<Result<yul::Object, ErrorGuaranteed>> {
+    let c = gcx.hir.contract(id);
+    if !c.can_be_deployed() || c.is_abstract() || c.linearization_failed() {
+        return None;
+    }
+    if !gcx.sess.opts.evm_version.has_bitwise_shifting() {
+        let msg = format!(
+            "the IR code generator requires an EVM version with bitwise shifting instructions, \
+             found `{}`",
+            gcx.sess.opts.evm_version
+        );
+        return Some(Err(gcx.dcx().err(msg).span(c.name.span).emit()));
+    }
+    Some(contract::creation_object(gcx, results, id))
+}
+
+/// The EVM bytecode of a contract.
+pub(crate) struct Bytecode {
+    /// The creation bytecode, which deploys the runtime bytecode.
+    pub(crate) creation: Vec<u8>,
+    /// The runtime bytecode, with zeroed immutables.
+    pub(crate) runtime: Vec<u8>,
+}
+
+/// Assembles the IR object of the given contract into EVM bytecode.
+pub(crate) fn bytecode(
+    gcx: Gcx<'_>,
+    id: hir::ContractId,
+    object: &yul::Object,
+) -> Result<Bytecode, ErrorGuaranteed> {
+    match evm::assemble(object, gcx.sess.opts.evm_version) {
+        Ok(assembled) => {
+            let runtime = assembled
+                .child(&format!("{}_deployed", object.name))
+                .map(|runtime| runtime.bytecode.clone())
+                .unwrap_or_default();
+            Ok(Bytecode { creation: assembled.bytecode, runtime })
+        }
+        Err(mut errors) => {
+            let span = gcx.hir.contract(id).name.span;
+            let mut seen = FxHashSet::default();
+            errors.retain(|e| seen.insert(e.clone()));
+            let mut guar = None;
+            for error in errors {
+                guar = Some(gcx.dcx().err(error).span(span).emit());
+            }
+            Err(guar.expect("no errors were returned"))
+        }
+    }
+}
+
+/// The code generation context of a single Yul object.
+pub(crate) struct ObjectCx<'a, 'gcx> {
+    pub(crate) gcx: Gcx<'gcx>,
+    pub(crate) results: &'a TypeckResults<'gcx>,
+    /// The most derived contract that is being compiled.
+    pub(crate) contract: hir::ContractId,
+    /// Whether this is the creation object. Immutables are kept in memory during creation.
+    pub(crate) creation: bool,
+    /// The functions of the object, by name. `None` while the function is being generated.
+    functions: BTreeMap<String, Option<yul::Function>>,
+    /// User functions that have been referenced but not generated yet.
+    queue: Vec<Job>,
+    /// Contracts created with `new`, which are included as sub-objects.
+    pub(crate) created: Vec<hir::ContractId>,
+    /// Set if any error was emitted.
+    guar: Result<(), ErrorGuaranteed>,
+}
+
+/// A user function to generate.
+#[derive(Clone, Copy)]
+enum Job {
+    /// An internal function, including its modifiers.
+    Function(hir::FunctionId),
+    /// A constant variable.
+    Constant(hir::VariableId),
+}
+
+impl<'a, 'gcx> ObjectCx<'a, 'gcx> {
+    pub(crate) fn new(
+        gcx: Gcx<'gcx>,
+        results: &'a TypeckResults<'gcx>,
+        contract: hir::ContractId,
+        creation: bool,
+    ) -> Self {
+        Self {
+            gcx,
+            results,
+            contract,
+            creation,
+            functions: BTreeMap::new(),
+            queue: Vec::new(),
+            created: Vec::new(),
+            guar: Ok(()),
+        }
+    }
+
+    /// Returns the type of the given expression.
+    #[track_caller]
+    pub(crate) fn ty_of(&self, expr: &hir::Expr<'_>) -> Ty<'gcx> {
+        match self.results.types.get(&expr.id) {
+            Some(&ty) => ty,
+            None => panic!("expression was not type checked: {expr:?}"),
+        }
+    }
+
+    /// Returns the resolution of the given identifier or member expression.
+    pub(crate) fn res_of(&self, expr: &hir::Expr<'_>) -> Option<hir::Res> {
+        self.results.res.get(&expr.id).copied()
+    }
+
+    /// Emits an error for a construct that is not supported yet.
+    pub(crate) fn unsupported(&mut self, span: Span, what: &str) -> ErrorGuaranteed {
+        let msg = format!("the IR code generator does not support {what} yet");
+        let guar = self.gcx.dcx().err(msg).span(span).emit();
+        self.guar = Err(guar);
+        guar
+    }
+
+    /// Records an error that was already emitted.
+    pub(crate) fn set_error(&mut self, guar: ErrorGuaranteed) {
+        self.guar = Err(guar);
+    }
+
+    /// Returns the name of a helper function, generating it with `f` if it does not exist yet.
+    ///
+    /// `f` returns the source code of the function, which must be named `name`.
+    pub(crate) fn helper(&mut self, name: String, f: impl FnOnce(&mut Self) -> String) -> String {
+        if !self.functions.contains_key(&name) {
+            self.functions.insert(name.clone(), None);
+            let src = f(self);
+            let function = yul::parse_function(&src);
+            debug_assert_eq!(function.name, name, "helper function name mismatch");
+            self.functions.insert(name.clone(), Some(function));
+        }
+        name
+    }
+
+    /// Adds an already built function to the object.
+    pub(crate) fn add_function(&mut self, function: yul::Function) {
+        let name = function.name.clone();
+        self.functions.insert(name, Some(function));
+    }
+
+    /// Returns the name of the Yul function for the given internal function, generating it later.
+    pub(crate) fn internal_function(&mut self, id: hir::FunctionId) -> String {
+        let name = self.function_name(id);
+        if !self.functions.contains_key(&name) {
+            self.functions.insert(name.clone(), None);
+            self.queue.push(Job::Function(id));
+        }
+        name
+    }
+
+    /// Returns the name of the Yul function that returns the value of the given constant.
+    pub(crate) fn constant_function(&mut self, id: hir::VariableId) -> String {
+        let name = format!("constant_{}", utils::var_name(self.gcx, id));
+        if !self.functions.contains_key(&name) {
+            self.functions.insert(name.clone(), None);
+            self.queue.push(Job::Constant(id));
+        }
+        name
+    }
+
+    /// Generates all the queued user functions.
+    pub(crate) fn generate_queued(&mut self) {
+        while let Some(job) = self.queue.pop() {
+            match job {
+                Job::Function(id) => function::generate_function(self, id),
+                Job::Constant(id) => function::generate_constant(self, id),
+            }
+        }
+    }
+
+    /// Returns the name of the Yul function for the given user function.
+    pub(crate) fn function_name(&self, id: hir::FunctionId) -> String {
+        let f = self.gcx.hir.function(id);
+        let name = match f.name {
+            Some(name) => name.to_string(),
+            None => f.kind.to_str().to_string(),
+        };
+        format!("fun_{name}_{}", id.index())
+    }
+
+    /// Resolves a virtual function or modifier call in the context of the most derived contract.
+    ///
+    /// If `after` is set, only the bases after the given contract in the linearization are
+    /// considered, which is used for `super` calls.
+    pub(crate) fn resolve_virtual(
+        &self,
+        id: hir::FunctionId,
+        after: Option<hir::ContractId>,
+    ) -> hir::FunctionId {
+        let f = self.gcx.hir.function(id);
+        if f.contract.is_none() || f.visibility == hir::Visibility::Private && after.is_none() {
+            return id;
+        }
+        let Some(name) = f.name else { return id };
+        let params = self.gcx.item_parameter_types(id);
+        let bases = self.gcx.hir.contract(self.contract).linearized_bases;
+        let skip = match after {
+            Some(after) => bases.iter().position(|&b| b == after).map_or(bases.len(), |i| i + 1),
+            None => 0,
+        };
+        for &base in &bases[skip..] {
+            for other_id in self.gcx.hir.contract(base).all_functions() {
+                let other = self.gcx.hir.function(other_id);
+                if other.kind == f.kind
+                    && other.name.is_some_and(|n| n.name == name.name)
+                    && (other.body.is_some() || other.gettee.is_some())
+                    && self.gcx.item_parameter_types(other_id) == params
+                {
+                    return other_id;
+                }
+            }
+        }
+        id
+    }
+
+    /// Builds the code block of the object from the given top-level statements and the generated
+    /// functions.
+    pub(crate) fn finish(
+        mut self,
+        mut stmts: Vec<yul::Stmt>,
+    ) -> Result<ObjectParts, ErrorGuaranteed> {
+        self.generate_queued();
+        self.guar?;
+        let functions = std::mem::take(&mut self.functions);
+        stmts.extend(
+            functions.into_values().map(|f| {
+                yul::Stmt::Function(f.expect("function was referenced but never generated"))
+            }),
+        );
+        let mut seen = FxHashSet::default();
+        let created = self.created.into_iter().filter(|id| seen.insert(*id)).collect();
+        Ok(ObjectParts { code: yul::Block::new(stmts), created })
+    }
+}
+
+/// The result of [`ObjectCx::finish`].
+pub(crate) struct ObjectParts {
+    pub(crate) code: yul::Block,
+    pub(crate) created: Vec<hir::ContractId>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::yul;
+    use crate::{Compiler, hir, ty::Gcx, typeck};
+    use solar_interface::{
+        ColorChoice, Session,
+        config::{CompilerOutput, EvmVersion, Opts},
+        source_map::FileName,
+    };
+    use std::path::PathBuf;
+
+    const SRC: &str = r#"
+        pragma solidity ^0.8.0;
+
+        library Math {
+            function max(uint256 a, uint256 b) internal pure returns (uint256) {
+                return a > b ? a : b;
+            }
+        }
+
+        contract Child {
+            uint256 public immutable x;
+            constructor(uint256 x_) { x = x_; }
+        }
+
+        contract C {
+            using Math for uint256;
+
+            enum State { Idle, Done }
+            struct S { uint128 a; bool b; }
+
+            mapping(address => uint256) public balances;
+            S public s;
+            State public state;
+            uint256[] public list;
+            string public name = "name";
+            event E(address indexed who, uint256 value);
+            error Err(uint256 value);
+
+            modifier nonZero(uint256 v) {
+                if (v == 0) revert Err(v);
+                _;
+            }
+
+            function run(uint256 a, uint256 b) external nonZero(a) returns (uint256 m, bytes32 h) {
+                m = a.max(b);
+                balances[msg.sender] += m;
+                list.push(m);
+                s.b = true;
+                h = keccak256(abi.encode(a, name));
+                emit E(msg.sender, m);
+                for (uint256 i; i < list.length; i++) {
+                    if (i > 3) break;
+                }
+            }
+
+            function create(uint256 v) external returns (Child) {
+                state = State.Done;
+                return new Child(v);
+            }
+
+            function get(Child c) external view returns (uint256) {
+                return c.x();
+            }
+
+            receive() external payable {}
+        }
+    "#;
+
+    /// Generates the IR object of contract `C` in [`SRC`] and passes it to `f`.
+    fn with_object<R: Send>(
+        mut opts: Opts,
+        f: impl FnOnce(Gcx<'_>, hir::ContractId, &yul::Object) -> R + Send,
+    ) -> (Compiler, R) {
+        opts.unstable.typeck = true;
+        let sess = Session::builder().with_buffer_emitter(ColorChoice::Never).opts(opts).build();
+        let mut compiler = Compiler::new(sess);
+        let r = compiler.enter_mut(|c| {
+            let mut pcx = c.parse();
+            let file = c.sess().source_map().new_source_file(PathBuf::from("test.sol"), SRC);
+            pcx.add_file(file.unwrap());
+            pcx.parse();
+            assert!(c.lower_asts().is_ok());
+            let gcx = c.gcx();
+            let results = typeck::check(gcx);
+            let id = gcx.hir.contract_ids().find(|&id| gcx.hir.contract(id).name.as_str() == "C");
+            let id = id.unwrap();
+            let object = super::generate(gcx, &results, id).unwrap().unwrap();
+            f(gcx, id, &object)
+        });
+        let errs = compiler.sess().dcx.emitted_errors().unwrap();
+        assert!(errs.is_ok(), "{errs:?}");
+        (compiler, r)
+    }
+
+    /// The generated IR must be valid Yul that can be parsed back.
+    #[test]
+    fn roundtrip() {
+        let opts = Opts { emit: vec![CompilerOutput::Ir], ..Default::default() };
+        let (compiler, objects) = with_object(opts, |_, _, object| object.to_string());
+
+        compiler.enter(|c| {
+            let sess = c.sess();
+            let arena = solar_ast::Arena::new();
+            let name = FileName::Custom("ir".into());
+            let mut parser =
+                solar_parse::Parser::from_source_code(sess, &arena, name, objects.clone()).unwrap();
+            let object = parser.parse_yul_file_object().map_err(|e| e.emit());
+            assert!(object.is_ok(), "{objects}");
+            let object = object.unwrap();
+            assert_eq!(object.name.value.as_str(), "C_2");
+            assert!(sess.dcx.has_errors().is_ok(), "{objects}");
+        });
+    }
+
+    /// The creation bytecode must contain the runtime bytecode, and must only use the
+    /// instructions of the target EVM version.
+    #[test]
+    fn bytecode() {
+        for (evm_version, push0) in [(EvmVersion::Paris, false), (EvmVersion::Shanghai, true)] {
+            let opts = Opts { emit: vec![CompilerOutput::Bin], evm_version, ..Default::default() };
+            let (_, bytecode) = with_object(opts, |gcx, id, object| {
+                super::bytecode(gcx, id, object).expect("failed to assemble")
+            });
+            let super::Bytecode { creation, runtime } = bytecode;
+            assert!(!runtime.is_empty());
+            assert!(creation.windows(runtime.len()).any(|w| w == runtime));
+
+            let mut has_push0 = false;
+            let mut pc = 0;
+            while pc < runtime.len() {
+                let opcode = runtime[pc];
+                has_push0 |= opcode == 0x5f;
+                pc +=
+                    1 + if (0x60..=0x7f).contains(&opcode) { (opcode - 0x5f) as usize } else { 0 };
+                if opcode == 0xfe {
+                    break;
+                }
+            }
+            assert_eq!(has_push0, push0, "{evm_version}");
+        }
+    }
+}
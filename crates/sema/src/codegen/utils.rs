@@ -0,0 +1,1157 @@
+//! Type helpers and the utility functions shared by the generated code.
+//!
+//! Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/codegen/YulUtilFunctions.cpp>
+
+use super::{ObjectCx, yul::template};
+use crate::{
+    hir,
+    ty::{Gcx, Ty, TyKind},
+};
+use alloy_primitives::U256;
+use solar_ast::{DataLocation, ElementaryType};
+
+/// `2**64`, the maximum length of arrays and the maximum memory pointer.
+pub(crate) const MAX_LEN: &str = "0xffffffffffffffff";
+
+/// Returns the name of the Yul variable for the given Solidity variable.
+pub(crate) fn var_name(gcx: Gcx<'_>, id: hir::VariableId) -> String {
+    match gcx.hir.variable(id).name {
+        Some(name) => format!("var_{name}_{}", id.index()),
+        None => format!("var_{}", id.index()),
+    }
+}
+
+/// Returns the names of the stack slots of the given variable.
+pub(crate) fn var_names<'gcx>(gcx: Gcx<'gcx>, id: hir::VariableId, ty: Ty<'gcx>) -> Vec<String> {
+    let name = var_name(gcx, id);
+    match stack_size(ty) {
+        1 => vec![name],
+        2 => vec![format!("{name}_offset"), format!("{name}_length")],
+        n => (0..n).map(|i| format!("{name}_{i}")).collect(),
+    }
+}
+
+/// Returns the number of stack slots that a value of the given type occupies.
+///
+/// Dynamic calldata arrays are represented by their offset and length, everything else by a
+/// single value, pointer or slot.
+pub(crate) fn stack_size(ty: Ty<'_>) -> usize {
+    match ty.kind {
+        TyKind::Ref(inner, DataLocation::Calldata) if inner.is_dynamically_sized() => 2,
+        TyKind::Slice(_) => 2,
+        TyKind::Tuple(tys) => tys.iter().map(|&ty| stack_size(ty)).sum(),
+        TyKind::FnPtr(f) if f.visibility == hir::Visibility::External => 2,
+        TyKind::Type(_)
+        | TyKind::Meta(_)
+        | TyKind::Module(_)
+        | TyKind::BuiltinModule(_)
+        | TyKind::Error(..)
+        | TyKind::Event(..)
+        | TyKind::Err(_) => 0,
+        _ => 1,
+    }
+}
+
+/// Returns a unique identifier for the given type, to be used in helper function names.
+///
+/// This is modeled after solc's type identifiers.
+pub(crate) fn type_id<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>) -> String {
+    let named =
+        |prefix: &str, name: solar_interface::Ident, id: usize| format!("t_{prefix}$_{name}_${id}");
+    match ty.kind {
+        TyKind::Elementary(ElementaryType::Address(true)) => "t_address_payable".to_string(),
+        TyKind::Elementary(ty) => {
+            let mut s = String::from("t_");
+            ty.write_abi_str(&mut s).unwrap();
+            s
+        }
+        TyKind::StringLiteral(..) => "t_stringliteral".to_string(),
+        TyKind::IntLiteral(..) => "t_rational".to_string(),
+        TyKind::Ref(inner, loc) => {
+            let loc = match loc {
+                DataLocation::Storage => "storage",
+                DataLocation::Transient => "transient",
+                DataLocation::Memory => "memory_ptr",
+                DataLocation::Calldata => "calldata_ptr",
+            };
+            format!("{}_{loc}", type_id(gcx, inner))
+        }
+        TyKind::DynArray(elem) => format!("t_array$_{}_$dyn", type_id(gcx, elem)),
+        TyKind::Array(elem, len) => format!("t_array$_{}_${len}", type_id(gcx, elem)),
+        TyKind::Slice(inner) => format!("{}_slice", type_id(gcx, inner)),
+        TyKind::Tuple(tys) => {
+            let tys = tys.iter().map(|&ty| type_id(gcx, ty)).collect::<Vec<_>>();
+            format!("t_tuple$_{}_$", tys.join("_$_"))
+        }
+        TyKind::Mapping(key, value) => {
+            format!("t_mapping$_{}_$_{}_$", type_id(gcx, key), type_id(gcx, value))
+        }
+        TyKind::FnPtr(f) => format!("t_function_{}", f.visibility),
+        TyKind::Contract(id) => named("contract", gcx.item_name(id), id.index()),
+        TyKind::Struct(id) => named("struct", gcx.item_name(id), id.index()),
+        TyKind::Enum(id) => named("enum", gcx.item_name(id), id.index()),
+        TyKind::Udvt(_, id) => named("userDefinedValueType", gcx.item_name(id), id.index()),
+        TyKind::Error(_, id) => named("error", gcx.item_name(id), id.index()),
+        TyKind::Event(_, id) => named("event", gcx.item_name(id), id.index()),
+        _ => "t_unknown".to_string(),
+    }
+}
+
+/// Returns a combined identifier for a list of types.
+pub(crate) fn types_id<'gcx>(gcx: Gcx<'gcx>, tys: &[Ty<'gcx>]) -> String {
+    tys.iter().map(|&ty| type_id(gcx, ty)).collect::<Vec<_>>().join("_")
+}
+
+/// The stack representation of a value type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Repr {
+    /// An unsigned integer of the given number of bits, right-aligned.
+    Unsigned(u16),
+    /// A signed integer of the given number of bits, sign-extended to 256 bits.
+    Signed(u16),
+    /// A fixed bytes value of the given number of bytes, left-aligned.
+    FixedBytes(u8),
+    /// A boolean, either `0` or `1`.
+    Bool,
+}
+
+impl Repr {
+    /// Returns the number of bytes that the value occupies.
+    pub(crate) fn bytes(self) -> u8 {
+        match self {
+            Self::Unsigned(bits) | Self::Signed(bits) => (bits / 8) as u8,
+            Self::FixedBytes(bytes) => bytes,
+            Self::Bool => 1,
+        }
+    }
+}
+
+/// Returns the stack representation of the given value type.
+pub(crate) fn repr<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>) -> Option<Repr> {
+    Some(match ty.kind {
+        TyKind::Elementary(ty) => match ty {
+            ElementaryType::Address(_) => Repr::Unsigned(160),
+            ElementaryType::Bool => Repr::Bool,
+            ElementaryType::Int(size) => Repr::Signed(size.bits()),
+            ElementaryType::UInt(size) => Repr::Unsigned(size.bits()),
+            ElementaryType::FixedBytes(size) => Repr::FixedBytes(size.bytes()),
+            _ => return None,
+        },
+        TyKind::Contract(_) => Repr::Unsigned(160),
+        TyKind::Enum(_) => Repr::Unsigned(gcx.storage_bytes(ty) as u16 * 8),
+        TyKind::Udvt(inner, _) => return repr(gcx, inner),
+        _ => return None,
+    })
+}
+
+/// Returns a mask of the lowest `bytes` bytes.
+pub(crate) fn low_mask(bytes: u8) -> U256 {
+    if bytes >= 32 { U256::MAX } else { (U256::from(1) << (bytes as usize * 8)) - U256::from(1) }
+}
+
+/// Returns a mask of the highest `bytes` bytes.
+pub(crate) fn high_mask(bytes: u8) -> U256 {
+    !(U256::MAX >> (bytes as usize * 8))
+}
+
+/// Formats a number like the Yul printer does.
+pub(crate) fn num(value: U256) -> String {
+    super::yul::Expr::Number(value).to_string()
+}
+
+/// Returns the cleanup expression of a value of the given representation.
+pub(crate) fn cleanup_expr(repr: Repr, value: &str) -> String {
+    match repr {
+        Repr::Unsigned(256) | Repr::Signed(256) | Repr::FixedBytes(32) => value.to_string(),
+        Repr::Unsigned(bits) => format!("and({value}, {})", num(low_mask((bits / 8) as u8))),
+        Repr::Signed(bits) => format!("signextend({}, {value})", bits / 8 - 1),
+        Repr::FixedBytes(bytes) => format!("and({value}, {})", num(high_mask(bytes))),
+        Repr::Bool => format!("iszero(iszero({value}))"),
+    }
+}
+
+impl<'gcx> ObjectCx<'_, 'gcx> {
+    pub(crate) fn type_id(&self, ty: Ty<'gcx>) -> String {
+        type_id(self.gcx, ty)
+    }
+
+    /// Returns the value representation of the given type, emitting an error if it is not a
+    /// supported value type.
+    pub(crate) fn value_repr(&mut self, ty: Ty<'gcx>) -> Repr {
+        repr(self.gcx, ty).unwrap_or(Repr::Unsigned(256))
+    }
+
+    // --- Reverts ---
+
+    /// `panic_error_<code>()`: reverts with `Panic(uint256)`.
+    pub(crate) fn panic_fn(&mut self, code: u8) -> String {
+        let name = format!("panic_error_{code:#04x}");
+        self.helper(name.clone(), |_| {
+            template!(
+                "function <name>() {
+                    mstore(0, shl(224, 0x4e487b71))
+                    mstore(4, <code>)
+                    revert(0, 0x24)
+                }",
+                name = name,
+                code = code,
+            )
+        })
+    }
+
+    /// `revert_forward()`: bubbles up the return data of the last call.
+    pub(crate) fn revert_forward_fn(&mut self) -> String {
+        self.helper("revert_forward".into(), |_| {
+            "function revert_forward() {
+                let pos := mload(64)
+                returndatacopy(pos, 0, returndatasize())
+                revert(pos, returndatasize())
+            }"
+            .into()
+        })
+    }
+
+    // --- Value types ---
+
+    /// `validator_revert_<ty>(value)`: reverts if the value is not clean.
+    pub(crate) fn validator_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let name = format!("validator_revert_{}", self.type_id(ty));
+        let check = if let TyKind::Enum(id) = ty.kind {
+            format!("lt(value, {})", self.gcx.hir.enumm(id).variants.len())
+        } else {
+            let repr = self.value_repr(ty);
+            format!("eq(value, {})", cleanup_expr(repr, "value"))
+        };
+        self.helper(name.clone(), |_| {
+            template!(
+                "function <name>(value) { if iszero(<check>) { revert(0, 0) } }",
+                name = name,
+                check = check,
+            )
+        })
+    }
+
+    // --- Arithmetic ---
+
+    /// Returns the minimum and maximum values of an integer representation, as two's complement.
+    pub(crate) fn int_bounds(repr: Repr) -> (U256, U256) {
+        match repr {
+            Repr::Unsigned(bits) => (U256::ZERO, low_mask((bits / 8) as u8)),
+            Repr::Signed(bits) => {
+                let max: U256 = low_mask((bits / 8) as u8) >> 1;
+                (!max, max)
+            }
+            _ => (U256::ZERO, U256::MAX),
+        }
+    }
+
+    /// `checked_<op>_<ty>(x, y) -> r` or `wrapping_<op>_<ty>(x, y) -> r`.
+    pub(crate) fn arith_fn(&mut self, op: hir::BinOpKind, ty: Ty<'gcx>, checked: bool) -> String {
+        use hir::BinOpKind::*;
+        let repr = self.value_repr(ty);
+        let op_name = match op {
+            Add => "add",
+            Sub => "sub",
+            Mul => "mul",
+            Div => "div",
+            Rem => "mod",
+            _ => unreachable!("not an arithmetic operator: {op:?}"),
+        };
+        // Division and modulo can only overflow for signed division.
+        let checked = checked || matches!(op, Div | Rem);
+        let prefix = if checked { "checked" } else { "wrapping" };
+        let name = format!("{prefix}_{op_name}_{}", self.type_id(ty));
+        let signed = matches!(repr, Repr::Signed(_));
+        let bits = match repr {
+            Repr::Unsigned(bits) | Repr::Signed(bits) => bits,
+            _ => 256,
+        };
+        let (min, max) = Self::int_bounds(repr);
+        let (min, max) = (num(min), num(max));
+        let cleanup = |v: &str| cleanup_expr(repr, v);
+        let overflow = self.panic_fn(0x11);
+        let body = match (op, checked) {
+            (Add | Sub | Mul, false) => {
+                format!("r := {}", cleanup(&format!("{op_name}(x, y)")))
+            }
+            (Add, true) => match (signed, bits) {
+                (false, 256) => format!("r := add(x, y) if gt(x, r) {{ {overflow}() }}"),
+                (true, 256) => format!(
+                    "r := add(x, y)
+                    if or(and(iszero(slt(x, 0)), slt(r, y)), and(slt(x, 0), iszero(slt(r, y)))) {{ {overflow}() }}"
+                ),
+                (false, _) => format!("r := add(x, y) if gt(r, {max}) {{ {overflow}() }}"),
+                (true, _) => format!(
+                    "r := add(x, y) if or(sgt(r, {max}), slt(r, {min})) {{ {overflow}() }}"
+                ),
+            },
+            (Sub, true) => match (signed, bits) {
+                (false, 256) => format!("r := sub(x, y) if gt(r, x) {{ {overflow}() }}"),
+                (true, 256) => format!(
+                    "r := sub(x, y)
+                    if or(and(iszero(slt(y, 0)), sgt(r, x)), and(slt(y, 0), slt(r, x))) {{ {overflow}() }}"
+                ),
+                (false, _) => format!("r := sub(x, y) if gt(r, {max}) {{ {overflow}() }}"),
+                (true, _) => format!(
+                    "r := sub(x, y) if or(sgt(r, {max}), slt(r, {min})) {{ {overflow}() }}"
+                ),
+            },
+            (Mul, true) => {
+                let mut body = String::from("r := mul(x, y)\n");
+                if signed {
+                    if bits > 128 {
+                        body += &format!(
+                            "if and(slt(x, 0), eq(y, {min})) {{ {overflow}() }}
+                            if iszero(or(iszero(x), eq(y, sdiv(r, x)))) {{ {overflow}() }}\n"
+                        );
+                    }
+                    if bits < 256 {
+                        body +=
+                            &format!("if or(sgt(r, {max}), slt(r, {min})) {{ {overflow}() }}\n");
+                    }
+                } else {
+                    if bits > 128 {
+                        body += &format!(
+                            "if iszero(or(iszero(x), eq(y, div(r, x)))) {{ {overflow}() }}\n"
+                        );
+                    }
+                    if bits < 256 {
+                        body += &format!("if gt(r, {max}) {{ {overflow}() }}\n");
+                    }
+                }
+                body
+            }
+            (Div | Rem, _) => {
+                let div_zero = self.panic_fn(0x12);
+                let mut body = format!("if iszero(y) {{ {div_zero}() }}\n");
+                if signed && op == Div {
+                    body += &format!(
+                        "if and(eq(x, {min}), eq(y, not(0))) {{ {overflow}() }}\n"
+                    );
+                }
+                let instr = match (op, signed) {
+                    (Div, false) => "div",
+                    (Div, true) => "sdiv",
+                    (_, false) => "mod",
+                    (_, true) => "smod",
+                };
+                body + &format!("r := {instr}(x, y)")
+            }
+            _ => unreachable!(),
+        };
+        self.helper(name.clone(), |_| {
+            template!("function <name>(x, y) -> r { <body> }", name = name, body = body)
+        })
+    }
+
+    /// `checked_exp_<base>_<exponent>(base, exponent) -> power`.
+    pub(crate) fn exp_fn(&mut self, ty: Ty<'gcx>, exp_ty: Ty<'gcx>, checked: bool) -> String {
+        let prefix = if checked { "checked" } else { "wrapping" };
+        let name = format!("{prefix}_exp_{}_{}", self.type_id(ty), self.type_id(exp_ty));
+        let repr = self.value_repr(ty);
+        let body = if checked {
+            let mul = self.arith_fn(hir::BinOpKind::Mul, ty, true);
+            template!(
+                "power := 1
+                for { } gt(exponent, 0) { } {
+                    if and(exponent, 1) { power := <mul>(power, base) }
+                    exponent := shr(1, exponent)
+                    if exponent { base := <mul>(base, base) }
+                }",
+                mul = mul,
+            )
+        } else {
+            format!("power := {}", cleanup_expr(repr, "exp(base, exponent)"))
+        };
+        self.helper(name.clone(), |_| {
+            template!(
+                "function <name>(base, exponent) -> power { <body> }",
+                name = name,
+                body = body,
+            )
+        })
+    }
+
+    // --- Memory ---
+
+    /// `allocate_unbounded() -> memPtr`.
+    pub(crate) fn allocate_unbounded_fn(&mut self) -> String {
+        self.helper("allocate_unbounded".into(), |_| {
+            "function allocate_unbounded() -> memPtr { memPtr := mload(64) }".into()
+        })
+    }
+
+    /// `round_up_to_mul_of_32(value) -> result`.
+    pub(crate) fn round_up_fn(&mut self) -> String {
+        self.helper("round_up_to_mul_of_32".into(), |_| {
+            "function round_up_to_mul_of_32(value) -> result {
+                result := and(add(value, 31), not(31))
+            }"
+            .into()
+        })
+    }
+
+    /// `finalize_allocation(memPtr, size)`: bumps the free memory pointer.
+    pub(crate) fn finalize_allocation_fn(&mut self) -> String {
+        self.helper("finalize_allocation".into(), |cx| {
+            template!(
+                "function finalize_allocation(memPtr, size) {
+                    let newFreePtr := add(memPtr, <round_up>(size))
+                    if or(gt(newFreePtr, <max>), lt(newFreePtr, memPtr)) { <panic>() }
+                    mstore(64, newFreePtr)
+                }",
+                round_up = cx.round_up_fn(),
+                max = MAX_LEN,
+                panic = cx.panic_fn(0x41),
+            )
+        })
+    }
+
+    /// `allocate_memory(size) -> memPtr`.
+    pub(crate) fn allocate_memory_fn(&mut self) -> String {
+        self.helper("allocate_memory".into(), |cx| {
+            template!(
+                "function allocate_memory(size) -> memPtr {
+                    memPtr := <unbounded>()
+                    <finalize>(memPtr, size)
+                }",
+                unbounded = cx.allocate_unbounded_fn(),
+                finalize = cx.finalize_allocation_fn(),
+            )
+        })
+    }
+
+    /// `copy_memory_to_memory_with_cleanup(src, dst, length)`.
+    pub(crate) fn copy_memory_fn(&mut self) -> String {
+        self.helper("copy_memory_to_memory_with_cleanup".into(), |_| {
+            "function copy_memory_to_memory_with_cleanup(src, dst, length) {
+                let i := 0
+                for { } lt(i, length) { i := add(i, 32) } {
+                    mstore(add(dst, i), mload(add(src, i)))
+                }
+                mstore(add(dst, length), 0)
+            }"
+            .into()
+        })
+    }
+
+    /// `copy_calldata_to_memory_with_cleanup(src, dst, length)`.
+    pub(crate) fn copy_calldata_fn(&mut self) -> String {
+        self.helper("copy_calldata_to_memory_with_cleanup".into(), |_| {
+            "function copy_calldata_to_memory_with_cleanup(src, dst, length) {
+                calldatacopy(dst, src, length)
+                mstore(add(dst, length), 0)
+            }"
+            .into()
+        })
+    }
+
+    /// `allocate_memory_array_<ty>(length) -> memPtr`: allocates an array and stores its length.
+    ///
+    /// The contents are not initialized.
+    pub(crate) fn allocate_array_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let ty = ty.peel_refs();
+        let name = format!("allocate_memory_array_{}", self.type_id(ty));
+        let is_bytes = matches!(ty.kind, TyKind::Elementary(_));
+        self.helper(name.clone(), |cx| {
+            let size = if is_bytes {
+                format!("add({}(length), 32)", cx.round_up_fn())
+            } else {
+                "add(mul(length, 32), 32)".to_string()
+            };
+            template!(
+                "function <name>(length) -> memPtr {
+                    if gt(length, <max>) { <panic>() }
+                    memPtr := <allocate>(<size>)
+                    mstore(memPtr, length)
+                }",
+                name = name,
+                max = MAX_LEN,
+                panic = cx.panic_fn(0x41),
+                allocate = cx.allocate_memory_fn(),
+                size = size,
+            )
+        })
+    }
+
+    /// Returns the zero value of the given type in memory, or on the stack for value types.
+    ///
+    /// Static arrays and structs are allocated, so the result must be bound before reuse.
+    pub(crate) fn zero_value(&mut self, ty: Ty<'gcx>) -> Vec<String> {
+        match ty.kind {
+            TyKind::Ref(inner, DataLocation::Memory) => match inner.kind {
+                TyKind::Array(..) | TyKind::Struct(_) => {
+                    vec![format!("{}()", self.allocate_zero_fn(inner))]
+                }
+                _ => vec!["96".into()],
+            },
+            TyKind::Ref(inner, DataLocation::Calldata) if inner.is_dynamically_sized() => {
+                vec!["calldatasize()".into(), "0".into()]
+            }
+            TyKind::Ref(..) if ty.peel_refs().is_dynamically_sized() => vec!["96".into()],
+            _ => vec!["0".into(); super::utils::stack_size(ty)],
+        }
+    }
+
+    /// `allocate_and_zero_memory_<ty>() -> memPtr` for static arrays and structs.
+    fn allocate_zero_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let name = format!("allocate_and_zero_memory_{}", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            let elems = memory_elements(cx.gcx, ty);
+            let mut body = format!("memPtr := {}({})\n", cx.allocate_memory_fn(), elems.len() * 32);
+            if elems.len() > 8 && elems.iter().all(|e| e.is_value_type()) {
+                body += &format!("calldatacopy(memPtr, calldatasize(), {})\n", elems.len() * 32);
+            } else {
+                for (i, elem) in elems.iter().enumerate() {
+                    let zero = cx.zero_value(elem.with_loc_if_ref(cx.gcx, DataLocation::Memory));
+                    body += &format!("mstore(add(memPtr, {}), {})\n", i * 32, zero[0]);
+                }
+            }
+            template!("function <name>() -> memPtr { <body> }", name = name, body = body)
+        })
+    }
+
+    /// `copy_literal_to_memory_<hash>() -> memPtr`: allocates a string literal.
+    pub(crate) fn string_literal_fn(&mut self, value: &[u8]) -> String {
+        let hash = alloy_primitives::keccak256(value);
+        let name = format!("copy_literal_to_memory_{}", alloy_primitives::hex::encode(&hash[..8]));
+        let value = value.to_vec();
+        self.helper(name.clone(), |cx| {
+            let mut body = format!(
+                "memPtr := {}({})\nmstore(memPtr, {})\n",
+                cx.allocate_array_fn(cx.gcx.types.bytes),
+                value.len(),
+                value.len()
+            );
+            for (i, chunk) in value.chunks(32).enumerate() {
+                body +=
+                    &format!("mstore(add(memPtr, {}), {})\n", 32 + i * 32, num(left_word(chunk)));
+            }
+            template!("function <name>() -> memPtr { <body> }", name = name, body = body)
+        })
+    }
+
+    // --- Storage ---
+
+    /// `read_from_storage_offset_<offset>_<ty>(slot) -> value` for value types.
+    pub(crate) fn storage_read_fn(&mut self, ty: Ty<'gcx>, offset: Option<u8>) -> String {
+        let repr = self.value_repr(ty);
+        let (name, params, shift) = match offset {
+            Some(offset) => (
+                format!("read_from_storage_offset_{offset}_{}", self.type_id(ty)),
+                "slot",
+                format!("shr({}, sload(slot))", offset as u32 * 8),
+            ),
+            None => (
+                format!("read_from_storage_dynamic_{}", self.type_id(ty)),
+                "slot, offset",
+                "shr(mul(offset, 8), sload(slot))".to_string(),
+            ),
+        };
+        let shift = if offset == Some(0) { "sload(slot)".to_string() } else { shift };
+        let body = from_storage_expr(repr, &shift);
+        self.helper(name.clone(), |_| {
+            template!(
+                "function <name>(<params>) -> value { value := <body> }",
+                name = name,
+                params = params,
+                body = body,
+            )
+        })
+    }
+
+    /// `update_storage_value_offset_<offset>_<ty>(slot, value)` for value types.
+    pub(crate) fn storage_write_fn(&mut self, ty: Ty<'gcx>, offset: Option<u8>) -> String {
+        let repr = self.value_repr(ty);
+        let bytes = repr.bytes();
+        let prepared = to_storage_expr(repr, "value");
+        let (name, params, body) = match offset {
+            Some(0) if bytes == 32 => (
+                format!("update_storage_value_offset_0_{}", self.type_id(ty)),
+                "slot, value",
+                format!("sstore(slot, {prepared})"),
+            ),
+            Some(offset) => {
+                let shift = offset as usize * 8;
+                let mask = num(low_mask(bytes) << shift);
+                (
+                    format!("update_storage_value_offset_{offset}_{}", self.type_id(ty)),
+                    "slot, value",
+                    format!(
+                        "let mask := {mask}
+                        let toInsert := shl({shift}, {prepared})
+                        sstore(slot, or(and(sload(slot), not(mask)), and(toInsert, mask)))"
+                    ),
+                )
+            }
+            None => (
+                format!("update_storage_value_{}", self.type_id(ty)),
+                "slot, offset, value",
+                format!(
+                    "let shiftBits := mul(offset, 8)
+                    let mask := shl(shiftBits, {})
+                    let toInsert := shl(shiftBits, {prepared})
+                    sstore(slot, or(and(sload(slot), not(mask)), and(toInsert, mask)))",
+                    num(low_mask(bytes))
+                ),
+            ),
+        };
+        self.helper(name.clone(), |_| {
+            template!(
+                "function <name>(<params>) { <body> }",
+                name = name,
+                params = params,
+                body = body,
+            )
+        })
+    }
+
+    /// `array_dataslot(slot) -> data`: the start of the data area of a dynamic storage array.
+    pub(crate) fn array_dataslot_fn(&mut self) -> String {
+        self.helper("array_dataslot".into(), |_| {
+            "function array_dataslot(slot) -> data {
+                mstore(0, slot)
+                data := keccak256(0, 0x20)
+            }"
+            .into()
+        })
+    }
+
+    /// `extract_byte_array_length(data) -> length`: the length of a storage byte array.
+    pub(crate) fn byte_array_length_fn(&mut self) -> String {
+        self.helper("extract_byte_array_length".into(), |cx| {
+            template!(
+                "function extract_byte_array_length(data) -> length {
+                    length := div(data, 2)
+                    let outOfPlaceEncoding := and(data, 1)
+                    if iszero(outOfPlaceEncoding) { length := and(length, 0x7f) }
+                    if eq(outOfPlaceEncoding, lt(length, 32)) { <panic>() }
+                }",
+                panic = cx.panic_fn(0x22),
+            )
+        })
+    }
+
+    /// `array_length_<ty>(slot) -> length` for storage arrays.
+    pub(crate) fn storage_array_length_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let ty = ty.peel_refs();
+        let name = format!("array_length_{}_storage", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            let body = match ty.kind {
+                TyKind::Array(_, len) => format!("length := {}", num(len)),
+                TyKind::DynArray(_) => "length := sload(slot)".to_string(),
+                _ => format!("length := {}(sload(slot))", cx.byte_array_length_fn()),
+            };
+            template!("function <name>(slot) -> length { <body> }", name = name, body = body)
+        })
+    }
+
+    /// `storage_array_index_access_<ty>(array, index) -> slot, offset`.
+    pub(crate) fn storage_index_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let ty = ty.peel_refs();
+        let elem = match ty.kind {
+            TyKind::Array(elem, _) | TyKind::DynArray(elem) => elem,
+            _ => unreachable!("not a storage array: {ty:?}"),
+        };
+        let name = format!("storage_array_index_access_{}", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            let length = cx.storage_array_length_fn(ty);
+            let data = if let TyKind::DynArray(_) = ty.kind {
+                format!("{}(array)", cx.array_dataslot_fn())
+            } else {
+                "array".to_string()
+            };
+            let elem_bytes = cx.gcx.storage_bytes(elem);
+            let access = if elem_bytes <= 16 {
+                let per_slot = 32 / elem_bytes;
+                format!(
+                    "slot := add(dataArea, div(index, {per_slot}))
+                    offset := mul(mod(index, {per_slot}), {elem_bytes})"
+                )
+            } else {
+                format!(
+                    "slot := add(dataArea, mul(index, {}))
+                    offset := 0",
+                    num(cx.gcx.storage_slots(elem))
+                )
+            };
+            template!(
+                "function <name>(array, index) -> slot, offset {
+                    if iszero(lt(index, <length>(array))) { <panic>() }
+                    let dataArea := <data>
+                    <access>
+                }",
+                name = name,
+                length = length,
+                panic = cx.panic_fn(0x32),
+                data = data,
+                access = access,
+            )
+        })
+    }
+
+    /// `array_push_zero_<ty>(array) -> slot, offset`: extends a dynamic storage array by one
+    /// element and returns its location.
+    pub(crate) fn storage_push_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let ty = ty.peel_refs();
+        let name = format!("array_push_zero_{}", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            template!(
+                "function <name>(array) -> slot, offset {
+                    let oldLen := sload(array)
+                    if iszero(lt(oldLen, 0x10000000000000000)) { <panic>() }
+                    sstore(array, add(oldLen, 1))
+                    slot, offset := <index>(array, oldLen)
+                }",
+                name = name,
+                panic = cx.panic_fn(0x41),
+                index = cx.storage_index_fn(ty),
+            )
+        })
+    }
+
+    /// `array_pop_<ty>(array)`: removes the last element of a dynamic storage array.
+    pub(crate) fn storage_pop_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let ty = ty.peel_refs();
+        let TyKind::DynArray(elem) = ty.kind else { unreachable!() };
+        let name = format!("array_pop_{}", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            let clear = cx.clear_storage_element(elem, "slot", "offset");
+            template!(
+                "function <name>(array) {
+                    let oldLen := sload(array)
+                    if iszero(oldLen) { <panic>() }
+                    let newLen := sub(oldLen, 1)
+                    let slot, offset := <index>(array, newLen)
+                    <clear>
+                    sstore(array, newLen)
+                }",
+                name = name,
+                panic = cx.panic_fn(0x31),
+                index = cx.storage_index_fn(ty),
+                clear = clear,
+            )
+        })
+    }
+
+    /// Returns the statement that clears an element at the given storage location.
+    pub(crate) fn clear_storage_element(
+        &mut self,
+        ty: Ty<'gcx>,
+        slot: &str,
+        offset: &str,
+    ) -> String {
+        let ty = ty.peel_refs();
+        if ty.is_value_type() {
+            format!("{}({slot}, {offset}, 0)", self.storage_write_fn(ty, None))
+        } else if let TyKind::Mapping(..) = ty.kind {
+            String::new()
+        } else {
+            format!("{}({slot})", self.clear_storage_fn(ty))
+        }
+    }
+
+    /// `clear_storage_<ty>(slot)`: resets a reference type in storage to its zero value.
+    pub(crate) fn clear_storage_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let ty = ty.peel_refs();
+        let name = format!("clear_storage_{}", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            let body = match ty.kind {
+                TyKind::Elementary(_) => template!(
+                    "let data := sload(slot)
+                    if and(data, 1) {
+                        let start := <dataslot>(slot)
+                        let end := add(start, div(add(div(data, 2), 31), 32))
+                        for { } lt(start, end) { start := add(start, 1) } { sstore(start, 0) }
+                    }
+                    sstore(slot, 0)",
+                    dataslot = cx.array_dataslot_fn(),
+                ),
+                TyKind::DynArray(elem) => {
+                    let clear = cx.clear_array_elements(elem, "data", "len");
+                    template!(
+                        "let len := sload(slot)
+                        sstore(slot, 0)
+                        let data := <dataslot>(slot)
+                        <clear>",
+                        dataslot = cx.array_dataslot_fn(),
+                        clear = clear,
+                    )
+                }
+                TyKind::Array(elem, len) => cx.clear_array_elements(elem, "slot", &num(len)),
+                TyKind::Struct(id) => {
+                    let mut body = String::new();
+                    let mut last_slot = None;
+                    for item in cx.gcx.struct_storage_layout(id).items {
+                        let field = item.ty.peel_refs();
+                        let field_slot = format!("add(slot, {})", num(item.slot));
+                        if field.is_value_type() {
+                            // Packed fields share a slot, which only has to be cleared once.
+                            if last_slot.replace(item.slot) != Some(item.slot) {
+                                body += &format!("sstore({field_slot}, 0)\n");
+                            }
+                        } else if !matches!(field.kind, TyKind::Mapping(..)) {
+                            body += &format!("{}({field_slot})\n", cx.clear_storage_fn(field));
+                        }
+                    }
+                    body
+                }
+                _ => String::new(),
+            };
+            template!("function <name>(slot) { <body> }", name = name, body = body)
+        })
+    }
+
+    /// Returns the code that clears `len` array elements starting at slot `data`.
+    fn clear_array_elements(&mut self, elem: Ty<'gcx>, data: &str, len: &str) -> String {
+        let elem = elem.peel_refs();
+        let elem_bytes = self.gcx.storage_bytes(elem);
+        if elem.is_value_type() || elem_bytes <= 16 {
+            let per_slot = (32 / elem_bytes).max(1);
+            format!(
+                "let end := add({data}, div(add({len}, {}), {per_slot}))
+                for {{ let i := {data} }} lt(i, end) {{ i := add(i, 1) }} {{ sstore(i, 0) }}",
+                per_slot - 1
+            )
+        } else if let TyKind::Mapping(..) = elem.kind {
+            String::new()
+        } else {
+            let slots = num(self.gcx.storage_slots(elem));
+            format!(
+                "for {{ let i := 0 }} lt(i, {len}) {{ i := add(i, 1) }} {{
+                    {}(add({data}, mul(i, {slots})))
+                }}",
+                self.clear_storage_fn(elem)
+            )
+        }
+    }
+
+    /// `mapping_index_access_<ty>(slot, key) -> dataSlot` for value type keys.
+    pub(crate) fn mapping_index_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let name = format!("mapping_index_access_{}", self.type_id(ty));
+        self.helper(name.clone(), |_| {
+            template!(
+                "function <name>(slot, key) -> dataSlot {
+                    mstore(0, key)
+                    mstore(0x20, slot)
+                    dataSlot := keccak256(0, 0x40)
+                }",
+                name = name,
+            )
+        })
+    }
+
+    /// `mapping_index_access_<ty>(slot, key) -> dataSlot` for byte array keys in memory.
+    pub(crate) fn mapping_index_bytes_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let name = format!("mapping_index_access_{}", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            template!(
+                "function <name>(slot, key) -> dataSlot {
+                    let pos := <unbounded>()
+                    let length := mload(key)
+                    <copy>(add(key, 0x20), pos, length)
+                    mstore(add(pos, length), slot)
+                    dataSlot := keccak256(pos, add(length, 0x20))
+                }",
+                name = name,
+                unbounded = cx.allocate_unbounded_fn(),
+                copy = cx.copy_memory_fn(),
+            )
+        })
+    }
+
+    /// `read_from_storage_<ty>(slot) -> memPtr`: copies a reference type from storage to memory.
+    pub(crate) fn storage_to_memory_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let ty = ty.peel_refs();
+        let name = format!("read_from_storage_{}", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            let body = match ty.kind {
+                TyKind::Elementary(_) => template!(
+                    "let data := sload(slot)
+                    let length := <length>(data)
+                    memPtr := <allocate>(length)
+                    let dst := add(memPtr, 32)
+                    switch and(data, 1)
+                    case 0 { mstore(dst, and(data, not(0xff))) }
+                    default {
+                        let src := <dataslot>(slot)
+                        for { let i := 0 } lt(i, length) { i := add(i, 32) } {
+                            mstore(add(dst, i), sload(src))
+                            src := add(src, 1)
+                        }
+                    }
+                    mstore(add(dst, length), 0)",
+                    length = cx.byte_array_length_fn(),
+                    allocate = cx.allocate_array_fn(ty),
+                    dataslot = cx.array_dataslot_fn(),
+                ),
+                TyKind::DynArray(elem) => {
+                    let read = cx.storage_element_to_memory(elem, "elemSlot", "elemOffset");
+                    template!(
+                        "let length := sload(slot)
+                        memPtr := <allocate>(length)
+                        let dst := add(memPtr, 32)
+                        for { let i := 0 } lt(i, length) { i := add(i, 1) } {
+                            let elemSlot, elemOffset := <index>(slot, i)
+                            mstore(add(dst, mul(i, 32)), <read>)
+                        }",
+                        allocate = cx.allocate_array_fn(ty),
+                        index = cx.storage_index_fn(ty),
+                        read = read,
+                    )
+                }
+                TyKind::Array(elem, len) => {
+                    let read = cx.storage_element_to_memory(elem, "elemSlot", "elemOffset");
+                    template!(
+                        "memPtr := <allocate>(<size>)
+                        for { let i := 0 } lt(i, <len>) { i := add(i, 1) } {
+                            let elemSlot, elemOffset := <index>(slot, i)
+                            mstore(add(memPtr, mul(i, 32)), <read>)
+                        }",
+                        allocate = cx.allocate_memory_fn(),
+                        size = num(len.saturating_mul(U256::from(32))),
+                        len = num(len),
+                        index = cx.storage_index_fn(ty),
+                        read = read,
+                    )
+                }
+                TyKind::Struct(id) => {
+                    let items = cx.gcx.struct_storage_layout(id).items;
+                    let fields: Vec<_> = items
+                        .iter()
+                        .filter(|item| !matches!(item.ty.peel_refs().kind, TyKind::Mapping(..)))
+                        .collect();
+                    let mut body =
+                        format!("memPtr := {}({})\n", cx.allocate_memory_fn(), fields.len() * 32);
+                    for (i, item) in fields.into_iter().enumerate() {
+                        let field_slot = format!("add(slot, {})", num(item.slot));
+                        let offset = item.offset.to_string();
+                        let read = cx.storage_element_to_memory(item.ty, &field_slot, &offset);
+                        body += &format!("mstore(add(memPtr, {}), {read})\n", i * 32);
+                    }
+                    body
+                }
+                _ => unreachable!("cannot copy {ty:?} from storage to memory"),
+            };
+            template!("function <name>(slot) -> memPtr { <body> }", name = name, body = body)
+        })
+    }
+
+    /// Returns the expression that reads an element from storage into a memory word.
+    fn storage_element_to_memory(&mut self, ty: Ty<'gcx>, slot: &str, offset: &str) -> String {
+        let ty = ty.peel_refs();
+        if ty.is_value_type() {
+            format!("{}({slot}, {offset})", self.storage_read_fn(ty, None))
+        } else {
+            format!("{}({slot})", self.storage_to_memory_fn(ty))
+        }
+    }
+
+    /// `copy_to_storage_from_memory_<ty>(slot, value)`: copies a reference type from memory to
+    /// storage, replacing the previous value.
+    pub(crate) fn memory_to_storage_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let ty = ty.peel_refs();
+        let name = format!("copy_to_storage_from_memory_{}", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            let body = match ty.kind {
+                TyKind::Elementary(_) => template!(
+                    "<clear>(slot)
+                    let newLen := mload(value)
+                    let src := add(value, 32)
+                    switch gt(newLen, 31)
+                    case 0 {
+                        let data := 0
+                        if newLen {
+                            data := and(mload(src), not(shr(mul(8, newLen), not(0))))
+                        }
+                        sstore(slot, or(data, mul(newLen, 2)))
+                    }
+                    default {
+                        let dst := <dataslot>(slot)
+                        for { let i := 0 } lt(i, newLen) { i := add(i, 32) } {
+                            let data := mload(add(src, i))
+                            let remaining := sub(newLen, i)
+                            if lt(remaining, 32) {
+                                data := and(data, not(shr(mul(8, remaining), not(0))))
+                            }
+                            sstore(dst, data)
+                            dst := add(dst, 1)
+                        }
+                        sstore(slot, add(mul(newLen, 2), 1))
+                    }",
+                    clear = cx.clear_storage_fn(ty),
+                    dataslot = cx.array_dataslot_fn(),
+                ),
+                TyKind::DynArray(elem) => {
+                    let write = cx.memory_element_to_storage(elem, "elemSlot", "elemOffset");
+                    template!(
+                        "<clear>(slot)
+                        let length := mload(value)
+                        sstore(slot, length)
+                        let src := add(value, 32)
+                        for { let i := 0 } lt(i, length) { i := add(i, 1) } {
+                            let elemSlot, elemOffset := <index>(slot, i)
+                            let elem := mload(add(src, mul(i, 32)))
+                            <write>
+                        }",
+                        clear = cx.clear_storage_fn(ty),
+                        index = cx.storage_index_fn(ty),
+                        write = write,
+                    )
+                }
+                TyKind::Array(elem, len) => {
+                    let write = cx.memory_element_to_storage(elem, "elemSlot", "elemOffset");
+                    template!(
+                        "for { let i := 0 } lt(i, <len>) { i := add(i, 1) } {
+                            let elemSlot, elemOffset := <index>(slot, i)
+                            let elem := mload(add(value, mul(i, 32)))
+                            <write>
+                        }",
+                        len = num(len),
+                        index = cx.storage_index_fn(ty),
+                        write = write,
+                    )
+                }
+                TyKind::Struct(id) => {
+                    let items = cx.gcx.struct_storage_layout(id).items;
+                    let fields = items
+                        .iter()
+                        .filter(|item| !matches!(item.ty.peel_refs().kind, TyKind::Mapping(..)));
+                    let mut body = String::new();
+                    for (i, item) in fields.enumerate() {
+                        let field_slot = format!("add(slot, {})", num(item.slot));
+                        let offset = item.offset.to_string();
+                        let write = cx.memory_element_to_storage(item.ty, &field_slot, &offset);
+                        body += &format!(
+                            "{{\nlet elem := mload(add(value, {}))\n{write}\n}}\n",
+                            i * 32
+                        );
+                    }
+                    body
+                }
+                _ => unreachable!("cannot copy {ty:?} from memory to storage"),
+            };
+            template!("function <name>(slot, value) { <body> }", name = name, body = body)
+        })
+    }
+
+    /// Returns the statement that writes the memory word `elem` to the given storage location.
+    fn memory_element_to_storage(&mut self, ty: Ty<'gcx>, slot: &str, offset: &str) -> String {
+        let ty = ty.peel_refs();
+        if ty.is_value_type() {
+            format!("{}({slot}, {offset}, elem)", self.storage_write_fn(ty, None))
+        } else {
+            format!("{}({slot}, elem)", self.memory_to_storage_fn(ty))
+        }
+    }
+
+    /// `copy_to_memory_from_calldata_<ty>(offset, length) -> memPtr` for byte arrays and arrays of
+    /// value types, or `(offset) -> memPtr` for static arrays and structs of value types.
+    pub(crate) fn calldata_to_memory_fn(&mut self, ty: Ty<'gcx>) -> String {
+        let ty = ty.peel_refs();
+        let name = format!("copy_to_memory_from_calldata_{}", self.type_id(ty));
+        self.helper(name.clone(), |cx| {
+            let (params, body) = match ty.kind {
+                TyKind::Elementary(_) => (
+                    "offset, length",
+                    template!(
+                        "memPtr := <allocate>(length)
+                        <copy>(offset, add(memPtr, 32), length)",
+                        allocate = cx.allocate_array_fn(ty),
+                        copy = cx.copy_calldata_fn(),
+                    ),
+                ),
+                TyKind::DynArray(elem) => (
+                    "offset, length",
+                    template!(
+                        "memPtr := <allocate>(length)
+                        let dst := add(memPtr, 32)
+                        for { let i := 0 } lt(i, length) { i := add(i, 1) } {
+                            let value := calldataload(add(offset, mul(i, 32)))
+                            <validator>(value)
+                            mstore(add(dst, mul(i, 32)), value)
+                        }",
+                        allocate = cx.allocate_array_fn(ty),
+                        validator = cx.validator_fn(elem),
+                    ),
+                ),
+                TyKind::Array(elem, len) => (
+                    "offset",
+                    template!(
+                        "memPtr := <allocate>(<size>)
+                        for { let i := 0 } lt(i, <len>) { i := add(i, 1) } {
+                            let value := calldataload(add(offset, mul(i, 32)))
+                            <validator>(value)
+                            mstore(add(memPtr, mul(i, 32)), value)
+                        }",
+                        allocate = cx.allocate_memory_fn(),
+                        size = num(len.saturating_mul(U256::from(32))),
+                        len = num(len),
+                        validator = cx.validator_fn(elem),
+                    ),
+                ),
+                TyKind::Struct(id) => {
+                    let fields = cx.gcx.struct_field_types(id);
+                    let mut body =
+                        format!("memPtr := {}({})\n", cx.allocate_memory_fn(), fields.len() * 32);
+                    for (i, &field) in fields.iter().enumerate() {
+                        body += &format!(
+                            "{{\nlet value := calldataload(add(offset, {0}))\n{1}(value)\nmstore(add(memPtr, {0}), value)\n}}\n",
+                            i * 32,
+                            cx.validator_fn(field)
+                        );
+                    }
+                    ("offset", body)
+                }
+                _ => unreachable!("cannot copy {ty:?} from calldata to memory"),
+            };
+            template!(
+                "function <name>(<params>) -> memPtr { <body> }",
+                name = name,
+                params = params,
+                body = body,
+            )
+        })
+    }
+}
+
+/// Returns the types of the memory words of a static array or struct.
+pub(crate) fn memory_elements<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>) -> Vec<Ty<'gcx>> {
+    match ty.peel_refs().kind {
+        TyKind::Array(elem, len) => vec![elem; len.try_into().unwrap_or(0)],
+        TyKind::Struct(id) => gcx
+            .struct_field_types(id)
+            .iter()
+            .copied()
+            .filter(|ty| !matches!(ty.peel_refs().kind, TyKind::Mapping(..)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the expression that converts a storage word, shifted to offset 0, to a stack value.
+fn from_storage_expr(repr: Repr, word: &str) -> String {
+    match repr {
+        Repr::Unsigned(256) | Repr::Signed(256) | Repr::FixedBytes(32) => word.to_string(),
+        Repr::FixedBytes(bytes) => format!("shl({}, {word})", 256 - bytes as u32 * 8),
+        Repr::Bool => format!("iszero(iszero(and({word}, 0xff)))"),
+        _ => cleanup_expr(repr, word),
+    }
+}
+
+/// Returns the expression that converts a stack value to its right-aligned storage form.
+fn to_storage_expr(repr: Repr, value: &str) -> String {
+    match repr {
+        Repr::FixedBytes(bytes) if bytes < 32 => {
+            format!("shr({}, {value})", 256 - bytes as u32 * 8)
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Returns the left-aligned word of up to 32 bytes.
+pub(crate) fn left_word(bytes: &[u8]) -> U256 {
+    let mut word = [0u8; 32];
+    word[..bytes.len()].copy_from_slice(bytes);
+    U256::from_be_bytes(word)
+}
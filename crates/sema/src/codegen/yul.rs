@@ -0,0 +1,840 @@
+//! The Yul tree built by the code generator.
+//!
+//! Unlike the [Yul AST](solar_ast::yul), this tree owns its contents and carries no spans, so that
+//! it can be built incrementally. Its [`Display`](fmt::Display) implementation prints Yul source
+//! code that can be parsed back with the Yul parser.
+
+use crate::hir;
+use alloy_primitives::U256;
+use serde_json::{Value as Json, json};
+use solar_ast::LitKind;
+use std::fmt::{self, Write};
+
+/// A Yul object: `object "name" { code { ... } ... }`.
+#[derive(Clone, Debug)]
+pub(crate) struct Object {
+    /// The name of the object.
+    pub(crate) name: String,
+    /// The `code` block.
+    pub(crate) code: Block,
+    /// Sub-objects.
+    pub(crate) children: Vec<Self>,
+    /// `data` sections, by name.
+    pub(crate) data: Vec<(String, Vec<u8>)>,
+}
+
+/// A block of Yul statements: `{ ... }`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Block {
+    pub(crate) stmts: Vec<Stmt>,
+}
+
+impl Block {
+    pub(crate) fn new(stmts: Vec<Stmt>) -> Self {
+        Self { stmts }
+    }
+}
+
+/// A Yul statement.
+#[derive(Clone, Debug)]
+pub(crate) enum Stmt {
+    /// A nested scope: `{ ... }`.
+    Block(Block),
+    /// A variable declaration: `let x, y := f()`.
+    Let(Vec<String>, Option<Expr>),
+    /// An assignment: `x, y := f()`.
+    Assign(Vec<String>, Expr),
+    /// An expression statement. This can only be a function call.
+    Expr(Expr),
+    /// `if cond { ... }`.
+    If(Expr, Block),
+    /// `switch expr case 0 { ... } default { ... }`.
+    Switch(Switch),
+    /// `for { ... } cond { ... } { ... }`.
+    For(Box<For>),
+    /// `break`.
+    Break,
+    /// `continue`.
+    Continue,
+    /// `leave`.
+    Leave,
+    /// A function definition.
+    Function(Function),
+}
+
+/// A Yul switch statement. See [`Stmt::Switch`].
+#[derive(Clone, Debug)]
+pub(crate) struct Switch {
+    pub(crate) selector: Expr,
+    pub(crate) cases: Vec<(U256, Block)>,
+    pub(crate) default: Option<Block>,
+}
+
+/// A Yul for loop. See [`Stmt::For`].
+#[derive(Clone, Debug)]
+pub(crate) struct For {
+    pub(crate) init: Block,
+    pub(crate) cond: Expr,
+    pub(crate) post: Block,
+    pub(crate) body: Block,
+}
+
+/// A Yul function definition: `function f(a, b) -> c { ... }`.
+#[derive(Clone, Debug)]
+pub(crate) struct Function {
+    pub(crate) name: String,
+    pub(crate) params: Vec<String>,
+    pub(crate) returns: Vec<String>,
+    pub(crate) body: Block,
+}
+
+/// A Yul expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Expr {
+    /// An identifier.
+    Ident(String),
+    /// A number literal.
+    Number(U256),
+    /// A string literal.
+    Str(String),
+    /// A function call: `f(a, b)`.
+    Call(String, Vec<Self>),
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Printer::new(f).object(self)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Printer::new(f).expr(self)
+    }
+}
+
+/// Pretty-printer for the Yul tree.
+struct Printer<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    indent: usize,
+}
+
+impl<'a, 'b> Printer<'a, 'b> {
+    fn new(f: &'a mut fmt::Formatter<'b>) -> Self {
+        Self { f, indent: 0 }
+    }
+
+    fn newline(&mut self) -> fmt::Result {
+        self.f.write_char('\n')?;
+        for _ in 0..self.indent {
+            self.f.write_str("    ")?;
+        }
+        Ok(())
+    }
+
+    fn object(&mut self, object: &Object) -> fmt::Result {
+        write!(self.f, "object \"{}\" {{", object.name)?;
+        self.indent += 1;
+        self.newline()?;
+        self.f.write_str("code ")?;
+        self.block(&object.code)?;
+        for child in &object.children {
+            self.newline()?;
+            self.object(child)?;
+        }
+        for (name, data) in &object.data {
+            self.newline()?;
+            write!(self.f, "data \"{name}\" hex\"{}\"", alloy_primitives::hex::encode(data))?;
+        }
+        self.indent -= 1;
+        self.newline()?;
+        self.f.write_char('}')
+    }
+
+    fn block(&mut self, block: &Block) -> fmt::Result {
+        if block.stmts.is_empty() {
+            return self.f.write_str("{ }");
+        }
+        self.f.write_char('{')?;
+        self.indent += 1;
+        for stmt in &block.stmts {
+            self.newline()?;
+            self.stmt(stmt)?;
+        }
+        self.indent -= 1;
+        self.newline()?;
+        self.f.write_char('}')
+    }
+
+    /// Prints the `init` and `post` blocks of a for loop on a single line if they are simple.
+    fn inline_block(&mut self, block: &Block) -> fmt::Result {
+        let simple = block.stmts.iter().all(|stmt| {
+            matches!(stmt, Stmt::Let(..) | Stmt::Assign(..) | Stmt::Expr(_) | Stmt::Break)
+        });
+        if block.stmts.len() > 1 || !simple {
+            return self.block(block);
+        }
+        match block.stmts.first() {
+            Some(stmt) => {
+                self.f.write_str("{ ")?;
+                self.stmt(stmt)?;
+                self.f.write_str(" }")
+            }
+            None => self.f.write_str("{ }"),
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> fmt::Result {
+        match stmt {
+            Stmt::Block(block) => self.block(block),
+            Stmt::Let(names, value) => {
+                write!(self.f, "let {}", names.join(", "))?;
+                if let Some(value) = value {
+                    self.f.write_str(" := ")?;
+                    self.expr(value)?;
+                }
+                Ok(())
+            }
+            Stmt::Assign(names, value) => {
+                write!(self.f, "{} := ", names.join(", "))?;
+                self.expr(value)
+            }
+            Stmt::Expr(expr) => self.expr(expr),
+            Stmt::If(cond, body) => {
+                self.f.write_str("if ")?;
+                self.expr(cond)?;
+                self.f.write_char(' ')?;
+                self.block(body)
+            }
+            Stmt::Switch(switch) => {
+                self.f.write_str("switch ")?;
+                self.expr(&switch.selector)?;
+                for (value, body) in &switch.cases {
+                    self.newline()?;
+                    self.f.write_str("case ")?;
+                    self.number(*value)?;
+                    self.f.write_char(' ')?;
+                    self.block(body)?;
+                }
+                if let Some(default) = &switch.default {
+                    self.newline()?;
+                    self.f.write_str("default ")?;
+                    self.block(default)?;
+                }
+                Ok(())
+            }
+            Stmt::For(for_) => {
+                self.f.write_str("for ")?;
+                self.inline_block(&for_.init)?;
+                self.f.write_char(' ')?;
+                self.expr(&for_.cond)?;
+                self.f.write_char(' ')?;
+                self.inline_block(&for_.post)?;
+                self.f.write_char(' ')?;
+                self.block(&for_.body)
+            }
+            Stmt::Break => self.f.write_str("break"),
+            Stmt::Continue => self.f.write_str("continue"),
+            Stmt::Leave => self.f.write_str("leave"),
+            Stmt::Function(function) => {
+                write!(self.f, "function {}({})", function.name, function.params.join(", "))?;
+                if !function.returns.is_empty() {
+                    write!(self.f, " -> {}", function.returns.join(", "))?;
+                }
+                self.f.write_char(' ')?;
+                self.block(&function.body)
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> fmt::Result {
+        match expr {
+            Expr::Ident(name) => self.f.write_str(name),
+            Expr::Number(value) => self.number(*value),
+            Expr::Str(s) => write!(self.f, "\"{}\"", s.escape_default()),
+            Expr::Call(name, args) => {
+                write!(self.f, "{name}(")?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        self.f.write_str(", ")?;
+                    }
+                    self.expr(arg)?;
+                }
+                self.f.write_char(')')
+            }
+        }
+    }
+
+    /// Prints small numbers in decimal, and everything else in hexadecimal.
+    fn number(&mut self, value: U256) -> fmt::Result {
+        if value < U256::from(1024) {
+            write!(self.f, "{value}")
+        } else {
+            write!(self.f, "{value:#x}")
+        }
+    }
+}
+
+impl Object {
+    /// Builds the tree of an analyzed Yul object.
+    pub(crate) fn from_hir(object: &hir::yul::Object<'_>) -> Self {
+        let lcx = HirLowering { code: &object.code };
+        Self {
+            name: object.name.value.to_string(),
+            code: lcx.block(&object.code.block),
+            children: object.children.iter().map(Self::from_hir).collect(),
+            data: object
+                .data
+                .iter()
+                .map(|data| {
+                    let value = match data.data.kind {
+                        LitKind::Str(_, value, _) => value.as_byte_str().to_vec(),
+                        _ => Vec::new(),
+                    };
+                    (data.name.value.to_string(), value)
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the JSON representation of the object, in the same format as solc's `irAst`.
+    ///
+    /// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libyul/AsmJsonConverter.cpp>
+    pub(crate) fn to_json(&self) -> Json {
+        let mut sub_objects: Vec<_> = self.children.iter().map(Self::to_json).collect();
+        sub_objects.extend(self.data.iter().map(|(name, data)| {
+            json!({
+                "nodeType": "YulData",
+                "name": name,
+                "value": alloy_primitives::hex::encode(data),
+            })
+        }));
+        json!({
+            "nodeType": "YulObject",
+            "name": self.name,
+            "code": {
+                "nodeType": "YulCode",
+                "block": block_json(&self.code),
+            },
+            "subObjects": sub_objects,
+        })
+    }
+}
+
+/// Lowers the code of an analyzed Yul object.
+struct HirLowering<'a, 'hir> {
+    code: &'a hir::yul::Code<'hir>,
+}
+
+impl HirLowering<'_, '_> {
+    fn block(&self, block: &hir::yul::Block<'_>) -> Block {
+        Block::new(block.iter().map(|stmt| self.stmt(stmt)).collect())
+    }
+
+    fn stmt(&self, stmt: &hir::yul::Stmt<'_>) -> Stmt {
+        use hir::yul::StmtKind;
+        match &stmt.kind {
+            StmtKind::Block(block) => Stmt::Block(self.block(block)),
+            StmtKind::Assign(lhs, rhs) => {
+                Stmt::Assign(lhs.iter().map(|expr| self.name(expr)).collect(), self.expr(rhs))
+            }
+            StmtKind::Expr(expr) => Stmt::Expr(self.expr(expr)),
+            StmtKind::If(cond, body) => Stmt::If(self.expr(cond), self.block(body)),
+            StmtKind::For(for_) => Stmt::For(Box::new(For {
+                init: self.block(&for_.init),
+                cond: self.expr(&for_.cond),
+                post: self.block(&for_.step),
+                body: self.block(&for_.body),
+            })),
+            StmtKind::Switch(switch) => {
+                let cases = switch
+                    .cases
+                    .iter()
+                    .filter_map(|case| {
+                        let value = crate::yul::literal_value(case.constant?).unwrap_or_default();
+                        Some((value, self.block(&case.body)))
+                    })
+                    .collect();
+                Stmt::Switch(Switch {
+                    selector: self.expr(&switch.selector),
+                    cases,
+                    default: switch.default_case().map(|case| self.block(&case.body)),
+                })
+            }
+            StmtKind::Leave => Stmt::Leave,
+            StmtKind::Break => Stmt::Break,
+            StmtKind::Continue => Stmt::Continue,
+            &StmtKind::FunctionDef(id) => {
+                let function = self.code.function(id);
+                Stmt::Function(Function {
+                    name: function.name.to_string(),
+                    params: self.vars(function.parameters),
+                    returns: self.vars(function.returns),
+                    body: self.block(&function.body),
+                })
+            }
+            StmtKind::VarDecl(vars, value) => {
+                Stmt::Let(self.vars(vars), value.map(|value| self.expr(value)))
+            }
+        }
+    }
+
+    fn vars(&self, vars: &[hir::yul::VarId]) -> Vec<String> {
+        vars.iter().map(|&var| self.code.variable(var).to_string()).collect()
+    }
+
+    fn name(&self, expr: &hir::yul::Expr<'_>) -> String {
+        match self.expr(expr) {
+            Expr::Ident(name) => name,
+            _ => unreachable!("invalid assignment target"),
+        }
+    }
+
+    fn expr(&self, expr: &hir::yul::Expr<'_>) -> Expr {
+        use hir::yul::ExprKind;
+        match &expr.kind {
+            &ExprKind::Var(var) => Expr::Ident(self.code.variable(var).to_string()),
+            ExprKind::External(ext) => Expr::Ident(ext.name.to_string()),
+            ExprKind::Call(call) => Expr::Call(
+                call.name.to_string(),
+                call.arguments.iter().map(|arg| self.expr(arg)).collect(),
+            ),
+            ExprKind::Lit(lit) => {
+                // Strings with non-printable characters, like most hex strings, become numbers.
+                if let LitKind::Str(_, value, _) = lit.kind
+                    && value.as_byte_str().iter().all(|&b| b.is_ascii_graphic() || b == b' ')
+                {
+                    Expr::Str(String::from_utf8_lossy(value.as_byte_str()).into_owned())
+                } else {
+                    Expr::Number(crate::yul::literal_value(lit).unwrap_or_default())
+                }
+            }
+            ExprKind::Err(_) => unreachable!("lowering errored Yul code"),
+        }
+    }
+}
+
+fn block_json(block: &Block) -> Json {
+    json!({
+        "nodeType": "YulBlock",
+        "statements": block.stmts.iter().map(stmt_json).collect::<Vec<_>>(),
+    })
+}
+
+fn stmt_json(stmt: &Stmt) -> Json {
+    let typed_names = |names: &[String]| -> Vec<Json> {
+        names
+            .iter()
+            .map(|name| json!({ "nodeType": "YulTypedName", "name": name, "type": "" }))
+            .collect()
+    };
+    match stmt {
+        Stmt::Block(block) => block_json(block),
+        Stmt::Let(names, value) => json!({
+            "nodeType": "YulVariableDeclaration",
+            "variables": typed_names(names),
+            "value": value.as_ref().map(expr_json),
+        }),
+        Stmt::Assign(names, value) => json!({
+            "nodeType": "YulAssignment",
+            "variableNames": names.iter().map(|name| ident_json(name)).collect::<Vec<_>>(),
+            "value": expr_json(value),
+        }),
+        Stmt::Expr(expr) => json!({
+            "nodeType": "YulExpressionStatement",
+            "expression": expr_json(expr),
+        }),
+        Stmt::If(cond, body) => json!({
+            "nodeType": "YulIf",
+            "condition": expr_json(cond),
+            "body": block_json(body),
+        }),
+        Stmt::Switch(switch) => {
+            let mut cases: Vec<_> = switch
+                .cases
+                .iter()
+                .map(|(value, body)| {
+                    json!({
+                        "nodeType": "YulCase",
+                        "value": expr_json(&Expr::Number(*value)),
+                        "body": block_json(body),
+                    })
+                })
+                .collect();
+            if let Some(default) = &switch.default {
+                cases.push(json!({
+                    "nodeType": "YulCase",
+                    "value": "default",
+                    "body": block_json(default),
+                }));
+            }
+            json!({
+                "nodeType": "YulSwitch",
+                "expression": expr_json(&switch.selector),
+                "cases": cases,
+            })
+        }
+        Stmt::For(for_) => json!({
+            "nodeType": "YulForLoop",
+            "pre": block_json(&for_.init),
+            "condition": expr_json(&for_.cond),
+            "post": block_json(&for_.post),
+            "body": block_json(&for_.body),
+        }),
+        Stmt::Break => json!({ "nodeType": "YulBreak" }),
+        Stmt::Continue => json!({ "nodeType": "YulContinue" }),
+        Stmt::Leave => json!({ "nodeType": "YulLeave" }),
+        Stmt::Function(function) => json!({
+            "nodeType": "YulFunctionDefinition",
+            "name": function.name,
+            "parameters": typed_names(&function.params),
+            "returnVariables": typed_names(&function.returns),
+            "body": block_json(&function.body),
+        }),
+    }
+}
+
+fn expr_json(expr: &Expr) -> Json {
+    match expr {
+        Expr::Ident(name) => ident_json(name),
+        Expr::Number(value) => json!({
+            "nodeType": "YulLiteral",
+            "kind": "number",
+            "type": "",
+            "value": value.to_string(),
+        }),
+        Expr::Str(s) => json!({
+            "nodeType": "YulLiteral",
+            "kind": "string",
+            "type": "",
+            "value": s,
+        }),
+        Expr::Call(name, args) => json!({
+            "nodeType": "YulFunctionCall",
+            "functionName": ident_json(name),
+            "arguments": args.iter().map(expr_json).collect::<Vec<_>>(),
+        }),
+    }
+}
+
+fn ident_json(name: &str) -> Json {
+    json!({ "nodeType": "YulIdentifier", "name": name })
+}
+
+/// Fills a Yul source template, replacing every `<key>` with the corresponding value.
+///
+/// See [`template!`].
+#[track_caller]
+pub(crate) fn fill_template(src: &str, vars: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut rest = src;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find('>').map(|end| start + end).expect("unclosed `<` in template");
+        let key = &rest[start + 1..end];
+        let Some((_, value)) = vars.iter().find(|(k, _)| *k == key) else {
+            panic!("missing value for `<{key}>` in Yul template:\n{src}")
+        };
+        write!(out, "{value}").unwrap();
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Fills a Yul source template: `template!("let <x> := 1", x = name)`.
+///
+/// Like solc's Whiskers templates, values are substituted for `<key>` placeholders.
+macro_rules! template {
+    ($src:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        $crate::codegen::yul::fill_template(
+            $src,
+            &[$((stringify!($key), &$value as &dyn std::fmt::Display)),*],
+        )
+    };
+}
+pub(crate) use template;
+
+/// Parses a Yul function definition from source code.
+///
+/// This is used to build helper functions from templates, so the source is trusted and any
+/// syntax error is a bug in the code generator.
+#[track_caller]
+pub(crate) fn parse_function(src: &str) -> Function {
+    match parse_stmts(src).as_mut_slice() {
+        [Stmt::Function(f)] => std::mem::replace(f, Function::empty()),
+        _ => panic!("expected a single Yul function definition:\n{src}"),
+    }
+}
+
+/// Parses a list of Yul statements from source code.
+///
+/// See [`parse_function`].
+#[track_caller]
+pub(crate) fn parse_stmts(src: &str) -> Vec<Stmt> {
+    let mut parser = TemplateParser { tokens: tokenize(src), pos: 0 };
+    let mut stmts = Vec::new();
+    while parser.peek().is_some() {
+        stmts.push(parser.stmt());
+    }
+    stmts
+}
+
+impl Function {
+    fn empty() -> Self {
+        Self {
+            name: String::new(),
+            params: Vec::new(),
+            returns: Vec::new(),
+            body: Block::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Number(U256),
+    Str(String),
+    Punct(&'static str),
+}
+
+#[track_caller]
+fn tokenize(src: &str) -> Vec<Token> {
+    let bytes = src.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        match c {
+            _ if c.is_ascii_whitespace() => i += 1,
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'{' | b'}' | b'(' | b')' | b',' => {
+                tokens.push(Token::Punct(match c {
+                    b'{' => "{",
+                    b'}' => "}",
+                    b'(' => "(",
+                    b')' => ")",
+                    _ => ",",
+                }));
+                i += 1;
+            }
+            b':' if bytes.get(i + 1) == Some(&b'=') => {
+                tokens.push(Token::Punct(":="));
+                i += 2;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'>') => {
+                tokens.push(Token::Punct("->"));
+                i += 2;
+            }
+            b'"' => {
+                let start = i + 1;
+                i = start;
+                while bytes[i] != b'"' {
+                    i += 1;
+                }
+                tokens.push(Token::Str(src[start..i].to_string()));
+                i += 1;
+            }
+            b'0'..=b'9' => {
+                let start = i;
+                while i < bytes.len() && bytes[i].is_ascii_alphanumeric() {
+                    i += 1;
+                }
+                let s = &src[start..i];
+                let value = match s.strip_prefix("0x") {
+                    Some(hex) => U256::from_str_radix(hex, 16),
+                    None => U256::from_str_radix(s, 10),
+                };
+                tokens.push(Token::Number(value.unwrap_or_else(|e| panic!("bad number {s}: {e}"))));
+            }
+            _ if c.is_ascii_alphabetic() || c == b'_' || c == b'$' => {
+                let start = i;
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'_' | b'$'))
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(src[start..i].to_string()));
+            }
+            _ => panic!("unexpected character {:?} in Yul template:\n{src}", c as char),
+        }
+    }
+    tokens
+}
+
+/// A parser for the Yul subset used by the code generator's templates.
+struct TemplateParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl TemplateParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn bump(&mut self) -> Token {
+        let token = self.tokens.get(self.pos).cloned().expect("unexpected end of Yul template");
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Punct(p)) if *p == punct) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &str) {
+        let token = self.bump();
+        assert!(
+            matches!(token, Token::Punct(p) if p == punct),
+            "expected `{punct}` in Yul template, found {token:?}"
+        );
+    }
+
+    fn ident(&mut self) -> String {
+        match self.bump() {
+            Token::Ident(name) => name,
+            token => panic!("expected identifier in Yul template, found {token:?}"),
+        }
+    }
+
+    fn idents(&mut self) -> Vec<String> {
+        let mut names = vec![self.ident()];
+        while self.eat(",") {
+            names.push(self.ident());
+        }
+        names
+    }
+
+    fn block(&mut self) -> Block {
+        self.expect("{");
+        let mut stmts = Vec::new();
+        while !self.eat("}") {
+            stmts.push(self.stmt());
+        }
+        Block::new(stmts)
+    }
+
+    fn stmt(&mut self) -> Stmt {
+        let keyword = match self.peek() {
+            Some(Token::Punct("{")) => return Stmt::Block(self.block()),
+            Some(Token::Ident(name)) => name.clone(),
+            _ => return Stmt::Expr(self.expr()),
+        };
+        match keyword.as_str() {
+            "let" => {
+                self.pos += 1;
+                let names = self.idents();
+                let value = self.eat(":=").then(|| self.expr());
+                Stmt::Let(names, value)
+            }
+            "if" => {
+                self.pos += 1;
+                let cond = self.expr();
+                Stmt::If(cond, self.block())
+            }
+            "switch" => {
+                self.pos += 1;
+                let selector = self.expr();
+                let mut cases = Vec::new();
+                let mut default = None;
+                loop {
+                    match self.peek() {
+                        Some(Token::Ident(kw)) if kw == "case" => {
+                            self.pos += 1;
+                            let Token::Number(value) = self.bump() else {
+                                panic!("expected number after `case` in Yul template")
+                            };
+                            cases.push((value, self.block()));
+                        }
+                        Some(Token::Ident(kw)) if kw == "default" => {
+                            self.pos += 1;
+                            default = Some(self.block());
+                        }
+                        _ => break,
+                    }
+                }
+                Stmt::Switch(Switch { selector, cases, default })
+            }
+            "for" => {
+                self.pos += 1;
+                let init = self.block();
+                let cond = self.expr();
+                let post = self.block();
+                let body = self.block();
+                Stmt::For(Box::new(For { init, cond, post, body }))
+            }
+            "break" => {
+                self.pos += 1;
+                Stmt::Break
+            }
+            "continue" => {
+                self.pos += 1;
+                Stmt::Continue
+            }
+            "leave" => {
+                self.pos += 1;
+                Stmt::Leave
+            }
+            "function" => {
+                self.pos += 1;
+                let name = self.ident();
+                self.expect("(");
+                let params = if self.eat(")") {
+                    Vec::new()
+                } else {
+                    let params = self.idents();
+                    self.expect(")");
+                    params
+                };
+                let returns = if self.eat("->") { self.idents() } else { Vec::new() };
+                let body = self.block();
+                Stmt::Function(Function { name, params, returns, body })
+            }
+            _ => {
+                if matches!(self.tokens.get(self.pos + 1), Some(Token::Punct(":=" | ","))) {
+                    let names = self.idents();
+                    self.expect(":=");
+                    Stmt::Assign(names, self.expr())
+                } else {
+                    Stmt::Expr(self.expr())
+                }
+            }
+        }
+    }
+
+    fn expr(&mut self) -> Expr {
+        match self.bump() {
+            Token::Ident(name) => {
+                if self.eat("(") {
+                    let mut args = Vec::new();
+                    if !self.eat(")") {
+                        loop {
+                            args.push(self.expr());
+                            if self.eat(")") {
+                                break;
+                            }
+                            self.expect(",");
+                        }
+                    }
+                    Expr::Call(name, args)
+                } else {
+                    Expr::Ident(name)
+                }
+            }
+            Token::Number(value) => Expr::Number(value),
+            Token::Str(s) => Expr::Str(s),
+            Token::Punct(p) => panic!("unexpected `{p}` in Yul template"),
+        }
+    }
+}
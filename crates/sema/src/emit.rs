@@ -1,6 +1,11 @@
-use crate::ty::Gcx;
+use crate::{
+    codegen, hir, natspec,
+    ty::{Gcx, StorageItem, Ty, TyKind},
+    typeck::TypeckResults,
+};
 use serde::Serialize;
-use solar_interface::config::CompilerOutput;
+use solar_ast::{DataLocation, ElementaryType};
+use solar_interface::{config::CompilerOutput, source_map::SourceFile};
 use std::{
     collections::BTreeMap,
     io::{self, Write},
@@ -11,31 +16,103 @@ use std::{
 struct CombinedJson {
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     contracts: BTreeMap<String, CombinedJsonContract>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    sources: BTreeMap<String, CombinedJsonSource>,
     version: &'static str,
 }
 
+#[derive(Default, Serialize)]
+struct CombinedJsonSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license: Option<String>,
+}
+
 #[derive(Default, Serialize)]
 struct CombinedJsonContract {
     #[serde(skip_serializing_if = "Option::is_none")]
     abi: Option<Abi>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    bin: Option<String>,
+    #[serde(rename = "bin-runtime", skip_serializing_if = "Option::is_none")]
+    bin_runtime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    devdoc: Option<natspec::DevDoc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     hashes: Option<Hashes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ir: Option<String>,
+    #[serde(rename = "ir-ast", skip_serializing_if = "Option::is_none")]
+    ir_ast: Option<serde_json::Value>,
+    #[serde(rename = "storage-layout", skip_serializing_if = "Option::is_none")]
+    storage_layout: Option<StorageLayout>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    userdoc: Option<natspec::UserDoc>,
 }
 
 type Abi = Vec<alloy_json_abi::AbiItem<'static>>;
 type Hashes = BTreeMap<String, String>;
 
-pub(crate) fn emit(gcx: Gcx<'_>) {
+/// Storage layout in the solc format.
+///
+/// Reference: <https://docs.soliditylang.org/en/latest/internals/layout_in_storage.html#json-output>
+#[derive(Serialize)]
+struct StorageLayout {
+    storage: Vec<StorageLayoutEntry>,
+    types: Option<BTreeMap<String, StorageLayoutType>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageLayoutEntry {
+    ast_id: usize,
+    contract: String,
+    label: String,
+    offset: u8,
+    slot: String,
+    #[serde(rename = "type")]
+    ty: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageLayoutType {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base: Option<String>,
+    encoding: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    members: Option<Vec<StorageLayoutEntry>>,
+    number_of_bytes: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+pub(crate) fn emit<'gcx>(gcx: Gcx<'gcx>, results: &TypeckResults<'gcx>) {
     let mut output = CombinedJson {
         contracts: Default::default(),
+        sources: Default::default(),
         version: solar_interface::config::version::SEMVER_VERSION,
     };
     for id in gcx.hir.contract_ids() {
         let name = gcx.contract_fully_qualified_name(id).to_string();
         let contract_output = output.contracts.entry(name).or_default();
+        let emits = |output| gcx.sess.opts.emit.contains(&output);
+        let needs_bytecode = emits(CompilerOutput::Bin) || emits(CompilerOutput::BinRuntime);
+        let needs_ir = emits(CompilerOutput::Ir) || emits(CompilerOutput::IrAst);
+        let object = (needs_bytecode || needs_ir)
+            .then(|| codegen::generate(gcx, results, id))
+            .flatten()
+            .and_then(Result::ok);
+        let bytecode = object
+            .as_ref()
+            .filter(|_| needs_bytecode)
+            .and_then(|object| codegen::bytecode(gcx, id, object).ok());
         for &emit in &gcx.sess.opts.emit {
             match emit {
                 CompilerOutput::Abi => contract_output.abi = Some(gcx.contract_abi(id)),
+                CompilerOutput::Devdoc => contract_output.devdoc = Some(natspec::devdoc(gcx, id)),
                 CompilerOutput::Hashes => {
                     let mut hashes = Hashes::default();
                     for f in gcx.interface_functions(id) {
@@ -46,20 +123,241 @@ pub(crate) fn emit(gcx: Gcx<'_>) {
                     }
                     contract_output.hashes = Some(hashes);
                 }
+                CompilerOutput::Bin => {
+                    let bin = bytecode.as_ref().map(|b| alloy_primitives::hex::encode(&b.creation));
+                    contract_output.bin = Some(bin.unwrap_or_default());
+                }
+                CompilerOutput::BinRuntime => {
+                    let bin = bytecode.as_ref().map(|b| alloy_primitives::hex::encode(&b.runtime));
+                    contract_output.bin_runtime = Some(bin.unwrap_or_default());
+                }
+                CompilerOutput::Ir => contract_output.ir = object.as_ref().map(ToString::to_string),
+                CompilerOutput::IrAst => {
+                    contract_output.ir_ast = object.as_ref().map(codegen::yul::Object::to_json)
+                }
+                CompilerOutput::StorageLayout => {
+                    contract_output.storage_layout = Some(storage_layout(gcx, id));
+                }
+                CompilerOutput::Userdoc => {
+                    contract_output.userdoc = Some(natspec::userdoc(gcx, id))
+                }
                 emit => todo!("{emit:?}"),
             }
         }
     }
+    for source in gcx.hir.sources() {
+        let Some(license) = source.license else { continue };
+        output.sources.insert(
+            source.file.name.display().to_string(),
+            CombinedJsonSource { license: Some(license.to_string()) },
+        );
+    }
+    write_combined_json(gcx, &output);
+}
+
+/// Emits the requested outputs of the given analyzed Yul objects.
+pub(crate) fn emit_yul(gcx: Gcx<'_>, objects: &[(&SourceFile, hir::yul::Object<'_>)]) {
+    let mut output = CombinedJson {
+        contracts: Default::default(),
+        sources: Default::default(),
+        version: solar_interface::config::version::SEMVER_VERSION,
+    };
+    for emit in &gcx.sess.opts.emit {
+        if !matches!(emit, CompilerOutput::Bin | CompilerOutput::Ir | CompilerOutput::IrAst) {
+            gcx.dcx().err(format!("`--emit={emit}` is not supported for Yul objects")).emit();
+        }
+    }
+    for (file, hir_object) in objects {
+        let name = format!("{}:{}", file.name.display(), hir_object.name.value);
+        let object_output = output.contracts.entry(name).or_default();
+        let object = codegen::yul::Object::from_hir(hir_object);
+        for &emit in &gcx.sess.opts.emit {
+            match emit {
+                CompilerOutput::Bin => {
+                    let evm_version = gcx.sess.opts.evm_version;
+                    match codegen::evm::assemble(&object, evm_version) {
+                        Ok(assembled) => {
+                            object_output.bin =
+                                Some(alloy_primitives::hex::encode(assembled.bytecode));
+                        }
+                        Err(errors) => {
+                            for error in errors {
+                                gcx.dcx().err(error).span(hir_object.span).emit();
+                            }
+                        }
+                    }
+                }
+                CompilerOutput::Ir => object_output.ir = Some(object.to_string()),
+                CompilerOutput::IrAst => object_output.ir_ast = Some(object.to_json()),
+                _ => {}
+            }
+        }
+    }
+    write_combined_json(gcx, &output);
+}
+
+fn write_combined_json(gcx: Gcx<'_>, output: &CombinedJson) {
+    if gcx.dcx().has_errors().is_err() {
+        return;
+    }
     let _ = (|| {
         let out_path = gcx.sess.opts.out_dir.as_deref().map(|dir| dir.join("combined.json"));
         let mut writer = out_writer(out_path.as_deref())?;
-        to_json(&mut writer, &output, gcx.sess.opts.pretty_json)?;
+        to_json(&mut writer, output, gcx.sess.opts.pretty_json)?;
         writer.flush()?;
         Ok::<_, io::Error>(())
     })()
     .map_err(|e| gcx.dcx().err(format!("failed to write to output: {e}")).emit());
 }
 
+fn storage_layout(gcx: Gcx<'_>, id: hir::ContractId) -> StorageLayout {
+    let mut types = BTreeMap::new();
+    let contract = gcx.contract_fully_qualified_name(id).to_string();
+    let storage = storage_layout_entries(gcx, gcx.storage_layout(id).items, &contract, &mut types);
+    StorageLayout { storage, types: (!types.is_empty()).then_some(types) }
+}
+
+fn storage_layout_entries<'gcx>(
+    gcx: Gcx<'gcx>,
+    items: &[StorageItem<'gcx>],
+    contract: &str,
+    types: &mut BTreeMap<String, StorageLayoutType>,
+) -> Vec<StorageLayoutEntry> {
+    items
+        .iter()
+        .map(|item| StorageLayoutEntry {
+            ast_id: item.var.index(),
+            contract: contract.to_string(),
+            label: gcx.item_name(item.var).to_string(),
+            offset: item.offset,
+            slot: item.slot.to_string(),
+            ty: storage_layout_type(gcx, item.ty, contract, types),
+        })
+        .collect()
+}
+
+/// Adds the given type and all the types it contains to `types`, and returns its identifier.
+fn storage_layout_type<'gcx>(
+    gcx: Gcx<'gcx>,
+    ty: Ty<'gcx>,
+    contract: &str,
+    types: &mut BTreeMap<String, StorageLayoutType>,
+) -> String {
+    let ty = ty.peel_refs();
+    let id = storage_type_id(gcx, ty, Some(DataLocation::Storage));
+    if types.contains_key(&id) {
+        return id;
+    }
+
+    let number_of_bytes = if ty.is_value_type() {
+        gcx.storage_bytes(ty).to_string()
+    } else {
+        gcx.storage_slots(ty).saturating_mul(alloy_primitives::U256::from(32)).to_string()
+    };
+    let mut entry = StorageLayoutType {
+        base: None,
+        encoding: "inplace",
+        key: None,
+        label: gcx.print_solc_param_ty(ty),
+        members: None,
+        number_of_bytes,
+        value: None,
+    };
+    match ty.kind {
+        TyKind::Elementary(ElementaryType::String | ElementaryType::Bytes) => {
+            entry.encoding = "bytes";
+        }
+        TyKind::Mapping(key, value) => {
+            entry.encoding = "mapping";
+            entry.key = Some(storage_type_id(gcx, key.peel_refs(), Some(DataLocation::Memory)));
+            entry.value = Some(storage_layout_type(gcx, value, contract, types));
+        }
+        TyKind::DynArray(elem) => {
+            entry.encoding = "dynamic_array";
+            entry.base = Some(storage_layout_type(gcx, elem, contract, types));
+        }
+        TyKind::Array(elem, _) => {
+            entry.base = Some(storage_layout_type(gcx, elem, contract, types));
+        }
+        TyKind::Struct(s) => {
+            // Insert a placeholder first to stop recursion through recursive structs.
+            types.insert(id.clone(), entry);
+            let members = gcx.struct_storage_layout(s).items;
+            let members = storage_layout_entries(gcx, members, contract, types);
+            types.get_mut(&id).unwrap().members = Some(members);
+            return id;
+        }
+        _ => {}
+    }
+    types.insert(id.clone(), entry);
+    id
+}
+
+/// Returns the solc type identifier of the given type.
+///
+/// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/ast/Types.h#L186>
+fn storage_type_id<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>, loc: Option<DataLocation>) -> String {
+    let loc_suffix = |loc: Option<DataLocation>| match loc {
+        Some(DataLocation::Storage) => "_storage",
+        Some(DataLocation::Memory) => "_memory_ptr",
+        Some(DataLocation::Calldata) => "_calldata_ptr",
+        Some(DataLocation::Transient) => "_transient",
+        None => "",
+    };
+    let inner = |ty: Ty<'gcx>| {
+        let (ty, inner_loc) = match ty.kind {
+            TyKind::Ref(ty, inner_loc) => (ty, Some(inner_loc)),
+            _ => (ty, loc),
+        };
+        storage_type_id(gcx, ty, inner_loc)
+    };
+    match ty.kind {
+        TyKind::Elementary(ElementaryType::Address(true)) => "t_address_payable".to_string(),
+        TyKind::Elementary(ty @ (ElementaryType::String | ElementaryType::Bytes)) => {
+            let mut s = String::from("t_");
+            ty.write_abi_str(&mut s).unwrap();
+            s + loc_suffix(loc)
+        }
+        TyKind::Elementary(ty) => {
+            let mut s = String::from("t_");
+            ty.write_abi_str(&mut s).unwrap();
+            s
+        }
+        TyKind::Contract(id) => format!("t_contract({}){}", gcx.item_name(id), id.index()),
+        TyKind::Enum(id) => format!("t_enum({}){}", gcx.item_name(id), id.index()),
+        TyKind::Udvt(_, id) => {
+            format!("t_userDefinedValueType({}){}", gcx.item_name(id), id.index())
+        }
+        TyKind::Struct(id) => {
+            format!("t_struct({}){}{}", gcx.item_name(id), id.index(), loc_suffix(loc))
+        }
+        TyKind::DynArray(elem) => format!("t_array({})dyn{}", inner(elem), loc_suffix(loc)),
+        TyKind::Array(elem, len) => format!("t_array({}){len}{}", inner(elem), loc_suffix(loc)),
+        TyKind::Mapping(key, value) => {
+            let key = storage_type_id(gcx, key.peel_refs(), Some(DataLocation::Memory));
+            format!("t_mapping({key},{})", inner(value))
+        }
+        TyKind::FnPtr(f) => {
+            let kind =
+                if f.visibility == hir::Visibility::External { "external" } else { "internal" };
+            let tys = |tys: &[Ty<'gcx>]| {
+                tys.iter()
+                    .map(|&ty| storage_type_id(gcx, ty.peel_refs(), ty.loc()))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            };
+            format!(
+                "t_function_{kind}_{}({})returns({})",
+                f.state_mutability,
+                tys(f.parameters),
+                tys(f.returns)
+            )
+        }
+        TyKind::Ref(ty, loc) => storage_type_id(gcx, ty, Some(loc)),
+        _ => format!("t_{}", gcx.print_solc_param_ty(ty)),
+    }
+}
+
 fn out_writer(path: Option<&Path>) -> io::Result<impl io::Write> {
     let out: Box<dyn io::Write> = if let Some(path) = path {
         Box::new(std::fs::File::create(path)?)
@@ -10,18 +10,22 @@ use solar_data_structures::{
     index::{Idx, IndexVec},
     newtype_index,
 };
-use solar_interface::{Ident, Span, diagnostics::ErrorGuaranteed, source_map::SourceFile};
+use solar_interface::{
+    Ident, Span, Symbol, diagnostics::ErrorGuaranteed, source_map::SourceFile, sym,
+};
 use std::{cell::Cell, fmt, ops::ControlFlow, sync::Arc};
 use strum::EnumIs;
 
 pub use ast::{
     BinOp, BinOpKind, ContractKind, DataLocation, ElementaryType, FunctionKind, Lit,
-    StateMutability, UnOp, UnOpKind, VarMut, Visibility,
+    StateMutability, UnOp, UnOpKind, UserDefinableOperator, VarMut, Visibility,
 };
 
 mod visit;
 pub use visit::Visit;
 
+pub mod yul;
+
 /// HIR arena allocator.
 pub struct Arena {
     bump: bumpalo::Bump,
@@ -442,6 +446,10 @@ pub struct Source<'hir> {
     pub imports: &'hir [(ast::ItemId, SourceId)],
     /// The source items.
     pub items: &'hir [ItemId],
+    /// The file-level `using for` directives.
+    pub usings: &'hir [UsingDirective<'hir>],
+    /// The SPDX license identifier.
+    pub license: Option<Symbol>,
 }
 
 impl fmt::Debug for Source<'_> {
@@ -450,6 +458,7 @@ impl fmt::Debug for Source<'_> {
             .field("file", &self.file.name)
             .field("imports", &self.imports)
             .field("items", &self.items)
+            .field("usings", &self.usings)
             .finish()
     }
 }
@@ -512,6 +521,21 @@ impl<'hir> Item<'_, 'hir> {
         }
     }
 
+    /// Returns the doc-comments of the item.
+    #[inline]
+    pub fn docs(self) -> &'hir [DocComment<'hir>] {
+        match self {
+            Item::Contract(c) => c.docs,
+            Item::Function(f) => f.docs,
+            Item::Struct(s) => s.docs,
+            Item::Enum(e) => e.docs,
+            Item::Udvt(u) => u.docs,
+            Item::Error(e) => e.docs,
+            Item::Event(e) => e.docs,
+            Item::Variable(v) => v.docs,
+        }
+    }
+
     /// Returns the contract ID if this item is part of a contract.
     #[inline]
     pub fn contract(self) -> Option<ContractId> {
@@ -662,6 +686,28 @@ impl ItemId {
     }
 }
 
+/// A doc-comment: `/// foo`, `/** bar */`.
+///
+/// Lowered from [`ast::DocComment`].
+#[derive(Clone, Copy, Debug)]
+pub struct DocComment<'hir> {
+    /// The comment kind.
+    pub kind: ast::token::CommentKind,
+    /// The comment's span including its "quotes" (`//`, `/**`).
+    pub span: Span,
+    /// The comment's contents excluding its "quotes" (`//`, `/**`).
+    pub symbol: Symbol,
+    /// The comment's NatSpec items.
+    pub natspec: &'hir [ast::NatSpecItem],
+}
+
+impl DocComment<'_> {
+    /// Returns the content of a natspec item excluding its tag.
+    pub fn natspec_content(&self, item: &ast::NatSpecItem) -> &str {
+        &self.symbol.as_str()[item.content_range()]
+    }
+}
+
 /// A contract, interface, or library.
 #[derive(Debug)]
 pub struct Contract<'hir> {
@@ -669,6 +715,8 @@ pub struct Contract<'hir> {
     pub source: SourceId,
     /// The contract span.
     pub span: Span,
+    /// The contract's doc-comments.
+    pub docs: &'hir [DocComment<'hir>],
     /// The contract name.
     pub name: Ident,
     /// The contract kind.
@@ -677,6 +725,8 @@ pub struct Contract<'hir> {
     pub bases: &'hir [ContractId],
     /// The base arguments, as declared in the source code.
     pub bases_args: &'hir [Modifier<'hir>],
+    /// The storage layout base slot expression (`layout at <expr>`), if any.
+    pub layout: Option<&'hir Expr<'hir>>,
     /// The linearized contract bases.
     ///
     /// The first element is always the contract itself, followed by its bases in order of
@@ -701,6 +751,10 @@ pub struct Contract<'hir> {
     /// Note that this only includes items defined in the contract itself, not inherited items.
     /// For getting all items, use [`Hir::contract_items`].
     pub items: &'hir [ItemId],
+    /// The `using for` directives declared in the contract.
+    ///
+    /// Note that these are not inherited.
+    pub usings: &'hir [UsingDirective<'hir>],
 }
 
 impl Contract<'_> {
@@ -777,6 +831,28 @@ pub struct Modifier<'hir> {
     pub args: CallArgs<'hir>,
 }
 
+/// A `using for` directive: `using { A, B.add as + } for uint256 global;`.
+#[derive(Debug)]
+pub struct UsingDirective<'hir> {
+    /// The directive span.
+    pub span: Span,
+    /// The attached functions.
+    pub list: UsingList<'hir>,
+    /// The type for which this directive applies. This is `*` if the value is `None`.
+    pub ty: Option<Type<'hir>>,
+    /// Whether the directive is `global`.
+    pub global: bool,
+}
+
+/// The attached functions of a [`UsingDirective`].
+#[derive(Clone, Copy, Debug)]
+pub enum UsingList<'hir> {
+    /// `using L for T;`: attaches all non-private functions of the library `L`.
+    Library(ContractId),
+    /// `using { f, L.g as + } for T;`: attaches the listed functions, optionally as operators.
+    Functions(&'hir [(FunctionId, Option<UserDefinableOperator>)]),
+}
+
 /// A function.
 #[derive(Debug)]
 pub struct Function<'hir> {
@@ -786,6 +862,8 @@ pub struct Function<'hir> {
     pub contract: Option<ContractId>,
     /// The function span.
     pub span: Span,
+    /// The function's doc-comments.
+    pub docs: &'hir [DocComment<'hir>],
     /// The function name.
     /// Only `None` if this is a constructor, fallback, or receive function.
     pub name: Option<Ident>,
@@ -795,6 +873,9 @@ pub struct Function<'hir> {
     pub visibility: Visibility,
     /// The state mutability of the function.
     pub state_mutability: StateMutability,
+    /// The span of the state mutability keyword, or an empty span where it would be inserted if
+    /// the state mutability is implicit.
+    pub state_mutability_span: Span,
     /// Modifiers, or base classes if this is a constructor.
     pub modifiers: &'hir [Modifier<'hir>],
     /// Whether this function is marked with the `virtual` keyword.
@@ -876,6 +957,8 @@ pub struct Struct<'hir> {
     pub contract: Option<ContractId>,
     /// The struct span.
     pub span: Span,
+    /// The struct's doc-comments.
+    pub docs: &'hir [DocComment<'hir>],
     /// The struct name.
     pub name: Ident,
     pub fields: &'hir [VariableId],
@@ -890,6 +973,8 @@ pub struct Enum<'hir> {
     pub contract: Option<ContractId>,
     /// The enum span.
     pub span: Span,
+    /// The enum's doc-comments.
+    pub docs: &'hir [DocComment<'hir>],
     /// The enum name.
     pub name: Ident,
     /// The enum variants.
@@ -905,6 +990,8 @@ pub struct Udvt<'hir> {
     pub contract: Option<ContractId>,
     /// The UDVT span.
     pub span: Span,
+    /// The UDVT's doc-comments.
+    pub docs: &'hir [DocComment<'hir>],
     /// The UDVT name.
     pub name: Ident,
     /// The UDVT type.
@@ -920,6 +1007,8 @@ pub struct Event<'hir> {
     pub contract: Option<ContractId>,
     /// The event span.
     pub span: Span,
+    /// The event's doc-comments.
+    pub docs: &'hir [DocComment<'hir>],
     /// The event name.
     pub name: Ident,
     /// Whether this event is anonymous.
@@ -936,6 +1025,8 @@ pub struct Error<'hir> {
     pub contract: Option<ContractId>,
     /// The error span.
     pub span: Span,
+    /// The error's doc-comments.
+    pub docs: &'hir [DocComment<'hir>],
     /// The error name.
     pub name: Ident,
     pub parameters: &'hir [VariableId],
@@ -952,6 +1043,8 @@ pub struct Variable<'hir> {
     pub function: Option<FunctionId>,
     /// The variable's span.
     pub span: Span,
+    /// The variable's doc-comments.
+    pub docs: &'hir [DocComment<'hir>],
     /// The kind of variable.
     pub kind: VarKind,
     /// The variable's type.
@@ -962,6 +1055,9 @@ pub struct Variable<'hir> {
     pub visibility: Option<Visibility>,
     pub mutability: Option<VarMut>,
     pub data_location: Option<DataLocation>,
+    /// The span of the data location keyword, or an empty span after the type if the data location
+    /// is implicit.
+    pub data_location_span: Span,
     pub override_: bool,
     pub overrides: &'hir [ContractId],
     pub indexed: bool,
@@ -978,12 +1074,14 @@ impl<'hir> Variable<'hir> {
             contract: None,
             function: None,
             span: Span::DUMMY,
+            docs: &[],
             kind,
             ty,
             name,
             visibility: None,
             mutability: None,
             data_location: None,
+            data_location_span: Span::DUMMY,
             override_: false,
             overrides: &[],
             indexed: false,
@@ -1160,9 +1258,9 @@ pub struct Stmt<'hir> {
 /// A kind of statement.
 #[derive(Debug)]
 pub enum StmtKind<'hir> {
-    // TODO: Yul to HIR.
-    // /// An assembly block, with optional flags: `assembly "evmasm" (...) { ... }`.
-    // Assembly(StmtAssembly<'hir>),
+    /// An assembly block, with optional flags: `assembly "evmasm" (...) { ... }`.
+    Assembly(&'hir StmtAssembly<'hir>),
+
     /// A single-variable declaration statement: `uint256 foo = 42;`.
     DeclSingle(VariableId),
 
@@ -1214,6 +1312,26 @@ pub enum StmtKind<'hir> {
     Err(ErrorGuaranteed),
 }
 
+/// An assembly block, with optional flags: `assembly "evmasm" (...) { ... }`.
+///
+/// Reference: <https://docs.soliditylang.org/en/latest/grammar.html#a4.SolidityParser.assemblyStatement>
+#[derive(Debug)]
+pub struct StmtAssembly<'hir> {
+    /// The assembly block dialect.
+    pub dialect: Option<ast::StrLit>,
+    /// Additional flags.
+    pub flags: &'hir [ast::StrLit],
+    /// The assembly code.
+    pub code: yul::Code<'hir>,
+}
+
+impl StmtAssembly<'_> {
+    /// Returns `true` if the block is marked with the `"memory-safe"` flag.
+    pub fn is_memory_safe(&self) -> bool {
+        self.flags.iter().any(|flag| flag.value == sym::memory_dash_safe)
+    }
+}
+
 /// A try statement: `try fooBar(42) returns (...) { ... } catch (...) { ... }`.
 ///
 /// Reference: <https://docs.soliditylang.org/en/latest/grammar.html#a4.SolidityParser.tryStatement>
@@ -1411,7 +1529,7 @@ pub enum ExprKind<'hir> {
     /// A `type()` expression: `type(uint256)`.
     TypeCall(Type<'hir>),
 
-    /// An elementary type name: `uint256`.
+    /// An elementary type name, or an array of one: `uint256`, `uint256[]`.
     Type(Type<'hir>),
 
     /// A unary operation: `!x`, `-x`, `x++`.
@@ -1660,14 +1778,14 @@ mod tests {
         assert_size::<Hir<'_>>(str!["216"]);
 
         assert_size::<Item<'_, '_>>(str!["16"]);
-        assert_size::<Contract<'_>>(str!["120"]);
-        assert_size::<Function<'_>>(str!["136"]);
-        assert_size::<Struct<'_>>(str!["48"]);
-        assert_size::<Enum<'_>>(str!["48"]);
-        assert_size::<Udvt<'_>>(str!["56"]);
-        assert_size::<Error<'_>>(str!["48"]);
-        assert_size::<Event<'_>>(str!["48"]);
-        assert_size::<Variable<'_>>(str!["96"]);
+        assert_size::<Contract<'_>>(str!["160"]);
+        assert_size::<Function<'_>>(str!["160"]);
+        assert_size::<Struct<'_>>(str!["64"]);
+        assert_size::<Enum<'_>>(str!["64"]);
+        assert_size::<Udvt<'_>>(str!["72"]);
+        assert_size::<Error<'_>>(str!["64"]);
+        assert_size::<Event<'_>>(str!["64"]);
+        assert_size::<Variable<'_>>(str!["120"]);
 
         assert_size::<TypeKind<'_>>(str!["16"]);
         assert_size::<Type<'_>>(str!["24"]);
@@ -51,6 +51,9 @@ pub trait Visit<'hir> {
         for base in contract.bases_args {
             self.visit_modifier(base)?;
         }
+        if let Some(layout) = contract.layout {
+            self.visit_expr(layout)?;
+        }
         visit_nested_items(self, contract.items)
     }
 
@@ -59,7 +62,7 @@ pub trait Visit<'hir> {
     }
 
     fn visit_function(&mut self, func: &'hir Function<'hir>) -> ControlFlow<Self::BreakValue> {
-        let Function { source: _, contract: _, span: _, name: _, kind: _, visibility: _, state_mutability: _, modifiers, marked_virtual: _, virtual_: _, override_: _, overrides: _, parameters, returns, body, body_span: _, gettee: _ } = func;
+        let Function { source: _, contract: _, span: _, docs: _, name: _, kind: _, visibility: _, state_mutability: _, state_mutability_span: _, modifiers, marked_virtual: _, virtual_: _, override_: _, overrides: _, parameters, returns, body, body_span: _, gettee: _ } = func;
         for &param in parameters.iter() {
             self.visit_nested_var(param)?;
         }
@@ -251,6 +254,7 @@ pub trait Visit<'hir> {
                     }
                 }
             }
+            StmtKind::Assembly(assembly) => self.visit_stmt_assembly(assembly)?,
             StmtKind::Expr(expr) => self.visit_expr(expr)?,
             StmtKind::Placeholder => {}
             StmtKind::Err(_guar) => {}
@@ -258,6 +262,83 @@ pub trait Visit<'hir> {
         ControlFlow::Continue(())
     }
 
+    fn visit_stmt_assembly(
+        &mut self,
+        assembly: &'hir StmtAssembly<'hir>,
+    ) -> ControlFlow<Self::BreakValue> {
+        self.visit_yul_code(&assembly.code)
+    }
+
+    /// Visits the block and the function definitions of a block of Yul code.
+    ///
+    /// Function definitions are visited after the block, through [`yul::Code::functions`].
+    fn visit_yul_code(&mut self, code: &'hir yul::Code<'hir>) -> ControlFlow<Self::BreakValue> {
+        self.visit_yul_block(&code.block)?;
+        for func in code.functions {
+            self.visit_yul_block(&func.body)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn visit_yul_block(&mut self, block: &'hir yul::Block<'hir>) -> ControlFlow<Self::BreakValue> {
+        for stmt in block.stmts {
+            self.visit_yul_stmt(stmt)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn visit_yul_stmt(&mut self, stmt: &'hir yul::Stmt<'hir>) -> ControlFlow<Self::BreakValue> {
+        match stmt.kind {
+            yul::StmtKind::Block(ref block) => self.visit_yul_block(block)?,
+            yul::StmtKind::Assign(lhs, rhs) => {
+                for expr in lhs {
+                    self.visit_yul_expr(expr)?;
+                }
+                self.visit_yul_expr(rhs)?;
+            }
+            yul::StmtKind::Expr(expr) => self.visit_yul_expr(expr)?,
+            yul::StmtKind::If(cond, ref block) => {
+                self.visit_yul_expr(cond)?;
+                self.visit_yul_block(block)?;
+            }
+            yul::StmtKind::For(for_) => {
+                self.visit_yul_block(&for_.init)?;
+                self.visit_yul_expr(&for_.cond)?;
+                self.visit_yul_block(&for_.step)?;
+                self.visit_yul_block(&for_.body)?;
+            }
+            yul::StmtKind::Switch(switch) => {
+                self.visit_yul_expr(&switch.selector)?;
+                for case in switch.cases {
+                    self.visit_yul_block(&case.body)?;
+                }
+            }
+            yul::StmtKind::Leave | yul::StmtKind::Break | yul::StmtKind::Continue => {}
+            yul::StmtKind::FunctionDef(_) => {}
+            yul::StmtKind::VarDecl(_, value) => {
+                if let Some(value) = value {
+                    self.visit_yul_expr(value)?;
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn visit_yul_expr(&mut self, expr: &'hir yul::Expr<'hir>) -> ControlFlow<Self::BreakValue> {
+        match expr.kind {
+            yul::ExprKind::Call(ref call) => {
+                for arg in call.arguments {
+                    self.visit_yul_expr(arg)?;
+                }
+            }
+            yul::ExprKind::Var(_) => {}
+            yul::ExprKind::External(_) => {}
+            yul::ExprKind::Lit(_) => {}
+            yul::ExprKind::Err(_guar) => {}
+        }
+        ControlFlow::Continue(())
+    }
+
     fn visit_ty(&mut self, ty: &'hir Type<'hir>) -> ControlFlow<Self::BreakValue> {
         match ty.kind {
             TypeKind::Elementary(_) => {}
@@ -0,0 +1,272 @@
+//! Yul HIR.
+
+use super::Res;
+use solar_ast::{Lit, StrLit};
+use solar_data_structures::newtype_index;
+use solar_interface::{Ident, Span, Symbol, diagnostics::ErrorGuaranteed, kw, sym};
+
+newtype_index! {
+    /// A Yul variable ID. Indexes into [`Code::variables`].
+    pub struct VarId;
+
+    /// A Yul function ID. Indexes into [`Code::functions`].
+    pub struct FunctionId;
+}
+
+/// A Yul object: `object "name" { code { ... } ... }`.
+#[derive(Debug)]
+pub struct Object<'hir> {
+    pub span: Span,
+    pub name: StrLit,
+    pub code: Code<'hir>,
+    pub children: &'hir [Self],
+    pub data: &'hir [Data<'hir>],
+}
+
+impl Object<'_> {
+    /// Returns `true` if the object has a sub-object or data section with the given name, or if
+    /// the name refers to the object itself.
+    pub fn has_data_object(&self, name: Symbol) -> bool {
+        self.name.value == name
+            || self.children.iter().any(|child| child.name.value == name)
+            || self.data.iter().any(|data| data.name.value == name)
+    }
+}
+
+/// A Yul data section: `data "name" "..."`.
+#[derive(Debug)]
+pub struct Data<'hir> {
+    pub span: Span,
+    pub name: StrLit,
+    pub data: &'hir Lit<'hir>,
+}
+
+/// A block of Yul code, together with the variables and functions declared in it.
+#[derive(Debug)]
+pub struct Code<'hir> {
+    /// The outermost block.
+    pub block: Block<'hir>,
+    /// The variables declared in the code, indexed by [`VarId`].
+    pub variables: &'hir [Ident],
+    /// The functions defined in the code, indexed by [`FunctionId`].
+    pub functions: &'hir [Function<'hir>],
+}
+
+impl<'hir> Code<'hir> {
+    /// Returns the name of the given variable.
+    #[inline]
+    pub fn variable(&self, id: VarId) -> Ident {
+        self.variables[id.index()]
+    }
+
+    /// Returns the given function.
+    #[inline]
+    pub fn function(&self, id: FunctionId) -> &Function<'hir> {
+        &self.functions[id.index()]
+    }
+}
+
+/// A block of Yul statements: `{ ... }`.
+#[derive(Clone, Copy, Debug)]
+pub struct Block<'hir> {
+    /// The span of the block, including the `{` and `}`.
+    pub span: Span,
+    /// The statements in the block.
+    pub stmts: &'hir [Stmt<'hir>],
+}
+
+impl<'hir> std::ops::Deref for Block<'hir> {
+    type Target = [Stmt<'hir>];
+
+    fn deref(&self) -> &Self::Target {
+        self.stmts
+    }
+}
+
+/// A Yul statement.
+#[derive(Debug)]
+pub struct Stmt<'hir> {
+    /// The span of the statement.
+    pub span: Span,
+    /// The kind of statement.
+    pub kind: StmtKind<'hir>,
+}
+
+/// A kind of Yul statement.
+#[derive(Debug)]
+pub enum StmtKind<'hir> {
+    /// A blocked scope: `{ ... }`.
+    Block(Block<'hir>),
+
+    /// An assignment statement: `x := 1` or `x, y := foo()`.
+    ///
+    /// The left-hand side expressions are always either [`ExprKind::Var`], [`ExprKind::External`],
+    /// or [`ExprKind::Err`].
+    Assign(&'hir [Expr<'hir>], &'hir Expr<'hir>),
+
+    /// An expression statement. This can only be a function call.
+    Expr(&'hir Expr<'hir>),
+
+    /// An if statement: `if lt(a, b) { ... }`.
+    If(&'hir Expr<'hir>, Block<'hir>),
+
+    /// A for statement: `for {let i := 0} lt(i,10) {i := add(i,1)} { ... }`.
+    For(&'hir StmtFor<'hir>),
+
+    /// A switch statement: `switch expr case 0 { ... } default { ... }`.
+    Switch(&'hir StmtSwitch<'hir>),
+
+    /// A leave statement: `leave`.
+    Leave,
+
+    /// A break statement: `break`.
+    Break,
+
+    /// A continue statement: `continue`.
+    Continue,
+
+    /// A function definition statement: `function f() { ... }`.
+    FunctionDef(FunctionId),
+
+    /// A variable declaration statement: `let x := 0`.
+    VarDecl(&'hir [VarId], Option<&'hir Expr<'hir>>),
+}
+
+/// A Yul for statement: `for {let i := 0} lt(i,10) {i := add(i,1)} { ... }`.
+///
+/// The variables declared in `init` are visible in the rest of the statement.
+#[derive(Debug)]
+pub struct StmtFor<'hir> {
+    pub init: Block<'hir>,
+    pub cond: Expr<'hir>,
+    pub step: Block<'hir>,
+    pub body: Block<'hir>,
+}
+
+/// A Yul switch statement: `switch expr case 0 { ... } default { ... }`.
+#[derive(Debug)]
+pub struct StmtSwitch<'hir> {
+    pub selector: Expr<'hir>,
+    /// The cases of the switch statement. Includes the default case in the last position, if any.
+    pub cases: &'hir [StmtSwitchCase<'hir>],
+}
+
+impl<'hir> StmtSwitch<'hir> {
+    /// Returns the default case of the switch statement, if any.
+    pub fn default_case(&self) -> Option<&StmtSwitchCase<'hir>> {
+        self.cases.last().filter(|case| case.constant.is_none())
+    }
+}
+
+/// A case of a Yul switch statement.
+#[derive(Debug)]
+pub struct StmtSwitchCase<'hir> {
+    pub span: Span,
+    /// The constant of the case, if any. `None` for the default case.
+    pub constant: Option<&'hir Lit<'hir>>,
+    pub body: Block<'hir>,
+}
+
+/// A Yul function definition: `function f() -> a, b { ... }`.
+#[derive(Debug)]
+pub struct Function<'hir> {
+    /// The span of the function definition statement.
+    pub span: Span,
+    pub name: Ident,
+    pub parameters: &'hir [VarId],
+    pub returns: &'hir [VarId],
+    pub body: Block<'hir>,
+}
+
+/// A Yul expression.
+#[derive(Debug)]
+pub struct Expr<'hir> {
+    /// The span of the expression.
+    pub span: Span,
+    /// The kind of expression.
+    pub kind: ExprKind<'hir>,
+}
+
+/// A kind of Yul expression.
+#[derive(Debug)]
+pub enum ExprKind<'hir> {
+    /// A reference to a Yul variable.
+    Var(VarId),
+    /// A reference to a Solidity declaration outside of the assembly block, with an optional
+    /// suffix: `x`, `x.slot`.
+    External(ExternalRef),
+    /// A function call: `foo(a, b)`.
+    Call(ExprCall<'hir>),
+    /// A literal.
+    Lit(&'hir Lit<'hir>),
+    /// An error occurred while resolving the expression.
+    Err(ErrorGuaranteed),
+}
+
+/// A reference to a Solidity declaration from inside of an assembly block.
+#[derive(Clone, Copy, Debug)]
+pub struct ExternalRef {
+    /// The name of the declaration.
+    pub name: Ident,
+    /// The resolved declaration.
+    pub res: Res,
+    /// The suffix, if any.
+    pub suffix: Option<Suffix>,
+}
+
+/// The suffix of an [`ExternalRef`]: `.slot`, `.offset`, `.length`, `.address`, `.selector`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Suffix {
+    /// `.slot`.
+    Slot,
+    /// `.offset`.
+    Offset,
+    /// `.length`.
+    Length,
+    /// `.address`.
+    Address,
+    /// `.selector`.
+    Selector,
+}
+
+impl Suffix {
+    /// Parses a suffix from the given symbol.
+    pub fn from_symbol(name: Symbol) -> Option<Self> {
+        Some(match name {
+            sym::slot => Self::Slot,
+            sym::offset => Self::Offset,
+            sym::length => Self::Length,
+            kw::Address => Self::Address,
+            sym::selector => Self::Selector,
+            _ => return None,
+        })
+    }
+
+    /// Returns the name of the suffix.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Slot => "slot",
+            Self::Offset => "offset",
+            Self::Length => "length",
+            Self::Address => "address",
+            Self::Selector => "selector",
+        }
+    }
+}
+
+/// A Yul function call expression: `foo(a, b)`.
+#[derive(Debug)]
+pub struct ExprCall<'hir> {
+    pub name: Ident,
+    pub callee: Callee,
+    pub arguments: &'hir [Expr<'hir>],
+}
+
+/// The resolved callee of a Yul function call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Callee {
+    /// A builtin function of the EVM dialect.
+    Builtin,
+    /// A user-defined function.
+    Function(FunctionId),
+}
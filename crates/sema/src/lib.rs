@@ -22,6 +22,7 @@ pub use solar_interface as interface;
 mod ast_lowering;
 mod ast_passes;
 
+mod codegen;
 mod compiler;
 pub use compiler::{Compiler, CompilerRef};
 
@@ -29,6 +30,7 @@ mod parse;
 pub use parse::{ParsingContext, Source, Sources};
 
 pub mod builtins;
+mod cfg;
 pub mod eval;
 
 pub mod hir;
@@ -39,7 +41,10 @@ pub use ty::{Gcx, Ty};
 
 mod typeck;
 
+pub mod yul;
+
 mod emit;
+mod natspec;
 
 pub mod stats;
 
@@ -76,7 +81,12 @@ pub(crate) fn lower(compiler: &mut CompilerRef<'_>) -> Result<ControlFlow<()>> {
         }
     }
 
-    if sess.opts.language.is_yul() || gcx.advance_stage(CompilerStage::Lowering).is_break() {
+    if gcx.advance_stage(CompilerStage::Lowering).is_break() {
+        return Ok(ControlFlow::Break(()));
+    }
+
+    if sess.opts.language.is_yul() {
+        yul_objects(gcx)?;
         return Ok(ControlFlow::Break(()));
     }
 
@@ -118,17 +128,66 @@ fn analysis(gcx: Gcx<'_>) -> Result<ControlFlow<()>> {
     });
     gcx.sess.dcx.has_errors()?;
 
-    typeck::check(gcx);
+    let results = typeck::check(gcx);
+    gcx.sess.dcx.has_errors()?;
+
+    cfg::check(gcx);
     gcx.sess.dcx.has_errors()?;
 
     if !gcx.sess.opts.emit.is_empty() {
-        emit::emit(gcx);
+        emit::emit(gcx, &results);
         gcx.sess.dcx.has_errors()?;
     }
 
     Ok(ControlFlow::Continue(()))
 }
 
+/// Lowers and analyzes the parsed Yul objects, and emits the requested outputs.
+#[instrument(level = "debug", skip_all)]
+fn yul_objects(gcx: Gcx<'_>) -> Result<()> {
+    let sess = gcx.sess;
+    let objects = gcx
+        .sources
+        .iter()
+        .filter_map(|source| {
+            let object = ast_lowering::lower_yul_object(sess, gcx.arena(), source.yul.as_ref()?);
+            yul::check_object(sess, &object);
+            Some((&*source.file, object))
+        })
+        .collect::<Vec<_>>();
+    sess.dcx.has_errors()?;
+
+    if !sess.opts.emit.is_empty() {
+        emit::emit_yul(gcx, &objects);
+        sess.dcx.has_errors()?;
+    }
+
+    if sess.opts.unstable.interpret_yul {
+        for (_, object) in &objects {
+            interpret_yul_object(object);
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs a Yul object in the interpreter, and prints the trace and the final state.
+///
+/// Execution errors, such as exceeding the step limit, are printed at the end of the trace.
+fn interpret_yul_object(object: &hir::yul::Object<'_>) {
+    let mut interpreter = yul::interpreter::Interpreter::new(yul::interpreter::MockHost::default());
+    println!("Trace:");
+    match interpreter.run_object(object) {
+        Ok(outcome) => {
+            for entry in &outcome.trace {
+                println!("  {entry}");
+            }
+        }
+        Err(e) => println!("  {e}"),
+    }
+    print!("{}", interpreter.dump());
+}
+
 fn dump_ast(sess: &Session, sources: &Sources<'_>, paths: Option<&[String]>) -> Result<()> {
     if let Some(paths) = paths {
         for path in paths {
@@ -0,0 +1,658 @@
+//! NatSpec documentation.
+//!
+//! Reference: <https://docs.soliditylang.org/en/latest/natspec-format.html>
+
+use crate::{
+    hir::{self, ContractId, FunctionId, ItemId},
+    ty::Gcx,
+};
+use serde::Serialize;
+use solar_ast::{NatSpecKind, PathSlice, token::CommentKind};
+use solar_data_structures::map::FxHashSet;
+use solar_interface::{Ident, Span, Symbol, error_code};
+use std::collections::BTreeMap;
+
+/// User documentation in the solc format.
+#[derive(Serialize)]
+pub(crate) struct UserDoc {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    errors: BTreeMap<String, Vec<UserDocItem>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    events: BTreeMap<String, UserDocItem>,
+    kind: &'static str,
+    methods: BTreeMap<String, UserDocItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notice: Option<String>,
+    version: u32,
+}
+
+#[derive(Serialize)]
+struct UserDocItem {
+    notice: String,
+}
+
+/// Developer documentation in the solc format.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DevDoc {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(flatten)]
+    custom: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    errors: BTreeMap<String, Vec<DevDocItem>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    events: BTreeMap<String, DevDocItem>,
+    kind: &'static str,
+    methods: BTreeMap<String, DevDocItem>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    state_variables: BTreeMap<String, DevDocItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    version: u32,
+}
+
+#[derive(Default, Serialize)]
+struct DevDocItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(flatten)]
+    custom: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    params: BTreeMap<String, String>,
+    #[serde(rename = "return", skip_serializing_if = "Option::is_none")]
+    return_: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    returns: BTreeMap<String, String>,
+}
+
+impl DevDocItem {
+    fn new(tags: &[DocTag]) -> Self {
+        let mut item = Self {
+            author: extract(tags, "author"),
+            details: extract(tags, "dev"),
+            ..Default::default()
+        };
+        for tag in tags {
+            if tag.name == "param" {
+                item.params.insert(tag.param.clone(), tag.content.clone());
+            } else if tag.name.starts_with("custom") {
+                item.custom.insert(tag.name.clone(), tag.content.clone());
+            }
+        }
+        item
+    }
+
+    fn is_empty(&self) -> bool {
+        self.author.is_none()
+            && self.custom.is_empty()
+            && self.details.is_none()
+            && self.params.is_empty()
+            && self.return_.is_none()
+            && self.returns.is_empty()
+    }
+}
+
+/// A documentation tag, such as `@notice` or `@param`.
+#[derive(Clone, Debug)]
+pub(crate) struct DocTag {
+    /// The tag name, without the leading `@`.
+    pub(crate) name: String,
+    /// The parameter name, for `@param` tags.
+    pub(crate) param: String,
+    /// The tag content.
+    pub(crate) content: String,
+}
+
+/// Returns the user documentation of the given contract.
+pub(crate) fn userdoc(gcx: Gcx<'_>, id: ContractId) -> UserDoc {
+    let c = gcx.hir.contract(id);
+    let mut doc = UserDoc {
+        errors: BTreeMap::new(),
+        events: BTreeMap::new(),
+        kind: "user",
+        methods: BTreeMap::new(),
+        notice: extract(&parse(c.docs), "notice"),
+        version: 1,
+    };
+    let notice = |tags: &[DocTag]| extract(tags, "notice").map(|notice| UserDocItem { notice });
+    if let Some(ctor) = c.ctor
+        && let Some(item) = notice(&parse(gcx.hir.function(ctor).docs))
+    {
+        doc.methods.insert("constructor".into(), item);
+    }
+    for f in gcx.interface_functions(id) {
+        if let Some(item) = notice(&function_tags(gcx, f.id)) {
+            doc.methods.insert(gcx.item_signature(f.id.into()).to_string(), item);
+        }
+    }
+    for item_id in interface_events_and_errors(gcx, id) {
+        let Some(item) = notice(&parse(gcx.hir.item(item_id).docs())) else { continue };
+        let signature = gcx.item_signature(item_id).to_string();
+        if matches!(item_id, ItemId::Error(_)) {
+            doc.errors.entry(signature).or_default().push(item);
+        } else {
+            doc.events.insert(signature, item);
+        }
+    }
+    doc
+}
+
+/// Returns the developer documentation of the given contract.
+pub(crate) fn devdoc(gcx: Gcx<'_>, id: ContractId) -> DevDoc {
+    let c = gcx.hir.contract(id);
+    let tags = parse(c.docs);
+    let mut doc = DevDoc {
+        author: extract(&tags, "author"),
+        custom: DevDocItem::new(&tags).custom,
+        details: extract(&tags, "dev"),
+        errors: BTreeMap::new(),
+        events: BTreeMap::new(),
+        kind: "dev",
+        methods: BTreeMap::new(),
+        state_variables: BTreeMap::new(),
+        title: extract(&tags, "title"),
+        version: 1,
+    };
+    if let Some(ctor) = c.ctor {
+        let item = DevDocItem::new(&parse(gcx.hir.function(ctor).docs));
+        if !item.is_empty() {
+            doc.methods.insert("constructor".into(), item);
+        }
+    }
+    for f in gcx.interface_functions(id) {
+        if gcx.hir.function(f.id).is_getter() {
+            continue;
+        }
+        let tags = function_tags(gcx, f.id);
+        let mut item = DevDocItem::new(&tags);
+        item.returns = returns(gcx, f.id, &tags);
+        if !item.is_empty() {
+            doc.methods.insert(gcx.item_signature(f.id.into()).to_string(), item);
+        }
+    }
+    for var_id in c.variables() {
+        let var = gcx.hir.variable(var_id);
+        let tags = match var.getter {
+            Some(getter) => function_tags(gcx, getter),
+            None => parse(var.docs),
+        };
+        let mut item = DevDocItem::new(&tags);
+        if tags.iter().filter(|tag| tag.name == "return").count() == 1 {
+            item.return_ = extract(&tags, "return");
+        }
+        if let Some(getter) = var.getter {
+            item.returns = returns(gcx, getter, &tags);
+        }
+        if !item.is_empty()
+            && let Some(name) = var.name
+        {
+            doc.state_variables.insert(name.to_string(), item);
+        }
+    }
+    for item_id in interface_events_and_errors(gcx, id) {
+        let item = DevDocItem::new(&parse(gcx.hir.item(item_id).docs()));
+        if item.is_empty() {
+            continue;
+        }
+        let signature = gcx.item_signature(item_id).to_string();
+        if matches!(item_id, ItemId::Error(_)) {
+            doc.errors.entry(signature).or_default().push(item);
+        } else {
+            doc.events.insert(signature, item);
+        }
+    }
+    doc
+}
+
+/// Validates the NatSpec documentation of an item.
+///
+/// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/analysis/DocStringTagParser.cpp>
+pub(crate) fn check(gcx: Gcx<'_>, id: ItemId) {
+    let item = gcx.hir.item(id);
+    let docs = last_group(item.docs());
+    if docs.is_empty() {
+        return;
+    }
+    let (valid_tags, description): (&[&str], _) = match item {
+        hir::Item::Contract(_) => (&["author", "title", "dev", "notice"], "contracts"),
+        hir::Item::Function(f) => match f.kind {
+            hir::FunctionKind::Constructor => {
+                (&["author", "dev", "notice", "param"], "constructor")
+            }
+            hir::FunctionKind::Modifier => (&["dev", "notice", "param", "inheritdoc"], "modifiers"),
+            _ => (&["dev", "notice", "return", "param", "inheritdoc"], "functions"),
+        },
+        hir::Item::Event(_) => (&["dev", "notice", "param"], "events"),
+        hir::Item::Error(_) => (&["dev", "notice", "param"], "errors"),
+        hir::Item::Variable(v) if v.is_state_variable() && v.is_public() => {
+            (&["dev", "notice", "return", "inheritdoc"], "public state variables")
+        }
+        hir::Item::Variable(v) if v.is_state_variable() => {
+            (&["dev", "notice", "inheritdoc"], "non-public state variables")
+        }
+        hir::Item::Variable(v) if v.is_file_level_variable() => (&["dev"], "file-level variables"),
+        _ => return,
+    };
+    // The function whose parameters and return values are documented.
+    let function_id = match id {
+        ItemId::Function(id) => Some(id),
+        ItemId::Variable(id) => gcx.hir.variable(id).getter,
+        _ => None,
+    };
+    let function = function_id.map(|id| gcx.hir.function(id));
+    let param_names = || {
+        item.parameters()
+            .unwrap_or_default()
+            .iter()
+            .chain(function.map_or(&[][..], |f| f.returns))
+            .filter_map(|&p| gcx.hir.variable(p).name)
+    };
+
+    let err = |code, msg: String, span: Span| gcx.dcx().err(msg).code(code).span(span).emit();
+    let mut returns = 0;
+    let mut inheritdoc = None;
+    for (i, doc) in docs.iter().enumerate() {
+        for (j, natspec) in doc.natspec.iter().enumerate() {
+            let span = natspec.span;
+            let tag = match &natspec.kind {
+                NatSpecKind::Title => "title",
+                NatSpecKind::Author => "author",
+                NatSpecKind::Notice => "notice",
+                NatSpecKind::Dev => "dev",
+                NatSpecKind::Param { .. } => "param",
+                NatSpecKind::Return { .. } => "return",
+                NatSpecKind::Inheritdoc { .. } => "inheritdoc",
+                NatSpecKind::Custom { .. } => "custom",
+                NatSpecKind::Internal { tag } => tag.name.as_str(),
+            };
+            let is_last = i == docs.len() - 1 && j == doc.natspec.len() - 1;
+            // Untagged comments are parsed as a `@notice` that spans the whole comment.
+            if is_last && span.hi() == doc.span.hi() && span != doc.span {
+                let msg = format!(
+                    "End of tag @{} not found",
+                    gcx.sess.source_map().span_to_snippet(span).unwrap_or_default()
+                );
+                err(error_code!(9222), msg, span);
+                continue;
+            }
+            if let NatSpecKind::Custom { name } = natspec.kind {
+                if name.as_str().is_empty() {
+                    let msg =
+                        "Custom documentation tag must contain a chosen name, i.e. @custom:mytag.";
+                    err(error_code!(6564), msg.into(), span);
+                } else if !is_valid_custom_tag(name.as_str()) {
+                    let msg = format!(
+                        "Invalid character in custom tag @custom:{name}. Only lowercase letters and \"-\" are permitted."
+                    );
+                    err(error_code!(2968), msg, span);
+                }
+                continue;
+            }
+            if !valid_tags.contains(&tag) {
+                let msg = format!("Documentation tag @{tag} not valid for {description}.");
+                err(error_code!(6546), msg, span);
+                continue;
+            }
+
+            let content = doc.natspec_content(natspec);
+            match natspec.kind {
+                NatSpecKind::Param { name } => {
+                    if name.as_str().is_empty() {
+                        err(error_code!(3335), "No param name given".into(), span);
+                    } else if content.lines().next().is_none_or(|line| line.trim().is_empty()) {
+                        let msg = format!("No description given for param {name}");
+                        err(error_code!(9942), msg, span);
+                    } else if !param_names().any(|param| param.name == name.name) {
+                        let msg = format!(
+                            "Documented parameter \"{name}\" not found in the parameter list of the function."
+                        );
+                        err(error_code!(3881), msg, span);
+                    }
+                }
+                NatSpecKind::Return { name } => {
+                    returns += 1;
+                    let text = tag_text(name.as_str(), content);
+                    if matches!(item, hir::Item::Variable(_)) {
+                        if returns > 1 {
+                            let msg = "Documentation tag \"@return\" is only allowed once on public state-variables.";
+                            err(error_code!(5256), msg.into(), span);
+                        }
+                    } else if let Some(f) = function {
+                        if let Some(&ret) = f.returns.get(returns - 1) {
+                            if let Some(ret_name) = gcx.hir.variable(ret).name
+                                && ret_name.name != name.name
+                            {
+                                let msg = format!(
+                                    "Documentation tag \"@return {text}\" does not contain the name of its return parameter."
+                                );
+                                err(error_code!(5856), msg, span);
+                            }
+                        } else {
+                            let msg = format!(
+                                "Documentation tag \"@return {text}\" exceeds the number of return parameters."
+                            );
+                            err(error_code!(2604), msg, span);
+                        }
+                    }
+                }
+                NatSpecKind::Inheritdoc { contract } => {
+                    inheritdoc = if inheritdoc.is_none() {
+                        Some(Ok((contract, span)))
+                    } else {
+                        let msg = "Documentation tag @inheritdoc can only be given once.";
+                        Some(Err(err(error_code!(5142), msg.into(), span)))
+                    };
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(Ok((contract, span))) = inheritdoc
+        && let Some(function_id) = function_id
+    {
+        check_inheritdoc(gcx, function_id, contract.as_str(), span);
+    }
+}
+
+fn check_inheritdoc(gcx: Gcx<'_>, id: FunctionId, name: &str, span: Span) {
+    let err = |code, msg: String| {
+        gcx.dcx().err(msg).code(code).span(span).emit();
+    };
+    if name.is_empty() {
+        let msg = "Expected contract name following documentation tag @inheritdoc.";
+        err(error_code!(1933), msg.into());
+        return;
+    }
+    if name.split('.').any(str::is_empty) {
+        let msg = format!("Documentation tag @inheritdoc reference \"{name}\" is malformed.");
+        err(error_code!(5967), msg);
+        return;
+    }
+    let contract = match resolve_inheritdoc(gcx, id, name) {
+        Some(hir::Res::Item(ItemId::Contract(contract))) => contract,
+        Some(_) => {
+            let msg =
+                format!("Documentation tag @inheritdoc reference \"{name}\" is not a contract.");
+            err(error_code!(1430), msg);
+            return;
+        }
+        None => {
+            let msg =
+                format!("Documentation tag @inheritdoc references inexistent contract \"{name}\".");
+            err(error_code!(9397), msg);
+            return;
+        }
+    };
+    let bases = base_functions(gcx, id);
+    if !bases.iter().any(|&base| gcx.hir.function(base).contract == Some(contract)) {
+        let msg = format!(
+            "Documentation tag @inheritdoc references contract \"{name}\", \
+             but the contract does not contain a function that is overridden by this function."
+        );
+        err(error_code!(4682), msg);
+    }
+}
+
+/// Returns `true` if the name of a `@custom:<name>` tag is valid: `[a-z][a-z-]*`.
+fn is_valid_custom_tag(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+}
+
+/// Returns the content of a tag with a name, such as `@return`, with continuation lines joined.
+fn tag_text(name: &str, content: &str) -> String {
+    let mut text = name.to_string();
+    for line in content.lines() {
+        let line = line.trim_start();
+        let line = line.strip_prefix('*').unwrap_or(line).trim();
+        if !line.is_empty() {
+            text.push(' ');
+            text.push_str(line);
+        }
+    }
+    text
+}
+
+/// Returns the events and errors of the given contract, including inherited ones, without
+/// duplicate signatures.
+fn interface_events_and_errors(gcx: Gcx<'_>, id: ContractId) -> Vec<ItemId> {
+    let mut seen = FxHashSet::default();
+    gcx.hir
+        .contract_item_ids(id)
+        .filter(|item| matches!(item, ItemId::Event(_) | ItemId::Error(_)))
+        .filter(|&item| seen.insert((matches!(item, ItemId::Error(_)), gcx.item_signature(item))))
+        .collect()
+}
+
+/// Returns the documentation of the `@return` tags of a function, keyed by the return parameter
+/// name, or `_<index>` if it is unnamed.
+fn returns(gcx: Gcx<'_>, id: FunctionId, tags: &[DocTag]) -> BTreeMap<String, String> {
+    let names = return_names(gcx, id);
+    let tags = tags.iter().filter(|tag| tag.name == "return");
+    let mut returns = BTreeMap::new();
+    for (i, (tag, name)) in tags.zip(&names).enumerate() {
+        if name.is_empty() {
+            returns.insert(format!("_{i}"), tag.content.clone());
+        } else {
+            let content = match tag.content.split_once([' ', '\t']) {
+                Some((word, rest)) if word == name => rest,
+                _ if tag.content == *name => "",
+                _ => &tag.content,
+            };
+            returns.insert(name.clone(), content.to_string());
+        }
+    }
+    returns
+}
+
+/// Returns the names of the return parameters of a function, or the empty string if unnamed.
+fn return_names(gcx: Gcx<'_>, id: FunctionId) -> Vec<String> {
+    gcx.hir
+        .function(id)
+        .returns
+        .iter()
+        .map(|&ret| gcx.hir.variable(ret).name.map(|name| name.to_string()).unwrap_or_default())
+        .collect()
+}
+
+/// Returns the documentation tags of a function or a getter, including the ones inherited from
+/// its base function.
+///
+/// Tags are inherited from the base function named with `@inheritdoc`, or implicitly if the
+/// function has no documentation, overrides exactly one function, and its parameter names match.
+pub(crate) fn function_tags(gcx: Gcx<'_>, id: FunctionId) -> Vec<DocTag> {
+    let f = gcx.hir.function(id);
+    let docs = match f.gettee {
+        Some(var) => gcx.hir.variable(var).docs,
+        None => f.docs,
+    };
+    let mut tags = parse(docs);
+    if f.is_constructor() {
+        return tags;
+    }
+    let bases = base_functions(gcx, id);
+    let base = if let Some(tag) = tags.iter().find(|tag| tag.name == "inheritdoc") {
+        let name = tag.content.split_whitespace().next().unwrap_or_default();
+        let contract = match resolve_inheritdoc(gcx, id, name) {
+            Some(hir::Res::Item(ItemId::Contract(contract))) => Some(contract),
+            _ => None,
+        };
+        bases
+            .iter()
+            .copied()
+            .find(|&base| contract.is_some() && gcx.hir.function(base).contract == contract)
+    } else if tags.is_empty()
+        && let [base] = bases[..]
+        && (f.is_getter() || param_names(gcx, id) == param_names(gcx, base))
+    {
+        Some(base)
+    } else {
+        None
+    };
+    if let Some(base) = base {
+        copy_missing_tags(gcx, &mut tags, id, base);
+    }
+    tags
+}
+
+/// Copies the tags of `base` into `tags`, unless `tags` already contains a tag with the same name.
+///
+/// `@return` tags are renamed to the return parameter names of the function.
+fn copy_missing_tags(gcx: Gcx<'_>, tags: &mut Vec<DocTag>, id: FunctionId, base: FunctionId) {
+    let base_tags = function_tags(gcx, base);
+    let names = return_names(gcx, id);
+    let base_names = (!gcx.hir.function(base).is_getter()).then(|| return_names(gcx, base));
+    let mut n = 0;
+    let present = |tags: &[DocTag], name: &str| tags.iter().any(|tag| tag.name == name);
+    let missing: Vec<_> = base_tags
+        .into_iter()
+        .filter(|tag| {
+            tag.name != "inheritdoc" && !tag.name.starts_with("custom") && !present(tags, &tag.name)
+        })
+        .collect();
+    for mut tag in missing {
+        if tag.name == "return" {
+            let word_end = tag.content.find([' ', '\t']);
+            let word = &tag.content[..word_end.unwrap_or(tag.content.len())];
+            if let Some(name) = names.get(n)
+                && word != name
+            {
+                let base_has_no_name = base_names
+                    .as_ref()
+                    .and_then(|names| names.get(n))
+                    .is_some_and(String::is_empty);
+                let rest = match word_end {
+                    None => "",
+                    Some(_) if base_has_no_name => &tag.content,
+                    Some(end) => &tag.content[end + 1..],
+                };
+                let prefix = if name.is_empty() { String::new() } else { format!("{name} ") };
+                tag.content = prefix + rest;
+            }
+            n += 1;
+        }
+        tags.push(tag);
+    }
+}
+
+/// Returns the functions or modifiers that are directly overridden by the given function, getter,
+/// or modifier.
+fn base_functions(gcx: Gcx<'_>, id: FunctionId) -> Vec<FunctionId> {
+    let f = gcx.hir.function(id);
+    let (Some(contract), Some(name)) = (f.contract, f.name) else { return Vec::new() };
+    let params = |id: FunctionId| gcx.item_parameter_types(id).iter().map(|ty| ty.peel_refs());
+    let mut bases = Vec::new();
+    for &base in gcx.hir.contract(contract).bases {
+        let found = gcx.hir.contract(base).linearized_bases.iter().find_map(|&b| {
+            gcx.hir.contract(b).functions().find(|&g| {
+                let g_f = gcx.hir.function(g);
+                g_f.name.is_some_and(|n| n.name == name.name)
+                    && g_f.kind == f.kind
+                    && g_f.visibility != hir::Visibility::Private
+                    && params(g).eq(params(id))
+            })
+        });
+        if let Some(found) = found
+            && !bases.contains(&found)
+        {
+            bases.push(found);
+        }
+    }
+    bases
+}
+
+/// Resolves the path of an `@inheritdoc` tag in the scope of the given function.
+fn resolve_inheritdoc(gcx: Gcx<'_>, id: FunctionId, name: &str) -> Option<hir::Res> {
+    let f = gcx.hir.function(id);
+    let segments = name
+        .split('.')
+        .map(|segment| Ident::new(Symbol::intern(segment), f.span))
+        .collect::<Vec<_>>();
+    gcx.symbol_resolver.resolve_path_in(PathSlice::from_slice(&segments), f.source, f.contract)
+}
+
+fn param_names(gcx: Gcx<'_>, id: FunctionId) -> Vec<Option<Symbol>> {
+    let f = gcx.hir.function(id);
+    f.parameters.iter().map(|&p| gcx.hir.variable(p).name.map(|name| name.name)).collect()
+}
+
+/// Concatenates the contents of all the tags with the given name.
+fn extract(tags: &[DocTag], name: &str) -> Option<String> {
+    let s: String =
+        tags.iter().filter(|tag| tag.name == name).map(|tag| tag.content.as_str()).collect();
+    (!s.is_empty()).then_some(s)
+}
+
+/// Returns the doc-comments that document an item.
+///
+/// Only the last doc-comment block documents the item: either a run of consecutive line
+/// doc-comments, or a single block doc-comment.
+fn last_group<'a, 'hir>(docs: &'a [hir::DocComment<'hir>]) -> &'a [hir::DocComment<'hir>] {
+    let start = match docs.last() {
+        None => return docs,
+        Some(doc) if doc.kind == CommentKind::Block => docs.len() - 1,
+        Some(_) => docs.iter().rposition(|doc| doc.kind == CommentKind::Block).map_or(0, |i| i + 1),
+    };
+    &docs[start..]
+}
+
+/// Parses the documentation tags of an item.
+pub(crate) fn parse(docs: &[hir::DocComment<'_>]) -> Vec<DocTag> {
+    let mut lines = Vec::new();
+    for doc in last_group(docs) {
+        let s = doc.symbol.as_str();
+        match doc.kind {
+            CommentKind::Line => lines.push(s),
+            CommentKind::Block => {
+                for (i, line) in s.lines().enumerate() {
+                    let line = if i == 0 { line } else { line.trim_start() };
+                    let line = if i == 0 { line } else { line.strip_prefix('*').unwrap_or(line) };
+                    lines.push(line);
+                }
+            }
+        }
+    }
+
+    let mut tags = Vec::<DocTag>::new();
+    let lines = lines.into_iter().filter(|line| !line.trim().is_empty());
+    for (i, line) in lines.enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(rest) = trimmed.strip_prefix('@') {
+            let (name, content) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let content = content.trim_start();
+            let (param, content) = if name == "param" {
+                let (param, content) =
+                    content.split_once(char::is_whitespace).unwrap_or((content, ""));
+                (param, content.trim_start())
+            } else {
+                ("", content)
+            };
+            tags.push(DocTag {
+                name: name.to_string(),
+                param: param.to_string(),
+                content: content.to_string(),
+            });
+        } else if let Some(tag) = tags.last_mut() {
+            if !line.is_empty() && !line.starts_with([' ', '\t']) {
+                tag.content.push(' ');
+            }
+            tag.content.push_str(line);
+        } else if i == 0 {
+            tags.push(DocTag {
+                name: "notice".to_string(),
+                param: String::new(),
+                content: trimmed.to_string(),
+            });
+        }
+    }
+    for tag in &mut tags {
+        tag.content.truncate(tag.content.trim_end().len());
+    }
+    tags
+}
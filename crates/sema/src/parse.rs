@@ -229,11 +229,11 @@ impl<'gcx> ParsingContext<'gcx> {
         for i in 0.. {
             let id = SourceId::from_usize(i);
             let Some(source) = sources.get(id) else { break };
-            if source.ast.is_some() {
+            if source.is_parsed() {
                 continue;
             }
 
-            let ast = self.parse_one(&source.file, arena);
+            let (ast, yul) = self.parse_one(&source.file, arena);
             let _guard = debug_span!("resolve_imports").entered();
             for (import_item_id, import_file) in
                 self.resolve_imports(&source.file.clone(), ast.as_ref())
@@ -241,6 +241,7 @@ impl<'gcx> ParsingContext<'gcx> {
                 sources.add_import(id, import_item_id, import_file, false);
             }
             sources[id].ast = ast;
+            sources[id].yul = yul;
         }
     }
 
@@ -253,7 +254,7 @@ impl<'gcx> ParsingContext<'gcx> {
         rayon::scope(|scope| {
             let sources = &*lock.lock();
             for (id, source) in sources.iter_enumerated() {
-                if source.ast.is_some() {
+                if source.is_parsed() {
                     continue;
                 }
                 let file = source.file.clone();
@@ -284,7 +285,7 @@ impl<'gcx> ParsingContext<'gcx> {
         scope: &rayon::Scope<'scope>,
     ) {
         // Parse and resolve imports.
-        let ast = self.parse_one(&file, arenas.get_or_default());
+        let (ast, yul) = self.parse_one(&file, arenas.get_or_default());
         let imports = {
             let _guard = debug_span!("resolve_imports").entered();
             self.resolve_imports(&file, ast.as_ref()).collect::<Vec<_>>()
@@ -293,8 +294,9 @@ impl<'gcx> ParsingContext<'gcx> {
         // Set AST, add imports and recursively spawn jobs for parsing them if necessary.
         let _guard = debug_span!("add_imports").entered();
         let sources = &mut *lock.lock();
-        assert!(sources[id].ast.is_none());
+        assert!(!sources[id].is_parsed());
         sources[id].ast = ast;
+        sources[id].yul = yul;
         for (import_item_id, import_file) in imports {
             let (import_id, is_new) =
                 sources.add_import(id, import_item_id, import_file.clone(), false);
@@ -304,20 +306,20 @@ impl<'gcx> ParsingContext<'gcx> {
         }
     }
 
-    /// Parses a single file.
+    /// Parses a single file, returning either the Solidity AST or the Yul object depending on the
+    /// language.
     #[instrument(level = "debug", skip_all, fields(file = %file.name.display()))]
     fn parse_one<'ast>(
         &self,
         file: &SourceFile,
         arena: &'ast ast::Arena,
-    ) -> Option<ast::SourceUnit<'ast>> {
+    ) -> (Option<ast::SourceUnit<'ast>>, Option<ast::yul::Object<'ast>>) {
         let lexer = Lexer::from_source_file(self.sess, file);
         let mut parser = Parser::from_lexer(arena, lexer);
         if self.sess.opts.language.is_yul() {
-            let _file = parser.parse_yul_file_object().map_err(|e| e.emit());
-            None
+            (None, parser.parse_yul_file_object().map_err(|e| e.emit()).ok())
         } else {
-            parser.parse_file().map_err(|e| e.emit()).ok()
+            (parser.parse_file().map_err(|e| e.emit()).ok(), None)
         }
     }
 
@@ -484,6 +486,11 @@ impl<'ast> Sources<'ast> {
         self.sources.iter().filter_map(|source| source.ast.as_ref())
     }
 
+    /// Returns an iterator over all the Yul objects.
+    pub fn yul_objects(&self) -> impl DoubleEndedIterator<Item = &ast::yul::Object<'ast>> {
+        self.sources.iter().filter_map(|source| source.yul.as_ref())
+    }
+
     /// Returns a parallel iterator over all the ASTs.
     pub fn par_asts(&self) -> impl ParallelIterator<Item = &ast::SourceUnit<'ast>> {
         self.sources.as_raw_slice().par_iter().filter_map(|source| source.ast.as_ref())
@@ -618,6 +625,14 @@ pub struct Source<'ast> {
     /// - the source is a Yul file
     /// - manually dropped to free memory
     pub ast: Option<ast::SourceUnit<'ast>>,
+    /// The Yul object.
+    ///
+    /// `None` if:
+    /// - not yet parsed
+    /// - an error occurred during parsing
+    /// - the source is a Solidity file
+    /// - manually dropped to free memory
+    pub yul: Option<ast::yul::Object<'ast>>,
 }
 
 impl fmt::Debug for Source<'_> {
@@ -626,6 +641,7 @@ impl fmt::Debug for Source<'_> {
             .field("file", &self.file.name)
             .field("imports", &self.imports)
             .field("ast", &self.ast)
+            .field("yul", &self.yul)
             .finish()
     }
 }
@@ -633,7 +649,12 @@ impl fmt::Debug for Source<'_> {
 impl Source<'_> {
     /// Creates a new empty source.
     pub fn new(file: Arc<SourceFile>) -> Self {
-        Self { file, ast: None, imports: Vec::new() }
+        Self { file, ast: None, yul: None, imports: Vec::new() }
+    }
+
+    /// Returns `true` if the source has been successfully parsed.
+    pub fn is_parsed(&self) -> bool {
+        self.ast.is_some() || self.yul.is_some()
     }
 
     fn count_contracts(&self) -> usize {
@@ -0,0 +1,168 @@
+use super::{Gcx, Recursiveness, Ty, TyKind};
+use crate::{eval::ConstantEvaluator, hir};
+use alloy_primitives::U256;
+use solar_ast::ElementaryType;
+use solar_data_structures::BumpExt;
+use solar_interface::error_code;
+
+/// The storage layout of a contract or a struct.
+///
+/// Return type of [`Gcx::storage_layout`] and [`Gcx::struct_storage_layout`].
+#[derive(Clone, Copy, Debug)]
+pub struct StorageLayout<'gcx> {
+    /// The storage variables, in declaration order.
+    pub items: &'gcx [StorageItem<'gcx>],
+    /// The number of slots occupied by the items, not including the base slot.
+    pub slots: U256,
+}
+
+/// A variable stored at a fixed storage location.
+#[derive(Clone, Copy, Debug)]
+pub struct StorageItem<'gcx> {
+    /// The variable ID. This is either a state variable or a struct field.
+    pub var: hir::VariableId,
+    /// The type of the variable.
+    pub ty: Ty<'gcx>,
+    /// The slot the variable starts at.
+    pub slot: U256,
+    /// The byte offset in the slot, counting from the least significant byte.
+    pub offset: u8,
+}
+
+impl<'gcx> Gcx<'gcx> {
+    /// Returns the number of bytes that the given type occupies in storage.
+    ///
+    /// Value types can be packed together in a single slot. All other types always use up full
+    /// slots, so `32` is returned for them.
+    ///
+    /// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/ast/Types.h#L254>
+    pub fn storage_bytes(self, ty: Ty<'gcx>) -> u8 {
+        match ty.peel_refs().kind {
+            TyKind::Elementary(ty) => match ty {
+                ElementaryType::Address(_) => 20,
+                ElementaryType::Bool => 1,
+                ElementaryType::String | ElementaryType::Bytes => 32,
+                ElementaryType::Fixed(size, _)
+                | ElementaryType::UFixed(size, _)
+                | ElementaryType::Int(size)
+                | ElementaryType::UInt(size)
+                | ElementaryType::FixedBytes(size) => size.bytes(),
+            },
+            TyKind::Contract(_) => 20,
+            TyKind::Enum(id) => {
+                let len = self.hir.enumm(id).variants.len().max(1);
+                let bits = usize::BITS - (len - 1).leading_zeros();
+                bits.div_ceil(8).max(1) as u8
+            }
+            TyKind::Udvt(ty, _) => self.storage_bytes(ty),
+            TyKind::FnPtr(f) => {
+                if f.visibility == hir::Visibility::External {
+                    24
+                } else {
+                    8
+                }
+            }
+            _ => 32,
+        }
+    }
+
+    /// Returns the number of slots that the given type occupies in storage.
+    ///
+    /// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/ast/Types.h#L249>
+    pub fn storage_slots(self, ty: Ty<'gcx>) -> U256 {
+        match ty.peel_refs().kind {
+            TyKind::Array(elem, len) => {
+                let elem_bytes = self.storage_bytes(elem);
+                if elem_bytes <= 16 {
+                    // Multiple elements fit in a single slot.
+                    let per_slot = U256::from(32 / elem_bytes);
+                    len.div_ceil(per_slot)
+                } else {
+                    len.saturating_mul(self.storage_slots(elem))
+                }
+            }
+            TyKind::Struct(id) => {
+                if let Recursiveness::Infinite(_) = self.struct_recursiveness(id) {
+                    return U256::from(1);
+                }
+                self.struct_storage_layout(id).slots.max(U256::from(1))
+            }
+            _ => U256::from(1),
+        }
+    }
+}
+
+/// Computes the storage layout of the given contract.
+///
+/// State variables are laid out starting with the most base contract. Constant, immutable and
+/// transient state variables are not stored in storage, so they are skipped.
+pub(super) fn contract_storage_layout<'gcx>(
+    gcx: Gcx<'gcx>,
+    id: hir::ContractId,
+) -> StorageLayout<'gcx> {
+    let c = gcx.hir.contract(id);
+    let vars = c.linearized_bases.iter().rev().flat_map(|&base| {
+        gcx.hir.contract(base).variables().filter(|&var_id| {
+            let var = gcx.hir.variable(var_id);
+            !(var.is_constant()
+                || var.is_immutable()
+                || var.data_location == Some(hir::DataLocation::Transient))
+        })
+    });
+
+    let base_slot = c
+        .layout
+        .and_then(|expr| ConstantEvaluator::new(gcx).eval(expr).ok())
+        .map_or(U256::ZERO, |int| int.data);
+    let layout = compute_layout(gcx, vars, base_slot);
+    if let Some(expr) = c.layout
+        && base_slot.checked_add(layout.slots.saturating_sub(U256::from(1))).is_none()
+    {
+        let msg = "contract extends past the end of storage when this base slot value is specified";
+        gcx.dcx().err(msg).code(error_code!(5015)).span(expr.span).emit();
+    }
+    layout
+}
+
+/// Computes the storage layout of the given struct, relative to the start of the struct.
+pub(super) fn struct_storage_layout<'gcx>(
+    gcx: Gcx<'gcx>,
+    id: hir::StructId,
+) -> StorageLayout<'gcx> {
+    compute_layout(gcx, gcx.hir.strukt(id).fields.iter().copied(), U256::ZERO)
+}
+
+/// Assigns slots and offsets to `vars`, packing value types together when possible.
+///
+/// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/ast/Types.cpp#L176>
+fn compute_layout<'gcx>(
+    gcx: Gcx<'gcx>,
+    vars: impl Iterator<Item = hir::VariableId>,
+    base_slot: U256,
+) -> StorageLayout<'gcx> {
+    let mut slot = U256::ZERO;
+    let mut offset = 0u8;
+    let items = vars.map(|var| {
+        let ty = gcx.type_of_item(var.into());
+        let bytes = gcx.storage_bytes(ty);
+        if offset as usize + bytes as usize > 32 {
+            // Would overflow, go to the next slot.
+            slot = slot.saturating_add(U256::from(1));
+            offset = 0;
+        }
+        let item = StorageItem { var, ty, slot: base_slot.wrapping_add(slot), offset };
+        let slots = gcx.storage_slots(ty);
+        if slots == U256::from(1) && offset as usize + bytes as usize <= 32 {
+            offset += bytes;
+        } else {
+            slot = slot.saturating_add(slots);
+            offset = 0;
+        }
+        item
+    });
+    let items = gcx.bump().alloc_from_iter(items);
+    if offset > 0 {
+        slot = slot.saturating_add(U256::from(1));
+    }
+    StorageLayout { items, slots: slot }
+}
@@ -17,7 +17,7 @@ use solar_data_structures::{
 use solar_interface::{
     Ident, Session, Span,
     config::CompilerStage,
-    diagnostics::{DiagCtxt, ErrorGuaranteed},
+    diagnostics::{Applicability, DiagBuilder, DiagCtxt, ErrorGuaranteed},
     source_map::{FileName, SourceFile},
 };
 use std::{
@@ -40,6 +40,9 @@ pub use common::{CommonTypes, EachDataLoc};
 mod interner;
 use interner::Interner;
 
+mod layout;
+pub use layout::{StorageItem, StorageLayout};
+
 #[allow(clippy::module_inception)]
 mod ty;
 pub use ty::{Ty, TyConvertError, TyData, TyFlags, TyFnPtr, TyKind};
@@ -655,14 +658,51 @@ impl<'gcx> Gcx<'gcx> {
         }
     }
 
+    /// Returns the members of the given type, including the functions attached to it with
+    /// `using for` directives that are visible in the given scope.
     pub fn members_of(
         self,
         ty: Ty<'gcx>,
         source: hir::SourceId,
         contract: Option<hir::ContractId>,
     ) -> members::MemberList<'gcx> {
-        let _ = (source, contract); // TODO
-        self.native_members(ty)
+        self.members_in_scope((ty, source, contract))
+    }
+
+    /// Returns the functions bound to the given operator for `ty` with `global` `using for`
+    /// directives.
+    ///
+    /// Only functions with a single parameter are returned if `unary` is true, and only functions
+    /// with two parameters otherwise. The result contains more than one function only if the
+    /// definitions are ambiguous.
+    ///
+    /// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/ast/Types.cpp#L395>
+    pub fn operator_definitions(
+        self,
+        ty: Ty<'gcx>,
+        op: hir::UserDefinableOperator,
+        unary: bool,
+    ) -> SmallVec<[hir::FunctionId; 1]> {
+        let mut definitions = SmallVec::new();
+        let ty = ty.peel_refs();
+        let TyKind::Udvt(_, id) = ty.kind else { return definitions };
+        let usings = self.hir.source(self.hir.udvt(id).source).usings;
+        for using in usings.iter().filter(|using| using.global) {
+            let hir::UsingList::Functions(functions) = using.list else { continue };
+            if using.ty.as_ref().is_none_or(|using_ty| self.type_of_hir_ty(using_ty) != ty) {
+                continue;
+            }
+            for &(f_id, f_op) in functions {
+                if f_op != Some(op) || definitions.contains(&f_id) {
+                    continue;
+                }
+                let TyKind::FnPtr(f) = self.type_of_item(f_id.into()).kind else { unreachable!() };
+                if f.parameters.len() == if unary { 1 } else { 2 } && f.parameters[0] == ty {
+                    definitions.push(f_id);
+                }
+            }
+        }
+        definitions
     }
 }
 
@@ -762,7 +802,11 @@ pub fn interface_functions(gcx: _, id: hir::ContractId) -> InterfaceFunctions<'g
                     format!("this type cannot be parameter or return type of a public {kind}")
                 };
                 let span = gcx.hir.variable(var_id).ty.span;
-                result = Err(gcx.dcx().err(msg).span(span).emit());
+                let mut err = gcx.dcx().err(msg).span(span);
+                if ty.has_mapping() && !f.is_getter() {
+                    err = err.help("types containing mappings can only be used in internal or library functions");
+                }
+                result = Err(err.emit());
             }
         }
         if result.is_err() {
@@ -908,9 +952,27 @@ pub fn struct_recursiveness(gcx: _, id: hir::StructId) -> Recursiveness {
     }
 }
 
-fn native_members(gcx: _, ty: Ty<'gcx>) -> members::MemberList<'gcx> {
+/// Returns the storage layout of the given contract, including inherited state variables.
+pub fn storage_layout(gcx: _, id: hir::ContractId) -> StorageLayout<'gcx> {
+    layout::contract_storage_layout(gcx, id)
+}
+
+/// Returns the storage layout of the given struct, relative to the first slot of the struct.
+pub fn struct_storage_layout(gcx: _, id: hir::StructId) -> StorageLayout<'gcx> {
+    layout::struct_storage_layout(gcx, id)
+}
+
+pub(crate) fn native_members(gcx: _, ty: Ty<'gcx>) -> members::MemberList<'gcx> {
     members::native_members(gcx, ty)
 }
+
+fn members_in_scope(
+    gcx: _,
+    key: (Ty<'gcx>, hir::SourceId, Option<hir::ContractId>)
+) -> members::MemberList<'gcx> {
+    let (ty, source, contract) = key;
+    members::members_in_scope(gcx, ty, source, contract)
+}
 }
 
 fn var_type<'gcx>(gcx: Gcx<'gcx>, var: &'gcx hir::Variable<'gcx>, ty: Ty<'gcx>) -> Ty<'gcx> {
@@ -950,7 +1012,6 @@ fn var_type<'gcx>(gcx: Gcx<'gcx>, var: &'gcx hir::Variable<'gcx>, ty: Ty<'gcx>)
     } else {
         &[None]
     };
-
     let mut var_loc = var.data_location;
     if !allowed.contains(&var_loc) {
         if !ty.references_error() {
@@ -978,7 +1039,7 @@ fn var_type<'gcx>(gcx: Gcx<'gcx>, var: &'gcx hir::Variable<'gcx>, ty: Ty<'gcx>)
                 );
                 err = err.note(note);
             }
-            err.emit();
+            suggest_data_locations(err, var, allowed).emit();
         }
         var_loc = allowed[0];
     }
@@ -999,6 +1060,23 @@ fn var_type<'gcx>(gcx: Gcx<'gcx>, var: &'gcx hir::Variable<'gcx>, ty: Ty<'gcx>)
                 if mut_specified {
                     let msg = "transient cannot be used as data location for constant or immutable variables";
                     gcx.dcx().err(msg).span(var.span).emit();
+                } else if has_reference_or_mapping_type {
+                    if !ty.references_error() {
+                        let msg = "transient storage is only supported for value types";
+                        let diag = gcx.dcx().err(msg).span(var.span);
+                        suggest_data_locations(diag, var, &[None]).emit();
+                    }
+                    return ty.with_loc_if_ref(gcx, Storage);
+                } else if !gcx.sess.opts.evm_version.supports_transient_storage() {
+                    let msg = format!(
+                        "transient storage is not supported by EVM version `{}`",
+                        gcx.sess.opts.evm_version
+                    );
+                    gcx.dcx()
+                        .err(msg)
+                        .span(var.data_location_span)
+                        .help("transient storage requires EVM version `cancun` or later")
+                        .emit();
                 }
                 if var.initializer.is_some() {
                     let msg =
@@ -1025,6 +1103,50 @@ fn var_type<'gcx>(gcx: Gcx<'gcx>, var: &'gcx hir::Variable<'gcx>, ty: Ty<'gcx>)
     ty.with_loc_if_ref(gcx, ty_loc)
 }
 
+/// Suggests replacing the data location of `var` with each of the `allowed` ones.
+fn suggest_data_locations<'a>(
+    mut diag: DiagBuilder<'a, ErrorGuaranteed>,
+    var: &hir::Variable<'_>,
+    allowed: &[Option<DataLocation>],
+) -> DiagBuilder<'a, ErrorGuaranteed> {
+    let span = var.data_location_span;
+    if span.is_dummy() {
+        return diag;
+    }
+    // `transient` is never the right fix for an invalid data location.
+    let allowed = allowed.iter().filter(|&&loc| loc != Some(DataLocation::Transient));
+    let applicability = if allowed.clone().count() == 1 {
+        Applicability::MachineApplicable
+    } else {
+        Applicability::MaybeIncorrect
+    };
+    for &loc in allowed {
+        diag = match (var.data_location, loc) {
+            (Some(_), None) => {
+                // Also remove the whitespace between the type and the keyword, if nothing else
+                // is in between.
+                let has_attrs = var.visibility.is_some()
+                    || var.mutability.is_some()
+                    || var.indexed
+                    || var.override_;
+                let span = if has_attrs { span } else { span.with_lo(var.ty.span.hi()) };
+                diag.span_suggestion(span, "remove the data location", "", applicability)
+            }
+            (Some(_), Some(loc)) => {
+                diag.span_suggestion(span, "change the data location", loc.to_str(), applicability)
+            }
+            (None, Some(loc)) => diag.span_suggestion(
+                span,
+                "specify a data location",
+                format!(" {loc}"),
+                applicability,
+            ),
+            (None, None) => diag,
+        };
+    }
+    diag
+}
+
 /// True if referencing the item returns its type directly rather than wrapped in Type().
 fn is_value_ns(id: hir::ItemId) -> bool {
     matches!(
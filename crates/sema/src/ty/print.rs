@@ -125,7 +125,7 @@ impl<'gcx> Gcx<'gcx> {
         s
     }
 
-    fn print_solc_param_ty(self, ty: Ty<'gcx>) -> String {
+    pub(crate) fn print_solc_param_ty(self, ty: Ty<'gcx>) -> String {
         let mut s = String::new();
         TySolcPrinter::new(self, &mut s).data_locations(false).print(ty).unwrap();
         s
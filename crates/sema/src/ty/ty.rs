@@ -539,14 +539,11 @@ impl<'gcx> Ty<'gcx> {
                         from_inner.try_convert_implicit_to(to_inner, gcx)
                     }
 
-                    // memory/calldata -> storage: allowed (copy semantics).
-                    (DataLocation::Memory, DataLocation::Storage)
-                    | (DataLocation::Calldata, DataLocation::Storage) => {
-                        from_inner.try_convert_implicit_to(to_inner, gcx)
-                    }
-
                     // storage -> calldata: never allowed.
                     // memory -> calldata: never allowed.
+                    // memory/calldata -> storage: not allowed, storage pointers can only point to
+                    // storage. Copying into storage references, such as state variables, is
+                    // handled by the type checker.
                     _ => Result::Err(TyConvertError::Incompatible),
                 }
             }
@@ -579,6 +576,11 @@ impl<'gcx> Ty<'gcx> {
             // byte literal -> bytesN/bytes
             // See: <https://docs.soliditylang.org/en/latest/types.html#index-34>
             (StringLiteral(_, _), Elementary(Bytes)) => Ok(()),
+            (StringLiteral(utf8, _), Ref(inner, DataLocation::Memory)) => match inner.kind {
+                Elementary(Bytes) => Ok(()),
+                Elementary(String) if utf8 => Ok(()),
+                _ => Result::Err(TyConvertError::Incompatible),
+            },
             (StringLiteral(_, size_from), Elementary(FixedBytes(size_to))) => {
                 if size_from.bytes() <= size_to.bytes() {
                     Ok(())
@@ -751,6 +753,9 @@ impl<'gcx> Ty<'gcx> {
 
             // address -> address payable.
             (Elementary(Address(false)), Elementary(Address(true))) => Ok(()),
+
+            // Non-negative integer literals that fit in 160 bits -> address.
+            (IntLiteral(false, size), Elementary(Address(false))) if size.bits() <= 160 => Ok(()),
             // IntLiteral -> IntLiteral: explicit conversion to a literal type shouldn't be
             // possible.
             (IntLiteral(_, _), IntLiteral(_, _)) => unreachable!(),
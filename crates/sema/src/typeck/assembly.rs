@@ -0,0 +1,281 @@
+//! Inline assembly checks.
+//!
+//! This module runs the Yul analyzer on assembly blocks, and validates the references to Solidity
+//! declarations from inside of them:
+//! - Only local variables can be accessed and assigned to directly
+//! - Storage variables must be accessed through their `.slot` and `.offset` suffixes
+//! - Suffixes must be supported by the variable's type and location
+//! - Constants must be number literals, and cannot be assigned to
+//!
+//! Reference: solc TypeChecker.cpp, `TypeChecker::visit(InlineAssembly const&)`
+
+use crate::{
+    hir::{self, Visit, yul},
+    ty::{Gcx, TyKind},
+};
+use solar_ast::DataLocation;
+use solar_data_structures::Never;
+use solar_interface::{Span, diagnostics::DiagId, error_code};
+use std::ops::ControlFlow;
+
+pub(super) fn check(gcx: Gcx<'_>, source: hir::SourceId) {
+    let _ = AssemblyChecker { gcx }.visit_nested_source(source);
+}
+
+struct AssemblyChecker<'gcx> {
+    gcx: Gcx<'gcx>,
+}
+
+impl AssemblyChecker<'_> {
+    fn err(&self, code: DiagId, msg: &str, span: Span) {
+        self.gcx.dcx().err(msg.to_string()).code(code).span(span).emit();
+    }
+
+    /// Checks a reference to a Solidity declaration.
+    fn check_external_ref(&self, ext: &yul::ExternalRef, span: Span, lvalue: bool) {
+        use yul::Suffix;
+
+        let id = match ext.res {
+            hir::Res::Item(hir::ItemId::Variable(id)) => id,
+            hir::Res::Err(_) => return,
+            _ => return self.check_non_variable_ref(ext, span, lvalue),
+        };
+        let var = self.gcx.hir.variable(id);
+        let ty = self.gcx.type_of_item(id.into());
+        if ty.references_error() {
+            return;
+        }
+
+        if var.is_immutable() {
+            return self.err(
+                error_code!(3773),
+                "assembly access to immutable variables is not supported",
+                span,
+            );
+        }
+
+        if var.is_constant() {
+            let Some(value) = self.root_constant_variable(id).initializer else {
+                return self.err(error_code!(3224), "constant has no value", span);
+            };
+            if lvalue {
+                self.err(error_code!(6252), "constant variables cannot be assigned to", span);
+            } else if ext.suffix.is_some() {
+                self.err(
+                    error_code!(6617),
+                    "the suffixes `.offset`, `.slot` and `.length` can only be used with non-constant storage or transient storage variables",
+                    span,
+                );
+            } else if !ty.is_value_type() || !is_number_constant(value) {
+                self.err(
+                    error_code!(7615),
+                    "only direct number constants and references to such constants are supported by inline assembly",
+                    span,
+                );
+            }
+            return;
+        }
+
+        let is_storage = var.is_state_variable()
+            || ty.data_stored_in(DataLocation::Storage)
+            || ty.data_stored_in(DataLocation::Transient);
+        let is_calldata_array =
+            ty.is_ref_at(DataLocation::Calldata) && ty.peel_refs().is_dynamically_sized();
+        let is_external_fn_ptr =
+            matches!(ty.kind, TyKind::FnPtr(f) if f.visibility == hir::Visibility::External);
+
+        let Some(suffix) = ext.suffix else {
+            if var.is_state_variable() {
+                self.gcx
+                    .dcx()
+                    .err("only local variables are supported")
+                    .code(error_code!(1408))
+                    .span(span)
+                    .help("to access storage variables, use the `.slot` and `.offset` suffixes")
+                    .emit();
+            } else if is_storage {
+                self.err(
+                    error_code!(9068),
+                    "you have to use the `.slot` or `.offset` suffix to access storage reference variables",
+                    span,
+                );
+            } else if is_calldata_array {
+                self.gcx
+                    .dcx()
+                    .err("calldata elements cannot be accessed directly")
+                    .code(error_code!(1397))
+                    .span(span)
+                    .help("use `.offset` and `.length` to access the calldata offset and length of this array, and then use `calldatacopy`")
+                    .emit();
+            } else if is_external_fn_ptr {
+                self.err(
+                    error_code!(9857),
+                    "only types that use one stack slot are supported",
+                    span,
+                );
+            }
+            return;
+        };
+
+        if is_storage {
+            if !matches!(suffix, Suffix::Slot | Suffix::Offset) {
+                self.err(
+                    error_code!(4656),
+                    "state variables only support `.slot` and `.offset`",
+                    span,
+                );
+            } else if lvalue && var.is_state_variable() {
+                self.gcx
+                    .dcx()
+                    .err("state variables cannot be assigned to")
+                    .code(error_code!(4713))
+                    .span(span)
+                    .help("use `sstore()` instead")
+                    .emit();
+            } else if lvalue && suffix != Suffix::Slot {
+                self.err(error_code!(9739), "only `.slot` can be assigned to", span);
+            }
+        } else if is_calldata_array {
+            if !matches!(suffix, Suffix::Offset | Suffix::Length) {
+                self.err(
+                    error_code!(1536),
+                    "calldata variables only support `.offset` and `.length`",
+                    span,
+                );
+            }
+        } else if is_external_fn_ptr {
+            if !matches!(suffix, Suffix::Selector | Suffix::Address) {
+                self.err(
+                    error_code!(9272),
+                    "variables of type function pointer only support `.selector` and `.address`",
+                    span,
+                );
+            }
+        } else {
+            let msg = format!(
+                "the suffix `.{}` is not supported by this variable or type",
+                suffix.name()
+            );
+            self.err(error_code!(3622), &msg, span);
+        }
+    }
+
+    /// Checks a reference to a declaration other than a variable.
+    fn check_non_variable_ref(&self, ext: &yul::ExternalRef, span: Span, lvalue: bool) {
+        if ext.suffix.is_some() {
+            self.err(
+                error_code!(7944),
+                "the suffixes `.offset`, `.slot` and `.length` can only be used with variables",
+                span,
+            );
+        } else if lvalue {
+            self.err(
+                error_code!(1990),
+                "only local variables can be assigned to in inline assembly",
+                span,
+            );
+        } else {
+            match ext.res {
+                hir::Res::Item(hir::ItemId::Function(_)) => self.err(
+                    error_code!(2025),
+                    "access to functions is not allowed in inline assembly",
+                    span,
+                ),
+                hir::Res::Item(hir::ItemId::Contract(id))
+                    if self.gcx.hir.contract(id).kind.is_library() => {}
+                res => {
+                    self.gcx
+                        .dcx()
+                        .err(format!(
+                            "{} `{}` cannot be accessed in inline assembly",
+                            res.description(),
+                            ext.name
+                        ))
+                        .span(span)
+                        .note("only variables and libraries can be accessed in inline assembly")
+                        .emit();
+                }
+            }
+        }
+    }
+
+    /// Follows the initializers of constant variables that reference other constant variables.
+    fn root_constant_variable(&self, mut id: hir::VariableId) -> &hir::Variable<'_> {
+        // Bound the number of steps in case of cycles, which are reported elsewhere.
+        for _ in 0..256 {
+            let var = self.gcx.hir.variable(id);
+            if let Some(init) = var.initializer
+                && let hir::ExprKind::Ident(&[hir::Res::Item(hir::ItemId::Variable(next))]) =
+                    init.kind
+                && self.gcx.hir.variable(next).is_constant()
+            {
+                id = next;
+                continue;
+            }
+            break;
+        }
+        self.gcx.hir.variable(id)
+    }
+}
+
+impl<'gcx> Visit<'gcx> for AssemblyChecker<'gcx> {
+    type BreakValue = Never;
+
+    fn hir(&self) -> &'gcx hir::Hir<'gcx> {
+        &self.gcx.hir
+    }
+
+    fn visit_nested_var(&mut self, _id: hir::VariableId) -> ControlFlow<Self::BreakValue> {
+        ControlFlow::Continue(())
+    }
+
+    fn visit_expr(&mut self, _expr: &'gcx hir::Expr<'gcx>) -> ControlFlow<Self::BreakValue> {
+        ControlFlow::Continue(())
+    }
+
+    fn visit_stmt_assembly(
+        &mut self,
+        assembly: &'gcx hir::StmtAssembly<'gcx>,
+    ) -> ControlFlow<Self::BreakValue> {
+        let sess = self.gcx.sess;
+        let dialect = crate::yul::Dialect::inline_assembly(sess.opts.evm_version);
+        crate::yul::check_code(sess, &assembly.code, dialect, None);
+        self.walk_stmt_assembly(assembly)
+    }
+
+    fn visit_yul_stmt(&mut self, stmt: &'gcx yul::Stmt<'gcx>) -> ControlFlow<Self::BreakValue> {
+        if let yul::StmtKind::Assign(lhs, rhs) = stmt.kind {
+            for expr in lhs {
+                if let yul::ExprKind::External(ext) = &expr.kind {
+                    self.check_external_ref(ext, expr.span, true);
+                }
+            }
+            return self.visit_yul_expr(rhs);
+        }
+        self.walk_yul_stmt(stmt)
+    }
+
+    fn visit_yul_expr(&mut self, expr: &'gcx yul::Expr<'gcx>) -> ControlFlow<Self::BreakValue> {
+        if let yul::ExprKind::External(ext) = &expr.kind {
+            self.check_external_ref(ext, expr.span, false);
+        }
+        self.walk_yul_expr(expr)
+    }
+}
+
+/// Returns `true` if the given constant initializer is a literal or evaluates to a rational
+/// number.
+fn is_number_constant(expr: &hir::Expr<'_>) -> bool {
+    matches!(expr.kind, hir::ExprKind::Lit(_)) || is_rational_expr(expr)
+}
+
+/// Returns `true` if the given expression is made up of only number literals.
+fn is_rational_expr(expr: &hir::Expr<'_>) -> bool {
+    match expr.kind {
+        hir::ExprKind::Lit(lit) => matches!(lit.kind, solar_ast::LitKind::Number(_)),
+        hir::ExprKind::Unary(_, expr) => is_rational_expr(expr),
+        hir::ExprKind::Binary(lhs, _, rhs) => is_rational_expr(lhs) && is_rational_expr(rhs),
+        hir::ExprKind::Tuple(&[Some(expr)]) => is_rational_expr(expr),
+        _ => false,
+    }
+}
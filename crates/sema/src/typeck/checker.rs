@@ -6,12 +6,16 @@ use crate::{
 use alloy_primitives::U256;
 use solar_ast::{DataLocation, ElementaryType, Span};
 use solar_data_structures::{Never, map::FxHashMap, pluralize, smallvec::SmallVec};
-use solar_interface::{diagnostics::DiagCtxt, sym};
+use solar_interface::{
+    diagnostics::{Applicability, DiagCtxt, ErrorGuaranteed},
+    error_code, sym,
+};
 use std::ops::ControlFlow;
 
-pub(super) fn check(gcx: Gcx<'_>, source: hir::SourceId) {
+pub(super) fn check(gcx: Gcx<'_>, source: hir::SourceId) -> super::TypeckResults<'_> {
     let mut checker = TypeChecker::new(gcx, source);
     let _ = checker.visit_nested_source(source);
+    super::TypeckResults { types: checker.types, res: checker.res }
 }
 
 struct TypeChecker<'gcx> {
@@ -20,6 +24,8 @@ struct TypeChecker<'gcx> {
     contract: Option<hir::ContractId>,
 
     types: FxHashMap<hir::ExprId, Ty<'gcx>>,
+    /// The resolution of identifier and member expressions, after overload resolution.
+    res: FxHashMap<hir::ExprId, hir::Res>,
 
     lvalue_context: Option<Result<(), NotLvalueReason>>,
 
@@ -27,12 +33,13 @@ struct TypeChecker<'gcx> {
     in_emit: bool,
     /// Whether we're directly inside a revert statement (for the immediate call only).
     in_revert: bool,
+    /// The arguments of the call whose callee is being checked, used for overload resolution.
+    call_args: Option<&'gcx hir::CallArgs<'gcx>>,
 }
 
 #[derive(Clone, Copy)]
 enum NotLvalueReason {
     Constant,
-    Immutable,
     CalldataArray,
     CalldataStruct,
     FixedBytesIndex,
@@ -47,9 +54,11 @@ impl<'gcx> TypeChecker<'gcx> {
             source,
             contract: None,
             types: Default::default(),
+            res: Default::default(),
             lvalue_context: None,
             in_emit: false,
             in_revert: false,
+            call_args: None,
         }
     }
 
@@ -103,6 +112,7 @@ impl<'gcx> TypeChecker<'gcx> {
         expr: &'gcx hir::Expr<'gcx>,
         expected: Option<Ty<'gcx>>,
     ) -> Ty<'gcx> {
+        let call_args = self.call_args.take();
         match expr.kind {
             hir::ExprKind::Array(exprs) => {
                 let mut common = expected.and_then(|arr| arr.base_type(self.gcx));
@@ -134,7 +144,7 @@ impl<'gcx> TypeChecker<'gcx> {
                             .span(expr.span);
                         return self.gcx.mk_ty_err(err.emit());
                     }
-                    let _ = self.expect_ty(rhs, ty);
+                    let _ = self.expect_ty(rhs, self.assigned_ty(lhs, ty));
                     ty
                 } else if let Some(op) = op {
                     let rhs_ty = self.check_expr(rhs);
@@ -145,7 +155,7 @@ impl<'gcx> TypeChecker<'gcx> {
                     );
                     result
                 } else {
-                    let _ = self.expect_ty(rhs, ty);
+                    let _ = self.expect_ty(rhs, self.assigned_ty(lhs, ty));
                     ty
                 }
             }
@@ -155,6 +165,7 @@ impl<'gcx> TypeChecker<'gcx> {
                 self.check_binop(lhs_e, lhs, rhs_e, rhs, op, false)
             }
             hir::ExprKind::Call(callee, ref args, ref _opts) => {
+                self.call_args = Some(args);
                 let mut callee_ty = self.check_expr(callee);
 
                 // Get the function type for struct constructors, keeping struct_id for field names.
@@ -167,6 +178,11 @@ impl<'gcx> TypeChecker<'gcx> {
                     None
                 };
 
+                if let Some(builtin) = self.variadic_builtin_callee(callee) {
+                    self.try_set_not_lvalue(NotLvalueReason::Generic);
+                    return self.check_variadic_builtin_call(expr.span, builtin, args);
+                }
+
                 // TODO: `array.push() = x;` is the only valid call lvalue
                 let is_array_push = false;
 
@@ -241,7 +257,18 @@ impl<'gcx> TypeChecker<'gcx> {
                 }
             }
             hir::ExprKind::Ident(res) => {
-                let res = self.resolve_overloads(res, expr.span);
+                let res = match call_args {
+                    Some(args) if res.len() > 1 => {
+                        let candidates = res.iter().map(|&res| (self.type_of_res(res), Some(res)));
+                        let candidates = candidates.collect::<SmallVec<[_; 4]>>();
+                        match self.resolve_call_overloads(&candidates, args, expr.span) {
+                            Ok(i) => res[i],
+                            Err(guar) => hir::Res::Err(guar),
+                        }
+                    }
+                    _ => self.resolve_overloads(res, expr.span),
+                };
+                self.res.insert(expr.id, res);
                 if let Some(reason) = res_not_lvalue_reason(self.gcx, res) {
                     self.try_set_not_lvalue(reason);
                 }
@@ -262,7 +289,7 @@ impl<'gcx> TypeChecker<'gcx> {
                 if let Some((index_ty, result_ty)) = self.index_types(ty) {
                     // Index expression.
                     if let Some(index) = index {
-                        let _ = self.expect_ty(index, index_ty);
+                        let _ = self.not_lvalue(|this| this.expect_ty(index, index_ty));
                     } else {
                         self.dcx().err("index expression cannot be omitted").span(expr.span).emit();
                     }
@@ -295,11 +322,12 @@ impl<'gcx> TypeChecker<'gcx> {
                     self.dcx().err("can only slice dynamic calldata arrays").span(expr.span).emit();
                 }
                 if let Some((_index_ty, _result_ty)) = self.index_types(ty) {
+                    let uint256 = self.gcx.types.uint(256);
                     if let Some(start) = start {
-                        let _ = self.expect_ty(start, self.gcx.types.uint(256));
+                        let _ = self.not_lvalue(|this| this.expect_ty(start, uint256));
                     }
                     if let Some(end) = end {
-                        let _ = self.expect_ty(end, self.gcx.types.uint(256));
+                        let _ = self.not_lvalue(|this| this.expect_ty(end, uint256));
                     }
                     if let TyKind::Slice(_) = ty.kind {
                         ty
@@ -311,20 +339,29 @@ impl<'gcx> TypeChecker<'gcx> {
                 }
             }
             hir::ExprKind::Lit(lit) => self.gcx.type_of_lit(lit),
-            hir::ExprKind::Member(expr, ident) => {
-                let expr_ty = self.check_expr(expr);
+            hir::ExprKind::Member(base, ident) => {
+                let expr_ty = self.check_expr(base);
                 if expr_ty.references_error() {
                     return expr_ty;
                 }
 
-                let possible_members = self
+                let mut possible_members = self
                     .gcx
                     .members_of(expr_ty, self.source, self.contract)
                     .iter()
                     .filter(|m| m.name == ident.name)
                     .collect::<SmallVec<[_; 4]>>();
 
-                // TODO: overload resolution
+                if let Some(args) = call_args
+                    && possible_members.len() > 1
+                {
+                    let candidates = possible_members.iter().map(|m| (m.ty, m.res));
+                    let candidates = candidates.collect::<SmallVec<[_; 4]>>();
+                    match self.resolve_call_overloads(&candidates, args, ident.span) {
+                        Ok(i) => possible_members = SmallVec::from_slice(&[possible_members[i]]),
+                        Err(guar) => return self.gcx.mk_ty_err(guar),
+                    }
+                }
 
                 let ty = match possible_members[..] {
                     [] => {
@@ -336,7 +373,12 @@ impl<'gcx> TypeChecker<'gcx> {
                         let err = self.dcx().err(msg).span(ident.span);
                         self.gcx.mk_ty_err(err.emit())
                     }
-                    [member] => member.ty,
+                    [member] => {
+                        if let Some(res) = member.res {
+                            self.res.insert(expr.id, res);
+                        }
+                        member.ty
+                    }
                     [..] => {
                         let msg = format!(
                             "member `{ident}` not unique on type `{}`",
@@ -526,7 +568,6 @@ impl<'gcx> TypeChecker<'gcx> {
                 }
             }
             hir::ExprKind::Type(ref ty) => {
-                debug_assert!(ty.kind.is_elementary(), "non-elementary ExprKind::Type: {ty:?}");
                 self.gcx.mk_ty(TyKind::Type(self.gcx.type_of_hir_ty(ty)))
             }
             hir::ExprKind::Unary(op, expr) => {
@@ -541,7 +582,11 @@ impl<'gcx> TypeChecker<'gcx> {
                 } else {
                     self.check_expr(expr)
                 };
-                // TODO: custom operators
+                if let Some(udo) = hir::UserDefinableOperator::from_un_op(op.kind)
+                    && let Some(ret) = self.user_defined_operator(ty, udo, true)
+                {
+                    return ret;
+                }
                 if valid_unop(ty, op.kind) {
                     // Propagate negativity for integer literals under unary negation.
                     if op.kind == hir::UnOpKind::Neg
@@ -602,6 +647,38 @@ impl<'gcx> TypeChecker<'gcx> {
         false
     }
 
+    /// Returns true if the expression refers to a variable that is a storage pointer rather than a
+    /// storage reference, that is any variable other than a state variable.
+    fn is_storage_pointer(&self, expr: &'gcx hir::Expr<'gcx>) -> bool {
+        if let hir::ExprKind::Ident(res_slice) = &expr.kind {
+            let res = self.resolve_overloads(res_slice, expr.span);
+            if let hir::Res::Item(hir::ItemId::Variable(var_id)) = res {
+                return !self.gcx.hir.variable(var_id).is_state_variable();
+            }
+        }
+        false
+    }
+
+    /// Returns the type that values assigned to `lhs`, of type `ty`, are checked against.
+    ///
+    /// Storage pointers can only be assigned storage references, while values assigned to any other
+    /// storage location are copied into storage.
+    fn assigned_ty(&self, lhs: &'gcx hir::Expr<'gcx>, ty: Ty<'gcx>) -> Ty<'gcx> {
+        match ty.kind {
+            TyKind::Ref(..) if !self.is_storage_pointer(lhs.peel_parens()) => {
+                storage_copy_ty(self.gcx, ty)
+            }
+            TyKind::Tuple(tys) => {
+                let hir::ExprKind::Tuple(exprs) = lhs.peel_parens().kind else { return ty };
+                let tys = std::iter::zip(tys, exprs)
+                    .map(|(&ty, expr)| expr.map_or(ty, |expr| self.assigned_ty(expr, ty)))
+                    .collect::<SmallVec<[_; 8]>>();
+                self.gcx.mk_ty_tuple(self.gcx.mk_tys(&tys))
+            }
+            _ => ty,
+        }
+    }
+
     fn check_binop(
         &mut self,
         lhs_e: &'gcx hir::Expr<'gcx>,
@@ -611,8 +688,16 @@ impl<'gcx> TypeChecker<'gcx> {
         op: hir::BinOp,
         assign: bool,
     ) -> Ty<'gcx> {
+        // User-defined operators are not considered for compound assignments.
+        if !assign
+            && let Some(udo) = hir::UserDefinableOperator::from_bin_op(op.kind)
+            && rhs.convert_implicit_to(lhs, self.gcx)
+            && let Some(ret) = self.user_defined_operator(lhs, udo, false)
+        {
+            return ret;
+        }
+
         let common = binop_common_type(self.gcx, lhs, rhs, op.kind);
-        // TODO: custom operators
         if let Some(common) = common
             && !(assign && common != lhs)
         {
@@ -630,6 +715,19 @@ impl<'gcx> TypeChecker<'gcx> {
         self.gcx.mk_ty_err(err.emit())
     }
 
+    /// Returns the result type of the user-defined operator bound to `op` for `ty`, if any.
+    fn user_defined_operator(
+        &self,
+        ty: Ty<'gcx>,
+        op: hir::UserDefinableOperator,
+        unary: bool,
+    ) -> Option<Ty<'gcx>> {
+        // Ambiguous definitions are reported when checking the `using for` directive.
+        let &f_id = self.gcx.operator_definitions(ty, op, unary).first()?;
+        let TyKind::FnPtr(f) = self.gcx.type_of_item(f_id.into()).kind else { unreachable!() };
+        Some(self.fn_call_return_type(f.returns))
+    }
+
     /// Returns `(index_ty, result_ty)` for the given value type, if it is indexable.
     ///
     /// Does not consider `TypeKind::Type`.
@@ -662,6 +760,9 @@ impl<'gcx> TypeChecker<'gcx> {
             );
         };
         let from = self.check_expr(from_expr);
+        if int_literal_converts_to_fixed_bytes(from_expr, to) {
+            return to;
+        }
         match from.try_convert_explicit_to(to, self.gcx) {
             Ok(result_ty) => result_ty,
             Err(err) => {
@@ -672,6 +773,251 @@ impl<'gcx> TypeChecker<'gcx> {
         }
     }
 
+    /// Returns the builtin called by `callee` if it takes arbitrary parameters.
+    fn variadic_builtin_callee(&self, callee: &'gcx hir::Expr<'gcx>) -> Option<Builtin> {
+        let hir::ExprKind::Member(base, ident) = callee.kind else { return None };
+        let base_ty = *self.types.get(&base.id)?;
+        let members = self.gcx.members_of(base_ty, self.source, self.contract);
+        let member = members.iter().find(|m| m.name == ident.name)?;
+        let Some(hir::Res::Builtin(builtin)) = member.res else { return None };
+        matches!(
+            builtin,
+            Builtin::AbiEncode
+                | Builtin::AbiEncodePacked
+                | Builtin::AbiEncodeWithSelector
+                | Builtin::AbiEncodeCall
+                | Builtin::AbiEncodeWithSignature
+                | Builtin::AbiDecode
+                | Builtin::StringConcat
+                | Builtin::BytesConcat
+        )
+        .then_some(builtin)
+    }
+
+    /// Checks a call to a builtin that takes arbitrary parameters, returning the type of the call.
+    ///
+    /// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/analysis/TypeChecker.cpp#L2081>
+    #[must_use]
+    fn check_variadic_builtin_call(
+        &mut self,
+        call_span: Span,
+        builtin: Builtin,
+        args: &'gcx hir::CallArgs<'gcx>,
+    ) -> Ty<'gcx> {
+        let hir::CallArgsKind::Unnamed(exprs) = args.kind else {
+            let guar = self
+                .dcx()
+                .err("named arguments cannot be used for functions that take arbitrary parameters")
+                .span(args.span)
+                .emit();
+            for arg in args.exprs() {
+                let _ = self.check_arg(arg);
+            }
+            return self.gcx.mk_ty_err(guar);
+        };
+
+        let bytes = self.gcx.types.bytes_ref.memory;
+        match builtin {
+            Builtin::AbiEncode | Builtin::AbiEncodePacked => {
+                let packed = builtin == Builtin::AbiEncodePacked;
+                for arg in exprs {
+                    self.check_abi_encode_arg(arg, packed);
+                }
+                bytes
+            }
+            Builtin::AbiEncodeWithSelector | Builtin::AbiEncodeWithSignature => {
+                let Some((first, rest)) = exprs.split_first() else {
+                    let guar = self.wrong_arg_count(call_span, args.span, 0, 1, true);
+                    return self.gcx.mk_ty_err(guar);
+                };
+                let first_ty = if builtin == Builtin::AbiEncodeWithSelector {
+                    self.gcx.types.fixed_bytes(4)
+                } else {
+                    self.gcx.types.string_ref.memory
+                };
+                self.expect_arg_ty(first, first_ty);
+                for arg in rest {
+                    self.check_abi_encode_arg(arg, false);
+                }
+                bytes
+            }
+            Builtin::AbiEncodeCall => {
+                let [f_expr, args_expr] = exprs else {
+                    let guar = self.wrong_arg_count(call_span, args.span, exprs.len(), 2, false);
+                    for arg in exprs {
+                        let _ = self.check_arg(arg);
+                    }
+                    return self.gcx.mk_ty_err(guar);
+                };
+                self.check_abi_encode_call(f_expr, args_expr);
+                bytes
+            }
+            Builtin::AbiDecode => {
+                let [data_expr, types_expr] = exprs else {
+                    let guar = self.wrong_arg_count(call_span, args.span, exprs.len(), 2, false);
+                    for arg in exprs {
+                        let _ = self.check_arg(arg);
+                    }
+                    return self.gcx.mk_ty_err(guar);
+                };
+                self.check_abi_decode(data_expr, types_expr)
+            }
+            Builtin::StringConcat => {
+                for arg in exprs {
+                    self.expect_arg_ty(arg, self.gcx.types.string_ref.memory);
+                }
+                self.gcx.types.string_ref.memory
+            }
+            Builtin::BytesConcat => {
+                for arg in exprs {
+                    let ty = self.check_arg(arg);
+                    if !ty.is_fixed_bytes() {
+                        self.check_expected(arg, ty, bytes);
+                    }
+                }
+                bytes
+            }
+            _ => unreachable!("{builtin:?} does not take arbitrary parameters"),
+        }
+    }
+
+    /// Checks an argument that is ABI-encoded by a builtin.
+    fn check_abi_encode_arg(&mut self, expr: &'gcx hir::Expr<'gcx>, packed: bool) {
+        let ty = self.check_arg(expr);
+        if ty.references_error() {
+            return;
+        }
+        if packed && matches!(ty.kind, TyKind::IntLiteral(..)) {
+            self.dcx()
+                .err("cannot perform packed encoding for a literal")
+                .span(expr.span)
+                .help("convert it to an explicit type first")
+                .emit();
+        } else if !is_abi_encodable(self.gcx, ty, packed) {
+            let msg = if packed {
+                format!("type `{}` is not supported in packed mode", ty.display(self.gcx))
+            } else {
+                format!("type `{}` cannot be ABI-encoded", ty.display(self.gcx))
+            };
+            self.dcx().err(msg).span(expr.span).emit();
+        }
+    }
+
+    /// Checks the arguments of `abi.encodeCall(f, (args...))` against the parameters of `f`.
+    fn check_abi_encode_call(
+        &mut self,
+        f_expr: &'gcx hir::Expr<'gcx>,
+        args_expr: &'gcx hir::Expr<'gcx>,
+    ) {
+        let f_ty = self.check_arg(f_expr);
+        let _ = self.check_arg(args_expr);
+        let params = match f_ty.kind {
+            TyKind::FnPtr(f) if f.visibility >= hir::Visibility::Public => Some(f.parameters),
+            TyKind::FnPtr(_) => {
+                self.dcx()
+                    .err("expected an external function, found an internal function")
+                    .span(f_expr.span)
+                    .help("did you forget to prefix `this.`?")
+                    .emit();
+                None
+            }
+            TyKind::Err(_) => None,
+            _ => {
+                let msg =
+                    format!("expected a function pointer, found `{}`", f_ty.display(self.gcx));
+                self.dcx().err(msg).span(f_expr.span).emit();
+                None
+            }
+        };
+        let Some(params) = params else { return };
+
+        let components = match args_expr.kind {
+            hir::ExprKind::Tuple(components) => components.iter().flatten().copied().collect(),
+            _ => SmallVec::<[_; 8]>::from_slice(&[args_expr]),
+        };
+        if components.len() != params.len() {
+            let msg = format!(
+                "expected {} component{} for the tuple parameter, found {}",
+                params.len(),
+                pluralize!(params.len()),
+                components.len(),
+            );
+            self.dcx().err(msg).span(args_expr.span).emit();
+            return;
+        }
+        for (component, &param) in components.into_iter().zip(params) {
+            self.expect_arg_ty(component, param);
+        }
+    }
+
+    /// Checks `abi.decode(data, (T...))` and returns the decoded types.
+    #[must_use]
+    fn check_abi_decode(
+        &mut self,
+        data_expr: &'gcx hir::Expr<'gcx>,
+        types_expr: &'gcx hir::Expr<'gcx>,
+    ) -> Ty<'gcx> {
+        let data_ty = self.check_arg(data_expr);
+        let types = &self.gcx.types;
+        if !data_ty.convert_implicit_to(types.bytes_ref.memory, self.gcx)
+            && !data_ty.convert_implicit_to(types.bytes_ref.calldata, self.gcx)
+        {
+            let msg = format!(
+                "the first argument to `abi.decode` must be implicitly convertible to `bytes memory` or `bytes calldata`, found `{}`",
+                data_ty.display(self.gcx),
+            );
+            self.dcx().err(msg).span(data_expr.span).emit();
+        }
+
+        let _ = self.check_arg(types_expr);
+        let components = match types_expr.kind {
+            hir::ExprKind::Tuple(components) => components.iter().flatten().copied().collect(),
+            _ => SmallVec::<[_; 8]>::from_slice(&[types_expr]),
+        };
+        let tys = components.into_iter().map(|component| {
+            let ty = self.get(component);
+            match ty.kind {
+                TyKind::Type(ty) => {
+                    let ty = ty.with_loc_if_ref(self.gcx, DataLocation::Memory);
+                    if !ty.references_error() && !is_abi_encodable(self.gcx, ty, false) {
+                        let msg =
+                            format!("decoding type `{}` is not supported", ty.display(self.gcx));
+                        self.dcx().err(msg).span(component.span).emit();
+                    }
+                    ty
+                }
+                TyKind::Err(_) => ty,
+                _ => self
+                    .gcx
+                    .mk_ty_err(self.dcx().err("expected a type name").span(component.span).emit()),
+            }
+        });
+        let tys = tys.collect::<SmallVec<[_; 8]>>();
+        self.fn_call_return_type(self.gcx.mk_tys(&tys))
+    }
+
+    fn wrong_arg_count(
+        &self,
+        call_span: Span,
+        args_span: Span,
+        given: usize,
+        expected: usize,
+        at_least: bool,
+    ) -> ErrorGuaranteed {
+        let expected_str =
+            if at_least { format!("at least {expected}") } else { expected.to_string() };
+        self.dcx()
+            .err(format!(
+                "wrong argument count for function call: {given} arguments given but expected {expected_str}"
+            ))
+            .span(call_span)
+            .span_label(
+                args_span,
+                format!("expected {expected_str} argument{}, found {given}", pluralize!(expected)),
+            )
+            .emit()
+    }
+
     #[track_caller]
     fn check_expected(
         &mut self,
@@ -680,6 +1026,9 @@ impl<'gcx> TypeChecker<'gcx> {
         expected: Ty<'gcx>,
     ) {
         let Err(err) = actual.try_convert_implicit_to(expected, self.gcx) else { return };
+        if int_literal_converts_to_fixed_bytes(expr, expected) {
+            return;
+        }
 
         let mut diag = self.dcx().err("mismatched types").span(expr.span);
         diag = diag.span_label(expr.span, err.message(actual, expected, self.gcx));
@@ -734,7 +1083,7 @@ impl<'gcx> TypeChecker<'gcx> {
                         .span(args.span)
                         .emit();
                     for arg in named_args {
-                        let _ = self.check_expr(&arg.value);
+                        let _ = self.check_arg(&arg.value);
                     }
                 }
             }
@@ -770,10 +1119,10 @@ impl<'gcx> TypeChecker<'gcx> {
 
         let count = std::cmp::min(exprs.len(), param_tys.len());
         for i in 0..count {
-            let _ = self.expect_ty(&exprs[i], param_tys[i]);
+            self.expect_arg_ty(&exprs[i], param_tys[i]);
         }
         for expr in exprs.iter().skip(count) {
-            let _ = self.check_expr(expr);
+            let _ = self.check_arg(expr);
         }
     }
 
@@ -817,7 +1166,7 @@ impl<'gcx> TypeChecker<'gcx> {
                     .err(format!("duplicate named argument `{arg_name}`"))
                     .span(arg.name.span)
                     .emit();
-                let _ = self.check_expr(&arg.value);
+                let _ = self.check_arg(&arg.value);
                 continue;
             }
             seen_names.push(arg_name);
@@ -826,7 +1175,7 @@ impl<'gcx> TypeChecker<'gcx> {
 
             match param_idx {
                 Some(idx) => {
-                    let _ = self.expect_ty(&arg.value, param_tys[idx]);
+                    self.expect_arg_ty(&arg.value, param_tys[idx]);
                 }
                 None => {
                     self.dcx()
@@ -835,12 +1184,31 @@ impl<'gcx> TypeChecker<'gcx> {
                         ))
                         .span(arg.name.span)
                         .emit();
-                    let _ = self.check_expr(&arg.value);
+                    let _ = self.check_arg(&arg.value);
                 }
             }
         }
     }
 
+    /// Checks a call argument, unless it was already checked during overload resolution.
+    #[must_use]
+    fn check_arg(&mut self, expr: &'gcx hir::Expr<'gcx>) -> Ty<'gcx> {
+        match self.types.get(&expr.id) {
+            Some(&ty) => ty,
+            None => self.check_expr(expr),
+        }
+    }
+
+    /// Checks that a call argument is implicitly convertible to the parameter type.
+    fn expect_arg_ty(&mut self, expr: &'gcx hir::Expr<'gcx>, expected: Ty<'gcx>) {
+        match self.types.get(&expr.id) {
+            Some(&ty) => self.check_expected(expr, ty, expected),
+            None => {
+                let _ = self.expect_ty(expr, expected);
+            }
+        }
+    }
+
     #[must_use]
     fn check_var(&mut self, id: hir::VariableId) -> Ty<'gcx> {
         self.check_var_(id, true)
@@ -859,7 +1227,9 @@ impl<'gcx> TypeChecker<'gcx> {
                     .span(var.span)
                     .emit();
             } else if expect {
-                let _ = self.expect_ty(init, ty);
+                let expected =
+                    if var.is_state_variable() { storage_copy_ty(self.gcx, ty) } else { ty };
+                let _ = self.expect_ty(init, expected);
             }
         }
 
@@ -893,6 +1263,12 @@ impl<'gcx> TypeChecker<'gcx> {
                     ty.display(self.gcx)
                 ))
                 .span(var.span)
+                .span_suggestion(
+                    var.data_location_span,
+                    "change the data location",
+                    "storage",
+                    Applicability::MachineApplicable,
+                )
                 .emit();
         }
 
@@ -959,7 +1335,6 @@ impl<'gcx> TypeChecker<'gcx> {
             let var_ty = self.check_var_(var, false);
             self.check_expected(expr, ty, var_ty);
         }
-        // TODO: checks from https://github.com/ethereum/solidity/blob/9d7cc42bc1c12bb43e9dccf8c6c36833fdfcbbca/libsolidity/analysis/TypeChecker.cpp#L1219
     }
 
     #[must_use]
@@ -987,7 +1362,6 @@ impl<'gcx> TypeChecker<'gcx> {
 
         let msg = match result {
             Err(NotLvalueReason::Constant) => "cannot assign to a constant variable",
-            Err(NotLvalueReason::Immutable) => "cannot assign to an immutable variable",
             Err(NotLvalueReason::CalldataArray) => "calldata arrays are read-only",
             Err(NotLvalueReason::CalldataStruct) => "calldata structs are read-only",
             Err(NotLvalueReason::FixedBytesIndex) => {
@@ -1009,6 +1383,14 @@ impl<'gcx> TypeChecker<'gcx> {
         }
     }
 
+    /// Runs `f` outside of any lvalue context, e.g. for the index of an index expression.
+    fn not_lvalue<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let prev = self.lvalue_context.take();
+        let r = f(self);
+        self.lvalue_context = prev;
+        r
+    }
+
     fn in_lvalue(&self) -> bool {
         self.lvalue_context.is_some()
     }
@@ -1026,6 +1408,94 @@ impl<'gcx> TypeChecker<'gcx> {
         }
     }
 
+    /// Resolves an overloaded callee by the types of the call arguments.
+    ///
+    /// The arguments are checked without an expected type, and the unique candidate that they are
+    /// all implicitly convertible to is selected. Returns the index of the selected candidate.
+    ///
+    /// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/analysis/TypeChecker.cpp#L3945>
+    fn resolve_call_overloads(
+        &mut self,
+        candidates: &[(Ty<'gcx>, Option<hir::Res>)],
+        args: &'gcx hir::CallArgs<'gcx>,
+        span: Span,
+    ) -> Result<usize, ErrorGuaranteed> {
+        // Nested calls in the arguments are not directly inside of emit/revert.
+        let (in_emit, in_revert) = (self.in_emit, self.in_revert);
+        self.in_emit = false;
+        self.in_revert = false;
+        let mut guar = Ok(());
+        for arg in args.exprs() {
+            guar = guar.and(self.check_expr(arg).error_reported());
+        }
+        self.in_emit = in_emit;
+        self.in_revert = in_revert;
+        guar?;
+
+        let matching = candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, (ty, _))| self.call_args_match(*ty, args))
+            .map(|(i, _)| i)
+            .collect::<SmallVec<[_; 4]>>();
+        let (msg, code) = match matching[..] {
+            [i] => return Ok(i),
+            [] => {
+                ("no matching declaration found after argument-dependent lookup", error_code!(9322))
+            }
+            [..] => {
+                ("no unique declaration found after argument-dependent lookup", error_code!(4487))
+            }
+        };
+        let mut err = self.dcx().err(msg).code(code).span(span);
+        for &(ty, res) in candidates {
+            let msg = format!("candidate: `{}`", ty.display(self.gcx));
+            err = match res {
+                Some(hir::Res::Item(id)) => {
+                    let item = self.gcx.hir.item(id);
+                    err.span_note(item.name().map_or(item.span(), |name| name.span), msg)
+                }
+                _ => err.note(msg),
+            };
+        }
+        Err(err.emit())
+    }
+
+    /// Returns `true` if the already checked call arguments are implicitly convertible to the
+    /// parameters of the given callee.
+    fn call_args_match(&self, callee_ty: Ty<'gcx>, args: &hir::CallArgs<'gcx>) -> bool {
+        let (param_tys, param_names) = match callee_ty.kind {
+            TyKind::FnPtr(f) => (
+                f.parameters,
+                f.function_id.map(|id| self.get_param_names(self.gcx.hir.function(id).parameters)),
+            ),
+            TyKind::Event(param_tys, id) => {
+                (param_tys, Some(self.get_param_names(self.gcx.hir.event(id).parameters)))
+            }
+            TyKind::Error(param_tys, id) => {
+                (param_tys, Some(self.get_param_names(self.gcx.hir.error(id).parameters)))
+            }
+            _ => return false,
+        };
+        if args.len() != param_tys.len() {
+            return false;
+        }
+        match args.kind {
+            hir::CallArgsKind::Unnamed(exprs) => exprs
+                .iter()
+                .zip(param_tys)
+                .all(|(arg, &param_ty)| self.get(arg).convert_implicit_to(param_ty, self.gcx)),
+            hir::CallArgsKind::Named(named_args) => {
+                let Some(param_names) = param_names else { return false };
+                named_args.iter().all(|arg| {
+                    param_names.iter().position(|&name| name == Some(arg.name.name)).is_some_and(
+                        |i| self.get(&arg.value).convert_implicit_to(param_tys[i], self.gcx),
+                    )
+                })
+            }
+        }
+    }
+
     fn try_resolve_overloads(&self, res: &[hir::Res]) -> Result<hir::Res, OverloadError> {
         match res {
             [] => unreachable!("no candidates for overload resolution"),
@@ -1274,15 +1744,13 @@ impl<T> FromIterator<T> for WantOne<T> {
     }
 }
 
+/// Returns the reason why the given resolution cannot be assigned to, if any.
+///
+/// Assignments to immutable variables are checked separately, in [`super::immutables`].
 fn res_not_lvalue_reason(gcx: Gcx<'_>, res: hir::Res) -> Option<NotLvalueReason> {
     match res {
         hir::Res::Item(hir::ItemId::Variable(var)) => {
-            let var = gcx.hir.variable(var);
-            match var.mutability {
-                Some(m) if m.is_constant() => Some(NotLvalueReason::Constant),
-                Some(m) if m.is_immutable() => Some(NotLvalueReason::Immutable),
-                _ => None,
-            }
+            gcx.hir.variable(var).is_constant().then_some(NotLvalueReason::Constant)
         }
         hir::Res::Err(_) => None,
         _ => Some(NotLvalueReason::Generic),
@@ -1340,6 +1808,64 @@ fn valid_unop(ty: Ty<'_>, op: hir::UnOpKind) -> bool {
     }
 }
 
+/// Returns `true` if `expr` is an integer literal that converts to the fixed bytes type `to`.
+///
+/// This is the case for zero, and for hexadecimal literals with exactly as many digits as `to`.
+///
+/// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/ast/Types.cpp#L1002>
+fn int_literal_converts_to_fixed_bytes(expr: &hir::Expr<'_>, to: Ty<'_>) -> bool {
+    let TyKind::Elementary(ElementaryType::FixedBytes(size)) = to.kind else { return false };
+    let hir::ExprKind::Lit(lit) = expr.peel_parens().kind else { return false };
+    let solar_ast::LitKind::Number(value) = lit.kind else { return false };
+    if value.is_zero() {
+        return true;
+    }
+    let s = lit.symbol.as_str();
+    s.strip_prefix("0x").is_some_and(|digits| {
+        digits.bytes().filter(|&b| b != b'_').count() == size.bytes() as usize * 2
+    })
+}
+
+/// Returns the type to check values against when they are copied into the storage reference `to`,
+/// such as a state variable or one of its members.
+///
+/// Values from any data location can be copied into storage, which is modeled as an implicit
+/// conversion to memory.
+fn storage_copy_ty<'gcx>(gcx: Gcx<'gcx>, to: Ty<'gcx>) -> Ty<'gcx> {
+    if let TyKind::Ref(_, DataLocation::Storage) = to.kind {
+        return to.with_loc(gcx, DataLocation::Memory);
+    }
+    to
+}
+
+/// Returns `true` if values of the given type can be ABI-encoded, either in standard or in packed
+/// mode.
+///
+/// Packed mode does not support structs and nested arrays.
+fn is_abi_encodable<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>, packed: bool) -> bool {
+    let Some(ty) = ty.mobile(gcx) else { return false };
+    if !ty.can_be_exported() {
+        return false;
+    }
+    match ty.peel_refs().kind {
+        TyKind::Elementary(_)
+        | TyKind::Contract(_)
+        | TyKind::Enum(_)
+        | TyKind::Udvt(..)
+        | TyKind::FnPtr(_) => true,
+        TyKind::Struct(_) => !packed,
+        TyKind::Slice(array) => is_abi_encodable(gcx, array, packed),
+        TyKind::Array(elem, _) | TyKind::DynArray(elem) => {
+            if packed {
+                elem.peel_refs().is_value_type()
+            } else {
+                is_abi_encodable(gcx, elem, packed)
+            }
+        }
+        _ => false,
+    }
+}
+
 fn binop_common_type<'gcx>(
     gcx: Gcx<'gcx>,
     ty: Ty<'gcx>,
@@ -1457,7 +1983,7 @@ fn struct_constructor<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>, id: hir::StructId) ->
     gcx.mk_builtin_fn(
         &gcx.struct_field_types(id)
             .iter()
-            .map(|&ty| ty.with_loc_if_ref(gcx, DataLocation::Memory))
+            .map(|&ty| ty.peel_refs().with_loc_if_ref(gcx, DataLocation::Memory))
             .collect::<Vec<_>>(),
         hir::StateMutability::Pure,
         &[ty.with_loc(gcx, DataLocation::Memory)],
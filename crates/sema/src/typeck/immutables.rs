@@ -0,0 +1,357 @@
+//! Immutable state variable checks.
+//!
+//! Immutable variables must be initialized exactly once, either inline or with an unconditional
+//! assignment in the constructor of the contract that declares them, and cannot be read before
+//! they are initialized during construction.
+//!
+//! Reference: solc `libsolidity/analysis/ImmutableValidator.cpp`
+
+use crate::{
+    hir::{self, Visit},
+    ty::Gcx,
+};
+use solar_data_structures::{Never, map::FxHashSet};
+use solar_interface::{
+    Span,
+    diagnostics::{DiagId, DiagMsg},
+    error_code,
+};
+use std::ops::ControlFlow;
+
+/// Checks the immutable variables of the given contract.
+///
+/// The creation code of the contract is visited in execution order, following internal function
+/// calls, and then all of the other functions of the contract are visited.
+///
+/// Only the code and immutable variables of the contract itself are reported, so that code of base
+/// contracts is not reported again for every derived contract.
+pub(super) fn check(gcx: Gcx<'_>, id: hir::ContractId) {
+    let contract = gcx.hir.contract(id);
+    if contract.linearization_failed() {
+        return;
+    }
+
+    let mut validator = ImmutableValidator::new(gcx, id);
+    for &base in contract.linearized_bases.iter().rev() {
+        let base_contract = gcx.hir.contract(base);
+        for var_id in base_contract.variables() {
+            let var = gcx.hir.variable(var_id);
+            if let Some(init) = var.initializer {
+                validator.owner = Some(base);
+                let _ = validator.visit_expr(init);
+                if var.is_immutable() {
+                    validator.initialized.insert(var_id);
+                }
+            }
+        }
+        if let Some(ctor) = base_contract.ctor {
+            validator.visit_callable_if_new(ctor);
+        }
+    }
+    validator.check_all_initialized(id, contract.name.span);
+
+    validator.in_creation = false;
+    for function in contract.all_functions() {
+        validator.visit_callable_if_new(function);
+    }
+}
+
+struct ImmutableValidator<'gcx> {
+    gcx: Gcx<'gcx>,
+    /// The contract being checked.
+    contract: hir::ContractId,
+    /// The contract that declares the code being visited. Diagnostics are only emitted if this is
+    /// the contract being checked.
+    owner: Option<hir::ContractId>,
+    /// The constructor being visited, if any. Not set in functions called by the constructor.
+    constructor: Option<&'gcx hir::Function<'gcx>>,
+    /// Whether the code being visited is executed during construction.
+    in_creation: bool,
+    in_loop: bool,
+    in_branch: bool,
+    in_try_catch: bool,
+    /// The immutable variables that have been initialized so far.
+    initialized: FxHashSet<hir::VariableId>,
+    /// The functions and modifiers that have already been visited.
+    visited: FxHashSet<hir::FunctionId>,
+}
+
+impl<'gcx> ImmutableValidator<'gcx> {
+    fn new(gcx: Gcx<'gcx>, contract: hir::ContractId) -> Self {
+        Self {
+            gcx,
+            contract,
+            owner: None,
+            constructor: None,
+            in_creation: true,
+            in_loop: false,
+            in_branch: false,
+            in_try_catch: false,
+            initialized: FxHashSet::default(),
+            visited: FxHashSet::default(),
+        }
+    }
+
+    /// Visits the body of the given function or modifier, unless it has already been visited.
+    fn visit_callable_if_new(&mut self, id: hir::FunctionId) {
+        if !self.visited.insert(id) {
+            return;
+        }
+        let func = self.gcx.hir.function(id);
+        let Some(body) = &func.body else { return };
+
+        let owner = std::mem::replace(&mut self.owner, func.contract);
+        let constructor =
+            std::mem::replace(&mut self.constructor, func.is_constructor().then_some(func));
+        let in_loop = std::mem::take(&mut self.in_loop);
+        let in_branch = std::mem::take(&mut self.in_branch);
+        let in_try_catch = std::mem::take(&mut self.in_try_catch);
+
+        for modifier in func.modifiers {
+            let _ = self.visit_call_args(&modifier.args);
+            if let hir::ItemId::Function(modifier) = modifier.id {
+                self.visit_callable_if_new(modifier);
+            }
+        }
+        for stmt in body.iter() {
+            let _ = self.visit_stmt(stmt);
+        }
+
+        self.owner = owner;
+        self.constructor = constructor;
+        self.in_loop = in_loop;
+        self.in_branch = in_branch;
+        self.in_try_catch = in_try_catch;
+    }
+
+    /// Returns `true` if diagnostics for the code being visited should be emitted.
+    fn should_report(&self) -> bool {
+        self.owner == Some(self.contract)
+    }
+
+    /// Visits an expression that is assigned to.
+    ///
+    /// `ordinary` is `false` if the previous value is also read, like in `x += 1` or `delete x`.
+    fn visit_lvalue(&mut self, expr: &'gcx hir::Expr<'gcx>, ordinary: bool) {
+        match &expr.peel_parens().kind {
+            hir::ExprKind::Ident(res) => {
+                if let Some(var) = immutable_res(self.gcx, res) {
+                    self.write(var, expr.span, ordinary);
+                } else {
+                    let _ = self.visit_expr(expr);
+                }
+            }
+            hir::ExprKind::Tuple(exprs) => {
+                for expr in exprs.iter().copied().flatten() {
+                    self.visit_lvalue(expr, ordinary);
+                }
+            }
+            _ => {
+                let _ = self.visit_expr(expr);
+            }
+        }
+    }
+
+    fn write(&mut self, id: hir::VariableId, span: Span, ordinary: bool) {
+        let var = self.gcx.hir.variable(id);
+        let invalid_location = match self.constructor {
+            None => Some((
+                "immutable variables can only be initialized inline or assigned directly in the constructor",
+                error_code!(1581),
+            )),
+            Some(ctor) if ctor.contract != var.contract => Some((
+                "immutable variables must be initialized in the constructor of the contract they are defined in",
+                error_code!(7484),
+            )),
+            Some(_) if self.in_loop => {
+                Some(("immutable variables cannot be initialized inside a loop", error_code!(6672)))
+            }
+            Some(_) if self.in_try_catch => Some((
+                "immutable variables cannot be initialized inside a try/catch statement",
+                error_code!(4130),
+            )),
+            Some(_) if self.in_branch => {
+                Some(("immutable variables cannot be initialized conditionally", error_code!(4599)))
+            }
+            Some(_) => None,
+        };
+        let initialized = !self.initialized.insert(id);
+        if let Some((reason, code)) = invalid_location {
+            self.report(format!("cannot write to immutable here: {reason}"), code, span, var);
+        } else if ordinary {
+            if initialized {
+                self.report(
+                    "immutable state variable already initialized",
+                    error_code!(1574),
+                    span,
+                    var,
+                );
+            }
+        } else if initialized {
+            self.report(
+                "immutable variables cannot be modified after initialization",
+                error_code!(2718),
+                span,
+                var,
+            );
+        } else {
+            self.report(
+                "immutable variables must be initialized using an assignment",
+                error_code!(3969),
+                span,
+                var,
+            );
+        }
+    }
+
+    fn read(&mut self, id: hir::VariableId, span: Span) {
+        if self.in_creation && !self.initialized.contains(&id) {
+            let var = self.gcx.hir.variable(id);
+            self.report(
+                "immutable variables cannot be read before they are initialized",
+                error_code!(7733),
+                span,
+                var,
+            );
+        }
+    }
+
+    fn report(&self, msg: impl Into<DiagMsg>, code: DiagId, span: Span, var: &hir::Variable<'_>) {
+        if !self.should_report() {
+            return;
+        }
+        self.gcx
+            .dcx()
+            .err(msg)
+            .code(code)
+            .span(span)
+            .span_note(var.span, "the immutable variable is declared here")
+            .emit();
+    }
+
+    /// Reports the immutable variables of the given contract that have not been initialized when
+    /// construction ends at `span`.
+    fn check_all_initialized(&self, contract: hir::ContractId, span: Span) {
+        if contract != self.contract {
+            return;
+        }
+        let mut uninitialized = self
+            .gcx
+            .hir
+            .contract(contract)
+            .variables()
+            .filter(|&id| self.gcx.hir.variable(id).is_immutable())
+            .filter(|id| !self.initialized.contains(id))
+            .peekable();
+        if uninitialized.peek().is_none() {
+            return;
+        }
+        let mut diag = self
+            .gcx
+            .dcx()
+            .err("construction control flow ends without initializing all immutable variables")
+            .code(error_code!(2658))
+            .span(span);
+        for id in uninitialized {
+            let var = self.gcx.hir.variable(id);
+            let name = var.name.map(|name| name.name.to_string()).unwrap_or_default();
+            diag = diag.span_note(var.span, format!("`{name}` is not initialized"));
+        }
+        diag.emit();
+    }
+}
+
+impl<'gcx> Visit<'gcx> for ImmutableValidator<'gcx> {
+    type BreakValue = Never;
+
+    fn hir(&self) -> &'gcx hir::Hir<'gcx> {
+        &self.gcx.hir
+    }
+
+    fn visit_stmt(&mut self, stmt: &'gcx hir::Stmt<'gcx>) -> ControlFlow<Never> {
+        match stmt.kind {
+            hir::StmtKind::Loop(..) => {
+                let prev = std::mem::replace(&mut self.in_loop, true);
+                self.walk_stmt(stmt)?;
+                self.in_loop = prev;
+            }
+            hir::StmtKind::If(cond, true_, false_) => {
+                self.visit_expr(cond)?;
+                let prev = std::mem::replace(&mut self.in_branch, true);
+                self.visit_stmt(true_)?;
+                if let Some(false_) = false_ {
+                    self.visit_stmt(false_)?;
+                }
+                self.in_branch = prev;
+            }
+            hir::StmtKind::Try(..) => {
+                let prev = std::mem::replace(&mut self.in_try_catch, true);
+                self.walk_stmt(stmt)?;
+                self.in_try_catch = prev;
+            }
+            hir::StmtKind::Return(_) => {
+                self.walk_stmt(stmt)?;
+                if self.in_creation
+                    && let Some(ctor) = self.constructor
+                    && let Some(contract) = ctor.contract
+                {
+                    self.check_all_initialized(contract, stmt.span);
+                }
+            }
+            _ => self.walk_stmt(stmt)?,
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn visit_expr(&mut self, expr: &'gcx hir::Expr<'gcx>) -> ControlFlow<Never> {
+        match expr.kind {
+            hir::ExprKind::Assign(lhs, op, rhs) => {
+                self.visit_expr(rhs)?;
+                self.visit_lvalue(lhs, op.is_none());
+            }
+            hir::ExprKind::Delete(operand) => self.visit_lvalue(operand, false),
+            hir::ExprKind::Unary(op, operand) if op.kind.has_side_effects() => {
+                self.visit_lvalue(operand, false);
+            }
+            hir::ExprKind::Binary(lhs, op, rhs)
+                if matches!(op.kind, hir::BinOpKind::And | hir::BinOpKind::Or) =>
+            {
+                self.visit_expr(lhs)?;
+                let prev = std::mem::replace(&mut self.in_branch, true);
+                self.visit_expr(rhs)?;
+                self.in_branch = prev;
+            }
+            hir::ExprKind::Ternary(cond, true_, false_) => {
+                self.visit_expr(cond)?;
+                let prev = std::mem::replace(&mut self.in_branch, true);
+                self.visit_expr(true_)?;
+                self.visit_expr(false_)?;
+                self.in_branch = prev;
+            }
+            hir::ExprKind::Ident(res) => {
+                if let Some(var) = immutable_res(self.gcx, res) {
+                    self.read(var, expr.span);
+                } else if self.in_creation
+                    && let [hir::Res::Item(hir::ItemId::Function(id))] = *res
+                {
+                    // Internal functions that are referenced during construction may be called.
+                    self.visit_callable_if_new(id);
+                }
+            }
+            _ => self.walk_expr(expr)?,
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Returns the immutable variable that the identifier resolves to, if any.
+///
+/// Public state variables also resolve to their getter, so the variable is looked up among all
+/// candidates.
+fn immutable_res(gcx: Gcx<'_>, res: &[hir::Res]) -> Option<hir::VariableId> {
+    let mut vars = res.iter().filter_map(hir::Res::as_variable);
+    match (vars.next(), vars.next()) {
+        (Some(id), None) if gcx.hir.variable(id).is_immutable() => Some(id),
+        _ => None,
+    }
+}
@@ -9,32 +9,71 @@ use solar_ast::{StateMutability, Visibility};
 use solar_data_structures::{
     map::{FxHashMap, FxHashSet},
     parallel,
+    sync::Mutex,
 };
 use solar_interface::error_code;
 
+mod assembly;
 mod checker;
+mod immutables;
 mod override_checker;
+mod view_pure;
 
-pub(crate) fn check(gcx: Gcx<'_>) {
+/// The results of type checking expression bodies.
+///
+/// Only populated when the expression type checker runs. See [`needs_results`].
+#[derive(Default)]
+pub(crate) struct TypeckResults<'gcx> {
+    /// The type of each checked expression.
+    pub(crate) types: FxHashMap<hir::ExprId, Ty<'gcx>>,
+    /// The resolution of identifier and member expressions, after overload resolution.
+    pub(crate) res: FxHashMap<hir::ExprId, Res>,
+}
+
+impl<'gcx> TypeckResults<'gcx> {
+    fn extend(&mut self, other: Self) {
+        self.types.extend(other.types);
+        self.res.extend(other.res);
+    }
+}
+
+/// Returns `true` if expression bodies have to be type checked.
+pub(crate) fn needs_results(gcx: Gcx<'_>) -> bool {
+    gcx.sess.opts.unstable.typeck
+}
+
+pub(crate) fn check(gcx: Gcx<'_>) -> TypeckResults<'_> {
+    let results = Mutex::new(TypeckResults::default());
     parallel!(
         gcx.sess,
         gcx.hir.par_contract_ids().for_each(|id| {
             check_duplicate_definitions(gcx, &gcx.symbol_resolver.contract_scopes[id]);
             check_storage_size_upper_bound(gcx, id);
+            check_storage_layout_specifier(gcx, id);
             check_payable_fallback_without_receive(gcx, id);
             check_external_type_clashes(gcx, id);
             check_receive_function(gcx, id);
             check_unimplemented_functions(gcx, id);
+            check_using_directives(gcx, gcx.hir.contract(id).source, Some(id));
             override_checker::check(gcx, id);
+            immutables::check(gcx, id);
         }),
         gcx.hir.par_source_ids().for_each(|id| {
             check_duplicate_definitions(gcx, &gcx.symbol_resolver.source_scopes[id]);
-            if gcx.sess.opts.unstable.typeck {
+            check_using_directives(gcx, id, None);
+            assembly::check(gcx, id);
+            if needs_results(gcx) {
                 // TODO: Parallelize more.
-                checker::check(gcx, id);
+                results.lock().extend(checker::check(gcx, id));
             }
         }),
+        gcx.hir.par_item_ids().for_each(|id| crate::natspec::check(gcx, id)),
     );
+    let results = results.into_inner();
+    if needs_results(gcx) && gcx.dcx().has_errors().is_ok() {
+        view_pure::check(gcx, &results);
+    }
+    results
 }
 
 fn check_external_type_clashes(gcx: Gcx<'_>, contract_id: hir::ContractId) {
@@ -247,6 +286,162 @@ fn check_receive_function(gcx: Gcx<'_>, contract_id: hir::ContractId) {
     }
 }
 
+/// Checks that the functions listed in `using for` directives can be attached to the type.
+///
+/// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/analysis/TypeChecker.cpp#L3973>
+fn check_using_directives(
+    gcx: Gcx<'_>,
+    source_id: hir::SourceId,
+    contract_id: Option<hir::ContractId>,
+) {
+    let usings = match contract_id {
+        Some(id) => gcx.hir.contract(id).usings,
+        None => gcx.hir.source(source_id).usings,
+    };
+    for using in usings {
+        let Some(using_ty) = &using.ty else { continue };
+        let ty = gcx.type_of_hir_ty(using_ty);
+        if ty.references_error() {
+            continue;
+        }
+
+        // `global` in contracts is rejected during AST validation.
+        if using.global && contract_id.is_none() {
+            let type_source = match ty.kind {
+                TyKind::Struct(id) => Some(gcx.hir.strukt(id).source),
+                TyKind::Enum(id) => Some(gcx.hir.enumm(id).source),
+                TyKind::Udvt(_, id) => Some(gcx.hir.udvt(id).source),
+                _ => None,
+            };
+            if type_source != Some(source_id) {
+                let msg = "can only use `global` with types defined in the same source unit at file level";
+                gcx.dcx().err(msg).code(error_code!(4117)).span(using.span).emit();
+            }
+        }
+
+        let hir::UsingList::Functions(functions) = using.list else { continue };
+        for &(f_id, op) in functions {
+            if let Some(op) = op {
+                check_operator_definition(gcx, using, ty, f_id, op);
+                continue;
+            }
+            let TyKind::FnPtr(f) = gcx.type_of_item(f_id.into()).kind else { unreachable!() };
+            let name = gcx.item_name(f_id);
+            let f_span = gcx.hir.function(f_id).span;
+            let Some(&self_ty) = f.parameters.first() else {
+                let msg = format!(
+                    "function `{name}` does not have any parameters, and therefore cannot be attached to the type `{}`",
+                    ty.display(gcx),
+                );
+                gcx.dcx()
+                    .err(msg)
+                    .code(error_code!(4731))
+                    .span(using.span)
+                    .span_note(f_span, "function declared here")
+                    .emit();
+                continue;
+            };
+            if self_ty.references_error() {
+                continue;
+            }
+            let ty = self_ty.loc().map_or(ty, |loc| ty.with_loc_if_ref(gcx, loc));
+            if !ty.convert_implicit_to(self_ty, gcx) {
+                let msg = format!(
+                    "function `{name}` cannot be attached to the type `{}` because the type cannot be implicitly converted to the first argument of the function (`{}`)",
+                    ty.display(gcx),
+                    self_ty.display(gcx),
+                );
+                gcx.dcx()
+                    .err(msg)
+                    .code(error_code!(3100))
+                    .span(using.span)
+                    .span_note(f_span, "function declared here")
+                    .emit();
+            }
+        }
+    }
+}
+
+/// Checks a function bound to a user-defined operator in a `using for` directive.
+///
+/// Reference: <https://github.com/argotorg/solidity/blob/v0.8.30/libsolidity/analysis/TypeChecker.cpp#L4005>
+fn check_operator_definition<'gcx>(
+    gcx: Gcx<'gcx>,
+    using: &hir::UsingDirective<'gcx>,
+    ty: Ty<'gcx>,
+    f_id: hir::FunctionId,
+    op: hir::UserDefinableOperator,
+) {
+    let func = gcx.hir.function(f_id);
+    let TyKind::FnPtr(f) = gcx.type_of_item(f_id.into()).kind else { unreachable!() };
+    let name = gcx.item_name(f_id);
+    let op_str = op.to_str();
+    let err = |msg: String, code| {
+        gcx.dcx()
+            .err(msg)
+            .code(code)
+            .span(using.span)
+            .span_note(func.span, "function declared here")
+    };
+
+    if !using.global {
+        let msg = "operators can only be defined in a global `using for` directive";
+        gcx.dcx().err(msg).code(error_code!(3320)).span(using.span).emit();
+    }
+    if f.state_mutability != StateMutability::Pure || !func.is_free() {
+        let msg = "only pure free functions can be used to define operators".to_string();
+        err(msg, error_code!(7775)).emit();
+    }
+    if !matches!(ty.kind, TyKind::Udvt(..)) {
+        let msg = "operators can only be implemented for user-defined value types";
+        gcx.dcx().err(msg).code(error_code!(5332)).span(using.span).emit();
+        return;
+    }
+    if f.parameters.iter().chain(f.returns).any(|ty| ty.references_error()) {
+        return;
+    }
+
+    let ty_str = ty.display(gcx);
+    let params = f.parameters;
+    let same_params = params.len() < 2 || params[0] == params[1];
+    let expected_params = if op.is_binary() && !op.is_unary() && (params.len() != 2 || !same_params)
+    {
+        Some(format!("two parameters of type `{ty_str}` and the same data location"))
+    } else if op.is_unary() && !op.is_binary() && params.len() != 1 {
+        Some(format!("exactly one parameter of type `{ty_str}`"))
+    } else if !matches!(params.len(), 1 | 2) || params[0] != ty || !same_params {
+        Some(format!("one or two parameters of type `{ty_str}` and the same data location"))
+    } else {
+        None
+    };
+    if let Some(expected) = expected_params {
+        let msg = format!(
+            "wrong parameters in operator definition: the function `{name}` needs to have {expected} to be used for the operator `{op_str}`"
+        );
+        err(msg, error_code!(1884)).emit();
+    }
+
+    let expected_ret = if op.is_cmp() { gcx.types.bool } else { ty };
+    if f.returns != [expected_ret] {
+        let msg = format!(
+            "wrong return parameters in operator definition: the function `{name}` needs to return exactly one value of type `{}` to be used for the operator `{op_str}`",
+            expected_ret.display(gcx),
+        );
+        err(msg, error_code!(7743)).emit();
+    }
+
+    if using.global
+        && matches!(params.len(), 1 | 2)
+        && gcx.operator_definitions(ty, op, params.len() == 1).len() > 1
+    {
+        let kind = if params.len() == 1 { "unary" } else { "binary" };
+        let msg = format!(
+            "user-defined {kind} operator `{op_str}` has more than one definition matching the operand type visible in the current scope"
+        );
+        gcx.dcx().err(msg).code(error_code!(4705)).span(using.span).emit();
+    }
+}
+
 /// Checks for violation of maximum storage size to ensure slot allocation algorithms works.
 ///
 /// Reference: <https://github.com/argotorg/solidity/blob/03e2739809769ae0c8d236a883aadc900da60536/libsolidity/analysis/ContractLevelChecker.cpp#L556C1-L570C2>
@@ -266,6 +461,13 @@ fn check_storage_size_upper_bound(gcx: Gcx<'_>, contract_id: hir::ContractId) {
     }
 }
 
+/// Checks the base slot given with `layout at`, if any.
+fn check_storage_layout_specifier(gcx: Gcx<'_>, contract_id: hir::ContractId) {
+    if gcx.hir.contract(contract_id).layout.is_some() {
+        let _ = gcx.storage_layout(contract_id);
+    }
+}
+
 fn storage_size_upper_bound(gcx: Gcx<'_>, contract_id: hir::ContractId) -> Option<U256> {
     let mut total_size = U256::ZERO;
     for item_id in gcx.hir.contract_item_ids(contract_id) {
@@ -0,0 +1,386 @@
+//! State mutability checks.
+//!
+//! Infers the state mutability that each function body requires, and checks it against the
+//! declared state mutability:
+//! - `pure` functions cannot read from the environment or state
+//! - `view` and `pure` functions cannot modify the state
+//! - `msg.value` and `callvalue()` can only be used in payable or internal functions
+//!
+//! Functions that could be declared with a more restrictive state mutability are warned about.
+//!
+//! Reference: solc `libsolidity/analysis/ViewPureChecker.cpp`
+
+use super::TypeckResults;
+use crate::{
+    builtins::Builtin,
+    hir::{self, Visit, yul},
+    ty::{Gcx, Ty, TyKind},
+};
+use rayon::prelude::*;
+use solar_ast::{DataLocation, StateMutability, Visibility};
+use solar_data_structures::Never;
+use solar_interface::{Span, diagnostics::Applicability, error_code};
+use std::ops::ControlFlow;
+
+pub(super) fn check<'gcx>(gcx: Gcx<'gcx>, results: &TypeckResults<'gcx>) {
+    gcx.hir.par_function_ids().for_each(|id| {
+        let func = gcx.hir.function(id);
+        if func.kind.is_modifier() || func.body.is_none() {
+            return;
+        }
+        let mut checker = ViewPureChecker::new(gcx, results, Some(func), func.span);
+        let _ = checker.visit_body(func);
+        checker.check_restrictable(func);
+    });
+}
+
+struct ViewPureChecker<'a, 'gcx> {
+    gcx: Gcx<'gcx>,
+    results: &'a TypeckResults<'gcx>,
+    /// The function whose body is being checked. `None` when inferring the state mutability of a
+    /// modifier.
+    function: Option<&'gcx hir::Function<'gcx>>,
+    /// The most restrictive state mutability that the visited code allows, and the location of
+    /// the expression that requires it.
+    best: (StateMutability, Span),
+    /// Whether the expression being visited is written to.
+    writes: bool,
+}
+
+impl<'a, 'gcx> ViewPureChecker<'a, 'gcx> {
+    fn new(
+        gcx: Gcx<'gcx>,
+        results: &'a TypeckResults<'gcx>,
+        function: Option<&'gcx hir::Function<'gcx>>,
+        span: Span,
+    ) -> Self {
+        Self { gcx, results, function, best: (StateMutability::Pure, span), writes: false }
+    }
+
+    fn visit_body(&mut self, func: &'gcx hir::Function<'gcx>) -> ControlFlow<Never> {
+        for modifier in func.modifiers {
+            self.visit_modifier(modifier)?;
+        }
+        for stmt in func.body.iter().flat_map(|body| body.iter()) {
+            self.visit_stmt(stmt)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Warns if the function could be declared with a more restrictive state mutability.
+    fn check_restrictable(&self, func: &hir::Function<'_>) {
+        let (best, _) = self.best;
+        if !(level(best) < level(func.state_mutability)
+            && !func.state_mutability.is_payable()
+            && func.body.is_some_and(|body| !body.is_empty())
+            && func.kind.is_function()
+            && !func.virtual_)
+        {
+            return;
+        }
+
+        let span = func.name.map_or(func.span, |name| name.span);
+        let mut diag = self
+            .gcx
+            .dcx()
+            .warn(format!("function state mutability can be restricted to `{best}`"))
+            .code(error_code!(2018))
+            .span(span);
+        diag = if func.state_mutability_span.lo() == func.state_mutability_span.hi() {
+            diag.span_suggestion(
+                func.state_mutability_span,
+                "add the state mutability",
+                format!(" {best}"),
+                Applicability::MachineApplicable,
+            )
+        } else {
+            diag.span_suggestion(
+                func.state_mutability_span,
+                "change the state mutability",
+                best.to_str(),
+                Applicability::MachineApplicable,
+            )
+        };
+        diag.emit();
+    }
+
+    /// Records that the expression at `span` requires the given state mutability, and reports it
+    /// if it is incompatible with the declared state mutability of the current function.
+    ///
+    /// `nested` is the location inside of the modifier definition, if `span` is a modifier
+    /// invocation.
+    fn report(&mut self, mutability: StateMutability, span: Span, nested: Option<Span>) {
+        if level(mutability) > level(self.best.0) {
+            self.best = (mutability, span);
+        }
+        let Some(func) = self.function else { return };
+        let declared = func.state_mutability;
+        if level(mutability) <= level(declared) {
+            return;
+        }
+
+        let dcx = self.gcx.dcx();
+        match mutability {
+            StateMutability::NonPayable => {
+                dcx.err(format!(
+                    "function cannot be declared as `{declared}` because this expression (potentially) modifies the state"
+                ))
+                .code(error_code!(8961))
+                .span(span)
+                .emit();
+            }
+            // Any use of `msg.value` requires at least `view`.
+            _ if declared == StateMutability::Pure => {
+                dcx.err(format!(
+                    "function declared as `{declared}`, but this expression (potentially) reads from the environment or state and thus requires `view`"
+                ))
+                .code(error_code!(2527))
+                .span(span)
+                .emit();
+            }
+            StateMutability::Payable => {
+                // Internal functions are allowed to use `msg.value`, and library functions cannot
+                // be payable.
+                let is_library =
+                    func.contract.is_some_and(|id| self.gcx.hir.contract(id).kind.is_library());
+                if !(func.is_constructor() || func.visibility >= Visibility::Public) || is_library {
+                    return;
+                }
+                if let Some(nested) = nested {
+                    dcx.err("this modifier uses `msg.value` or `callvalue()` and thus the function has to be payable or internal")
+                        .code(error_code!(4006))
+                        .span(span)
+                        .span_note(nested, "`msg.value` or `callvalue()` is used here")
+                        .emit();
+                } else {
+                    dcx.err("`msg.value` and `callvalue()` can only be used in payable public functions")
+                        .code(error_code!(5887))
+                        .span(span)
+                        .help("make the function `payable` or use an internal function to avoid this error")
+                        .emit();
+                }
+            }
+            StateMutability::Pure | StateMutability::View => unreachable!(),
+        }
+    }
+
+    /// Reports an access to storage at `span`, which is a write if the expression is written to.
+    fn report_storage_access(&mut self, span: Span, writes: bool) {
+        let mutability = if writes { StateMutability::NonPayable } else { StateMutability::View };
+        self.report(mutability, span, None);
+    }
+
+    /// Returns the state mutability that the given modifier requires, and the location of the
+    /// expression that requires it.
+    fn modifier_mutability(&self, id: hir::FunctionId) -> (StateMutability, Span) {
+        let modifier = self.gcx.hir.function(id);
+        let mut checker = ViewPureChecker::new(self.gcx, self.results, None, modifier.span);
+        let _ = checker.visit_body(modifier);
+        checker.best
+    }
+
+    fn ty(&self, expr: &hir::Expr<'_>) -> Option<Ty<'gcx>> {
+        self.results.types.get(&expr.id).copied()
+    }
+
+    fn is_stored_in_storage(&self, expr: &hir::Expr<'_>) -> bool {
+        self.ty(expr).is_some_and(|ty| {
+            ty.data_stored_in(DataLocation::Storage) || ty.data_stored_in(DataLocation::Transient)
+        })
+    }
+
+    /// Returns `true` if the variable is a state variable that lives in storage.
+    fn is_storage_variable(&self, id: hir::VariableId) -> bool {
+        let var = self.gcx.hir.variable(id);
+        var.is_state_variable() && !var.is_constant() && !var.is_immutable()
+    }
+
+    /// Reports the state mutability required by a reference to the given resolution.
+    fn check_res(&mut self, res: hir::Res, span: Span, writes: bool) {
+        match res {
+            hir::Res::Item(hir::ItemId::Variable(id)) => {
+                let var = self.gcx.hir.variable(id);
+                if var.is_immutable() {
+                    // Immutables that are assigned number literals are pure.
+                    let is_literal = var
+                        .initializer
+                        .and_then(|init| self.ty(init))
+                        .is_some_and(|ty| matches!(ty.kind, TyKind::IntLiteral(..)));
+                    if !is_literal {
+                        self.report(StateMutability::View, span, None);
+                    }
+                } else if self.is_storage_variable(id) {
+                    self.report_storage_access(span, writes);
+                }
+            }
+            hir::Res::Builtin(builtin) => {
+                let mutability = builtin_mutability(builtin);
+                if mutability != StateMutability::Pure {
+                    self.report(mutability, span, None);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_expr(&mut self, expr: &'gcx hir::Expr<'gcx>, writes: bool) {
+        match expr.kind {
+            hir::ExprKind::Ident(_) => {
+                if let Some(&res) = self.results.res.get(&expr.id) {
+                    self.check_res(res, expr.span, writes);
+                }
+            }
+            hir::ExprKind::Member(base, _) => match self.results.res.get(&expr.id) {
+                Some(&hir::Res::Builtin(Builtin::ArrayLength)) => {
+                    if self.is_stored_in_storage(base) {
+                        self.report(StateMutability::View, expr.span, None);
+                    }
+                }
+                Some(&res) => self.check_res(res, expr.span, writes),
+                None => {
+                    let is_struct = self
+                        .ty(base)
+                        .is_some_and(|ty| matches!(ty.peel_refs().kind, TyKind::Struct(_)));
+                    if is_struct && self.is_stored_in_storage(base) {
+                        self.report_storage_access(expr.span, writes);
+                    }
+                }
+            },
+            hir::ExprKind::Index(base, Some(_)) if self.is_stored_in_storage(base) => {
+                self.report_storage_access(expr.span, writes);
+            }
+            hir::ExprKind::Slice(base, ..) if self.is_stored_in_storage(base) => {
+                self.report(StateMutability::View, expr.span, None);
+            }
+            hir::ExprKind::Call(callee, ..) => {
+                let mutability = match self.ty(callee).map(|ty| ty.kind) {
+                    // Only `nonpayable` is required to call a payable function.
+                    Some(TyKind::FnPtr(f)) if f.state_mutability.is_payable() => {
+                        StateMutability::NonPayable
+                    }
+                    Some(TyKind::FnPtr(f)) => f.state_mutability,
+                    Some(TyKind::Event(..)) => StateMutability::NonPayable,
+                    // Errors, type conversions and struct constructors.
+                    _ => return,
+                };
+                if mutability != StateMutability::Pure {
+                    self.report(mutability, expr.span, None);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'gcx> Visit<'gcx> for ViewPureChecker<'_, 'gcx> {
+    type BreakValue = Never;
+
+    fn hir(&self) -> &'gcx hir::Hir<'gcx> {
+        &self.gcx.hir
+    }
+
+    fn visit_modifier(&mut self, modifier: &'gcx hir::Modifier<'gcx>) -> ControlFlow<Never> {
+        self.visit_call_args(&modifier.args)?;
+        // Base constructor arguments are checked on their own.
+        if let hir::ItemId::Function(id) = modifier.id {
+            let (mutability, nested) = self.modifier_mutability(id);
+            self.report(mutability, modifier.span, Some(nested));
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn visit_expr(&mut self, expr: &'gcx hir::Expr<'gcx>) -> ControlFlow<Never> {
+        let writes = std::mem::take(&mut self.writes);
+        match expr.kind {
+            hir::ExprKind::Assign(lhs, _, rhs) => {
+                self.writes = true;
+                self.visit_expr(lhs)?;
+                self.visit_expr(rhs)?;
+            }
+            hir::ExprKind::Delete(operand) => {
+                self.writes = true;
+                self.visit_expr(operand)?;
+            }
+            hir::ExprKind::Unary(op, operand) if op.kind.has_side_effects() => {
+                self.writes = true;
+                self.visit_expr(operand)?;
+            }
+            hir::ExprKind::Tuple(exprs) if writes => {
+                for expr in exprs.iter().copied().flatten() {
+                    self.writes = true;
+                    self.visit_expr(expr)?;
+                }
+            }
+            _ => self.walk_expr(expr)?,
+        }
+        self.check_expr(expr, writes);
+        ControlFlow::Continue(())
+    }
+
+    fn visit_yul_expr(&mut self, expr: &'gcx yul::Expr<'gcx>) -> ControlFlow<Never> {
+        self.walk_yul_expr(expr)?;
+        if let yul::ExprKind::Call(call) = &expr.kind
+            && call.callee == yul::Callee::Builtin
+        {
+            let mutability = yul_builtin_mutability(call.name.as_str());
+            if mutability != StateMutability::Pure {
+                self.report(mutability, expr.span, None);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Returns the rank of the state mutability, from most to least restrictive.
+///
+/// Unlike the declaration order of [`StateMutability`], `nonpayable` is more restrictive than
+/// `payable`.
+fn level(mutability: StateMutability) -> u8 {
+    match mutability {
+        StateMutability::Pure => 0,
+        StateMutability::View => 1,
+        StateMutability::NonPayable => 2,
+        StateMutability::Payable => 3,
+    }
+}
+
+/// Returns the state mutability required to access the given builtin variable or member.
+///
+/// Builtin functions are accounted for through their type when they are called.
+fn builtin_mutability(builtin: Builtin) -> StateMutability {
+    match builtin {
+        Builtin::MsgValue => StateMutability::Payable,
+        Builtin::This
+        | Builtin::BlockCoinbase
+        | Builtin::BlockTimestamp
+        | Builtin::BlockDifficulty
+        | Builtin::BlockPrevrandao
+        | Builtin::BlockNumber
+        | Builtin::BlockGaslimit
+        | Builtin::BlockChainid
+        | Builtin::BlockBasefee
+        | Builtin::BlockBlobbasefee
+        | Builtin::MsgSender
+        | Builtin::MsgGas
+        | Builtin::TxOrigin
+        | Builtin::TxGasPrice
+        | Builtin::AddressBalance
+        | Builtin::AddressCode
+        | Builtin::AddressCodehash => StateMutability::View,
+        _ => StateMutability::Pure,
+    }
+}
+
+/// Returns the state mutability required to call the given inline assembly builtin.
+fn yul_builtin_mutability(name: &str) -> StateMutability {
+    match name {
+        "callvalue" => StateMutability::Payable,
+        "sstore" | "tstore" | "log0" | "log1" | "log2" | "log3" | "log4" | "create" | "create2"
+        | "call" | "callcode" | "delegatecall" | "selfdestruct" => StateMutability::NonPayable,
+        "address" | "selfbalance" | "balance" | "origin" | "caller" | "chainid" | "basefee"
+        | "blobbasefee" | "gas" | "gasprice" | "extcodesize" | "extcodecopy" | "extcodehash"
+        | "blockhash" | "blobhash" | "coinbase" | "timestamp" | "number" | "prevrandao"
+        | "difficulty" | "gaslimit" | "staticcall" | "sload" | "tload" => StateMutability::View,
+        _ => StateMutability::Pure,
+    }
+}
@@ -0,0 +1,368 @@
+//! Yul semantic analysis.
+//!
+//! Checks the lowered Yul code for:
+//! - function arity and the number of values produced by expressions
+//! - the placement of `break`, `continue`, and `leave`
+//! - builtins that are not available in the target EVM version
+//! - invalid literals and literal arguments
+//!
+//! Name resolution, shadowing, and declaration-before-use are checked during lowering.
+//!
+//! Reference: solc `libyul/AsmAnalysis.cpp`
+
+use super::{Dialect, builtin, literal_value};
+use crate::{codegen::evm::instructions::Instruction, hir::yul};
+use solar_ast::{Lit, LitKind};
+use solar_interface::{Session, Span, diagnostics::DiagCtxt, error_code, kw};
+
+/// Checks a Yul object and all of its sub-objects.
+pub(crate) fn check_object(sess: &Session, object: &yul::Object<'_>) {
+    let dialect = Dialect::objects(sess.opts.evm_version);
+    check_code(sess, &object.code, dialect, Some(object));
+    for child in object.children {
+        check_object(sess, child);
+    }
+}
+
+/// Checks a block of Yul code.
+///
+/// `object` is the object that contains the code, if any, and is used to validate data object
+/// references.
+pub(crate) fn check_code(
+    sess: &Session,
+    code: &yul::Code<'_>,
+    dialect: Dialect,
+    object: Option<&yul::Object<'_>>,
+) {
+    let mut analyzer =
+        Analyzer { dcx: &sess.dcx, code, dialect, object, in_loop_body: false, in_function: false };
+    analyzer.check_block(&code.block);
+}
+
+struct Analyzer<'a, 'hir> {
+    dcx: &'a DiagCtxt,
+    code: &'a yul::Code<'hir>,
+    dialect: Dialect,
+    object: Option<&'a yul::Object<'hir>>,
+    /// Whether we are directly inside of a for-loop body, in which `break` and `continue` are
+    /// allowed.
+    in_loop_body: bool,
+    /// Whether we are inside of a function body, in which `leave` is allowed.
+    in_function: bool,
+}
+
+impl<'hir> Analyzer<'_, 'hir> {
+    fn check_block(&mut self, block: &yul::Block<'hir>) {
+        for stmt in block.iter() {
+            self.check_stmt(stmt);
+        }
+    }
+
+    fn check_stmt(&mut self, stmt: &yul::Stmt<'hir>) {
+        match &stmt.kind {
+            yul::StmtKind::Block(block) => self.check_block(block),
+            yul::StmtKind::Assign(lhs, rhs) => {
+                if let Some(values) = self.check_expr(rhs)
+                    && values != lhs.len()
+                {
+                    self.dcx
+                        .err(format!(
+                            "variable count for assignment does not match number of values ({} vs. {values})",
+                            lhs.len()
+                        ))
+                        .code(error_code!(8678))
+                        .span(stmt.span)
+                        .emit();
+                }
+            }
+            yul::StmtKind::Expr(expr) => {
+                if let Some(values) = self.check_expr(expr)
+                    && values != 0
+                {
+                    self.dcx
+                        .err(format!(
+                            "top-level expressions are not supposed to return values (this expression returns {values} {})",
+                            plural_values(values)
+                        ))
+                        .code(error_code!(3083))
+                        .span(expr.span)
+                        .help("use `pop()` or assign them")
+                        .emit();
+                }
+            }
+            yul::StmtKind::If(cond, block) => {
+                self.check_single_value(cond);
+                self.check_block(block);
+            }
+            yul::StmtKind::For(for_) => {
+                for stmt in for_.init.iter() {
+                    if let yul::StmtKind::FunctionDef(_) = stmt.kind {
+                        self.dcx
+                            .err("functions cannot be defined inside a for-loop init block")
+                            .code(error_code!(3441))
+                            .span(stmt.span)
+                            .emit();
+                    }
+                }
+                let in_loop_body = std::mem::replace(&mut self.in_loop_body, false);
+                self.check_block(&for_.init);
+                self.check_single_value(&for_.cond);
+                self.check_block(&for_.step);
+                self.in_loop_body = true;
+                self.check_block(&for_.body);
+                self.in_loop_body = in_loop_body;
+            }
+            yul::StmtKind::Switch(switch) => self.check_switch(switch),
+            yul::StmtKind::Leave => {
+                if !self.in_function {
+                    self.dcx
+                        .err("keyword `leave` can only be used inside a function")
+                        .code(error_code!(8149))
+                        .span(stmt.span)
+                        .emit();
+                }
+            }
+            yul::StmtKind::Break | yul::StmtKind::Continue => {
+                if !self.in_loop_body {
+                    self.dcx
+                        .err("keywords `break` and `continue` can only be used inside for-loop bodies")
+                        .code(error_code!(2592))
+                        .span(stmt.span)
+                        .emit();
+                }
+            }
+            &yul::StmtKind::FunctionDef(id) => {
+                let function = self.code.function(id);
+                let in_loop_body = std::mem::replace(&mut self.in_loop_body, false);
+                let in_function = std::mem::replace(&mut self.in_function, true);
+                self.check_block(&function.body);
+                self.in_loop_body = in_loop_body;
+                self.in_function = in_function;
+            }
+            yul::StmtKind::VarDecl(vars, value) => {
+                if let Some(value) = value
+                    && let Some(values) = self.check_expr(value)
+                    && values != vars.len()
+                {
+                    let names = vars
+                        .iter()
+                        .map(|&var| self.code.variable(var).to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    self.dcx
+                        .err(format!(
+                            "variable count mismatch for declaration of `{names}`: {} variables and {values} {}",
+                            vars.len(),
+                            plural_values(values),
+                        ))
+                        .code(error_code!(3812))
+                        .span(stmt.span)
+                        .emit();
+                }
+            }
+        }
+    }
+
+    fn check_switch(&mut self, switch: &yul::StmtSwitch<'hir>) {
+        self.check_single_value(&switch.selector);
+        let mut seen = Vec::<(_, Span)>::with_capacity(switch.cases.len());
+        for case in switch.cases {
+            if let Some(constant) = case.constant {
+                self.check_lit(constant);
+                if let Some(value) = literal_value(constant) {
+                    if let Some(&(_, prev)) = seen.iter().find(|(v, _)| *v == value) {
+                        self.dcx
+                            .err("duplicate case defined")
+                            .code(error_code!(6792))
+                            .span(constant.span)
+                            .span_note(prev, "previous case is here")
+                            .emit();
+                    } else {
+                        seen.push((value, constant.span));
+                    }
+                }
+            }
+            self.check_block(&case.body);
+        }
+    }
+
+    /// Checks that the expression evaluates to exactly one value.
+    fn check_single_value(&mut self, expr: &yul::Expr<'hir>) {
+        if let Some(values) = self.check_expr(expr)
+            && values != 1
+        {
+            self.dcx
+                .err(format!(
+                    "expected expression to evaluate to one value, but got {values} {} instead",
+                    plural_values(values)
+                ))
+                .code(error_code!(3950))
+                .span(expr.span)
+                .emit();
+        }
+    }
+
+    /// Checks an expression, returning the number of values it evaluates to, or `None` if it
+    /// contains an error.
+    fn check_expr(&mut self, expr: &yul::Expr<'hir>) -> Option<usize> {
+        match &expr.kind {
+            yul::ExprKind::Var(_) | yul::ExprKind::External(_) => Some(1),
+            yul::ExprKind::Lit(lit) => {
+                self.check_lit(lit);
+                Some(1)
+            }
+            yul::ExprKind::Call(call) => self.check_call(call),
+            yul::ExprKind::Err(_) => None,
+        }
+    }
+
+    fn check_call(&mut self, call: &yul::ExprCall<'hir>) -> Option<usize> {
+        let (inputs, outputs, builtin) = match call.callee {
+            yul::Callee::Builtin => {
+                let builtin = builtin(call.name.name, self.dialect)?;
+                if let Some(instruction) = builtin.instruction {
+                    self.check_instruction(instruction, call.name.span);
+                }
+                (builtin.inputs, builtin.outputs, Some(builtin))
+            }
+            yul::Callee::Function(id) => {
+                let function = self.code.function(id);
+                (function.parameters.len(), function.returns.len(), None)
+            }
+        };
+
+        if call.arguments.len() != inputs {
+            self.dcx
+                .err(format!(
+                    "function `{}` expects {inputs} arguments but got {}",
+                    call.name,
+                    call.arguments.len()
+                ))
+                .code(error_code!(7000))
+                .span(call.name.span)
+                .emit();
+        }
+
+        for (i, arg) in call.arguments.iter().enumerate() {
+            if let Some(builtin) = &builtin
+                && builtin.is_literal_arg(i)
+            {
+                self.check_literal_arg(call, arg);
+            } else {
+                self.check_single_value(arg);
+            }
+        }
+
+        Some(outputs)
+    }
+
+    /// Checks an argument of a builtin that must be a literal.
+    fn check_literal_arg(&mut self, call: &yul::ExprCall<'hir>, arg: &yul::Expr<'hir>) {
+        let yul::ExprKind::Lit(lit) = arg.kind else {
+            if !matches!(arg.kind, yul::ExprKind::Err(_)) {
+                self.dcx
+                    .err("function expects direct literals as arguments")
+                    .code(error_code!(9114))
+                    .span(arg.span)
+                    .emit();
+            }
+            return;
+        };
+        match call.name.name {
+            kw::Memoryguard => self.check_lit(lit),
+            kw::Datasize | kw::Dataoffset => {
+                if let Some(object) = self.object
+                    && !object.has_data_object(lit.symbol)
+                {
+                    self.dcx
+                        .err(format!("unknown data object `{}`", lit.symbol))
+                        .code(error_code!(3517))
+                        .span(arg.span)
+                        .emit();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks that the instruction is available in the target EVM version.
+    fn check_instruction(&self, instruction: &Instruction, span: Span) {
+        let evm = self.dialect.evm_version;
+        let name = instruction.name;
+        match name {
+            // `prevrandao` and `difficulty` share the same opcode, and are only renamed.
+            "prevrandao" if !evm.has_prev_randao() => {
+                self.dcx
+                    .warn(format!(
+                        "`prevrandao` is not supported by the VM version and will be treated as `difficulty` (you are currently compiling for `{evm}`)"
+                    ))
+                    .code(error_code!(5761))
+                    .span(span)
+                    .emit();
+                return;
+            }
+            "difficulty" if evm.has_prev_randao() => {
+                self.dcx
+                    .warn("`difficulty` was renamed and supplanted by `prevrandao` in the VM version paris")
+                    .code(error_code!(3242))
+                    .span(span)
+                    .emit();
+                return;
+            }
+            "selfdestruct" => {
+                self.dcx
+                    .warn("`selfdestruct` has been deprecated")
+                    .code(error_code!(1699))
+                    .span(span)
+                    .note("starting from the Cancun hard fork, the underlying opcode no longer deletes the code and data associated with an account and only transfers its Ether to the beneficiary, unless executed in the same transaction in which the contract was created (see EIP-6780)")
+                    .emit();
+                return;
+            }
+            _ => {}
+        }
+        if instruction.is_available(evm) {
+            return;
+        }
+
+        let (code, vm) = match name {
+            "returndatasize" | "returndatacopy" => (error_code!(7756), "Byzantium"),
+            "staticcall" => (error_code!(1503), "Byzantium"),
+            "shl" | "shr" | "sar" => (error_code!(6612), "Constantinople"),
+            "create2" => (error_code!(6166), "Constantinople"),
+            "extcodehash" => (error_code!(7110), "Constantinople"),
+            "chainid" => (error_code!(1561), "Istanbul"),
+            "selfbalance" => (error_code!(7721), "Istanbul"),
+            "basefee" => (error_code!(5430), "London"),
+            "blobbasefee" => (error_code!(6679), "Cancun"),
+            "blobhash" => (error_code!(8314), "Cancun"),
+            "mcopy" => (error_code!(7755), "Cancun"),
+            "tload" | "tstore" => (error_code!(6243), "Cancun"),
+            // Accepted in all VM versions.
+            _ => return,
+        };
+        self.dcx
+            .err(format!(
+                "the `{name}` instruction is only available for {vm}-compatible VMs (you are currently compiling for `{evm}`)"
+            ))
+            .code(code)
+            .span(span)
+            .emit();
+    }
+
+    fn check_lit(&self, lit: &Lit<'_>) {
+        if let LitKind::Str(_, value, _) = lit.kind {
+            let len = value.as_byte_str().len();
+            if len > 32 {
+                self.dcx
+                    .err(format!("string literal too long ({len} > 32)"))
+                    .code(error_code!(3069))
+                    .span(lit.span)
+                    .emit();
+            }
+        }
+    }
+}
+
+fn plural_values(n: usize) -> &'static str {
+    if n == 1 { "value" } else { "values" }
+}
@@ -0,0 +1,53 @@
+//! Yul builtin functions.
+
+use super::Dialect;
+use crate::codegen::evm::instructions::{self, Instruction};
+use solar_interface::{Symbol, kw};
+
+/// A Yul builtin function.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BuiltinFunction {
+    /// The number of arguments.
+    pub(crate) inputs: usize,
+    /// The number of return values.
+    pub(crate) outputs: usize,
+    /// The indices of the arguments that must be literals.
+    pub(crate) literal_args: &'static [usize],
+    /// The EVM instruction, if the builtin maps directly to one.
+    pub(crate) instruction: Option<&'static Instruction>,
+}
+
+impl BuiltinFunction {
+    /// Returns `true` if the argument at the given index must be a literal.
+    pub(crate) fn is_literal_arg(&self, index: usize) -> bool {
+        self.literal_args.contains(&index)
+    }
+}
+
+/// Returns the builtin function with the given name in the given dialect.
+///
+/// Note that this does not check whether the builtin is available in the dialect's EVM version.
+pub(crate) fn builtin(name: Symbol, dialect: Dialect) -> Option<BuiltinFunction> {
+    if !name.is_yul_builtin() {
+        return None;
+    }
+    if let Some(instruction) = instructions::by_name(name.as_str()) {
+        return Some(BuiltinFunction {
+            inputs: instruction.inputs as usize,
+            outputs: instruction.outputs as usize,
+            literal_args: &[],
+            instruction: Some(instruction),
+        });
+    }
+    if !dialect.objects {
+        return None;
+    }
+    let (inputs, outputs, literal_args): (_, _, &[_]) = match name {
+        kw::Datasize | kw::Dataoffset | kw::Loadimmutable | kw::Linkersymbol => (1, 1, &[0]),
+        kw::Memoryguard => (1, 1, &[0]),
+        kw::Datacopy => (3, 0, &[]),
+        kw::Setimmutable => (3, 0, &[1]),
+        _ => return None,
+    };
+    Some(BuiltinFunction { inputs, outputs, literal_args, instruction: None })
+}
@@ -0,0 +1,561 @@
+use super::{Error, Exit, Host, Interpreter, Outcome, TraceEntry};
+use crate::{
+    hir::yul,
+    yul::{
+        interpreter::{Call, CallKind, Create, Log},
+        literal_value,
+    },
+};
+use alloy_primitives::{Address, B256, I256, U256, keccak256};
+use solar_data_structures::map::FxHashMap;
+use std::ops::Range;
+
+/// The reason why the execution of a statement or expression was cut short.
+enum Interrupt {
+    /// The code halted by executing a terminating instruction.
+    Halt(Exit),
+    /// The interpreter cannot continue.
+    Error(Error),
+}
+
+impl From<Error> for Interrupt {
+    fn from(error: Error) -> Self {
+        Self::Error(error)
+    }
+}
+
+type Result<T, E = Interrupt> = std::result::Result<T, E>;
+
+/// The control flow after executing a statement.
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Leave,
+}
+
+/// Executes a single block of code, and its functions.
+pub(super) struct Executor<'a, 'c, 'hir, H> {
+    interp: &'a mut Interpreter<H>,
+    code: &'c yul::Code<'hir>,
+    /// The variables of the current function call, or of the top-level code.
+    frame: FxHashMap<yul::VarId, U256>,
+    depth: usize,
+    steps: u64,
+    trace: Vec<TraceEntry>,
+    /// The data passed to `return` or `revert`.
+    output: Vec<u8>,
+}
+
+impl<'a, 'c, 'hir, H: Host> Executor<'a, 'c, 'hir, H> {
+    pub(super) fn new(interp: &'a mut Interpreter<H>, code: &'c yul::Code<'hir>) -> Self {
+        Self {
+            interp,
+            code,
+            frame: FxHashMap::default(),
+            depth: 0,
+            steps: 0,
+            trace: Vec::new(),
+            output: Vec::new(),
+        }
+    }
+
+    /// Executes the top-level code.
+    pub(super) fn run(mut self) -> Result<Outcome, Error> {
+        let code = self.code;
+        let result = self.exec_block(&code.block).map(|_| Vec::new());
+        self.finish(result)
+    }
+
+    /// Calls a function with the given arguments.
+    pub(super) fn call(mut self, id: yul::FunctionId, args: &[U256]) -> Result<Outcome, Error> {
+        let result = self.call_function(id, args);
+        self.finish(result)
+    }
+
+    fn finish(self, result: Result<Vec<U256>>) -> Result<Outcome, Error> {
+        let (exit, outputs) = match result {
+            Ok(outputs) => (Exit::Stop, outputs),
+            Err(Interrupt::Halt(exit)) => (exit, Vec::new()),
+            Err(Interrupt::Error(error)) => return Err(error),
+        };
+        Ok(Outcome { exit, outputs, output: self.output, trace: self.trace })
+    }
+
+    fn step(&mut self) -> Result<()> {
+        self.steps += 1;
+        if self.steps > self.interp.limits.steps {
+            return Err(Error::StepLimit.into());
+        }
+        Ok(())
+    }
+
+    fn call_function(&mut self, id: yul::FunctionId, args: &[U256]) -> Result<Vec<U256>> {
+        if self.depth >= self.interp.limits.call_depth {
+            return Err(Error::CallDepthLimit.into());
+        }
+        let function = self.code.function(id);
+        let frame = function.parameters.iter().copied().zip(args.iter().copied()).collect();
+        let caller_frame = std::mem::replace(&mut self.frame, frame);
+        self.depth += 1;
+        let result = self.exec_block(&function.body);
+        self.depth -= 1;
+        let frame = std::mem::replace(&mut self.frame, caller_frame);
+        result?;
+        Ok(function.returns.iter().map(|var| frame.get(var).copied().unwrap_or_default()).collect())
+    }
+
+    fn exec_block(&mut self, block: &yul::Block<'hir>) -> Result<Flow> {
+        for stmt in block.iter() {
+            match self.exec_stmt(stmt)? {
+                Flow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn exec_stmt(&mut self, stmt: &yul::Stmt<'hir>) -> Result<Flow> {
+        self.step()?;
+        match &stmt.kind {
+            yul::StmtKind::Block(block) => return self.exec_block(block),
+            yul::StmtKind::Assign(lhs, rhs) => {
+                let values = self.eval(rhs)?;
+                for (lhs, value) in lhs.iter().zip(values) {
+                    self.assign(lhs, value)?;
+                }
+            }
+            yul::StmtKind::Expr(expr) => {
+                self.eval(expr)?;
+            }
+            yul::StmtKind::If(cond, block) => {
+                if !self.eval_one(cond)?.is_zero() {
+                    return self.exec_block(block);
+                }
+            }
+            yul::StmtKind::For(for_) => {
+                if let flow @ Flow::Leave = self.exec_block(&for_.init)? {
+                    return Ok(flow);
+                }
+                loop {
+                    self.step()?;
+                    if self.eval_one(&for_.cond)?.is_zero() {
+                        break;
+                    }
+                    match self.exec_block(&for_.body)? {
+                        Flow::Normal | Flow::Continue => {}
+                        Flow::Break => break,
+                        Flow::Leave => return Ok(Flow::Leave),
+                    }
+                    if let flow @ Flow::Leave = self.exec_block(&for_.step)? {
+                        return Ok(flow);
+                    }
+                }
+            }
+            yul::StmtKind::Switch(switch) => {
+                let selector = self.eval_one(&switch.selector)?;
+                // The default case is always last, so it only matches if no other case does.
+                let case = switch.cases.iter().find(|case| {
+                    case.constant.is_none_or(|lit| literal_value(lit) == Some(selector))
+                });
+                if let Some(case) = case {
+                    return self.exec_block(&case.body);
+                }
+            }
+            yul::StmtKind::Leave => return Ok(Flow::Leave),
+            yul::StmtKind::Break => return Ok(Flow::Break),
+            yul::StmtKind::Continue => return Ok(Flow::Continue),
+            yul::StmtKind::FunctionDef(_) => {}
+            yul::StmtKind::VarDecl(vars, value) => {
+                let values = match value {
+                    Some(value) => self.eval(value)?,
+                    None => vec![U256::ZERO; vars.len()],
+                };
+                for (&var, value) in vars.iter().zip(values) {
+                    self.frame.insert(var, value);
+                }
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn assign(&mut self, lhs: &yul::Expr<'hir>, value: U256) -> Result<()> {
+        match &lhs.kind {
+            &yul::ExprKind::Var(var) => {
+                self.frame.insert(var, value);
+            }
+            yul::ExprKind::External(ext) => {
+                self.interp.externals.insert(external_key(ext), value);
+            }
+            _ => return Err(Error::InvalidCode.into()),
+        }
+        Ok(())
+    }
+
+    fn eval_one(&mut self, expr: &yul::Expr<'hir>) -> Result<U256> {
+        self.eval(expr)?.first().copied().ok_or(Interrupt::Error(Error::InvalidCode))
+    }
+
+    fn eval(&mut self, expr: &yul::Expr<'hir>) -> Result<Vec<U256>> {
+        let value = match &expr.kind {
+            yul::ExprKind::Var(var) => self.frame.get(var).copied().unwrap_or_default(),
+            yul::ExprKind::External(ext) => {
+                self.interp.externals.get(&external_key(ext)).copied().unwrap_or_default()
+            }
+            yul::ExprKind::Lit(lit) => literal_value(lit).ok_or(Error::InvalidCode)?,
+            yul::ExprKind::Call(call) => {
+                return match call.callee {
+                    yul::Callee::Builtin => Ok(self.builtin(call)?.into_iter().collect()),
+                    yul::Callee::Function(id) => {
+                        let args = self.eval_args(call.arguments)?;
+                        self.call_function(id, &args)
+                    }
+                };
+            }
+            yul::ExprKind::Err(_) => return Err(Error::InvalidCode.into()),
+        };
+        Ok(vec![value])
+    }
+
+    /// Evaluates the arguments of a function call, from right to left.
+    fn eval_args(&mut self, args: &[yul::Expr<'hir>]) -> Result<Vec<U256>> {
+        let mut values = Vec::with_capacity(args.len());
+        for arg in args.iter().rev() {
+            values.push(self.eval_one(arg)?);
+        }
+        values.reverse();
+        Ok(values)
+    }
+
+    /// Returns the string value of a literal argument.
+    fn literal_arg(call: &yul::ExprCall<'hir>, i: usize) -> Result<&'hir str> {
+        match call.arguments.get(i).map(|arg| &arg.kind) {
+            Some(yul::ExprKind::Lit(lit)) => Ok(lit.symbol.as_str()),
+            _ => Err(Error::InvalidCode.into()),
+        }
+    }
+
+    /// Executes a builtin function, returning its output, if any.
+    fn builtin(&mut self, call: &yul::ExprCall<'hir>) -> Result<Option<U256>> {
+        let name = call.name.as_str();
+
+        // Builtins with literal arguments.
+        match name {
+            // There is no bytecode, so the sizes and offsets of data objects are made up from
+            // their names.
+            "datasize" | "dataoffset" => {
+                let hash = keccak256(word(Self::literal_arg(call, 0)?));
+                return Ok(Some(U256::from_be_bytes(hash.0) & U256::from(0xfff)));
+            }
+            "linkersymbol" => {
+                let hash = keccak256(Self::literal_arg(call, 0)?);
+                return Ok(Some(address_to_u256(Address::from_word(hash))));
+            }
+            "loadimmutable" => {
+                let name = Self::literal_arg(call, 0)?;
+                return Ok(Some(self.interp.immutables.get(name).copied().unwrap_or_default()));
+            }
+            "setimmutable" => {
+                let name = Self::literal_arg(call, 1)?;
+                let [value] = self.eval_args(&call.arguments[2..])?[..] else {
+                    return Err(Error::InvalidCode.into());
+                };
+                self.eval_args(&call.arguments[..1])?;
+                self.interp.immutables.insert(name.to_string(), value);
+                return Ok(None);
+            }
+            _ => {}
+        }
+
+        let args = self.eval_args(call.arguments)?;
+        let arg = |i: usize| args.get(i).copied().ok_or(Interrupt::Error(Error::InvalidCode));
+        let env = &self.interp.env;
+        let value = match name {
+            // Arithmetic.
+            "add" => arg(0)?.wrapping_add(arg(1)?),
+            "sub" => arg(0)?.wrapping_sub(arg(1)?),
+            "mul" => arg(0)?.wrapping_mul(arg(1)?),
+            "div" => arg(0)?.checked_div(arg(1)?).unwrap_or_default(),
+            "sdiv" => signed_op(arg(0)?, arg(1)?, I256::wrapping_div),
+            "mod" => arg(0)?.checked_rem(arg(1)?).unwrap_or_default(),
+            "smod" => signed_op(arg(0)?, arg(1)?, I256::wrapping_rem),
+            "exp" => arg(0)?.wrapping_pow(arg(1)?),
+            "addmod" => arg(0)?.add_mod(arg(1)?, arg(2)?),
+            "mulmod" => arg(0)?.mul_mod(arg(1)?, arg(2)?),
+            "signextend" => sign_extend(arg(0)?, arg(1)?),
+
+            // Comparison and bitwise logic.
+            "lt" => U256::from(arg(0)? < arg(1)?),
+            "gt" => U256::from(arg(0)? > arg(1)?),
+            "slt" => U256::from(I256::from_raw(arg(0)?) < I256::from_raw(arg(1)?)),
+            "sgt" => U256::from(I256::from_raw(arg(0)?) > I256::from_raw(arg(1)?)),
+            "eq" => U256::from(arg(0)? == arg(1)?),
+            "iszero" => U256::from(arg(0)?.is_zero()),
+            "and" => arg(0)? & arg(1)?,
+            "or" => arg(0)? | arg(1)?,
+            "xor" => arg(0)? ^ arg(1)?,
+            "not" => !arg(0)?,
+            "byte" => match shift_amount(arg(0)?) {
+                Some(i) if i < 32 => U256::from(arg(1)?.byte(31 - i)),
+                _ => U256::ZERO,
+            },
+            "shl" => match shift_amount(arg(0)?) {
+                Some(shift) => arg(1)? << shift,
+                None => U256::ZERO,
+            },
+            "shr" => match shift_amount(arg(0)?) {
+                Some(shift) => arg(1)? >> shift,
+                None => U256::ZERO,
+            },
+            "sar" => {
+                let value = I256::from_raw(arg(1)?);
+                match shift_amount(arg(0)?) {
+                    Some(shift) => value.asr(shift).into_raw(),
+                    None if value.is_negative() => U256::MAX,
+                    None => U256::ZERO,
+                }
+            }
+            "keccak256" => U256::from_be_bytes(keccak256(self.read_memory(arg(0)?, arg(1)?)?).0),
+
+            // Environment.
+            "address" => address_to_u256(env.address),
+            "origin" => address_to_u256(env.origin),
+            "caller" => address_to_u256(env.caller),
+            "callvalue" => env.call_value,
+            "calldataload" => U256::from_be_slice(&padded(&env.calldata, arg(0)?, 32)),
+            "calldatasize" => U256::from(env.calldata.len()),
+            "codesize" => U256::from(env.code.len()),
+            "gasprice" => env.gas_price,
+            "returndatasize" => U256::from(self.interp.return_data.len()),
+            "coinbase" => address_to_u256(env.coinbase),
+            "timestamp" => env.timestamp,
+            "number" => env.number,
+            "difficulty" | "prevrandao" => env.prevrandao,
+            "gaslimit" => env.gas_limit,
+            "chainid" => env.chain_id,
+            "basefee" => env.base_fee,
+            "blobbasefee" => env.blob_base_fee,
+            "gas" => env.gas,
+            "balance" => self.interp.host.balance(u256_to_address(arg(0)?)),
+            "selfbalance" => {
+                let address = env.address;
+                self.interp.host.balance(address)
+            }
+            "extcodesize" => U256::from(self.interp.host.code(u256_to_address(arg(0)?)).len()),
+            "extcodehash" => self.interp.host.code_hash(u256_to_address(arg(0)?)),
+            "blockhash" => self.interp.host.block_hash(arg(0)?),
+            "blobhash" => self.interp.host.blob_hash(arg(0)?),
+
+            // Memory, storage, and transient storage.
+            "pop" => return Ok(None),
+            "mload" => U256::from_be_slice(&self.read_memory(arg(0)?, U256::from(32))?),
+            "mstore" => {
+                self.write_memory(arg(0)?, &arg(1)?.to_be_bytes::<32>())?;
+                return Ok(None);
+            }
+            "mstore8" => {
+                self.write_memory(arg(0)?, &[arg(1)?.byte(0)])?;
+                return Ok(None);
+            }
+            "msize" => U256::from(self.interp.memory.len()),
+            "mcopy" => {
+                self.trace(name, &args, Vec::new());
+                let data = self.read_memory(arg(1)?, arg(2)?)?;
+                self.write_memory(arg(0)?, &data)?;
+                return Ok(None);
+            }
+            "sload" => self.interp.storage.get(&arg(0)?).copied().unwrap_or_default(),
+            "sstore" => {
+                self.interp.storage.insert(arg(0)?, arg(1)?);
+                return Ok(None);
+            }
+            "tload" => self.interp.transient_storage.get(&arg(0)?).copied().unwrap_or_default(),
+            "tstore" => {
+                self.interp.transient_storage.insert(arg(0)?, arg(1)?);
+                return Ok(None);
+            }
+
+            // Copies into memory.
+            "calldatacopy" | "codecopy" | "datacopy" | "returndatacopy" | "extcodecopy" => {
+                self.trace(name, &args, Vec::new());
+                let (dest, offset, size, src) = match name {
+                    "calldatacopy" => (arg(0)?, arg(1)?, arg(2)?, self.interp.env.calldata.clone()),
+                    "codecopy" | "datacopy" => {
+                        (arg(0)?, arg(1)?, arg(2)?, self.interp.env.code.clone())
+                    }
+                    "returndatacopy" => {
+                        let src = &self.interp.return_data;
+                        let end = arg(1)?.checked_add(arg(2)?);
+                        if end.is_none_or(|end| end > U256::from(src.len())) {
+                            return Err(Interrupt::Halt(Exit::Invalid));
+                        }
+                        (arg(0)?, arg(1)?, arg(2)?, src.clone())
+                    }
+                    _ => {
+                        let code = self.interp.host.code(u256_to_address(arg(0)?));
+                        (arg(1)?, arg(2)?, arg(3)?, code)
+                    }
+                };
+                let range = self.memory_range(dest, size)?;
+                let data = padded(&src, offset, range.len());
+                self.interp.memory[range].copy_from_slice(&data);
+                return Ok(None);
+            }
+
+            // Logs.
+            "log0" | "log1" | "log2" | "log3" | "log4" => {
+                let data = self.read_memory(arg(0)?, arg(1)?)?;
+                self.trace(name, &args, data.clone());
+                self.interp.host.log(Log { topics: args[2..].to_vec(), data });
+                return Ok(None);
+            }
+
+            // Calls and contract creation.
+            "create" | "create2" => {
+                let init_code = self.read_memory(arg(1)?, arg(2)?)?;
+                self.trace(name, &args, init_code.clone());
+                let salt = if name == "create2" { Some(arg(3)?) } else { None };
+                let create = Create { value: arg(0)?, init_code, salt };
+                let outcome = self.interp.host.create(&create);
+                self.interp.return_data = outcome.output;
+                outcome.address.map(address_to_u256).unwrap_or_default()
+            }
+            "call" | "callcode" | "delegatecall" | "staticcall" => {
+                let (kind, value, rest) = match name {
+                    "call" => (CallKind::Call, arg(2)?, &args[3..]),
+                    "callcode" => (CallKind::CallCode, arg(2)?, &args[3..]),
+                    "delegatecall" => (CallKind::DelegateCall, U256::ZERO, &args[2..]),
+                    _ => (CallKind::StaticCall, U256::ZERO, &args[2..]),
+                };
+                let &[in_offset, in_size, out_offset, out_size] = rest else {
+                    return Err(Error::InvalidCode.into());
+                };
+                let input = self.read_memory(in_offset, in_size)?;
+                self.trace(name, &args, input.clone());
+                let call =
+                    Call { kind, gas: arg(0)?, address: u256_to_address(arg(1)?), value, input };
+                let outcome = self.interp.host.call(&call);
+                let range = self.memory_range(out_offset, out_size)?;
+                let n = range.len().min(outcome.output.len());
+                self.interp.memory[range.start..range.start + n]
+                    .copy_from_slice(&outcome.output[..n]);
+                self.interp.return_data = outcome.output;
+                U256::from(outcome.success)
+            }
+
+            // Halting.
+            "stop" => return Err(Interrupt::Halt(Exit::Stop)),
+            "return" | "revert" => {
+                let data = self.read_memory(arg(0)?, arg(1)?)?;
+                self.trace(name, &args, data.clone());
+                self.output = data;
+                let exit = if name == "return" { Exit::Return } else { Exit::Revert };
+                return Err(Interrupt::Halt(exit));
+            }
+            "invalid" => {
+                self.trace(name, &args, Vec::new());
+                return Err(Interrupt::Halt(Exit::Invalid));
+            }
+            "selfdestruct" => {
+                self.trace(name, &args, Vec::new());
+                self.interp.host.self_destruct(u256_to_address(arg(0)?));
+                return Err(Interrupt::Halt(Exit::SelfDestruct));
+            }
+
+            "memoryguard" => arg(0)?,
+            _ => return Err(Error::InvalidCode.into()),
+        };
+        Ok(Some(value))
+    }
+
+    fn trace(&mut self, name: &str, args: &[U256], data: Vec<u8>) {
+        self.trace.push(TraceEntry { name: name.to_uppercase(), args: args.to_vec(), data });
+    }
+
+    /// Expands the memory to include the given range, and returns it.
+    ///
+    /// Empty ranges do not expand the memory, regardless of their offset.
+    fn memory_range(&mut self, offset: U256, size: U256) -> Result<Range<usize>> {
+        if size.is_zero() {
+            return Ok(0..0);
+        }
+        let (Ok(offset), Ok(size)) = (usize::try_from(offset), usize::try_from(size)) else {
+            return Err(Error::MemoryLimit.into());
+        };
+        let end = offset
+            .checked_add(size)
+            .filter(|&end| end <= self.interp.limits.memory)
+            .ok_or(Error::MemoryLimit)?;
+        let len = end.next_multiple_of(32);
+        if self.interp.memory.len() < len {
+            self.interp.memory.resize(len, 0);
+        }
+        Ok(offset..end)
+    }
+
+    fn read_memory(&mut self, offset: U256, size: U256) -> Result<Vec<u8>> {
+        let range = self.memory_range(offset, size)?;
+        Ok(self.interp.memory[range].to_vec())
+    }
+
+    fn write_memory(&mut self, offset: U256, data: &[u8]) -> Result<()> {
+        let range = self.memory_range(offset, U256::from(data.len()))?;
+        self.interp.memory[range].copy_from_slice(data);
+        Ok(())
+    }
+}
+
+/// Returns the key of an external reference in [`Interpreter::externals`].
+fn external_key(ext: &yul::ExternalRef) -> String {
+    match ext.suffix {
+        Some(suffix) => format!("{}.{}", ext.name, suffix.name()),
+        None => ext.name.to_string(),
+    }
+}
+
+/// Returns `size` bytes of `src` starting at `offset`, padded with zeros.
+fn padded(src: &[u8], offset: U256, size: usize) -> Vec<u8> {
+    let mut data = vec![0; size];
+    if let Ok(offset) = usize::try_from(offset)
+        && offset < src.len()
+    {
+        let n = size.min(src.len() - offset);
+        data[..n].copy_from_slice(&src[offset..offset + n]);
+    }
+    data
+}
+
+/// Returns a string left-aligned in a word.
+fn word(s: &str) -> B256 {
+    let mut word = B256::ZERO;
+    let n = s.len().min(32);
+    word[..n].copy_from_slice(&s.as_bytes()[..n]);
+    word
+}
+
+/// Returns the shift amount if it is less than 256.
+fn shift_amount(shift: U256) -> Option<usize> {
+    (shift < U256::from(256)).then(|| shift.to())
+}
+
+/// Applies a signed division operation, returning zero if the divisor is zero.
+fn signed_op(a: U256, b: U256, op: fn(I256, I256) -> I256) -> U256 {
+    if b.is_zero() { U256::ZERO } else { op(I256::from_raw(a), I256::from_raw(b)).into_raw() }
+}
+
+fn sign_extend(b: U256, x: U256) -> U256 {
+    if b >= U256::from(31) {
+        return x;
+    }
+    let bit = b.to::<usize>() * 8 + 7;
+    let mask = (U256::from(1) << bit) - U256::from(1);
+    if x.bit(bit) { x | !mask } else { x & mask }
+}
+
+fn address_to_u256(address: Address) -> U256 {
+    U256::from_be_slice(address.as_slice())
+}
+
+fn u256_to_address(value: U256) -> Address {
+    Address::from_word(B256::from(value))
+}
@@ -0,0 +1,230 @@
+//! The interface between the interpreter and the outside world.
+
+use alloy_primitives::{Address, U256, keccak256};
+use solar_data_structures::map::FxHashMap;
+use std::collections::VecDeque;
+
+/// The outside world of the interpreted code.
+///
+/// The host handles everything that the EVM would delegate to other accounts or to the chain:
+/// message calls, contract creation, account queries, and logs.
+///
+/// Every method has a default implementation that behaves as if the code was executed in an
+/// empty world where all calls succeed without returning any data.
+pub trait Host {
+    /// Handles a `call`, `callcode`, `delegatecall`, or `staticcall`.
+    fn call(&mut self, call: &Call) -> CallOutcome {
+        let _ = call;
+        CallOutcome { success: true, output: Vec::new() }
+    }
+
+    /// Handles a `create` or `create2`.
+    fn create(&mut self, create: &Create) -> CreateOutcome {
+        let _ = create;
+        CreateOutcome { address: None, output: Vec::new() }
+    }
+
+    /// Returns the balance of the given account.
+    fn balance(&mut self, address: Address) -> U256 {
+        let _ = address;
+        U256::ZERO
+    }
+
+    /// Returns the code of the given account.
+    fn code(&mut self, address: Address) -> Vec<u8> {
+        let _ = address;
+        Vec::new()
+    }
+
+    /// Returns the code hash of the given account, or zero if the account does not exist.
+    fn code_hash(&mut self, address: Address) -> U256 {
+        let code = self.code(address);
+        if code.is_empty() { U256::ZERO } else { U256::from_be_bytes(keccak256(code).0) }
+    }
+
+    /// Returns the hash of the given block.
+    fn block_hash(&mut self, number: U256) -> U256 {
+        let _ = number;
+        U256::ZERO
+    }
+
+    /// Returns the versioned hash of the blob at the given index of the transaction.
+    fn blob_hash(&mut self, index: U256) -> U256 {
+        let _ = index;
+        U256::ZERO
+    }
+
+    /// Records a log emitted by `log0` to `log4`.
+    fn log(&mut self, log: Log) {
+        let _ = log;
+    }
+
+    /// Handles a `selfdestruct`.
+    fn self_destruct(&mut self, beneficiary: Address) {
+        let _ = beneficiary;
+    }
+}
+
+impl<H: Host + ?Sized> Host for &mut H {
+    fn call(&mut self, call: &Call) -> CallOutcome {
+        (**self).call(call)
+    }
+
+    fn create(&mut self, create: &Create) -> CreateOutcome {
+        (**self).create(create)
+    }
+
+    fn balance(&mut self, address: Address) -> U256 {
+        (**self).balance(address)
+    }
+
+    fn code(&mut self, address: Address) -> Vec<u8> {
+        (**self).code(address)
+    }
+
+    fn code_hash(&mut self, address: Address) -> U256 {
+        (**self).code_hash(address)
+    }
+
+    fn block_hash(&mut self, number: U256) -> U256 {
+        (**self).block_hash(number)
+    }
+
+    fn blob_hash(&mut self, index: U256) -> U256 {
+        (**self).blob_hash(index)
+    }
+
+    fn log(&mut self, log: Log) {
+        (**self).log(log)
+    }
+
+    fn self_destruct(&mut self, beneficiary: Address) {
+        (**self).self_destruct(beneficiary)
+    }
+}
+
+/// The kind of a message call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallKind {
+    /// `call`.
+    Call,
+    /// `callcode`.
+    CallCode,
+    /// `delegatecall`.
+    DelegateCall,
+    /// `staticcall`.
+    StaticCall,
+}
+
+/// A message call made by the interpreted code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Call {
+    pub kind: CallKind,
+    pub gas: U256,
+    pub address: Address,
+    /// The value sent with the call. Always zero for `delegatecall` and `staticcall`.
+    pub value: U256,
+    pub input: Vec<u8>,
+}
+
+/// The result of a [`Call`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CallOutcome {
+    pub success: bool,
+    /// The return data, or the revert data if the call failed.
+    pub output: Vec<u8>,
+}
+
+/// A contract creation made by the interpreted code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Create {
+    pub value: U256,
+    pub init_code: Vec<u8>,
+    /// The salt of a `create2`. `None` for `create`.
+    pub salt: Option<U256>,
+}
+
+/// The result of a [`Create`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CreateOutcome {
+    /// The address of the created contract, or `None` if the creation failed.
+    pub address: Option<Address>,
+    /// The revert data if the creation failed.
+    pub output: Vec<u8>,
+}
+
+/// A log emitted by the interpreted code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Log {
+    pub topics: Vec<U256>,
+    pub data: Vec<u8>,
+}
+
+/// A [`Host`] that records every interaction, and replies with preconfigured outcomes.
+///
+/// Calls and creations are answered in order from [`call_outcomes`](Self::call_outcomes) and
+/// [`create_outcomes`](Self::create_outcomes), falling back to the default [`Host`] behavior
+/// once they are exhausted.
+#[derive(Clone, Debug, Default)]
+pub struct MockHost {
+    /// The balances of the accounts.
+    pub balances: FxHashMap<Address, U256>,
+    /// The code of the accounts.
+    pub codes: FxHashMap<Address, Vec<u8>>,
+    /// The block hashes, by block number.
+    pub block_hashes: FxHashMap<U256, U256>,
+    /// The blob hashes of the transaction.
+    pub blob_hashes: Vec<U256>,
+    /// The outcomes of the next message calls.
+    pub call_outcomes: VecDeque<CallOutcome>,
+    /// The outcomes of the next contract creations.
+    pub create_outcomes: VecDeque<CreateOutcome>,
+
+    /// The message calls that were made.
+    pub calls: Vec<Call>,
+    /// The contract creations that were made.
+    pub creates: Vec<Create>,
+    /// The logs that were emitted.
+    pub logs: Vec<Log>,
+    /// The beneficiaries of the `selfdestruct`s that were executed.
+    pub self_destructs: Vec<Address>,
+}
+
+impl Host for MockHost {
+    fn call(&mut self, call: &Call) -> CallOutcome {
+        self.calls.push(call.clone());
+        self.call_outcomes.pop_front().unwrap_or(CallOutcome { success: true, output: Vec::new() })
+    }
+
+    fn create(&mut self, create: &Create) -> CreateOutcome {
+        self.creates.push(create.clone());
+        self.create_outcomes.pop_front().unwrap_or_default()
+    }
+
+    fn balance(&mut self, address: Address) -> U256 {
+        self.balances.get(&address).copied().unwrap_or_default()
+    }
+
+    fn code(&mut self, address: Address) -> Vec<u8> {
+        self.codes.get(&address).cloned().unwrap_or_default()
+    }
+
+    fn block_hash(&mut self, number: U256) -> U256 {
+        self.block_hashes.get(&number).copied().unwrap_or_default()
+    }
+
+    fn blob_hash(&mut self, index: U256) -> U256 {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.blob_hashes.get(index).copied())
+            .unwrap_or_default()
+    }
+
+    fn log(&mut self, log: Log) {
+        self.logs.push(log);
+    }
+
+    fn self_destruct(&mut self, beneficiary: Address) {
+        self.self_destructs.push(beneficiary);
+    }
+}
@@ -0,0 +1,529 @@
+//! Yul interpreter.
+//!
+//! Executes lowered Yul code without compiling it to bytecode, which allows testing assembly
+//! snippets in isolation. The interpreter models the memory, storage, transient storage,
+//! calldata, and return data of a single call frame; everything outside of it, such as message
+//! calls and contract creation, is delegated to a [`Host`].
+//!
+//! Gas is not metered. Instead, execution is bounded by the [`Limits`].
+//!
+//! Reference: solc `test/tools/yulInterpreter`
+
+use crate::hir::yul;
+use alloy_primitives::{Address, U256, hex};
+use solar_data_structures::map::FxHashMap;
+use std::{collections::BTreeMap, fmt};
+
+mod exec;
+
+mod host;
+pub use host::{Call, CallKind, CallOutcome, Create, CreateOutcome, Host, Log, MockHost};
+
+/// A Yul interpreter.
+///
+/// The state is preserved across runs, so the same interpreter can be used to execute several
+/// functions against the same storage, for example.
+#[derive(Clone, Debug, Default)]
+pub struct Interpreter<H = MockHost> {
+    /// The host that handles the interactions with the outside world.
+    pub host: H,
+    /// The execution environment.
+    pub env: Env,
+    /// The execution limits.
+    pub limits: Limits,
+    /// The memory.
+    pub memory: Vec<u8>,
+    /// The persistent storage.
+    pub storage: BTreeMap<U256, U256>,
+    /// The transient storage.
+    pub transient_storage: BTreeMap<U256, U256>,
+    /// The return data of the last message call or contract creation.
+    pub return_data: Vec<u8>,
+    /// The values of the immutables, set by `setimmutable` and read by `loadimmutable`.
+    pub immutables: BTreeMap<String, U256>,
+    /// The values of the Solidity declarations referenced from inline assembly, keyed by their
+    /// name and suffix, e.g. `x` or `x.slot`. Missing values read as zero.
+    pub externals: FxHashMap<String, U256>,
+}
+
+/// The execution environment of the interpreted code.
+#[derive(Clone, Debug)]
+pub struct Env {
+    /// The address of the executing account.
+    pub address: Address,
+    pub caller: Address,
+    pub origin: Address,
+    pub call_value: U256,
+    pub calldata: Vec<u8>,
+    /// The code of the executing account, used by `codesize`, `codecopy`, and `datacopy`.
+    pub code: Vec<u8>,
+    /// The value returned by `gas()`.
+    pub gas: U256,
+    pub gas_price: U256,
+    pub coinbase: Address,
+    pub timestamp: U256,
+    pub number: U256,
+    /// The value returned by `prevrandao()` and `difficulty()`.
+    pub prevrandao: U256,
+    pub gas_limit: U256,
+    pub chain_id: U256,
+    pub base_fee: U256,
+    pub blob_base_fee: U256,
+}
+
+impl Default for Env {
+    fn default() -> Self {
+        Self {
+            address: Address::ZERO,
+            caller: Address::ZERO,
+            origin: Address::ZERO,
+            call_value: U256::ZERO,
+            calldata: Vec::new(),
+            code: Vec::new(),
+            gas: U256::from(30_000_000),
+            gas_price: U256::ZERO,
+            coinbase: Address::ZERO,
+            timestamp: U256::ZERO,
+            number: U256::ZERO,
+            prevrandao: U256::ZERO,
+            gas_limit: U256::from(30_000_000),
+            chain_id: U256::from(1),
+            base_fee: U256::ZERO,
+            blob_base_fee: U256::from(1),
+        }
+    }
+}
+
+/// The limits that bound the execution, in place of gas.
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    /// The maximum number of statements and loop iterations that can be executed.
+    pub steps: u64,
+    /// The maximum size of the memory, in bytes.
+    pub memory: usize,
+    /// The maximum depth of nested function calls.
+    pub call_depth: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self { steps: 1_000_000, memory: 1 << 24, call_depth: 256 }
+    }
+}
+
+/// The result of a successful [`Interpreter`] run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    /// How the execution ended.
+    pub exit: Exit,
+    /// The values returned by the function, if a function was called and it did not halt.
+    pub outputs: Vec<U256>,
+    /// The data passed to `return` or `revert`.
+    pub output: Vec<u8>,
+    /// The trace of the observable effects of the execution.
+    pub trace: Vec<TraceEntry>,
+}
+
+/// How the execution of Yul code ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    /// The end of the code or function was reached, or `stop()` was executed.
+    Stop,
+    /// `return(p, n)` was executed.
+    Return,
+    /// `revert(p, n)` was executed.
+    Revert,
+    /// `invalid()` was executed, or an exceptional halt occurred.
+    Invalid,
+    /// `selfdestruct(a)` was executed.
+    SelfDestruct,
+}
+
+/// An error that prevents the interpreter from finishing the execution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The step limit was exceeded.
+    StepLimit,
+    /// The memory limit was exceeded.
+    MemoryLimit,
+    /// The call depth limit was exceeded.
+    CallDepthLimit,
+    /// The function to call does not exist.
+    FunctionNotFound(String),
+    /// The function was called with the wrong number of arguments.
+    ArgumentCount { expected: usize, found: usize },
+    /// The code contains an unresolved expression, or an unknown builtin.
+    InvalidCode,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StepLimit => f.write_str("step limit exceeded"),
+            Self::MemoryLimit => f.write_str("memory limit exceeded"),
+            Self::CallDepthLimit => f.write_str("call depth limit exceeded"),
+            Self::FunctionNotFound(name) => write!(f, "function `{name}` not found"),
+            Self::ArgumentCount { expected, found } => {
+                write!(f, "expected {expected} arguments, found {found}")
+            }
+            Self::InvalidCode => f.write_str("the code contains errors"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// An observable effect of the execution: an external interaction, a copy into memory, or the
+/// end of the execution.
+///
+/// Formatted like solc's interpreter traces, e.g. `CALL(0, 0x1234, 0, 0, 4, 0, 0) [12345678]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// The uppercase name of the instruction.
+    pub name: String,
+    pub args: Vec<U256>,
+    /// The data read from memory by the instruction, if any.
+    pub data: Vec<u8>,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.name)?;
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            fmt_number(*arg, f)?;
+        }
+        f.write_str(")")?;
+        if !self.data.is_empty() {
+            write!(f, " [{}]", hex::encode(&self.data))?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats small numbers in decimal, and large numbers in compact hex.
+fn fmt_number(value: U256, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if value > U256::from(0x1000000) {
+        let bytes = value.to_be_bytes_trimmed_vec();
+        write!(f, "0x{}", hex::encode(bytes))
+    } else {
+        write!(f, "{value}")
+    }
+}
+
+impl<H: Host> Interpreter<H> {
+    /// Creates a new interpreter with the given host and an empty state.
+    pub fn new(host: H) -> Self {
+        Self {
+            host,
+            env: Env::default(),
+            limits: Limits::default(),
+            memory: Vec::new(),
+            storage: BTreeMap::new(),
+            transient_storage: BTreeMap::new(),
+            return_data: Vec::new(),
+            immutables: BTreeMap::new(),
+            externals: FxHashMap::default(),
+        }
+    }
+
+    /// Executes the code of a Yul object.
+    ///
+    /// Sub-objects are not executed, but they can be referenced by the object builtins.
+    pub fn run_object(&mut self, object: &yul::Object<'_>) -> Result<Outcome, Error> {
+        exec::Executor::new(self, &object.code).run()
+    }
+
+    /// Executes a block of Yul code, such as the body of an inline assembly block.
+    pub fn run_code(&mut self, code: &yul::Code<'_>) -> Result<Outcome, Error> {
+        exec::Executor::new(self, code).run()
+    }
+
+    /// Calls the function with the given name in a block of Yul code, returning its outputs.
+    ///
+    /// The rest of the code is not executed. If there are several functions with the same name
+    /// in different scopes, the first one is called.
+    pub fn call_function(
+        &mut self,
+        code: &yul::Code<'_>,
+        name: &str,
+        args: &[U256],
+    ) -> Result<Outcome, Error> {
+        let Some(id) = code.functions.iter().position(|f| f.name.as_str() == name) else {
+            return Err(Error::FunctionNotFound(name.to_string()));
+        };
+        let function = &code.functions[id];
+        if function.parameters.len() != args.len() {
+            return Err(Error::ArgumentCount {
+                expected: function.parameters.len(),
+                found: args.len(),
+            });
+        }
+        exec::Executor::new(self, code).call(yul::FunctionId::from_usize(id), args)
+    }
+
+    /// Returns a dump of the memory and storage, in the format of solc's interpreter tests.
+    ///
+    /// Only non-zero words are included.
+    pub fn dump(&self) -> impl fmt::Display + '_ {
+        solar_data_structures::fmt::from_fn(move |f| {
+            writeln!(f, "Memory dump:")?;
+            for (i, word) in self.memory.chunks(32).enumerate() {
+                if word.iter().any(|&b| b != 0) {
+                    writeln!(f, "  {:>4X}: {}", i * 32, hex::encode(word))?;
+                }
+            }
+            for (name, storage) in
+                [("Storage", &self.storage), ("Transient storage", &self.transient_storage)]
+            {
+                writeln!(f, "{name} dump:")?;
+                for (slot, value) in storage {
+                    if !value.is_zero() {
+                        let (slot, value) = (slot.to_be_bytes::<32>(), value.to_be_bytes::<32>());
+                        writeln!(f, "  {}: {}", hex::encode(slot), hex::encode(value))?;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Compiler, hir};
+    use solar_interface::{ColorChoice, Session, source_map::FileName};
+    use std::path::PathBuf;
+
+    /// Parses and lowers a Yul object, and passes it to `f`.
+    fn with_object<R: Send>(src: &str, f: impl FnOnce(&yul::Object<'_>) -> R + Send) -> R {
+        let sess = Session::builder().with_buffer_emitter(ColorChoice::Never).build();
+        sess.enter(|| {
+            let ast_arena = solar_ast::Arena::new();
+            let arena = hir::Arena::new();
+            let name = FileName::Custom("test".into());
+            let mut parser =
+                solar_parse::Parser::from_source_code(&sess, &ast_arena, name, src.to_string())
+                    .unwrap();
+            let object = parser.parse_yul_file_object().map_err(|e| e.emit()).unwrap();
+            let object = crate::yul::lower_object(&sess, &arena, &object);
+            let errs = sess.dcx.emitted_errors().unwrap();
+            assert!(errs.is_ok(), "{errs:?}");
+            f(&object.unwrap())
+        })
+    }
+
+    fn run(src: &str) -> (Interpreter, Outcome) {
+        with_object(src, |object| {
+            let mut interpreter = Interpreter::new(MockHost::default());
+            let outcome = interpreter.run_object(object).unwrap();
+            (interpreter, outcome)
+        })
+    }
+
+    fn u256_address(n: u64) -> Address {
+        Address::from_word(U256::from(n).into())
+    }
+
+    fn call(src: &str, name: &str, args: &[u64]) -> Result<Vec<U256>, Error> {
+        let args = args.iter().map(|&arg| U256::from(arg)).collect::<Vec<_>>();
+        with_object(src, |object| {
+            let mut interpreter = Interpreter::new(MockHost::default());
+            let outcome = interpreter.call_function(&object.code, name, &args)?;
+            assert_eq!(outcome.exit, Exit::Stop);
+            Ok(outcome.outputs)
+        })
+    }
+
+    #[test]
+    fn arithmetic() {
+        let src = r#"{
+            function f(a, b) -> r0, r1, r2, r3, r4 {
+                r0 := sdiv(sub(0, a), b)
+                r1 := smod(sub(0, a), b)
+                r2 := slt(sub(0, a), b)
+                r3 := div(a, 0)
+                r4 := exp(a, b)
+            }
+            function g(x) -> r0, r1, r2, r3 {
+                r0 := signextend(0, x)
+                r1 := byte(31, x)
+                r2 := sar(4, not(0))
+                r3 := shr(256, x)
+            }
+        }"#;
+        let neg = |n: u64| U256::ZERO - U256::from(n);
+        let outputs = call(src, "f", &[7, 2]).unwrap();
+        assert_eq!(outputs, [neg(3), neg(1), U256::from(1), U256::ZERO, U256::from(49)]);
+        let outputs = call(src, "g", &[0x1ff]).unwrap();
+        assert_eq!(outputs, [neg(1), U256::from(0xff), U256::MAX, U256::ZERO]);
+    }
+
+    #[test]
+    fn control_flow() {
+        let src = r#"{
+            function sum(n) -> r {
+                for { let i := 0 } 1 { i := add(i, 1) } {
+                    if gt(i, n) { break }
+                    if mod(i, 2) { continue }
+                    r := add(r, i)
+                }
+            }
+            function fib(n) -> r {
+                if lt(n, 2) {
+                    r := n
+                    leave
+                }
+                switch n
+                case 2 { r := 1 }
+                default { r := add(fib(sub(n, 1)), fib(sub(n, 2))) }
+            }
+        }"#;
+        assert_eq!(call(src, "sum", &[10]).unwrap(), [U256::from(30)]);
+        assert_eq!(call(src, "fib", &[10]).unwrap(), [U256::from(55)]);
+        assert_eq!(call(src, "fib", &[]), Err(Error::ArgumentCount { expected: 1, found: 0 }));
+        assert_eq!(call(src, "nope", &[]), Err(Error::FunctionNotFound("nope".into())));
+    }
+
+    #[test]
+    fn state() {
+        let (interpreter, outcome) = run(r#"{
+            mstore(0x20, 0x0b)
+            mstore8(0x40, 0xff)
+            sstore(1, eq(keccak256(0x1000, 0), 0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470))
+            tstore(2, msize())
+            return(0x3f, 2)
+        }"#);
+        assert_eq!(outcome.exit, Exit::Return);
+        assert_eq!(outcome.output, [0x0b, 0xff]);
+        assert_eq!(outcome.trace.len(), 1);
+        assert_eq!(outcome.trace[0].to_string(), "RETURN(63, 2) [0bff]");
+        assert_eq!(
+            interpreter.dump().to_string(),
+            "\
+Memory dump:
+    20: 000000000000000000000000000000000000000000000000000000000000000b
+    40: ff00000000000000000000000000000000000000000000000000000000000000
+Storage dump:
+  0000000000000000000000000000000000000000000000000000000000000001: 0000000000000000000000000000000000000000000000000000000000000001
+Transient storage dump:
+  0000000000000000000000000000000000000000000000000000000000000002: 0000000000000000000000000000000000000000000000000000000000000060
+"
+        );
+    }
+
+    #[test]
+    fn host() {
+        let src = r#"{
+            calldatacopy(0, 0, calldatasize())
+            let ok := call(gas(), 0x1234, 5, 0, 4, 0x20, 0x20)
+            sstore(0, ok)
+            sstore(1, mload(0x20))
+            sstore(2, returndatasize())
+            log2(0, 4, 0xaa, 0xbb)
+            sstore(3, create2(0, 0, 4, 7))
+            revert(0, 0)
+        }"#;
+        with_object(src, |object| {
+            let mut host = MockHost::default();
+            host.call_outcomes.push_back(CallOutcome { success: true, output: vec![0x42; 33] });
+            let created = u256_address(0xcc);
+            host.create_outcomes
+                .push_back(CreateOutcome { address: Some(created), ..Default::default() });
+            let mut interpreter = Interpreter::new(&mut host);
+            interpreter.env.calldata = vec![0xde, 0xad, 0xbe, 0xef];
+            let outcome = interpreter.run_object(object).unwrap();
+            assert_eq!(outcome.exit, Exit::Revert);
+            let names = outcome.trace.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>();
+            assert_eq!(names, ["CALLDATACOPY", "CALL", "LOG2", "CREATE2", "REVERT"]);
+            assert_eq!(
+                outcome.trace[1].to_string(),
+                "CALL(0x01c9c380, 4660, 5, 0, 4, 32, 32) [deadbeef]"
+            );
+
+            let storage = |slot: u64| interpreter.storage[&U256::from(slot)];
+            assert_eq!(storage(0), U256::from(1));
+            assert_eq!(storage(1), U256::from_be_slice(&[0x42; 32]));
+            assert_eq!(storage(2), U256::from(33));
+            assert_eq!(storage(3), U256::from(0xcc));
+
+            assert_eq!(host.calls.len(), 1);
+            assert_eq!(host.calls[0].kind, CallKind::Call);
+            assert_eq!(host.calls[0].address, u256_address(0x1234));
+            assert_eq!(host.calls[0].input, [0xde, 0xad, 0xbe, 0xef]);
+            assert_eq!(
+                host.logs,
+                [Log {
+                    topics: vec![U256::from(0xaa), U256::from(0xbb)],
+                    data: vec![0xde, 0xad, 0xbe, 0xef]
+                }]
+            );
+            assert_eq!(host.creates[0].salt, Some(U256::from(7)));
+        });
+    }
+
+    #[test]
+    fn limits() {
+        let src = r#"{
+            function f(x) -> r { r := f(x) }
+            for {} 1 {} {}
+        }"#;
+        with_object(src, |object| {
+            let mut interpreter = Interpreter::new(MockHost::default());
+            assert_eq!(interpreter.run_object(object), Err(Error::StepLimit));
+            assert_eq!(
+                interpreter.call_function(&object.code, "f", &[U256::ZERO]),
+                Err(Error::CallDepthLimit)
+            );
+        });
+        let (_, outcome) = run("{ returndatacopy(0, 0, 1) }");
+        assert_eq!(outcome.exit, Exit::Invalid);
+        with_object("{ mstore(not(0), 1) }", |object| {
+            let mut interpreter = Interpreter::new(MockHost::default());
+            assert_eq!(interpreter.run_object(object), Err(Error::MemoryLimit));
+        });
+    }
+
+    /// Inline assembly blocks can be run with the values of the Solidity variables they access.
+    #[test]
+    fn inline_assembly() {
+        let src = r#"
+            function f(uint256 x) pure returns (uint256 y) {
+                assembly {
+                    function double(v) -> r { r := shl(1, v) }
+                    y := add(double(x), 1)
+                }
+            }
+        "#;
+        let sess = Session::builder().with_buffer_emitter(ColorChoice::Never).build();
+        let mut compiler = Compiler::new(sess);
+        compiler.enter_mut(|c| {
+            let mut pcx = c.parse();
+            let file = c.sess().source_map().new_source_file(PathBuf::from("test.sol"), src);
+            pcx.add_file(file.unwrap());
+            pcx.parse();
+            assert!(c.lower_asts().is_ok());
+            let gcx = c.gcx();
+            let f = gcx.hir.functions().find(|f| f.name.is_some_and(|n| n.as_str() == "f"));
+            let body = f.unwrap().body.unwrap();
+            let Some(hir::StmtKind::Assembly(assembly)) = body.stmts.first().map(|s| &s.kind)
+            else {
+                panic!("expected an assembly block");
+            };
+
+            let mut interpreter = Interpreter::new(MockHost::default());
+            interpreter.externals.insert("x".into(), U256::from(20));
+            let outcome = interpreter.run_code(&assembly.code).unwrap();
+            assert_eq!(outcome.exit, Exit::Stop);
+            assert_eq!(interpreter.externals["y"], U256::from(41));
+
+            let outputs = interpreter.call_function(&assembly.code, "double", &[U256::from(4)]);
+            assert_eq!(outputs.unwrap().outputs, [U256::from(8)]);
+        });
+        let errs = compiler.sess().dcx.emitted_errors().unwrap();
+        assert!(errs.is_ok(), "{errs:?}");
+    }
+}
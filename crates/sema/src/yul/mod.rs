@@ -0,0 +1,67 @@
+//! Yul language support: builtins, semantic analysis, and interpretation.
+
+use crate::hir;
+use alloy_primitives::U256;
+use solar_ast::{self as ast, Lit, LitKind};
+use solar_interface::{Result, Session, config::EvmVersion};
+
+mod analyzer;
+pub(crate) use analyzer::{check_code, check_object};
+
+mod builtins;
+pub(crate) use builtins::builtin;
+
+pub mod interpreter;
+
+/// Lowers a parsed Yul object and its sub-objects, and runs the semantic analysis on them.
+///
+/// Returns an error if any errors were emitted.
+pub fn lower_object<'hir>(
+    sess: &Session,
+    arena: &'hir hir::Arena,
+    object: &ast::yul::Object<'_>,
+) -> Result<hir::yul::Object<'hir>> {
+    let object = crate::ast_lowering::lower_yul_object(sess, arena, object);
+    check_object(sess, &object);
+    sess.dcx.has_errors()?;
+    Ok(object)
+}
+
+/// The Yul dialect that a block of code is written in.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Dialect {
+    /// The target EVM version.
+    pub(crate) evm_version: EvmVersion,
+    /// Whether the code is part of a Yul object, and can access the object builtins such as
+    /// `datasize`. `false` for inline assembly.
+    pub(crate) objects: bool,
+}
+
+impl Dialect {
+    /// Returns the dialect of inline assembly blocks.
+    pub(crate) fn inline_assembly(evm_version: EvmVersion) -> Self {
+        Self { evm_version, objects: false }
+    }
+
+    /// Returns the dialect of Yul objects.
+    pub(crate) fn objects(evm_version: EvmVersion) -> Self {
+        Self { evm_version, objects: true }
+    }
+}
+
+/// Returns the 256-bit value of a Yul literal, or `None` if it does not fit in a word.
+///
+/// Strings are left-aligned.
+pub(crate) fn literal_value(lit: &Lit<'_>) -> Option<U256> {
+    match lit.kind {
+        LitKind::Number(n) => Some(n),
+        LitKind::Bool(b) => Some(U256::from(b)),
+        LitKind::Str(_, value, _) => {
+            let bytes = value.as_byte_str();
+            let mut word = [0u8; 32];
+            word.get_mut(..bytes.len())?.copy_from_slice(bytes);
+            Some(U256::from_be_bytes(word))
+        }
+        _ => None,
+    }
+}
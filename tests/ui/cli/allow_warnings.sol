@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: MIT
+//@ compile-flags: -A warnings -W 6321
+
+contract C {
+    function f() public pure returns (uint256) {
+        return 1;
+        f();
+    }
+
+    function g(bool b) public pure returns (uint256) { //~ WARN: unnamed return variable can remain unassigned
+        if (b) return 1;
+    }
+}
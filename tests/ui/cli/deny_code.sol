@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MIT
+//@ compile-flags: -D 6321
+
+contract C {
+    function f() public pure returns (uint256) {
+        return 1;
+        f(); //~ WARN: unreachable code
+    }
+
+    function g(bool b) public pure returns (uint256) { //~ ERROR: unnamed return variable can remain unassigned
+        //~^ NOTE: `-D 6321` turned this warning into an error
+        if (b) return 1;
+    }
+}
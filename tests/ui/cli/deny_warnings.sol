@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MIT
+//@ compile-flags: -D warnings -A 5740
+
+contract C {
+    function f() public pure returns (uint256) {
+        return 1;
+        f();
+    }
+
+    function g(bool b) public pure returns (uint256) { //~ ERROR: unnamed return variable can remain unassigned
+        //~^ NOTE: `-D warnings` turned this warning into an error
+        if (b) return 1;
+    }
+}
@@ -0,0 +1,2 @@
+// SPDX-License-Identifier: MIT
+//@ compile-flags: --explain 9914
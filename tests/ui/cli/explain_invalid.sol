@@ -0,0 +1,3 @@
+//@ compile-flags: --explain 12345
+//@ error-in-other-file: `12345` is not a valid diagnostic code
+// check-fail
@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT
+//@ compile-flags: --fix-dry-run -Ztypeck
+
+contract C {
+    function f() public returns (uint256) {
+        return 1;
+    }
+
+    fallback() external payable {}
+}
+
+
+
+//~^^^^^^^^^^ WARN: contract has a payable fallback function, but no receive ether function
+//~^^^^^^^^^^ WARN: function state mutability can be restricted to `pure`
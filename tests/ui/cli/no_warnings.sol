@@ -0,0 +1,7 @@
+//@ compile-flags: --no-warnings
+
+contract C {
+    function g(bool b) public pure returns (uint256) {
+        if (b) return 1;
+    }
+}
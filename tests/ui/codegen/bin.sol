@@ -0,0 +1,23 @@
+//@compile-flags: -Ztypeck --emit=bin,bin-runtime --evm-version=cancun --pretty-json
+
+contract Counter {
+    uint256 public count;
+    uint256 immutable step;
+
+    constructor(uint256 step_) {
+        step = step_;
+    }
+
+    function increment() external returns (uint256) {
+        count += step;
+        return count;
+    }
+}
+
+interface I {
+    function f() external;
+}
+
+abstract contract A {
+    function g() public virtual;
+}
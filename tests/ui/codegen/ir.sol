@@ -0,0 +1,26 @@
+//@ignore-host: windows
+//@compile-flags: -Ztypeck --emit=ir --pretty-json
+
+interface I {
+    function get() external view returns (uint256);
+}
+
+abstract contract Base {
+    uint256 internal count;
+
+    function get() public view virtual returns (uint256);
+}
+
+contract Counter is Base, I {
+    event Incremented(address indexed by, uint256 count);
+
+    function get() public view override(Base, I) returns (uint256) {
+        return count;
+    }
+
+    function increment(uint256 by) external {
+        require(by != 0, "zero");
+        count += by;
+        emit Incremented(msg.sender, count);
+    }
+}
@@ -0,0 +1,20 @@
+//@ignore-host: windows
+//@compile-flags: -Ztypeck --emit=ir --pretty-json
+
+contract Abi {
+    function encode(uint256 a, address b) public pure returns (bytes memory) {
+        return abi.encode(a, b);
+    }
+
+    function encodePacked(uint8 a, bytes32 b) public pure returns (bytes memory) {
+        return abi.encodePacked(a, b);
+    }
+
+    function encodeWithSelector(uint256 a) public pure returns (bytes memory) {
+        return abi.encodeWithSelector(0x12345678, a);
+    }
+
+    function decode(bytes memory data) public pure returns (uint256, address) {
+        return abi.decode(data, (uint256, address));
+    }
+}
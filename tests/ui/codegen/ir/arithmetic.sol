@@ -0,0 +1,22 @@
+//@ignore-host: windows
+//@compile-flags: -Ztypeck --emit=ir --pretty-json
+
+contract Arithmetic {
+    function unsignedOps(uint256 a, uint256 b) public pure returns (uint256, uint256, uint256, uint256, uint256) {
+        return (a + b, a - b, a * b, a / b, a % b);
+    }
+
+    function signedOps(int128 a, int128 b) public pure returns (int128, int128, int128) {
+        return (a + b, a * b, a / b);
+    }
+
+    function narrow(uint8 a, uint8 b) public pure returns (uint8) {
+        return a * b;
+    }
+
+    function unchecked_(uint256 a, uint256 b) public pure returns (uint256) {
+        unchecked {
+            return a - b;
+        }
+    }
+}
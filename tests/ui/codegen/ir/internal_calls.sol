@@ -0,0 +1,26 @@
+//@ignore-host: windows
+//@compile-flags: -Ztypeck --emit=ir --pretty-json
+
+contract InternalCalls {
+    function square(uint256 x) internal pure returns (uint256) {
+        return x * x;
+    }
+
+    function sumOfSquares(uint256 a, uint256 b) public pure returns (uint256) {
+        return add(square(a), square(b));
+    }
+
+    function add(uint256 a, uint256 b) private pure returns (uint256) {
+        return a + b;
+    }
+
+    function divmod(uint256 a, uint256 b) internal pure returns (uint256 q, uint256 r) {
+        q = a / b;
+        r = a % b;
+    }
+
+    function quotient(uint256 a, uint256 b) public pure returns (uint256) {
+        (uint256 q, ) = divmod(a, b);
+        return q;
+    }
+}
@@ -0,0 +1,18 @@
+//@ignore-host: windows
+//@compile-flags: -Ztypeck --emit=ir --pretty-json
+
+contract Revert {
+    function checkRequire(uint256 a) public pure {
+        require(a > 1, "too small");
+    }
+
+    function checkRevert(uint256 a) public pure {
+        if (a == 0) {
+            revert("zero");
+        }
+    }
+
+    function checkRequireNoMessage(bool ok) public pure {
+        require(ok);
+    }
+}
@@ -0,0 +1,36 @@
+//@ignore-host: windows
+//@compile-flags: -Ztypeck --emit=ir --pretty-json
+
+contract Storage {
+    struct Point {
+        uint128 x;
+        uint128 y;
+    }
+
+    uint256 total;
+    bool flag;
+    address owner;
+    mapping(address => uint256) balances;
+    mapping(address => mapping(uint256 => bool)) approvals;
+    Point point;
+
+    function deposit(uint256 amount) public {
+        balances[msg.sender] += amount;
+        total += amount;
+    }
+
+    function approve(uint256 id) public {
+        approvals[msg.sender][id] = true;
+    }
+
+    function isApproved(address who, uint256 id) public view returns (bool) {
+        return approvals[who][id];
+    }
+
+    function setPoint(uint128 x, uint128 y) public {
+        point.x = x;
+        point.y = y;
+        flag = true;
+        owner = msg.sender;
+    }
+}
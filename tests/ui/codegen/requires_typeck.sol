@@ -0,0 +1,6 @@
+//@ compile-flags: --emit=abi,ir,bin
+//@ error-in-other-file: `--emit ir` requires `-Ztypeck`
+//@ error-in-other-file: `--emit bin` requires `-Ztypeck`
+// check-fail
+
+contract C {}
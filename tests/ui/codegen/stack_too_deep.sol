@@ -0,0 +1,28 @@
+//@compile-flags: -Ztypeck --emit=bin
+
+contract C {
+//~^ ERROR: stack too deep: variable `headStart`
+//~| ERROR: stack too deep: variable `param_8_0`
+//~| ERROR: stack too deep: variable `var_p_15`
+    function f(
+        uint256 a,
+        uint256 b,
+        uint256 c,
+        uint256 d,
+        uint256 e,
+        uint256 f,
+        uint256 g,
+        uint256 h,
+        uint256 i,
+        uint256 j,
+        uint256 k,
+        uint256 l,
+        uint256 m,
+        uint256 n,
+        uint256 o,
+        uint256 p,
+        uint256 q
+    ) external pure returns (uint256) {
+        return a + b + c + d + e + f + g + h + i + j + k + l + m + n + o + p + q;
+    }
+}
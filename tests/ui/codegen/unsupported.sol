@@ -0,0 +1,13 @@
+//@compile-flags: -Ztypeck --emit=ir
+
+contract C {
+    function f(function() internal g) internal {
+        g(); //~ ERROR: the IR code generator does not support calling function pointers yet
+    }
+
+    function h() internal {}
+
+    function run() public {
+        f(h); //~ ERROR: the IR code generator does not support function pointers yet
+    }
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+contract C {
+    // Balanced: ‮ override ‬ and ⁧ isolate ⁩.
+    /* Nested: ‭ ‫ embedding ‬ override ‬ */
+
+    // Unterminated: ‮
+    //~^ ERROR: mismatching Unicode direction override markers in comment
+
+    /* Underflow: ‬ ‮ ‬ */
+    //~^ ERROR: Unicode direction override underflow in comment
+
+    /// Doc-comments: ⁨
+    //~^ ERROR: mismatching Unicode direction override markers in comment
+
+    string s1 = unicode"balanced ‮‬";
+    string s2 = unicode"unterminated ‮"; //~ ERROR: mismatching Unicode direction override markers in string literal
+    string s3 = unicode"underflow ⁩"; //~ ERROR: Unicode direction override underflow in string literal
+    bytes s4 = hex"00";
+}
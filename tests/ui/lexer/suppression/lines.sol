@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MIT
+
+contract C {
+    function f() public pure returns (uint256) {
+        return 1;
+        // solar-disable-next-line 5740
+        f();
+    }
+
+    function g() public pure returns (uint256) {
+        return 1;
+        f(); // solar-disable-line
+    }
+
+    function h() public pure returns (uint256) {
+        return 1;
+        /* solar-disable-line 6321, 5740 */ f();
+    }
+
+    // solar-disable-next-line 5740
+    function i(bool b) public pure returns (uint256) { //~ WARN: unnamed return variable can remain unassigned
+    //~^^ WARN: unused suppression directive
+        if (b) return 1;
+    }
+
+    function j() public pure returns (uint256) {
+        return 1;
+        f(); //~ WARN: unreachable code
+    }
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT
+
+// solar-disable 5740
+contract C {
+    function f() public pure returns (uint256) {
+        return 1;
+        f();
+    }
+
+    // solar-disable 6321
+    function g(bool b) public pure returns (uint256) {
+        if (b) return 1;
+    }
+    // solar-enable 6321
+
+    function h(bool b) public pure returns (uint256) { //~ WARN: unnamed return variable can remain unassigned
+        if (b) return 1;
+        f();
+    }
+}
+// solar-enable
+
+contract D {
+    function f() public pure returns (uint256) {
+        return 1;
+        f(); //~ WARN: unreachable code
+    }
+
+    // solar-disable
+    function g(bool b) public pure returns (uint256) {
+        if (b) return 1;
+        g(b);
+    }
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: MIT
+
+// solar-disable-next-line
+contract C {
+//~^^ WARN: unused suppression directive
+    function f() public pure returns (uint256) {
+        return 1;
+        // solar-disable-next-line 6321
+        f(); //~ WARN: unreachable code
+        //~^^ WARN: unused suppression directive
+    }
+
+    // solar-disable 5740
+    function g() public pure {}
+    // solar-enable 5740
+    //~^^^ WARN: unused suppression directive
+
+    // solar-enable 1234
+    //~^ WARN: `solar-enable` directive without a matching `solar-disable`
+
+    // solar-disable-nextline
+    //~^ WARN: unknown suppression directive `solar-disable-nextline`
+}
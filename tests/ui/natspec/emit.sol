@@ -0,0 +1,67 @@
+//@compile-flags: --emit=userdoc,devdoc --pretty-json
+
+/// @title A base token
+/// @author Alice
+/// @custom:security-contact sec@example.com
+interface IToken {
+    /// @notice Transfers `amount` tokens to `to`.
+    /// @dev Reverts on failure.
+    /// @param to The recipient.
+    /// @param amount The amount
+    ///        to transfer.
+    /// @return success Whether it worked.
+    function transfer(address to, uint256 amount) external returns (bool success);
+
+    /// @notice Returns the balance.
+    /// @return The balance of `owner`.
+    function balanceOf(address owner) external view returns (uint256);
+
+    /// @notice Emitted on transfer.
+    /// @param from The sender.
+    event Transfer(address indexed from, address indexed to, uint256 value);
+
+    /// @notice Not enough funds.
+    /// @dev Thrown when `balance < amount`.
+    error Insufficient(uint256 balance, uint256 amount);
+}
+
+/**
+ * @notice A simple token.
+ * @dev Implements
+ *      the token interface.
+ */
+contract Token is IToken {
+    /// @notice Total supply.
+    /// @dev Fixed.
+    /// @return The supply.
+    uint256 public totalSupply;
+
+    /// @dev The balances.
+    mapping(address => uint256) internal balances;
+
+    /// @notice Creates the token.
+    /// @param supply The initial supply.
+    constructor(uint256 supply) {
+        totalSupply = supply;
+    }
+
+    /// @inheritdoc IToken
+    /// @custom:audit ok
+    function transfer(address to, uint256 amount) external returns (bool ok) {
+        to; amount;
+        return true;
+    }
+
+    function balanceOf(address owner) external view returns (uint256) {
+        return balances[owner];
+    }
+
+    /// Does something.
+    /// @return a The first value.
+    /// @return The second value.
+    function foo() public pure virtual returns (uint256 a, uint256) {}
+}
+
+contract Derived is Token(1) {
+    function foo() public pure override returns (uint256 x, uint256 y) {}
+}
@@ -0,0 +1,97 @@
+/// @title Base
+/// @author Alice
+/// @custom:security-contact sec@example.com
+interface IBase {
+    /// @notice Does something.
+    function foo(uint256 a) external returns (uint256);
+
+    function bar() external;
+}
+
+/// @param x Not a constructor.
+//~^ ERROR: Documentation tag @param not valid for contracts.
+/// @custom:Invalid Uppercase.
+//~^ ERROR: Invalid character in custom tag @custom:Invalid.
+/// @custom: Empty.
+//~^ ERROR: Custom documentation tag must contain a chosen name
+contract C is IBase {
+    /// @dev Valid.
+    /// @return The supply.
+    uint256 public totalSupply;
+
+    /// @return A
+    //~^ ERROR: Documentation tag @return not valid for non-public state variables.
+    uint256 internal hidden;
+
+    /// @return a The first.
+    /// @return b The second.
+    //~^ ERROR: is only allowed once on public state-variables.
+    uint256 public twice;
+
+    /// @param x The parameter.
+    /// @return y The return value.
+    constructor(uint256 x) {}
+    //~^^ ERROR: Documentation tag @return not valid for constructor.
+
+    /// @inheritdoc IBase
+    function foo(uint256 a) external returns (uint256) {}
+
+    /// @inheritdoc Missing
+    //~^ ERROR: Documentation tag @inheritdoc references inexistent contract "Missing".
+    function bar() external {}
+
+    /// @param b Does not exist.
+    //~^ ERROR: Documented parameter "b" not found in the parameter list of the function.
+    /// @param
+    //~^ ERROR: No param name given
+    /// @param a
+    //~^ ERROR: No description given for param a
+    /// @return r The result.
+    /// @return s The other result.
+    //~^ ERROR: Documentation tag "@return s The other result." exceeds the number of return parameters.
+    function baz(uint256 a) public returns (uint256 r) {}
+
+    /// @return wrong The result.
+    //~^ ERROR: does not contain the name of its return parameter.
+    function qux() public returns (uint256 r) {}
+
+    /// @inheritdoc IBase
+    //~^ ERROR: but the contract does not contain a function that is overridden by this function.
+    function quux() public {}
+
+    /// @inheritdoc E
+    //~^ ERROR: Documentation tag @inheritdoc reference "E" is not a contract.
+    function corge() public {}
+
+    /// @inheritdoc IBase
+    /// @inheritdoc IBase
+    //~^ ERROR: Documentation tag @inheritdoc can only be given once.
+    function grault() public {}
+
+    enum E { A }
+
+    /// @notice Emitted.
+    /// @return Nothing.
+    //~^ ERROR: Documentation tag @return not valid for events.
+    event Ev(uint256 a);
+
+    /// @param b Not a parameter.
+    //~^ ERROR: Documented parameter "b" not found
+    error Er(uint256 a);
+
+    /// @dev Valid.
+    /// @param a The parameter.
+    modifier m(uint256 a) {
+        _;
+    }
+}
+
+/// @notice Not valid.
+//~^ ERROR: Documentation tag @notice not valid for file-level variables.
+uint256 constant X = 1;
+
+contract D {
+    /// @notice
+    //~^ ERROR: End of tag @notice not found
+    function f() public {}
+}
@@ -0,0 +1,9 @@
+// SPDX-License-Identifier: MIT
+
+// Binary operators are left-associative, except for `**`.
+struct S {
+    uint256[1 - 1 - 1] a; //~ ERROR: failed to evaluate constant: arithmetic overflow
+    uint256[8 / 4 / 2 - 1] b; //~ ERROR: array length must be greater than zero
+    uint256[2 ** 2 ** 3 - 256] c; //~ ERROR: array length must be greater than zero
+    uint256[(1 << 2 << 3) - 32] d; //~ ERROR: array length must be greater than zero
+}
@@ -0,0 +1,4 @@
+// SPDX-License-Identifier: MIT$
+//~^ ERROR: invalid SPDX license identifier
+
+contract C {}
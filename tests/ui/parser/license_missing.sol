@@ -0,0 +1,7 @@
+//@compile-flags: -W 1878
+pragma solidity ^0.8.0; //~ WARN: SPDX license identifier not provided in source file
+
+contract C {
+    // Identifiers inside of items are ignored.
+    // SPDX-License-Identifier: MIT
+}
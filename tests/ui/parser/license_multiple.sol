@@ -0,0 +1,7 @@
+// SPDX-License-Identifier: MIT
+/**
+ * SPDX-License-Identifier: GPL-3.0
+ */
+//~^^ ERROR: multiple SPDX license identifiers found in source file
+
+contract C {}
@@ -0,0 +1,6 @@
+//@compile-flags: --ignore-pragma-version
+
+pragma solidity ^0.7.0;
+pragma solidity >=0.9.0;
+
+contract C {}
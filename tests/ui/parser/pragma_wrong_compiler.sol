@@ -0,0 +1,12 @@
+pragma solidity ^0.8.0;
+pragma solidity >=0.8.0 <0.9.0;
+pragma solidity 0.8.30;
+
+pragma solidity ^0.7.0;
+//~^ ERROR: source file requires different compiler version
+pragma solidity >=0.8.31;
+//~^ ERROR: source file requires different compiler version
+pragma solidity ^0.4.0 || ^0.5.0;
+//~^ ERROR: source file requires different compiler version
+
+contract C {}
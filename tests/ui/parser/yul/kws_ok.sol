@@ -1,3 +1,4 @@
+//@ compile-flags: --stop-after parsing
 contract C {
     uint number;
     function f() external {
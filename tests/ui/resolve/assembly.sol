@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MIT
+contract C {
+    uint256 s;
+
+    function f(uint256 a) public pure returns (uint256 r) {
+        assembly {
+            let x := add(a, 1)
+            r := g(x)
+            function g(v) -> w {
+                w := h(v)
+            }
+            function h(v) -> w {
+                w := v
+            }
+            for { let i := 0 } lt(i, 10) { i := add(i, 1) } {
+                x := add(x, i)
+            }
+            switch x
+            case 0 { r := 1 }
+            default { r := x }
+        }
+
+        assembly {
+            r := y //~ ERROR: identifier `y` not found
+            r := s_slot //~ ERROR: identifier `s_slot` not found
+            r := a.foo //~ ERROR: identifier `a.foo` not found
+            r := a.b.slot //~ ERROR: identifier `a.b.slot` not found
+            foo() //~ ERROR: function `foo` not found
+            pop(datasize("C")) //~ ERROR: function `datasize` not found
+            { let z := 0 }
+            r := z //~ ERROR: identifier `z` not found
+        }
+
+        assembly {
+            let x := 1
+            function g() -> w {
+                w := a //~ ERROR: cannot access local Solidity variables from inside an inline assembly function
+                w := x //~ ERROR: identifier `x` not found
+            }
+        }
+
+        assembly {
+            let a := 1 //~ ERROR: this declaration shadows a declaration outside the inline assembly block
+            let this := 1 //~ ERROR: the identifier name `this` is reserved
+            function super() {} //~ ERROR: the identifier name `super` is reserved
+        }
+    }
+}
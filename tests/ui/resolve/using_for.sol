@@ -0,0 +1,19 @@
+contract C {
+    function f(uint256 x) public pure returns (uint256) {
+        return x;
+    }
+}
+
+library L {}
+
+function f(uint256 x) pure returns (uint256) {
+    return x;
+}
+
+contract E {
+    using L for uint256;
+    using {f, L.g} for uint256; //~ ERROR: unresolved symbol `g`
+    using C for uint256; //~ ERROR: expected library, found contract
+    using {C.f} for uint256; //~ ERROR: only file-level functions and library functions can be attached
+    using f for uint256; //~ ERROR: expected library, found function
+}
@@ -0,0 +1,30 @@
+//@ignore-host: windows
+//@compile-flags: --emit=storage-layout --pretty-json
+
+contract A {
+    uint128 a;
+    bool b;
+    uint256 constant C = 1;
+    address immutable D = address(0);
+    uint8 transient t;
+}
+
+contract B is A {
+    enum E { X, Y }
+    struct S {
+        uint64 x;
+        uint8[3] y;
+        mapping(string => S) z;
+        S[] w;
+    }
+
+    E e;
+    address owner;
+    mapping(address => uint256) balances;
+    S s;
+    bytes32[2] fixedArray;
+    uint16[] dynArray;
+    string name;
+    function(uint256) external returns (bool) f;
+    bool last;
+}
@@ -0,0 +1,12 @@
+//@ignore-host: windows
+//@compile-flags: --emit=storage-layout --pretty-json
+
+uint256 constant BASE = 0x100;
+
+contract A layout at BASE + 2 {
+    uint64 a;
+    uint64 b;
+    uint256 c;
+}
+
+contract Empty {}
@@ -0,0 +1,9 @@
+contract A layout at 115792089237316195423570985008687907853269984665640564039457584007913129639935 {
+    //~^ ERROR: contract extends past the end of storage when this base slot value is specified
+    uint256 a;
+    uint256 b;
+}
+
+contract B layout at 115792089237316195423570985008687907853269984665640564039457584007913129639935 {
+    uint256 a;
+}
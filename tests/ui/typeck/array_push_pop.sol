@@ -0,0 +1,21 @@
+//@compile-flags: -Ztypeck
+// SPDX-License-Identifier: MIT
+
+contract C {
+    uint256[] arr;
+    bytes data;
+
+    function f() public {
+        arr.push(1);
+        arr.push();
+        uint256 x = arr.push();
+        arr.pop();
+        data.push(bytes1(0x01));
+        data.push();
+        data.pop();
+
+        arr.push(1, 2); //~ ERROR: no matching declaration found
+        arr.pop(1); //~ ERROR: wrong argument count
+        uint256 y = arr.pop(); //~ ERROR: mismatched number of components
+    }
+}
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT
+library L {}
+
+contract C {
+    struct S { uint256 a; }
+
+    uint256 s;
+    S st;
+    uint256 constant K = 1 + 2;
+    uint256 constant K2 = K;
+    bytes32 constant H = keccak256("a");
+    uint256 immutable I = 1;
+
+    function g() internal {}
+
+    function f(
+        uint256[] memory m,
+        uint256[] calldata cd,
+        S storage sp,
+        function() external e
+    ) internal {
+        uint256 x;
+        assembly {
+            x := K
+            x := K2
+            x := H //~ ERROR: only direct number constants and references to such constants are supported by inline assembly
+            K := 1 //~ ERROR: constant variables cannot be assigned to
+            x := K.slot //~ ERROR: the suffixes `.offset`, `.slot` and `.length` can only be used with non-constant storage or transient storage variables
+            x := I //~ ERROR: assembly access to immutable variables is not supported
+
+            x := s //~ ERROR: only local variables are supported
+            s := 1 //~ ERROR: only local variables are supported
+            x := s.slot
+            x := st.offset
+            s.slot := 1 //~ ERROR: state variables cannot be assigned to
+            x := s.length //~ ERROR: state variables only support `.slot` and `.offset`
+
+            x := m
+            x := m.slot //~ ERROR: the suffix `.slot` is not supported by this variable or type
+
+            x := cd //~ ERROR: calldata elements cannot be accessed directly
+            x := cd.offset
+            x := cd.length
+            x := cd.slot //~ ERROR: calldata variables only support `.offset` and `.length`
+
+            x := sp //~ ERROR: you have to use the `.slot` or `.offset` suffix to access storage reference variables
+            sp.slot := 1
+            sp.offset := 1 //~ ERROR: only `.slot` can be assigned to
+
+            x := e //~ ERROR: only types that use one stack slot are supported
+            x := e.selector
+            x := e.address
+            x := e.slot //~ ERROR: variables of type function pointer only support `.selector` and `.address`
+
+            x := g //~ ERROR: access to functions is not allowed in inline assembly
+            g := 1 //~ ERROR: only local variables can be assigned to in inline assembly
+            x := g.slot //~ ERROR: the suffixes `.offset`, `.slot` and `.length` can only be used with variables
+            x := L
+            x := C //~ ERROR: contract `C` cannot be accessed in inline assembly
+            x := msg //~ ERROR: builtin `msg` cannot be accessed in inline assembly
+        }
+    }
+}
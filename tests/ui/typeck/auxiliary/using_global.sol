@@ -0,0 +1,15 @@
+type Fixed is uint256;
+
+using {add, unwrapped} for Fixed global;
+
+function add(Fixed a, Fixed b) pure returns (Fixed) {
+    return Fixed.wrap(Fixed.unwrap(a) + Fixed.unwrap(b));
+}
+
+function unwrapped(Fixed a) pure returns (uint256) {
+    return Fixed.unwrap(a);
+}
+
+function notAttached(Fixed a) pure returns (Fixed) {
+    return a;
+}
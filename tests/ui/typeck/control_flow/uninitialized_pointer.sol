@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: MIT
+contract C {
+    struct S {
+        uint a;
+    }
+
+    S s;
+    mapping(uint => uint) m;
+
+    function access() public view {
+        S storage p;
+        p.a; //~ ERROR: this variable is of storage pointer type and can be accessed without prior assignment
+    }
+
+    function returned() internal pure returns (S storage p) {} //~ ERROR: this variable is of storage pointer type and can be returned without prior assignment
+
+    function returnedUnnamed() internal pure returns (S storage) {} //~ ERROR: this variable is of storage pointer type and can be returned without prior assignment
+
+    function mapping_() internal view returns (mapping(uint => uint) storage r) { //~ ERROR: this variable is of storage pointer type and can be returned without prior assignment
+        if (m[0] == 0) {
+            r = m;
+        }
+    }
+
+    function calldataAccess() external pure {
+        uint[] calldata c;
+        c.length; //~ ERROR: this variable is of calldata pointer type and can be accessed without prior assignment
+    }
+
+    function someBranches(bool b) public view {
+        S storage p;
+        if (b) {
+            p = s;
+        }
+        p.a; //~ ERROR: this variable is of storage pointer type and can be accessed without prior assignment
+    }
+
+    function allBranches(bool b) public view returns (uint) {
+        S storage p;
+        if (b) {
+            p = s;
+        } else {
+            p = s;
+        }
+        return p.a;
+    }
+
+    function assignedInAssembly() public view returns (uint) {
+        S storage p;
+        assembly {
+            p.slot := 0
+        }
+        return p.a;
+    }
+
+    function accessedInAssembly() public pure returns (uint r) {
+        S storage p;
+        assembly {
+            r := p.slot //~ ERROR: this variable is of storage pointer type and can be accessed without prior assignment
+        }
+    }
+
+    function revertingPath() public view returns (uint) {
+        S storage p;
+        if (s.a == 0) {
+            p.a;
+            revert();
+        }
+        p = s;
+        return p.a;
+    }
+
+    function unreachablePath() public view returns (uint) {
+        S storage p;
+        return 1;
+        p.a; //~ WARN: unreachable code
+    }
+
+    function loop(uint n) public view {
+        S storage p;
+        for (uint i = 0; i < n; i++) {
+            if (i > 0) {
+                p.a; //~ ERROR: this variable is of storage pointer type and can be accessed without prior assignment
+            }
+        }
+        p = s;
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MIT
+contract C {
+    function unassigned() public pure returns (uint) { //~ WARN: unnamed return variable can remain unassigned
+        uint x = 1;
+        x;
+    }
+
+    function someBranches(bool b) public pure returns (uint, uint) { //~ WARN: unnamed return variable can remain unassigned
+    //~^ WARN: unnamed return variable can remain unassigned
+        if (b) {
+            return (1, 2);
+        }
+    }
+
+    function allBranches(bool b) public pure returns (uint) {
+        if (b) {
+            return 1;
+        } else {
+            return 2;
+        }
+    }
+
+    function reverts(bool b) public pure returns (uint) {
+        if (b) {
+            return 1;
+        }
+        revert();
+    }
+
+    function loop() public pure returns (uint) {
+        while (true) {
+            return 1;
+        }
+    }
+
+    function named() public pure returns (uint r) {}
+
+    function emptyBody() public pure returns (uint) {}
+
+    function inAssembly() public pure returns (uint) {
+        assembly {
+            return(0, 0x20)
+        }
+    }
+
+    modifier m() {
+        _;
+        return;
+    }
+
+    modifier onlyIf(bool b) {
+        if (b) _;
+    }
+
+    function returnInModifier() public pure m returns (uint) {
+        return 1;
+    }
+
+    function skippedByModifier(bool b) public pure onlyIf(b) returns (uint) { //~ WARN: unnamed return variable can remain unassigned
+        return 1;
+    }
+}
@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: MIT
+contract C {
+    uint x;
+
+    modifier reverts() {
+        revert();
+        _; //~ WARN: unreachable code
+    }
+
+    function afterReturn() public returns (uint) {
+        return 1;
+        x = 2; //~ WARN: unreachable code
+    }
+
+    function afterRevert() public {
+        revert("no");
+        x = 1; //~ WARN: unreachable code
+        x = 2;
+    }
+
+    function afterRevertStmt() public {
+        revert E();
+        x = 1; //~ WARN: unreachable code
+    }
+
+    function inBranch(bool b) public {
+        if (b) {
+            return;
+            x = 1; //~ WARN: unreachable code
+        }
+        x = 2;
+    }
+
+    function bothBranches(bool b) public {
+        if (b) {
+            return;
+        } else {
+            revert();
+        }
+        x = 2; //~ WARN: unreachable code
+    }
+
+    function afterLoop() public {
+        while (true) {
+            x++;
+        }
+        x = 2;
+        for (;;) {
+            x++;
+        }
+        x = 3; //~ WARN: unreachable code
+    }
+
+    function breakAndContinue(uint n) public {
+        for (uint i = 0; i < n; i++) {
+            if (i == 1) {
+                continue;
+                x = 1; //~ WARN: unreachable code
+            }
+            break;
+            x = 2; //~ WARN: unreachable code
+        }
+        do {
+            return;
+        } while (n > 0); //~ WARN: unreachable code
+    }
+
+    function loopNext() public {
+        for (uint i = 0; i < 10; i++) { //~ WARN: unreachable code
+            return;
+        }
+    }
+
+    function alwaysReverts() internal pure {
+        revert();
+    }
+
+    function alwaysRevertsIndirectly() internal pure {
+        alwaysReverts();
+    }
+
+    function afterRevertingCall() public {
+        alwaysRevertsIndirectly();
+        x = 1; //~ WARN: unreachable code
+    }
+
+    function modified() public reverts {
+        x = 1; //~ WARN: unreachable code
+    }
+
+    function inAssembly() public {
+        assembly {
+            return(0, 0)
+        }
+        x = 1; //~ WARN: unreachable code
+    }
+
+    function reachable(bool b) public {
+        if (b) return;
+        require(b);
+        assert(b);
+        x = b ? 1 : 2;
+        b && (x == 1);
+        assembly {
+            if b { revert(0, 0) }
+            switch b
+            case 0 { stop() }
+        }
+        x = 3;
+    }
+
+    error E();
+}
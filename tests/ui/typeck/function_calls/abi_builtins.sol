@@ -0,0 +1,87 @@
+//@compile-flags: -Ztypeck
+
+interface IToken {
+    function transfer(address to, uint256 amount) external returns (bool);
+}
+
+contract C {
+    struct S {
+        uint256 a;
+        bytes b;
+    }
+
+    struct WithMapping {
+        mapping(uint256 => uint256) m;
+    }
+
+    mapping(uint256 => uint256) map;
+    function() internal pure fnPtr;
+
+    function target(uint256 x, string memory s) external {}
+    function internalTarget(uint256 x) internal {}
+
+    function encode(
+        IToken token,
+        S memory s,
+        uint256[][] memory nested,
+        uint256[] memory flat
+    ) external view {
+        bytes memory b = abi.encode(1, s, nested, "abc", this.target);
+        b = abi.encodePacked(uint8(1), flat, "abc", msg.sender, bytes32(uint256(1)));
+        b = abi.encodeWithSelector(token.transfer.selector, msg.sender, 1);
+        b = abi.encodeWithSignature("transfer(address,uint256)", msg.sender, 1);
+        b = abi.encodeCall(token.transfer, (msg.sender, 1));
+        b = abi.encodeCall(this.target, (1, "abc"));
+    }
+
+    function badEncode(S memory s, uint256[][] memory nested) external view {
+        abi.encode(map); //~ ERROR: cannot be ABI-encoded
+        abi.encode(fnPtr); //~ ERROR: cannot be ABI-encoded
+        abi.encode(C); //~ ERROR: cannot be ABI-encoded
+        abi.encodePacked(1); //~ ERROR: cannot perform packed encoding for a literal
+        abi.encodePacked(s); //~ ERROR: is not supported in packed mode
+        abi.encodePacked(nested); //~ ERROR: is not supported in packed mode
+        abi.encode({x: 1}); //~ ERROR: named arguments cannot be used
+        abi.encodeWithSelector(); //~ ERROR: wrong argument count
+        abi.encodeWithSelector(uint256(1)); //~ ERROR: mismatched types
+        abi.encodeWithSignature(1); //~ ERROR: mismatched types
+    }
+
+    function badEncodeCall(IToken token) external view {
+        abi.encodeCall(token.transfer, (msg.sender)); //~ ERROR: expected 2 components for the tuple parameter, found 1
+        abi.encodeCall(token.transfer, (msg.sender, "abc")); //~ ERROR: mismatched types
+        abi.encodeCall(internalTarget, (1)); //~ ERROR: expected an external function, found an internal function
+        abi.encodeCall(1, (1)); //~ ERROR: expected a function pointer
+        abi.encodeCall(token.transfer); //~ ERROR: wrong argument count
+    }
+
+    function decode(bytes calldata data) external pure {
+        (uint256 a, string memory s, S memory st, uint256[] memory arr) =
+            abi.decode(data, (uint256, string, S, uint256[]));
+        uint256 b = abi.decode(data, (uint256));
+        address c = abi.decode(data, (address));
+        abi.decode(data, ());
+    }
+
+    function badDecode(bytes calldata data) external pure {
+        abi.decode(1, (uint256)); //~ ERROR: the first argument to `abi.decode` must be implicitly convertible
+        abi.decode(data, (uint256, 1)); //~ ERROR: expected a type name
+        abi.decode(data, (WithMapping)); //~ ERROR: decoding type
+        abi.decode(data); //~ ERROR: wrong argument count
+        uint256 x = abi.decode(data, (string)); //~ ERROR: mismatched types
+    }
+
+    function concat(string memory s, bytes memory b, bytes32 h) external pure {
+        string memory s2 = string.concat(s, "abc", s);
+        bytes memory b2 = bytes.concat(b, "abc", h, bytes4(h));
+        s2 = string.concat();
+        b2 = bytes.concat();
+    }
+
+    function badConcat(string memory s, bytes memory b) external pure {
+        string.concat(b); //~ ERROR: mismatched types
+        string.concat(1); //~ ERROR: mismatched types
+        bytes.concat(s); //~ ERROR: mismatched types
+        bytes.concat(1); //~ ERROR: mismatched types
+    }
+}
@@ -0,0 +1,65 @@
+//@compile-flags: -Ztypeck
+
+interface IToken {
+    function transfer(address to, uint256 amount) external returns (bool);
+    function transfer(address to, uint256 amount, bytes calldata data) external returns (bool);
+}
+
+contract C {
+    event Transfer(address from, address to);
+    event Transfer(address from, address to, uint256 amount);
+
+    function f(uint8 x) internal pure returns (uint8) {
+        return x;
+    }
+    function f(int8 x) internal pure returns (int8) {
+        return x;
+    }
+    function f(uint256 x, bool b) internal pure returns (uint256) {
+        return b ? x : 0;
+    }
+
+    function g(uint16 x) internal pure {}
+    function g(uint32 x) internal pure {}
+
+    function h(bytes4 b) internal pure {}
+    function h(bytes32 b) internal pure {}
+
+    // === Resolved by argument types ===
+    function resolved(IToken token, address to, bytes calldata data) external {
+        token.transfer(to, 1);
+        token.transfer(to, 1, data);
+        uint8 a = f(uint8(1));
+        int8 b = f(int8(-1));
+        uint256 c = f(1, true);
+        c = f({b: false, x: 2});
+        emit Transfer(to, address(this));
+        emit Transfer(to, address(this), 1);
+    }
+
+    function resolvedNested() external pure {
+        f(f(uint8(1)));
+        f(f(uint8(1)), f(1, true) > 0);
+    }
+
+    // === No matching declaration ===
+    function noMatch(IToken token, address to) external {
+        f(true); //~ ERROR: no matching declaration found after argument-dependent lookup
+        f(1, 2, 3); //~ ERROR: no matching declaration found after argument-dependent lookup
+        token.transfer(to); //~ ERROR: no matching declaration found after argument-dependent lookup
+        emit Transfer(1); //~ ERROR: no matching declaration found after argument-dependent lookup
+        f({y: 1}); //~ ERROR: no matching declaration found after argument-dependent lookup
+    }
+
+    // === Multiple matching declarations ===
+    function ambiguous() external pure {
+        g(1); //~ ERROR: no unique declaration found after argument-dependent lookup
+        f(1); //~ ERROR: no unique declaration found after argument-dependent lookup
+        h("abc"); //~ ERROR: no unique declaration found after argument-dependent lookup
+    }
+
+    // === Errors in the arguments are not reported again ===
+    function badArg() external pure {
+        f(1 + true); //~ ERROR: cannot apply builtin operator
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT
+
+contract Base {
+    uint256 immutable a;
+    uint256 immutable b = 1;
+    uint256 immutable c;
+    uint256 immutable d;
+    uint256 immutable e;
+    uint256 immutable f;
+    uint256 immutable g;
+
+    constructor(bool flag) {
+        a = 1;
+        a = 2; //~ ERROR: immutable state variable already initialized
+        b = 2; //~ ERROR: immutable state variable already initialized
+        c += 1; //~ ERROR: immutable variables must be initialized using an assignment
+        c++; //~ ERROR: immutable variables cannot be modified after initialization
+        for (uint256 i = 0; i < 1; i++) {
+            d = i; //~ ERROR: cannot write to immutable here: immutable variables cannot be initialized inside a loop
+        }
+        if (flag) {
+            e = 1; //~ ERROR: cannot write to immutable here: immutable variables cannot be initialized conditionally
+        }
+        flag ? f = 1 : f = 2;
+        //~^ ERROR: cannot write to immutable here: immutable variables cannot be initialized conditionally
+        //~| ERROR: cannot write to immutable here: immutable variables cannot be initialized conditionally
+        try this.ext() {
+            g = 1; //~ ERROR: cannot write to immutable here: immutable variables cannot be initialized inside a try/catch statement
+        } catch {}
+    }
+
+    function ext() external {
+        a = 3; //~ ERROR: cannot write to immutable here: immutable variables can only be initialized inline or assigned directly in the constructor
+    }
+
+    function set() internal {
+        delete b; //~ ERROR: cannot write to immutable here: immutable variables can only be initialized inline or assigned directly in the constructor
+    }
+}
+
+contract Derived is Base {
+    constructor() Base(true) {
+        a = 4; //~ ERROR: cannot write to immutable here: immutable variables must be initialized in the constructor of the contract they are defined in
+    }
+}
@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MIT
+
+contract ReadBeforeInit {
+    uint256 immutable a;
+    uint256 immutable b = a + 1; //~ ERROR: immutable variables cannot be read before they are initialized
+    uint256 immutable c;
+    uint256 x = get();
+
+    constructor() {
+        a = c; //~ ERROR: immutable variables cannot be read before they are initialized
+        c = 1;
+    }
+
+    function get() internal view returns (uint256) {
+        return c; //~ ERROR: immutable variables cannot be read before they are initialized
+    }
+
+    // Functions that are not called during construction can read immutables.
+    function read() external view returns (uint256) {
+        return a + b + c;
+    }
+}
+
+contract Uninitialized { //~ ERROR: construction control flow ends without initializing all immutable variables
+    uint256 immutable a;
+    uint256 immutable b;
+    uint256 immutable c = 1;
+}
+
+contract EarlyReturn {
+    uint256 immutable a;
+
+    constructor(bool flag) {
+        if (flag) {
+            return; //~ ERROR: construction control flow ends without initializing all immutable variables
+        }
+        a = 1;
+    }
+}
+
+contract Ok {
+    uint256 immutable a;
+    uint256 immutable b = 1;
+    address public immutable owner;
+
+    constructor() {
+        a = b + 1;
+        (owner) = msg.sender;
+    }
+
+    function read() external view returns (uint256, address) {
+        return (a + b, owner);
+    }
+}
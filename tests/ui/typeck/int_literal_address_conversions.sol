@@ -0,0 +1,17 @@
+//@compile-flags: -Ztypeck
+// SPDX-License-Identifier: MIT
+
+contract C {
+    address constant ZERO = address(0);
+
+    function f() public pure {
+        address a = address(0);
+        address b = address(1);
+        address c = address(0x1234);
+        address payable d = payable(address(0));
+
+        address e = address(-1); //~ ERROR: invalid explicit type conversion
+        address g = address(0x10000000000000000000000000000000000000000); //~ ERROR: invalid explicit type conversion
+        address h = 1; //~ ERROR: mismatched types
+    }
+}
@@ -0,0 +1,28 @@
+//@compile-flags: -Ztypeck
+// SPDX-License-Identifier: MIT
+
+contract C {
+    bytes4 constant SELECTOR = 0x12345678;
+
+    function implicit() public pure {
+        bytes1 a = 0;
+        bytes4 b = 0x00000000;
+        bytes4 c = 0xdeadbeef;
+        bytes4 d = 0xdead_beef;
+        bytes32 e = 0x0000000000000000000000000000000000000000000000000000000000000001;
+        bytes2 f = (0x1234);
+
+        bytes4 g = 1; //~ ERROR: mismatched types
+        bytes4 h = 0x123456; //~ ERROR: mismatched types
+        bytes4 i = 0x1234567890; //~ ERROR: mismatched types
+    }
+
+    function explicit() public pure {
+        bytes4 a = bytes4(0);
+        bytes4 b = bytes4(0xdeadbeef);
+        bytes2 c = bytes2(0x1234);
+
+        bytes4 d = bytes4(1); //~ ERROR: invalid explicit type conversion
+        bytes4 e = bytes4(0x123456); //~ ERROR: invalid explicit type conversion
+    }
+}
@@ -5,11 +5,10 @@ contract Test {
     uint256 immutable IMMUT;
 
     constructor() {
-        // This should be OK in constructor but currently errors
-        IMMUT = 1; //~ ERROR: cannot assign to an immutable variable
+        IMMUT = 1;
     }
 
     function test() external {
-        IMMUT = 2; //~ ERROR: cannot assign to an immutable variable
+        IMMUT = 2; //~ ERROR: cannot write to immutable here
     }
 }
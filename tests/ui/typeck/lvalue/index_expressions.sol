@@ -0,0 +1,19 @@
+//@compile-flags: -Ztypeck
+// SPDX-License-Identifier: MIT
+
+contract C {
+    uint256 constant IDX = 1;
+    uint256 immutable other = 0;
+    uint256[2] arr;
+    uint256[2][2] nested;
+    mapping(uint256 => uint256) map;
+
+    function index(uint256 x) public {
+        arr[IDX] = x;
+        arr[IDX - 1] = x;
+        nested[IDX][other] = x;
+        map[IDX] = x;
+        map[other]++;
+        delete arr[IDX];
+    }
+}
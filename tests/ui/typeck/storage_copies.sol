@@ -0,0 +1,41 @@
+//@compile-flags: -Ztypeck
+
+contract C {
+    struct S {
+        string t;
+    }
+
+    string s = true ? "abc" : "def";
+    string s2 = s;
+    S st;
+    string[] ss;
+
+    function f(bool c, string memory m, string calldata cd) public {
+        s = c ? "a" : "b";
+        s = (c ? m : "x");
+        s = m;
+        s = cd;
+        s = s2;
+        st.t = c ? "a" : m;
+        ss[0] = c ? "a" : "b";
+        (s, st.t) = (m, m);
+        ss.push(m);
+        ss.push("a");
+        ss.push(s);
+        st = S(m);
+
+        string storage p = s;
+        p = s2;
+        p = m; //~ ERROR: mismatched types
+        p = cd; //~ ERROR: mismatched types
+        (p, s) = (m, m); //~ ERROR: mismatched types
+        string storage q = m; //~ ERROR: mismatched types
+        g(s);
+        g(m); //~ ERROR: mismatched types
+    }
+
+    function g(string storage x) internal {
+        x = s;
+        x = "abc"; //~ ERROR: mismatched types
+    }
+}
@@ -0,0 +1,28 @@
+//@compile-flags: -Ztypeck
+
+contract C {
+    struct S {
+        string t;
+    }
+
+    string s = "abc";
+    bytes b = hex"00";
+    S st;
+    bytes[] bs;
+
+    function f(string calldata c) public {
+        string memory m = "abc";
+        bytes memory n = "abc";
+        m = "def";
+        n = hex"00";
+        s = "def";
+        b = "def";
+        st.t = "def";
+        bs[0] = "def";
+
+        string storage p = s;
+        p = "abc"; //~ ERROR: mismatched types
+        bytes storage q = "ab"; //~ ERROR: mismatched types
+        c = "abc"; //~ ERROR: mismatched types
+    }
+}
@@ -0,0 +1,49 @@
+//@compile-flags: -Ztypeck
+
+type Fixed is int256;
+
+using {add as +, sub as -, neg as -, mul as *, bitNot as ~, eq as ==, lt as <} for Fixed global;
+
+function add(Fixed a, Fixed b) pure returns (Fixed) {
+    return Fixed.wrap(Fixed.unwrap(a) + Fixed.unwrap(b));
+}
+
+function sub(Fixed a, Fixed b) pure returns (Fixed) {
+    return Fixed.wrap(Fixed.unwrap(a) - Fixed.unwrap(b));
+}
+
+function neg(Fixed a) pure returns (Fixed) {
+    return Fixed.wrap(-Fixed.unwrap(a));
+}
+
+function mul(Fixed a, Fixed b) pure returns (Fixed) {
+    return Fixed.wrap(Fixed.unwrap(a) * Fixed.unwrap(b) / 1e18);
+}
+
+function bitNot(Fixed a) pure returns (Fixed) {
+    return Fixed.wrap(~Fixed.unwrap(a));
+}
+
+function eq(Fixed a, Fixed b) pure returns (bool) {
+    return Fixed.unwrap(a) == Fixed.unwrap(b);
+}
+
+function lt(Fixed a, Fixed b) pure returns (bool) {
+    return Fixed.unwrap(a) < Fixed.unwrap(b);
+}
+
+contract C {
+    function ok(Fixed a, Fixed b) external pure returns (Fixed, bool) {
+        Fixed c = a + b - -a * ~b;
+        bool d = a == b && a < c;
+        return (c, d);
+    }
+
+    function notDefined(Fixed a, Fixed b) external pure {
+        a / b; //~ ERROR: cannot apply builtin operator `/`
+        a + 1; //~ ERROR: cannot apply builtin operator `+`
+        a != b; //~ ERROR: cannot apply builtin operator `!=`
+        a += b; //~ ERROR: cannot apply builtin operator `+` to `Fixed` and `Fixed`
+        !a; //~ ERROR: cannot apply unary operator `!`
+    }
+}
@@ -0,0 +1,70 @@
+//@compile-flags: -Ztypeck
+
+type T is uint256;
+type U is uint256;
+
+using {add as +} for T; //~ ERROR: operators can only be defined in a global `using for` directive
+
+using {L.add as *} for T global; //~ ERROR: only pure free functions can be used to define operators
+
+using {viewAdd as /} for T global; //~ ERROR: only pure free functions can be used to define operators
+
+using {addUint as %} for uint256 global;
+//~^ ERROR: operators can only be implemented for user-defined value types
+//~| ERROR: can only use `global` with types defined in the same source unit at file level
+
+using {mixed as |} for T global; //~ ERROR: wrong parameters in operator definition
+
+using {add as ~} for T global; //~ ERROR: wrong parameters in operator definition
+
+using {unary as &} for T global; //~ ERROR: wrong parameters in operator definition
+
+using {three as -} for T global; //~ ERROR: wrong parameters in operator definition
+
+using {other as ^} for T global; //~ ERROR: wrong parameters in operator definition
+
+using {add as ==} for T global; //~ ERROR: wrong return parameters in operator definition
+
+using {cmp as +} for T global;
+//~^ ERROR: wrong return parameters in operator definition
+//~| ERROR: user-defined binary operator `+` has more than one definition
+
+using {add as +} for T global; //~ ERROR: user-defined binary operator `+` has more than one definition
+
+function add(T a, T b) pure returns (T) {
+    return T.wrap(T.unwrap(a) + T.unwrap(b));
+}
+
+function viewAdd(T a, T b) view returns (T) {
+    return T.wrap(T.unwrap(a) + T.unwrap(b) + block.number);
+}
+
+function addUint(uint256 a, uint256 b) pure returns (uint256) {
+    return a + b;
+}
+
+function mixed(T a, uint256 b) pure returns (T) {
+    return T.wrap(T.unwrap(a) | b);
+}
+
+function unary(T a) pure returns (T) {
+    return a;
+}
+
+function three(T a, T b, T c) pure returns (T) {
+    return T.wrap(T.unwrap(a) - T.unwrap(b) - T.unwrap(c));
+}
+
+function other(U a, U b) pure returns (T) {
+    return T.wrap(U.unwrap(a) ^ U.unwrap(b));
+}
+
+function cmp(T a, T b) pure returns (bool) {
+    return T.unwrap(a) < T.unwrap(b);
+}
+
+library L {
+    function add(T a, T b) internal pure returns (T) {
+        return T.wrap(T.unwrap(a) + T.unwrap(b));
+    }
+}
@@ -0,0 +1,63 @@
+//@compile-flags: -Ztypeck
+
+import {Fixed} from "./auxiliary/using_global.sol";
+
+library SafeMath {
+    function add(uint256 a, uint256 b) internal pure returns (uint256) {
+        return a + b;
+    }
+
+    function neg(int256 a) internal pure returns (int256) {
+        return -a;
+    }
+
+    function hidden(uint256 a) private pure returns (uint256) {
+        return a;
+    }
+}
+
+library Arrays {
+    function sum(uint256[] storage self) internal view returns (uint256 s) {
+        for (uint256 i = 0; i < self.length; i++) {
+            s += self[i];
+        }
+    }
+}
+
+function double(uint256 x) pure returns (uint256) {
+    return x * 2;
+}
+
+using {double} for uint256;
+
+contract C {
+    using SafeMath for uint256;
+    using Arrays for uint256[];
+
+    uint256[] values;
+
+    function f(uint256 x, Fixed y) public view returns (uint256) {
+        uint256 a = x.add(1);
+        uint256 b = x.double();
+        uint256 c = values.sum();
+        uint256 d = y.add(y).unwrapped();
+        x.neg(); //~ ERROR: member `neg` not found
+        x.hidden(); //~ ERROR: member `hidden` not found
+        y.notAttached(); //~ ERROR: member `notAttached` not found
+        return a + b + c + d;
+    }
+}
+
+contract D {
+    function f(uint256 x) public pure returns (uint256) {
+        // `using for` directives are not inherited by other contracts.
+        return x.add(1); //~ ERROR: member `add` not found
+    }
+}
+
+function noParams() pure {}
+function takesInt(int256) pure {}
+
+using {noParams} for uint256; //~ ERROR: function `noParams` does not have any parameters
+using {takesInt} for uint256; //~ ERROR: function `takesInt` cannot be attached to the type `uint256`
+using {double} for uint256 global; //~ ERROR: can only use `global` with types defined in the same source unit at file level
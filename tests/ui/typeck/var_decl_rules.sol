@@ -1,6 +1,6 @@
 //@compile-flags: -Ztypeck
 
-contract C {
+contract C { //~ ERROR: construction control flow ends without initializing all immutable variables
     // Immutable with non-value type (array)
     uint[] immutable IMMUT_ARRAY; //~ ERROR: immutable variables cannot have a non-value type
 
@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MIT
+struct S {
+    uint x;
+}
+
+contract C {
+    S s;
+    uint[] transient t; //~ ERROR: transient storage is only supported for value types
+    uint transient u;
+
+    constructor(uint[] calldata a) {} //~ ERROR: invalid data location `calldata`
+
+    function locals() internal view {
+        uint[] a = new uint[](0); //~ ERROR: expected data location
+        S b = s; //~ ERROR: expected data location
+        uint memory c = 0; //~ ERROR: data location can only be specified for array, struct or mapping types
+        a; b; c;
+    }
+
+    function params(uint storage a, bytes b) external {} //~ ERROR: data location can only be specified for array, struct or mapping types
+    //~^ ERROR: expected data location
+
+    function returns_() public returns (string) {} //~ ERROR: expected data location
+
+    function tryCatch() external {
+        try this.returns_() returns (string calldata r) { //~ ERROR: invalid data location `calldata`
+            r;
+        } catch {}
+    }
+}
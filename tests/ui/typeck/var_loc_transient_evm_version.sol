@@ -0,0 +1,5 @@
+// SPDX-License-Identifier: MIT
+//@compile-flags: --evm-version shanghai
+contract C {
+    uint transient t; //~ ERROR: transient storage is not supported by EVM version `shanghai`
+}
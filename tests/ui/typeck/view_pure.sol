@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: MIT
+//@compile-flags: -Ztypeck
+contract C {
+    uint256 x;
+    uint256[] arr;
+    mapping(uint256 => uint256) m;
+    struct S {
+        uint256 a;
+    }
+    S s;
+    uint256 immutable lit = 1;
+    uint256 immutable num = block.number;
+    event E();
+
+    modifier onlyValue() {
+        require(msg.value > 0);
+        _;
+    }
+
+    modifier readsX() {
+        require(x > 0);
+        _;
+    }
+
+    function readState() public pure returns (uint256) {
+        return x; //~ ERROR: function declared as `pure`, but this expression (potentially) reads from the environment or state and thus requires `view`
+    }
+
+    function readBlock() public pure returns (uint256) {
+        return block.timestamp; //~ ERROR: function declared as `pure`, but this expression (potentially) reads from the environment or state and thus requires `view`
+    }
+
+    function readImmutable() external pure returns (uint256) {
+        return num; //~ ERROR: function declared as `pure`, but this expression (potentially) reads from the environment or state and thus requires `view`
+    }
+
+    function readLiteralImmutable() external pure returns (uint256) {
+        return lit;
+    }
+
+    function readThis() external pure returns (address) {
+        return address(this); //~ ERROR: function declared as `pure`, but this expression (potentially) reads from the environment or state and thus requires `view`
+    }
+
+    function writes() public view {
+        x = 1; //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        x += 1; //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        x++; //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        delete x; //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        (x, x) = (x, x);
+        //~^ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        //~| ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        m[1] = 2; //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        s.a = 1; //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        arr.push(1); //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        emit E(); //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+    }
+
+    function writesInPure() public pure {
+        x = 1; //~ ERROR: function cannot be declared as `pure` because this expression (potentially) modifies the state
+    }
+
+    function reads() public view returns (uint256) {
+        return x + m[1] + s.a + arr.length + address(this).balance + block.number;
+    }
+
+    function localStorage() public view returns (uint256) {
+        S storage p = s;
+        p.a = 1; //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        return p.a;
+    }
+
+    function set() public {
+        x = 1;
+    }
+
+    function calls(address a) public view {
+        a.call(""); //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        a.delegatecall(""); //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        a.staticcall("");
+        this.reads();
+        this.set(); //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+    }
+
+    function value() public view returns (uint256) {
+        return msg.value; //~ ERROR: `msg.value` and `callvalue()` can only be used in payable public functions
+    }
+
+    function valueInPure() internal pure returns (uint256) {
+        return msg.value; //~ ERROR: function declared as `pure`, but this expression (potentially) reads from the environment or state and thus requires `view`
+    }
+
+    function internalValue() internal view returns (uint256) {
+        return msg.value;
+    }
+
+    function payableValue() public payable returns (uint256) {
+        return msg.value;
+    }
+
+    function modifierValue() public onlyValue {}
+    //~^ ERROR: this modifier uses `msg.value` or `callvalue()` and thus the function has to be payable or internal
+
+    function modifierReads() public pure readsX {}
+    //~^ ERROR: function declared as `pure`, but this expression (potentially) reads from the environment or state and thus requires `view`
+
+    function modifierReadsView() public view readsX {}
+
+    function assemblyReads() public pure returns (uint256 r) {
+        assembly {
+            r := sload(0) //~ ERROR: function declared as `pure`, but this expression (potentially) reads from the environment or state and thus requires `view`
+        }
+    }
+
+    function assemblyWrites() public view {
+        assembly {
+            sstore(0, 1) //~ ERROR: function cannot be declared as `view` because this expression (potentially) modifies the state
+        }
+    }
+
+    function assemblyValue() public view returns (uint256 r) {
+        assembly {
+            r := callvalue() //~ ERROR: `msg.value` and `callvalue()` can only be used in payable public functions
+        }
+    }
+
+    function assemblyPure() public pure returns (uint256 r) {
+        assembly {
+            r := add(mload(0x40), calldataload(0))
+        }
+    }
+}
+
+library L {
+    function value() public view returns (uint256) {
+        return msg.value;
+    }
+}
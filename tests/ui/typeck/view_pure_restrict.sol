@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT
+//@compile-flags: -Ztypeck
+contract C {
+    uint256 x;
+    uint256[] arr;
+    uint256 immutable lit = 1;
+
+    modifier reads() {
+        require(x > 0);
+        _;
+    }
+
+    function canBeView() public returns (uint256) { //~ WARN: function state mutability can be restricted to `view`
+        return x;
+    }
+
+    function canBePure() public view returns (uint256) { //~ WARN: function state mutability can be restricted to `pure`
+        return 1;
+    }
+
+    function canBePureImmutable() external returns (uint256) { //~ WARN: function state mutability can be restricted to `pure`
+        return lit;
+    }
+
+    function canBeViewInternal(uint256 a) internal returns (uint256) { //~ WARN: function state mutability can be restricted to `view`
+        return a + x;
+    }
+
+    function canBeViewModifier() public reads { //~ WARN: function state mutability can be restricted to `view`
+        x;
+    }
+
+    function balance() public returns (uint256) { //~ WARN: function state mutability can be restricted to `view`
+        return address(this).balance;
+    }
+
+    function length() public returns (uint256) { //~ WARN: function state mutability can be restricted to `view`
+        return arr.length;
+    }
+
+    function memoryLength(uint256[] memory a) public returns (uint256) { //~ WARN: function state mutability can be restricted to `pure`
+        return a.length;
+    }
+
+    function assemblyReads() public returns (uint256 r) { //~ WARN: function state mutability can be restricted to `view`
+        assembly {
+            r := timestamp()
+        }
+    }
+
+    // Not restrictable.
+    function empty() public {}
+    function isVirtual() public virtual returns (uint256) {
+        return 1;
+    }
+    function isPayable() public payable returns (uint256) {
+        return 1;
+    }
+    function writes() public {
+        x = 2;
+    }
+    function pushes() public {
+        arr.push(1);
+    }
+    function calls(address a) public {
+        a.call("");
+    }
+    function internalValue() internal returns (uint256) {
+        return msg.value;
+    }
+    function isPure() public pure returns (uint256) {
+        return 1;
+    }
+    constructor() {
+        lit;
+    }
+    receive() external payable {}
+    fallback() external {
+        x;
+    }
+}
+
+function free(uint256 a) returns (uint256) { //~ WARN: function state mutability can be restricted to `pure`
+    return a;
+}
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT
+//@ compile-flags: --evm-version homestead
+
+contract C {
+    function f() public {
+        assembly {
+            pop(returndatasize()) //~ ERROR: the `returndatasize` instruction is only available for Byzantium-compatible VMs
+            pop(staticcall(0, 0, 0, 0, 0, 0)) //~ ERROR: the `staticcall` instruction is only available for Byzantium-compatible VMs
+            pop(shl(1, 1)) //~ ERROR: the `shl` instruction is only available for Constantinople-compatible VMs
+            pop(create2(0, 0, 0, 0)) //~ ERROR: the `create2` instruction is only available for Constantinople-compatible VMs
+            pop(extcodehash(0)) //~ ERROR: the `extcodehash` instruction is only available for Constantinople-compatible VMs
+            pop(chainid()) //~ ERROR: the `chainid` instruction is only available for Istanbul-compatible VMs
+            pop(selfbalance()) //~ ERROR: the `selfbalance` instruction is only available for Istanbul-compatible VMs
+            pop(basefee()) //~ ERROR: the `basefee` instruction is only available for London-compatible VMs
+            pop(blobbasefee()) //~ ERROR: the `blobbasefee` instruction is only available for Cancun-compatible VMs
+            pop(blobhash(0)) //~ ERROR: the `blobhash` instruction is only available for Cancun-compatible VMs
+            mcopy(0, 0, 0) //~ ERROR: the `mcopy` instruction is only available for Cancun-compatible VMs
+            tstore(0, tload(0)) //~ ERROR: the `tstore` instruction is only available for Cancun-compatible VMs
+            //~^ ERROR: the `tload` instruction is only available for Cancun-compatible VMs
+            pop(prevrandao()) //~ WARN: `prevrandao` is not supported by the VM version
+            pop(difficulty())
+            selfdestruct(0) //~ WARN: `selfdestruct` has been deprecated
+        }
+    }
+}
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MIT
+
+contract C {
+    function f() public pure {
+        assembly {
+            pop(difficulty()) //~ WARN: `difficulty` was renamed and supplanted by `prevrandao`
+            pop(prevrandao())
+            tstore(0, tload(0))
+            mcopy(0, 0, 0)
+            function g() -> a, b {}
+            let y := g() //~ ERROR: variable count mismatch for declaration of `y`
+        }
+    }
+}
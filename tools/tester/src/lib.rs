@@ -84,11 +84,8 @@ fn config(cmd: &'static Path, args: &ui_test::Args, mode: Mode) -> ui_test::Conf
         program: ui_test::CommandBuilder {
             program: cmd.into(),
             args: {
-                let mut args =
-                    vec!["-j1", "--error-format=rustc-json", "-Zui-testing", "-Zparse-yul"];
-                if mode.is_solc() {
-                    args.push("--stop-after=parsing");
-                }
+                // Most tests don't specify a license; use `-W 1878` to report it.
+                let args = ["-j1", "--error-format=rustc-json", "-Zui-testing", "-A", "1878"];
                 args.into_iter().map(Into::into).collect()
             },
             out_dir_flag: None,
@@ -224,8 +221,18 @@ fn solc_per_file_config(config: &mut ui_test::Config, src: &str, path: &Path, cf
     };
     config.comment_defaults.base().exit_status = code.map(Spanned::dummy).into();
 
+    let flags = &mut config.comment_defaults.base().compile_flags;
+    // NOTE: `yulInterpreterTests` are only parsed, since their expected traces and memory and
+    // storage dumps are not compared with the output of `-Zinterpret-yul`.
+    if matches!(cfg.mode, Mode::SolcSolidity) {
+        if let Some(stage) = solc::solidity::stop_after(path) {
+            flags.push(format!("--stop-after={stage}"));
+        }
+    } else {
+        flags.push("--stop-after=parsing".into());
+    }
+
     if matches!(cfg.mode, Mode::SolcSolidity) {
-        let flags = &mut config.comment_defaults.base().compile_flags;
         let has_delimiters = solc::solidity::handle_delimiters(src, path, cfg.tmp_dir, |arg| {
             flags.push(arg.into_string().unwrap())
         });
@@ -29,23 +29,6 @@ pub(crate) fn should_skip(path: &Path) -> Result<(), &'static str> {
         return Err("solidity experimental is not implemented");
     }
 
-    // We don't parse licenses.
-    if path_contains("/license/") {
-        return Err("licenses are not checked");
-    }
-
-    if path_contains("natspec") {
-        return Err("natspec is not checked");
-    }
-
-    if path_contains("_direction_override") {
-        return Err("Unicode direction override checks not implemented");
-    }
-
-    if path_contains("wrong_compiler_") {
-        return Err("Solidity pragma version is not checked");
-    }
-
     // Directories starting with `_` are not tests.
     if path_contains("/_")
         && !path.components().next_back().unwrap().as_os_str().to_str().unwrap().starts_with('_')
@@ -112,6 +95,25 @@ pub(crate) fn should_skip(path: &Path) -> Result<(), &'static str> {
     Ok(())
 }
 
+/// Returns the compiler stage after which to stop, or `None` to run all the stages.
+///
+/// Most tests only need to be parsed, but some diagnostics are only emitted by later stages.
+pub(crate) fn stop_after(path: &Path) -> Option<&'static str> {
+    let path_contains = path_contains_curry(path);
+
+    // NatSpec tags are validated during analysis.
+    if path_contains("natspec") {
+        return None;
+    }
+
+    // Version pragmas and licenses are checked in the AST passes, at the start of lowering.
+    if path_contains("wrong_compiler_") || path_contains("/license/") {
+        return Some("lowering");
+    }
+
+    Some("parsing")
+}
+
 /// Handles `====` delimiters in a solc test file, and creates temporary files as necessary.
 ///
 /// Returns `true` if it contains delimiters and the caller should not compile the original file.